@@ -0,0 +1,62 @@
+//! 向大量订阅者发布大消息的基准测试。
+//!
+//! 消息体在服务器内部只有一份，所有订阅者共享同一块内存（见 `cmd::subscribe` 中的单元测试），
+//! 因此耗时应当随订阅者数量线性增长，而不是随消息大小与订阅者数量的乘积增长。
+
+use std::net::SocketAddr;
+
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tokio::net::TcpListener;
+use tokio::runtime::Runtime;
+
+use mini_redis::{client, server};
+
+fn fan_out(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let addr = rt.block_on(start_server());
+
+    let mut group = c.benchmark_group("publish_fan_out");
+    group.sample_size(10);
+
+    for subscribers in [10, 100, 500] {
+        for size in [1024, 64 * 1024] {
+            let (mut publisher, mut receivers) = rt.block_on(async {
+                let mut receivers = Vec::with_capacity(subscribers);
+                for _ in 0..subscribers {
+                    let client = client::connect(addr).await.unwrap();
+                    receivers.push(client.subscribe(vec!["bench".into()]).await.unwrap());
+                }
+                (client::connect(addr).await.unwrap(), receivers)
+            });
+            let msg = Bytes::from(vec![b'x'; size]);
+
+            group.throughput(Throughput::Elements(subscribers as u64));
+            group.bench_function(BenchmarkId::new(format!("{}B", size), subscribers), |b| {
+                b.iter(|| {
+                    rt.block_on(async {
+                        publisher.publish("bench", msg.clone()).await.unwrap();
+                        for subscriber in &mut receivers {
+                            subscriber.next_message().await.unwrap().unwrap();
+                        }
+                    })
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// 运行 Redis 服务器并返回绑定的套接字地址
+async fn start_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move { server::run(listener, tokio::signal::ctrl_c()).await });
+
+    addr
+}
+
+criterion_group!(benches, fan_out);
+criterion_main!(benches);
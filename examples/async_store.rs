@@ -0,0 +1,64 @@
+//! Server with a custom store.
+//!
+//! Runs a mini-redis server whose GET, SET and DEL commands read and write an
+//! `AsyncKvStore` implemented here instead of the built-in in-memory database.
+//! A disk-backed store (SQLite, sled, ...) would implement the same trait.
+//!
+//! You can test this out by running:
+//!
+//!     cargo run --example async_store
+//!
+//! And then in another terminal run:
+//!
+//!     cargo run --example get_set
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::Bytes;
+use mini_redis::config::ServerConfig;
+use mini_redis::server::{self, AsyncKvStore, BoxFuture, WrongType};
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+
+/// An in-memory store guarded by an async mutex. Expiration is not supported.
+#[derive(Default)]
+struct MemoryStore {
+    entries: Mutex<HashMap<String, Bytes>>,
+}
+
+impl AsyncKvStore for MemoryStore {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Bytes>, WrongType>> {
+        Box::pin(async move { Ok(self.entries.lock().await.get(key).cloned()) })
+    }
+
+    fn set(&self, key: String, value: Bytes, _expire: Option<Duration>) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            println!("set {}", key);
+            self.entries.lock().await.insert(key, value);
+        })
+    }
+
+    fn set_keep_ttl(&self, key: String, value: Bytes) -> BoxFuture<'_, ()> {
+        self.set(key, value, None)
+    }
+
+    fn del(&self, key: String) -> BoxFuture<'_, usize> {
+        Box::pin(async move { self.entries.lock().await.remove(&key).map_or(0, |_| 1) })
+    }
+}
+
+#[tokio::main]
+pub async fn main() -> std::io::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:6379").await?;
+
+    server::run_with_store(
+        listener,
+        tokio::signal::ctrl_c(),
+        ServerConfig::default(),
+        MemoryStore::default(),
+    )
+    .await;
+
+    Ok(())
+}
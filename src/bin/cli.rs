@@ -14,11 +14,10 @@ use mini_redis::{client, logger};
     author, // 作者信息，从Cargo.toml自动获取
     about = "Issue Redis commands" // 应用简介
 )]
-
 struct Cli {
     #[clap(subcommand)] // 表示以下字段为子命令
     cmd: Command, // Redis命令
-    
+
     #[clap(name = "hostname", long, default_value = "127.0.0.1")]
     host: String, // 主机名
 
@@ -66,7 +65,7 @@ async fn main() -> Result<(), MiniRedisClientError> {
         }
 
         Command::Get { key } => {
-            // 执行 Get 命令
+            // 执行 Get 命令，与 redis-cli 一样，空字符串打印为 `""`，只有键不存在时才打印 `(nil)`
             if let Some(v) = client.get(&key).await? {
                 if let Ok(s) = std::str::from_utf8(&v) {
                     println!("\"{}\"", s);
@@ -97,11 +96,8 @@ async fn main() -> Result<(), MiniRedisClientError> {
             client.set_expire(&key, value, expire).await?;
             println!("OK");
         }
-        
-        Command::Publish {
-            channel,
-            message,
-        } => {
+
+        Command::Publish { channel, message } => {
             // 执行 Publish 命令
             client.publish(&channel, message).await?;
             println!("publish ok");
@@ -110,15 +106,21 @@ async fn main() -> Result<(), MiniRedisClientError> {
         Command::Subscribe { channels } => {
             // 执行 Subscribe 命令
             if channels.is_empty() {
-                return Err(MiniRedisConnectionError::InvalidArgument("channel(s) must be provided".into(),).into());
+                return Err(MiniRedisConnectionError::InvalidArgument(
+                    "channel(s) must be provided".into(),
+                )
+                .into());
             }
 
             let mut subscriber = client.subscribe(channels).await?;
 
             while let Some(msg) = subscriber.next_message().await? {
-                println!("got message from the channel: {}; message = {:?}", msg.channel, msg.content);
+                println!(
+                    "got message from the channel: {}; message = {:?}",
+                    msg.channel, msg.content
+                );
             }
-        } 
+        }
 
         Command::Del { key } => {
             // 执行 Del 命令
@@ -127,4 +129,4 @@ async fn main() -> Result<(), MiniRedisClientError> {
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
@@ -4,12 +4,17 @@
 //!
 //! `clap` 包用于解析参数。
 
+use std::future::Future;
+use std::io;
+
 use clap::Parser; // 导入命令行参数解析库
 use dotenv::dotenv; // 导入环境变量处理库
 use tokio::net::TcpListener; // 异步网络编程库中的TCP监听器
+#[cfg(unix)]
+use tokio::net::UnixListener; // unix 域套接字监听器
 use tokio::signal; // 异步信号处理
 
-use mini_redis::consts::DEFAULT_PORT; // 导入默认端口常量
+use mini_redis::config::{parse_config_file, ServerConfig}; // 导入服务器配置
 use mini_redis::error::MiniRedisServerError; // 导入自定义服务端错误类型
 use mini_redis::{logger, server}; // 导入日志和服务器模块
 
@@ -21,11 +26,20 @@ use mini_redis::{logger, server}; // 导入日志和服务器模块
     author, // 从Cargo.toml自动获取作者信息
     about = "A mini redis server" // 关于此程序的简短描述
 )]
-
 // 定义客户端结构体，包含命令行参数
 struct Cli {
     #[clap(long)] // 用来指定命令行参数的长选项 例如 --port
-    port: Option<u16>, // 可选的端口号
+    port: Option<u16>, // 可选的端口号，优先于配置文件中的 port
+
+    #[clap(long)]
+    bind: Option<String>, // 可选的监听地址，优先于配置文件中的 bind，默认为 0.0.0.0
+
+    #[cfg(unix)]
+    #[clap(long)]
+    unixsocket: Option<String>, // 可选的 unix 域套接字路径，指定后不再监听 TCP 端口
+
+    #[clap(long)]
+    config: Option<String>, // 可选的 redis.conf 格式的配置文件路径
 }
 
 /// 初始化 mini-redis 服务器，解析命令行参数并设置日志。
@@ -37,18 +51,62 @@ fn init() -> Cli {
     Cli::parse() // 解析命令行参数并返回一个 Cli 对象
 }
 
+/// 返回一个在收到关闭信号时完成的 future：Ctrl+C，或者在 unix 上收到 SIGTERM。
+///
+/// 容器编排系统通过 SIGTERM 通知进程退出，收到它时与 Ctrl+C 一样优雅地关闭服务器。
+/// SIGTERM 的处理在返回之前就已经注册，之后收到的 SIGTERM 不会直接终止进程。
+fn shutdown_signal() -> io::Result<impl Future<Output = ()>> {
+    #[cfg(unix)]
+    let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())?;
+
+    Ok(async move {
+        #[cfg(unix)]
+        tokio::select! {
+            _ = signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+
+        #[cfg(not(unix))]
+        let _ = signal::ctrl_c().await;
+    })
+}
+
 /// mini-redis 服务器的入口点。
 ///
-/// 使用 `tokio` 的异步主函数。绑定TCP监听器到指定端口，然后运行服务器。
-/// 如果接收到 Ctrl+C 信号，服务器将停止运行。
+/// 使用 `tokio` 的异步主函数。绑定TCP监听器到指定地址和端口，或者绑定到指定的 unix 域套接字，然后运行服务器。
+/// 如果接收到 Ctrl+C 信号或者 SIGTERM，服务器将停止运行。
 #[tokio::main] // 标记为异步主函数，能够使用 await
 pub async fn main() -> Result<(), MiniRedisServerError> {
     let cli = init(); // 初始化并解析命令行参数
-    let port = cli.port.unwrap_or(DEFAULT_PORT); // 获取端口号，如果未指定，则使用默认值
 
-    let listener = TcpListener::bind(&format!("0.0.0.0:{}", port)).await?; // 异步监听 0.0.0.0:port
+    // 读取配置文件，如果未指定，则使用默认配置
+    let mut config = match cli.config {
+        Some(path) => parse_config_file(path)?,
+        None => ServerConfig::default(),
+    };
+    // 命令行参数覆盖配置文件中的值
+    if let Some(port) = cli.port {
+        config.port = port;
+    }
+    if let Some(bind) = cli.bind {
+        config.bind = bind;
+    }
+    // 服务器库不会修改全局的日志级别，由可执行文件应用配置中的级别
+    config.apply_log_level();
+
+    #[cfg(unix)]
+    if let Some(path) = cli.unixsocket {
+        // 与 Redis 一样，先删除上次运行留下的套接字文件，否则绑定会失败
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)?;
+        server::run_with_config(listener, shutdown_signal()?, config).await;
+        let _ = std::fs::remove_file(&path);
+        return Ok(());
+    }
+
+    let listener = TcpListener::bind(&format!("{}:{}", config.bind, config.port)).await?; // 异步监听 bind:port
 
-    server::run(listener, signal::ctrl_c()).await; // 运行服务器，等待 Ctrl+C 信号
+    server::run_with_config(listener, shutdown_signal()?, config).await; // 运行服务器，等待 Ctrl+C 或 SIGTERM 信号
 
     Ok(())
 }
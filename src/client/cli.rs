@@ -2,21 +2,68 @@
 //!
 //! 提供异步连接和发出支持的命令的方法。
 
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use async_stream::try_stream;
 use bytes::Bytes;
 use log::{debug, error};
+use tokio_stream::Stream;
 
 use crate::client::subscriber::Subscriber;
+use crate::cmd::append::Append;
+use crate::cmd::bpop::BPop;
+use crate::cmd::client_cmd::ClientCmd;
+use crate::cmd::config::Config;
+use crate::cmd::dbsize::DbSize;
+use crate::cmd::debug::Debug;
+use crate::cmd::del::Del;
+use crate::cmd::dump::Dump;
+use crate::cmd::expire::Expire;
+use crate::cmd::expireat::ExpireAt;
+use crate::cmd::flushall::FlushAll;
 use crate::cmd::get::Get;
+use crate::cmd::getbit::GetBit;
+use crate::cmd::getdel::GetDel;
+use crate::cmd::hdel::HDel;
+use crate::cmd::hexists::HExists;
+use crate::cmd::hget::HGet;
+use crate::cmd::hgetall::HGetAll;
+use crate::cmd::hlen::HLen;
+use crate::cmd::hset::HSet;
+use crate::cmd::incrbyfloat::IncrByFloat;
+use crate::cmd::info::Info;
+use crate::cmd::llen::LLen;
+use crate::cmd::lpop::LPop;
+use crate::cmd::lpush::LPush;
+use crate::cmd::lrange::LRange;
+use crate::cmd::memory::Memory;
+use crate::cmd::mset::MSet;
+use crate::cmd::object::Object;
 use crate::cmd::ping::Ping;
 use crate::cmd::publish::Publish;
+use crate::cmd::quit::Quit;
+use crate::cmd::ready::Ready;
+use crate::cmd::rename::Rename;
+use crate::cmd::reset::Reset;
+use crate::cmd::restore::Restore;
+use crate::cmd::rpop::RPop;
+use crate::cmd::rpush::RPush;
+use crate::cmd::sadd::SAdd;
+use crate::cmd::scan::Scan;
 use crate::cmd::set::Set;
+use crate::cmd::setbit::SetBit;
+use crate::cmd::setex::SetEx;
+use crate::cmd::setrange::SetRange;
+use crate::cmd::sismember::SIsMember;
+use crate::cmd::smembers::SMembers;
 use crate::cmd::subscribe::Subscribe;
-use crate::cmd::del::Del;
+use crate::cmd::touch::Touch;
+use crate::cmd::ttl::Ttl;
+use crate::cmd::unlink::Unlink;
 use crate::connection::connect::Connection;
-use crate::connection::frame::Frame;
-use crate::error::MiniRedisConnectionError;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::error::{ErrorKind, MiniRedisConnectionError};
 
 /// 与 Redis 服务器建立连接。
 ///
@@ -33,6 +80,15 @@ pub struct Client {
     pub(crate) conn: Connection,
 }
 
+/// `Client::publish_detailed` 的结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublishDetails {
+    /// 收到消息的订阅者数量
+    pub receivers: u64,
+    /// 服务器上已经没有订阅者的频道数量
+    pub stale_channels: u64,
+}
+
 impl Client {
     /// 从套接字读取响应帧。
     ///
@@ -41,6 +97,10 @@ impl Client {
         let response = self.conn.read_frame().await?;
         debug!("read response: {:?}", response);
         match response {
+            // 类型不匹配的错误单独返回，调用者可以区分
+            Some(Frame::Error(msg)) if msg.starts_with(ErrorKind::WrongType.as_str()) => {
+                Err(MiniRedisConnectionError::WrongType(msg))
+            }
             Some(Frame::Error(msg)) => Err(MiniRedisConnectionError::CommandExecute(msg)),
             Some(frame) => Ok(frame),
             // 在这里接收 `None` 表示服务器在没有发送帧的情况下关闭了连接。这是意外的，
@@ -49,9 +109,26 @@ impl Client {
         }
     }
 
+    /// 与 `read_response` 相同，但把服务器回复的错误放在内层的 `Err` 中。
+    ///
+    /// 外层的 `Err` 表示连接出错，此时连接已经不可用；内层的 `Err` 只影响这一个请求，
+    /// 流水线中后续请求的响应仍然可以继续读取。
+    async fn read_reply(
+        &mut self,
+    ) -> Result<Result<Frame, MiniRedisConnectionError>, MiniRedisConnectionError> {
+        match self.read_response().await {
+            Ok(frame) => Ok(Ok(frame)),
+            Err(
+                err @ (MiniRedisConnectionError::CommandExecute(_)
+                | MiniRedisConnectionError::WrongType(_)),
+            ) => Ok(Err(err)),
+            Err(err) => Err(err),
+        }
+    }
+
     /// 核心 `SET` 逻辑，由 `set` 和 `set_expires` 使用。
     async fn set_cmd(&mut self, cmd: Set) -> Result<(), MiniRedisConnectionError> {
-        let frame = cmd.into_frame()?;
+        let frame = cmd.into_frame();
         debug!("set request: {:?}", frame);
 
         self.conn.write_frame(&frame).await?;
@@ -68,10 +145,10 @@ impl Client {
         channels: &[String],
     ) -> Result<(), MiniRedisConnectionError> {
         // 将 `Subscribe` 命令转换为帧
-        let frame = Subscribe::new(channels).into_frame()?;
+        let frame = Subscribe::new(channels).into_frame();
         debug!("subcribe request: {:?}", frame);
 
-         // 将帧写入套接字
+        // 将帧写入套接字
         self.conn.write_frame(&frame).await?;
 
         // 对于每个被订阅的频道，服务器会发送一条确认订阅该频道的消息
@@ -87,7 +164,7 @@ impl Client {
                     //
                     // 其中 channel 是频道名称，num-subscribed 是客户端当前订阅的频道数量。
                     [subscribe, schannel, ..]
-                        if *subscribe == "subscribe" && *schannel == channel =>
+                        if *subscribe == "subscribe" && *schannel == channel.as_str() =>
                     {
                         debug!("subscribe channel: {} success", channel);
                     }
@@ -111,6 +188,38 @@ impl Client {
         Ok(())
     }
 
+    /// 发送由任意参数组成的命令，返回服务器的原始响应帧，不做解释。
+    ///
+    /// 可以用来发送类型化的 API 还不支持的命令。与其他方法一样，错误帧会被转换为 `Err`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::client::Frame;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let response = client.raw(&["ping".into()]).await.unwrap();
+    ///     assert_eq!(Frame::Simple("PONG".to_string()), response);
+    /// }
+    /// ```
+    pub async fn raw(&mut self, args: &[Bytes]) -> Result<Frame, MiniRedisConnectionError> {
+        if args.is_empty() {
+            return Err(MiniRedisConnectionError::InvalidArgument(
+                "raw command requires at least the command name".into(),
+            ));
+        }
+
+        let frame = FrameBuilder::new().bulks(args.iter().cloned()).build();
+        debug!("raw request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        self.read_response().await
+    }
+
     /// 向服务器发送 Ping。
     ///
     /// 如果没有提供参数，则返回 PONG，否则返回参数的副本作为批量数据。
@@ -131,203 +240,1960 @@ impl Client {
     /// }
     /// ```
     pub async fn ping(&mut self, msg: Option<String>) -> Result<Bytes, MiniRedisConnectionError> {
-        let frame = Ping::new(msg).into_frame()?;
+        let frame = Ping::new(msg).into_frame();
         debug!("ping request: {:?}", frame);
         self.conn.write_frame(&frame).await?;
-        match self.read_response().await? {
-            Frame::Simple(v) => Ok(v.into()),
-            Frame::Bulk(v) => Ok(v),
-            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
-        }
+        self.read_response().await?.into_bytes()
     }
 
-    /// 获取键的值。
+    /// 获取服务器的运行信息和统计数据。
     ///
-    /// 如果键不存在，则返回特殊值 `None`。
+    /// 返回服务器回复的原始文本，每行一个 `key:value`。
     ///
     /// # 示例
     ///
-    /// 演示基本用法。
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let info = client.info().await.unwrap();
+    ///     println!("{}", info);
+    /// }
+    /// ```
+    pub async fn info(&mut self) -> Result<String, MiniRedisConnectionError> {
+        let frame = Info::new().into_frame();
+        debug!("info request: {:?}", frame);
+        self.conn.write_frame(&frame).await?;
+        self.read_response().await?.into_string()
+    }
+
+    /// 返回数据库中键的数量。
+    ///
+    /// # 示例
     ///
     /// ```no_run
     /// #[tokio::main]
     /// async fn main() {
     ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
     ///
-    ///     let val = client.get("foo").await.unwrap();
-    ///     println!("获得 = {:?}", val);
+    ///     let keys = client.dbsize().await.unwrap();
+    ///     println!("{} keys", keys);
     /// }
     /// ```
-    pub async fn get(&mut self, key: &str) -> Result<Option<Bytes>, MiniRedisConnectionError> {
-        let frame = Get::new(key).into_frame()?;
-        debug!("get request: {:?}", frame);
+    pub async fn dbsize(&mut self) -> Result<u64, MiniRedisConnectionError> {
+        let frame = DbSize::new().into_frame();
+        debug!("dbsize request: {:?}", frame);
 
         self.conn.write_frame(&frame).await?;
 
         match self.read_response().await? {
-            Frame::Simple(v) => Ok(Some(v.into())),
-            Frame::Bulk(v) => Ok(Some(v)),
+            Frame::Integer(keys) if keys >= 0 => Ok(keys as u64),
             frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
         }
     }
 
-    /// 设置键的值。
+    /// 删除所有的键。
     ///
-    /// 该值与键关联，直到它被下次调用 `set` 覆盖或被移除。
+    /// # 示例
     ///
-    /// 如果键已经持有一个值，它将被覆盖。成功的 SET 操作将丢弃与键相关的任何先前的生存时间。
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
     ///
-    /// # 示例
+    ///     client.flushall().await.unwrap();
+    ///     assert_eq!(0, client.dbsize().await.unwrap());
+    /// }
+    /// ```
+    pub async fn flushall(&mut self) -> Result<(), MiniRedisConnectionError> {
+        let frame = FlushAll::new().into_frame();
+        debug!("flushall request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 检查服务器是否就绪，即可以处理请求并且后台任务仍在运行。
     ///
-    /// 演示基本用法。
+    /// 服务器回复 `NOTREADY` 错误时返回 `false`。
+    ///
+    /// # 示例
     ///
     /// ```no_run
     /// #[tokio::main]
     /// async fn main() {
     ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
     ///
-    ///     client.set("foo", "bar".into()).await.unwrap();
-    ///
-    ///     // 立即获取值
-    ///     let val = client.get("foo").await.unwrap().unwrap();
-    ///     assert_eq!(val, "bar");
+    ///     assert!(client.ready().await.unwrap());
     /// }
     /// ```
-    pub async fn set(&mut self, key: &str, value: Bytes) -> Result<(), MiniRedisConnectionError> {
-        self.set_cmd(Set::new(key, value, None)).await
+    pub async fn ready(&mut self) -> Result<bool, MiniRedisConnectionError> {
+        let frame = Ready::new().into_frame();
+        debug!("ready request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await {
+            Ok(Frame::Simple(response)) if response == "READY" => Ok(true),
+            Err(MiniRedisConnectionError::CommandExecute(msg))
+                if msg.starts_with(ErrorKind::NotReady.as_str()) =>
+            {
+                Ok(false)
+            }
+            Ok(frame) => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+            Err(err) => Err(err),
+        }
     }
 
-    /// 设置键的值。该值在 `expiration` 之后过期。
-    ///
-    /// 该值与键关联，直到以下之一：
-    /// - 它过期。
-    /// - 它被下次调用 `set` 覆盖。
-    /// - 它被移除。
+    /// 让服务器处理这个连接的任务等待 `duration` 之后再回复，用于测试超时。
     ///
-    /// 如果键已经持有一个值，它将被覆盖。成功的 SET 操作将丢弃与键相关的任何先前的生存时间。
+    /// 服务器需要开启 `debug_commands`，否则返回错误。
     ///
     /// # 示例
     ///
-    /// 演示基本用法。此示例不能 **保证** 始终有效，因为它依赖于基于时间的逻辑并假设客户端和服务器在时间上保持相对同步。
-    /// 现实世界往往不会那么有利。
-    ///
     /// ```no_run
-    /// use tokio::time;
     /// use std::time::Duration;
     ///
     /// #[tokio::main]
     /// async fn main() {
-    ///     let ttl = Duration::from_millis(500);
     ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
     ///
-    ///     client.set_expire("foo", "bar".into(), ttl).await.unwrap();
+    ///     client.debug_sleep(Duration::from_millis(100)).await.unwrap();
+    /// }
+    /// ```
+    pub async fn debug_sleep(
+        &mut self,
+        duration: Duration,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let frame = Debug::sleep(duration).into_frame();
+        debug!("debug sleep request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 读取服务器配置项 `parameter` 的当前值。
     ///
-    ///     // 立即获取值
-    ///     let val = client.get("foo").await.unwrap().unwrap();
-    ///     assert_eq!(val, "bar");
+    /// 如果服务器不认识这个配置项，返回 `None`。
     ///
-    ///     // 等待 TTL 过期
-    ///     time::sleep(ttl).await;
+    /// # 示例
     ///
-    ///     let val = client.get("foo").await.unwrap();
-    ///     assert!(val.is_some());
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let maxclients = client.config_get("maxclients").await.unwrap();
+    ///     println!("maxclients = {:?}", maxclients);
     /// }
     /// ```
-    pub async fn set_expire(
+    pub async fn config_get(
         &mut self,
-        key: &str,
-        value: Bytes,
-        epxiration: Duration,
-    ) -> Result<(), MiniRedisConnectionError> {
-        // 创建一个 `Set` 命令并传递给 `set_cmd`。一个单独的方法用于设置带有过期时间的值。
-        // 两个函数的共同部分由 `set_cmd` 实现。
-        self.set_cmd(Set::new(key, value, Some(epxiration))).await
+        parameter: &str,
+    ) -> Result<Option<String>, MiniRedisConnectionError> {
+        let frame = Config::get(parameter).into_frame();
+        debug!("config get request: {:?}", frame);
+        self.conn.write_frame(&frame).await?;
+        match self.read_response().await? {
+            Frame::Array(ref parts) => match parts.as_slice() {
+                [_, value] => Ok(Some(value.to_string())),
+                [] => Ok(None),
+                _ => Err(MiniRedisConnectionError::InvalidFrameType),
+            },
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
     }
 
-    /// 向指定的 `channel` 发布 `message`。
+    /// 为当前连接设置名称 `name`，`name` 为空字符串时清除名称。
     ///
-    /// 返回当前在频道上收听的订阅者数量。不能保证这些订阅者会收到消息，因为他们可能随时断开连接。
+    /// 名称不能包含空格、换行等特殊字符，否则服务器回复错误。
     ///
     /// # 示例
     ///
-    /// 演示基本用法。
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.client_setname("worker-1").await.unwrap();
+    /// }
+    /// ```
+    pub async fn client_setname(&mut self, name: &str) -> Result<(), MiniRedisConnectionError> {
+        let frame = ClientCmd::set_name(name).into_frame();
+        debug!("client setname request: {:?}", frame);
+        self.conn.write_frame(&frame).await?;
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 返回当前连接的名称。
+    ///
+    /// 如果没有设置名称，返回 `None`。
+    ///
+    /// # 示例
     ///
     /// ```no_run
     /// #[tokio::main]
     /// async fn main() {
     ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
     ///
-    ///     let val = client.publish("foo", "bar".into()).await.unwrap();
-    ///     println!("获得 = {:?}", val);
+    ///     let name = client.client_getname().await.unwrap();
+    ///     println!("name = {:?}", name);
     /// }
     /// ```
-    pub async fn publish(
-        &mut self,
-        channel: &str,
-        message: Bytes,
-    ) -> Result<u64, MiniRedisConnectionError> {
-        // 将 `Publish` 命令转换为帧
-        let frame = Publish::new(channel, message).into_frame()?;
-        debug!("publish request: {:?}", frame);
-        // 将帧写入套接字
+    pub async fn client_getname(&mut self) -> Result<Option<String>, MiniRedisConnectionError> {
+        let frame = ClientCmd::get_name().into_frame();
+        debug!("client getname request: {:?}", frame);
         self.conn.write_frame(&frame).await?;
-        // 读取响应
         match self.read_response().await? {
-            Frame::Integer(response) => Ok(response),
+            Frame::Bulk(name) if name.is_empty() => Ok(None),
+            Frame::Bulk(name) => Ok(Some(String::from_utf8_lossy(&name).into_owned())),
             frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
         }
     }
 
-    /// 订阅客户端到指定的频道。
+    /// 返回当前连接的 id。
     ///
-    /// 一旦客户端发出订阅命令，它不能再发出任何非发布/订阅命令。该函数消耗 `self` 并返回一个 `Subscriber`。
+    /// id 在服务器中唯一，按照连接的先后顺序递增。
     ///
-    /// `Subscriber` 值用于接收消息以及管理客户端订阅的频道列表。
-    pub async fn subscribe(
-        mut self,
-        channels: Vec<String>,
-    ) -> Result<Subscriber, MiniRedisConnectionError> {
-        // 向服务器发出订阅命令并等待确认。
-        // 然后客户端将被转换为“订阅者”状态，从那时起只能发出发布/订阅命令。
-        self.subscribe_cmd(&channels).await?;
-        Ok(Subscriber {
-            client: self,
-            subscribed_channels: channels,
-        })
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let id = client.client_id().await.unwrap();
+    ///     println!("id = {}", id);
+    /// }
+    /// ```
+    pub async fn client_id(&mut self) -> Result<u64, MiniRedisConnectionError> {
+        let frame = ClientCmd::id().into_frame();
+        debug!("client id request: {:?}", frame);
+        self.conn.write_frame(&frame).await?;
+        match self.read_response().await? {
+            Frame::Integer(id) if id >= 0 => Ok(id as u64),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
     }
 
-    /// 删除指定的键。  
-    ///  
-    /// 如果键不存在，则此操作无效。  
-    ///  
-    /// # 示例  
-    ///  
-    /// 演示基本用法。  
-    ///  
-    /// ```no_run  
-    /// #[tokio::main]  
-    /// async fn main() {  
-    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();  
-    ///  
-    ///     client.set("foo", "bar".into()).await.unwrap();  
-    ///     client.del("foo").await.unwrap();  
-    ///  
-    ///     let val = client.get("foo").await.unwrap();  
-    ///     assert!(val.is_none());  
-    /// }  
-    /// ```  
-    pub async fn del(&mut self, key: &str) -> Result<u64, MiniRedisConnectionError> {  
-        // 构造 DEL 命令的帧  
-        let frame = Del::new(key).into_frame()?;
-        debug!("del request: {:?}", frame);
+    /// 返回所有已连接的客户端，每个客户端一行。
+    ///
+    /// 每行的格式为 `id=<id> addr=<addr> name=<name> age=<秒>`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let list = client.client_list().await.unwrap();
+    ///     print!("{}", list);
+    /// }
+    /// ```
+    pub async fn client_list(&mut self) -> Result<String, MiniRedisConnectionError> {
+        let frame = ClientCmd::list().into_frame();
+        debug!("client list request: {:?}", frame);
+        self.conn.write_frame(&frame).await?;
+        match self.read_response().await? {
+            Frame::Bulk(list) => Ok(String::from_utf8_lossy(&list).into_owned()),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 请求服务器关闭连接。
+    ///
+    /// 服务器回复 `OK` 之后关闭连接，`Client` 不能再继续使用，因此这个方法会消耗 `self`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.quit().await.unwrap();
+    /// }
+    /// ```
+    pub async fn quit(mut self) -> Result<(), MiniRedisConnectionError> {
+        let frame = Quit::new().into_frame();
+        debug!("quit request: {:?}", frame);
+        self.conn.write_frame(&frame).await?;
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
 
-        // 将帧写入套接字  
-        self.conn.write_frame(&frame).await?;  
-  
-        // 读取响应  
-        match self.read_response().await? {  
-            Frame::Integer(deleted_cnt) => Ok(deleted_cnt), 
-            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),  
-        }  
-    }  
+    /// 将连接恢复到刚建立时的状态，例如协议版本恢复为 RESP2。
+    ///
+    /// 处于订阅状态的连接使用 [`Subscriber::reset`]。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.reset().await.unwrap();
+    /// }
+    /// ```
+    pub async fn reset(&mut self) -> Result<(), MiniRedisConnectionError> {
+        let frame = Reset::new().into_frame();
+        debug!("reset request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "RESET" => Ok(()),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 获取键的值。
+    ///
+    /// 如果键不存在，则返回特殊值 `None`；键的值是空字符串时返回 `Some` 包含的空值，两者可以区分。
+    ///
+    /// # 示例
+    ///
+    /// 演示基本用法。
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let val = client.get("foo").await.unwrap();
+    ///     println!("获得 = {:?}", val);
+    /// }
+    /// ```
+    pub async fn get(&mut self, key: &str) -> Result<Option<Bytes>, MiniRedisConnectionError> {
+        let frame = Get::new(key).into_frame();
+        debug!("get request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Null => Ok(None),
+            frame => frame.into_bytes().map(Some),
+        }
+    }
+
+    /// 获取键的值及其剩余的生存时间。
+    ///
+    /// 如果键不存在，则返回 `None`；如果键没有设置过期时间，则返回的生存时间为 `None`。
+    ///
+    /// `GET` 和 `PTTL` 两个请求会被流水线式地一起发送，只需要一次网络往返。
+    ///
+    /// # 示例
+    ///
+    /// 演示基本用法。
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set_expire("foo", "bar".into(), Duration::from_secs(10)).await.unwrap();
+    ///
+    ///     let (val, ttl) = client.get_with_ttl("foo").await.unwrap().unwrap();
+    ///     println!("获得 = {:?}, 剩余时间 = {:?}", val, ttl);
+    /// }
+    /// ```
+    pub async fn get_with_ttl(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<(Bytes, Option<Duration>)>, MiniRedisConnectionError> {
+        let get = Get::new(key).into_frame();
+        let pttl = Ttl::millis(key).into_frame();
+        debug!("get with ttl request: {:?}, {:?}", get, pttl);
+
+        // 先写出两个请求，再按顺序读取两个响应
+        self.conn.write_frame(&get).await?;
+        self.conn.write_frame(&pttl).await?;
+
+        // 先读完两个响应再检查，避免 `GET` 出错时把 `PTTL` 的响应留在连接里
+        let value = self.read_reply().await?;
+        let ttl = self.read_reply().await?;
+
+        let value = match value? {
+            Frame::Simple(v) => Some(Bytes::from(v)),
+            Frame::Bulk(v) => Some(v),
+            Frame::Null => None,
+            frame => return Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        };
+
+        let ttl = match ttl? {
+            Frame::Integer(ms) if ms >= 0 => Some(Duration::from_millis(ms as u64)),
+            // 键没有设置过期时间
+            Frame::Integer(-1) => None,
+            // 键在两个请求之间过期了
+            Frame::Integer(-2) => return Ok(None),
+            frame => return Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        };
+
+        Ok(value.map(|value| (value, ttl)))
+    }
+
+    /// 获取多个键的值，返回值的顺序与 `keys` 相同。
+    ///
+    /// 服务器没有 `MGET` 命令，这里把所有 `GET` 请求流水线式地一起发送，再按顺序读取响应，
+    /// 只需要一次网络往返。某个键的请求返回错误（例如键的值不是字符串）时，只有对应位置是 `Err`，
+    /// 其他键的结果不受影响；连接出错时整个调用返回错误。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///
+    ///     let keys = vec!["foo".to_string(), "missing".to_string()];
+    ///     let values = client.get_multi(&keys).await.unwrap();
+    ///     assert_eq!(values[0].as_ref().unwrap().as_deref(), Some(&b"bar"[..]));
+    ///     assert!(values[1].as_ref().unwrap().is_none());
+    /// }
+    /// ```
+    pub async fn get_multi(
+        &mut self,
+        keys: &[String],
+    ) -> Result<Vec<Result<Option<Bytes>, MiniRedisConnectionError>>, MiniRedisConnectionError>
+    {
+        // 先写出所有请求，最后只刷新一次
+        for key in keys {
+            let frame = Get::new(key).into_frame();
+            debug!("get request: {:?}", frame);
+            self.conn.write_frame_buffered(&frame).await?;
+        }
+        self.conn.flush().await?;
+
+        let mut values = Vec::with_capacity(keys.len());
+        for _ in keys {
+            // 服务器对这个键回复了错误，继续读取其他键的响应
+            let value = match self.read_reply().await? {
+                Ok(Frame::Null) => Ok(None),
+                Ok(frame) => frame.into_bytes().map(Some),
+                Err(err) => Err(err),
+            };
+            values.push(value);
+        }
+
+        Ok(values)
+    }
+
+    /// 设置键的值。
+    ///
+    /// 该值与键关联，直到它被下次调用 `set` 覆盖或被移除。
+    ///
+    /// 如果键已经持有一个值，它将被覆盖。成功的 SET 操作将丢弃与键相关的任何先前的生存时间。
+    ///
+    /// # 示例
+    ///
+    /// 演示基本用法。
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///
+    ///     // 立即获取值
+    ///     let val = client.get("foo").await.unwrap().unwrap();
+    ///     assert_eq!(val, "bar");
+    /// }
+    /// ```
+    pub async fn set(&mut self, key: &str, value: Bytes) -> Result<(), MiniRedisConnectionError> {
+        self.set_cmd(Set::new(key, value, None)).await
+    }
+
+    /// 原子地设置多个键值对。
+    ///
+    /// 与 `set` 一样，已存在的值会被覆盖，之前的生存时间会被丢弃。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.mset(&[("foo", "1".into()), ("bar", "2".into())]).await.unwrap();
+    ///
+    ///     let val = client.get("bar").await.unwrap().unwrap();
+    ///     assert_eq!(val, "2");
+    /// }
+    /// ```
+    pub async fn mset(&mut self, pairs: &[(&str, Bytes)]) -> Result<(), MiniRedisConnectionError> {
+        let pairs = pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect();
+        let frame = MSet::new(pairs).into_frame();
+        debug!("mset request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 获取键的值并删除该键。
+    ///
+    /// 如果键不存在，返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///
+    ///     let val = client.getdel("foo").await.unwrap();
+    ///     assert_eq!(val.unwrap(), "bar");
+    ///     assert!(client.getdel("foo").await.unwrap().is_none());
+    /// }
+    /// ```
+    pub async fn getdel(&mut self, key: &str) -> Result<Option<Bytes>, MiniRedisConnectionError> {
+        let frame = GetDel::new(key).into_frame();
+        debug!("getdel request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(v) => Ok(Some(v)),
+            Frame::Null => Ok(None),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 访问一个或多个键但不返回值，返回其中存在的键的数量。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///
+    ///     let touched = client.touch(&["foo".into(), "missing".into()]).await.unwrap();
+    ///     assert_eq!(touched, 1);
+    /// }
+    /// ```
+    pub async fn touch(&mut self, keys: &[String]) -> Result<u64, MiniRedisConnectionError> {
+        let frame = Touch::new(keys).into_frame();
+        debug!("touch request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(touched) if touched >= 0 => Ok(touched as u64),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 从游标 `cursor` 开始遍历一批键，返回下一次遍历的游标和这一批中匹配 `pattern` 的键。
+    ///
+    /// 第一次调用时 `cursor` 为 0，返回的游标为 0 时遍历完成。`count` 是每批遍历的键数量的提示。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let mut cursor = 0;
+    ///     loop {
+    ///         let (next, keys) = client.scan(cursor, Some("user:*"), None).await.unwrap();
+    ///         println!("{:?}", keys);
+    ///
+    ///         if next == 0 {
+    ///             break;
+    ///         }
+    ///         cursor = next;
+    ///     }
+    /// }
+    /// ```
+    pub async fn scan(
+        &mut self,
+        cursor: u64,
+        pattern: Option<&str>,
+        count: Option<u64>,
+    ) -> Result<(u64, Vec<String>), MiniRedisConnectionError> {
+        let frame = Scan::new(cursor, pattern.map(|p| p.to_string()), count).into_frame();
+        debug!("scan request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(reply) => match &reply[..] {
+                [Frame::Bulk(cursor), Frame::Array(keys)] => {
+                    let cursor = std::str::from_utf8(cursor)
+                        .ok()
+                        .and_then(|cursor| cursor.parse().ok())
+                        .ok_or_else(|| {
+                            MiniRedisConnectionError::CommandExecute(format!(
+                                "invalid cursor: {:?}",
+                                cursor
+                            ))
+                        })?;
+                    let keys = keys
+                        .iter()
+                        .map(|key| match key {
+                            Frame::Bulk(key) => Ok(String::from_utf8_lossy(key).into_owned()),
+                            frame => {
+                                Err(MiniRedisConnectionError::CommandExecute(frame.to_string()))
+                            }
+                        })
+                        .collect::<Result<_, _>>()?;
+                    Ok((cursor, keys))
+                }
+                _ => Err(MiniRedisConnectionError::CommandExecute(
+                    Frame::Array(reply).to_string(),
+                )),
+            },
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 返回一个 `Stream`，逐个返回匹配 `pattern` 的键，`pattern` 为 `None` 时返回所有的键。
+    ///
+    /// 在内部反复调用 [`Client::scan`]，直到服务器返回的游标回到 0。遍历期间一直存在的键一定会被返回；
+    /// 遍历期间被重新设置的键服务器可能返回多次，stream 会记录已经返回的键并跳过重复的键，
+    /// 因此每个键只返回一次，代价是记录的键占用的内存随着遍历增长。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use tokio_stream::StreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let keys = client.scan_iter(Some("user:*".to_string()));
+    ///     tokio::pin!(keys);
+    ///     while let Some(key) = keys.next().await {
+    ///         println!("{}", key.unwrap());
+    ///     }
+    /// }
+    /// ```
+    pub fn scan_iter(
+        &mut self,
+        pattern: Option<String>,
+    ) -> impl Stream<Item = Result<String, MiniRedisConnectionError>> + '_ {
+        try_stream! {
+            let mut seen = HashSet::new();
+            let mut cursor = 0;
+            loop {
+                let (next, keys) = self.scan(cursor, pattern.as_deref(), None).await?;
+                for key in keys {
+                    if seen.insert(key.clone()) {
+                        yield key;
+                    }
+                }
+                if next == 0 {
+                    break;
+                }
+                cursor = next;
+            }
+        }
+    }
+
+    /// 将 `values` 依次插入列表 `key` 的头部，返回插入后列表的长度。
+    ///
+    /// 如果 `key` 不存在，则先创建一个空列表。如果 `key` 的值不是列表，返回
+    /// `MiniRedisConnectionError::WrongType`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let len = client.lpush("list", &["a".into(), "b".into()]).await.unwrap();
+    ///     assert_eq!(len, 2);
+    ///
+    ///     let values = client.lrange("list", 0, -1).await.unwrap();
+    ///     assert_eq!(values, vec!["b", "a"]);
+    /// }
+    /// ```
+    pub async fn lpush(
+        &mut self,
+        key: &str,
+        values: &[Bytes],
+    ) -> Result<u64, MiniRedisConnectionError> {
+        let frame = LPush::new(key, values.to_vec()).into_frame();
+        self.push_cmd(frame).await
+    }
+
+    /// 将 `values` 依次插入列表 `key` 的尾部，返回插入后列表的长度。
+    ///
+    /// 如果 `key` 不存在，则先创建一个空列表。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.rpush("list", &["a".into(), "b".into()]).await.unwrap();
+    ///
+    ///     let values = client.lrange("list", 0, -1).await.unwrap();
+    ///     assert_eq!(values, vec!["a", "b"]);
+    /// }
+    /// ```
+    pub async fn rpush(
+        &mut self,
+        key: &str,
+        values: &[Bytes],
+    ) -> Result<u64, MiniRedisConnectionError> {
+        let frame = RPush::new(key, values.to_vec()).into_frame();
+        self.push_cmd(frame).await
+    }
+
+    /// 核心 `LPUSH`/`RPUSH` 逻辑，由 `lpush` 和 `rpush` 使用。
+    async fn push_cmd(&mut self, frame: Frame) -> Result<u64, MiniRedisConnectionError> {
+        debug!("push request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(len) if len >= 0 => Ok(len as u64),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 移除并返回列表 `key` 头部的值。
+    ///
+    /// 如果 `key` 不存在，则返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.rpush("list", &["a".into(), "b".into()]).await.unwrap();
+    ///
+    ///     let val = client.lpop("list").await.unwrap();
+    ///     assert_eq!(val, Some("a".into()));
+    /// }
+    /// ```
+    pub async fn lpop(&mut self, key: &str) -> Result<Option<Bytes>, MiniRedisConnectionError> {
+        let frame = LPop::new(key).into_frame();
+        self.pop_cmd(frame).await
+    }
+
+    /// 移除并返回列表 `key` 尾部的值。
+    ///
+    /// 如果 `key` 不存在，则返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.rpush("list", &["a".into(), "b".into()]).await.unwrap();
+    ///
+    ///     let val = client.rpop("list").await.unwrap();
+    ///     assert_eq!(val, Some("b".into()));
+    /// }
+    /// ```
+    pub async fn rpop(&mut self, key: &str) -> Result<Option<Bytes>, MiniRedisConnectionError> {
+        let frame = RPop::new(key).into_frame();
+        self.pop_cmd(frame).await
+    }
+
+    /// 核心 `LPOP`/`RPOP` 逻辑，由 `lpop` 和 `rpop` 使用。
+    async fn pop_cmd(&mut self, frame: Frame) -> Result<Option<Bytes>, MiniRedisConnectionError> {
+        debug!("pop request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(Some(value)),
+            Frame::Null => Ok(None),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 移除并返回 `keys` 中第一个非空列表头部的值，所有列表都为空时阻塞等待。
+    ///
+    /// 返回值所在的键和值。等待超过 `timeout` 时返回 `None`，`timeout` 为零表示一直等待。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let popped = client.blpop(&["list".into()], Duration::from_secs(1)).await.unwrap();
+    ///     if let Some((key, value)) = popped {
+    ///         println!("popped {:?} from {}", value, key);
+    ///     }
+    /// }
+    /// ```
+    pub async fn blpop(
+        &mut self,
+        keys: &[String],
+        timeout: Duration,
+    ) -> Result<Option<(String, Bytes)>, MiniRedisConnectionError> {
+        let frame = BPop::left(keys, timeout).into_frame();
+        self.blocking_pop_cmd(frame).await
+    }
+
+    /// 移除并返回 `keys` 中第一个非空列表尾部的值，所有列表都为空时阻塞等待。
+    ///
+    /// 与 [`Client::blpop`] 相同，只是从列表尾部移除。
+    pub async fn brpop(
+        &mut self,
+        keys: &[String],
+        timeout: Duration,
+    ) -> Result<Option<(String, Bytes)>, MiniRedisConnectionError> {
+        let frame = BPop::right(keys, timeout).into_frame();
+        self.blocking_pop_cmd(frame).await
+    }
+
+    /// 核心 `BLPOP`/`BRPOP` 逻辑，由 `blpop` 和 `brpop` 使用。
+    async fn blocking_pop_cmd(
+        &mut self,
+        frame: Frame,
+    ) -> Result<Option<(String, Bytes)>, MiniRedisConnectionError> {
+        debug!("blocking pop request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        let mut parts = match self.read_response().await? {
+            Frame::Null => return Ok(None),
+            frame => frame.into_array()?.into_iter(),
+        };
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(key), Some(value), None) => Ok(Some((key.into_string()?, value.into_bytes()?))),
+            _ => Err(MiniRedisConnectionError::InvalidFrameType),
+        }
+    }
+
+    /// 返回列表 `key` 中下标从 `start` 到 `stop`（包含）的值，负数下标从列表尾部开始计算。
+    ///
+    /// 如果 `key` 不存在，则返回空的 `Vec`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.rpush("list", &["a".into(), "b".into(), "c".into()]).await.unwrap();
+    ///
+    ///     let values = client.lrange("list", -2, -1).await.unwrap();
+    ///     assert_eq!(values, vec!["b", "c"]);
+    /// }
+    /// ```
+    pub async fn lrange(
+        &mut self,
+        key: &str,
+        start: i64,
+        stop: i64,
+    ) -> Result<Vec<Bytes>, MiniRedisConnectionError> {
+        let frame = LRange::new(key, start, stop).into_frame();
+        debug!("lrange request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        self.read_response()
+            .await?
+            .into_array()?
+            .into_iter()
+            .map(Frame::into_bytes)
+            .collect()
+    }
+
+    /// 返回列表 `key` 的长度，`key` 不存在时返回 0。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.rpush("list", &["a".into(), "b".into()]).await.unwrap();
+    ///
+    ///     let len = client.llen("list").await.unwrap();
+    ///     assert_eq!(len, 2);
+    /// }
+    /// ```
+    pub async fn llen(&mut self, key: &str) -> Result<u64, MiniRedisConnectionError> {
+        let frame = LLen::new(key).into_frame();
+        debug!("llen request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(len) if len >= 0 => Ok(len as u64),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 返回 `key` 的值的编码名称，例如 `int`、`embstr`。
+    ///
+    /// 如果 `key` 不存在，则返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("counter", "10".into()).await.unwrap();
+    ///
+    ///     let encoding = client.object_encoding("counter").await.unwrap();
+    ///     assert_eq!(encoding.as_deref(), Some("int"));
+    /// }
+    /// ```
+    pub async fn object_encoding(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<String>, MiniRedisConnectionError> {
+        let frame = Object::encoding(key).into_frame();
+        debug!("object encoding request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(encoding) => Ok(Some(String::from_utf8_lossy(&encoding).into_owned())),
+            Frame::Null => Ok(None),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 返回 `key` 距离上次被访问经过的时间，精确到秒。查询本身不算一次访问。
+    ///
+    /// 如果 `key` 不存在，则返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let idle = client.object_idle_time("foo").await.unwrap();
+    ///     println!("idle for {:?}", idle);
+    /// }
+    /// ```
+    pub async fn object_idle_time(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<Duration>, MiniRedisConnectionError> {
+        let frame = Object::idle_time(key).into_frame();
+        debug!("object idletime request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(secs) if secs >= 0 => Ok(Some(Duration::from_secs(secs as u64))),
+            Frame::Null => Ok(None),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 返回 `key` 的值的引用计数。值不会在键之间共享，存在的键总是返回 1。
+    ///
+    /// 如果 `key` 不存在，则返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let refcount = client.object_refcount("foo").await.unwrap();
+    ///     println!("refcount {:?}", refcount);
+    /// }
+    /// ```
+    pub async fn object_refcount(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<u64>, MiniRedisConnectionError> {
+        let frame = Object::refcount(key).into_frame();
+        debug!("object refcount request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(refcount) if refcount >= 0 => Ok(Some(refcount as u64)),
+            Frame::Null => Ok(None),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 估算 `key` 占用的内存，单位是字节，包括键名、值和每个键固定的开销。
+    ///
+    /// 如果 `key` 不存在，则返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///
+    ///     let usage = client.memory_usage("foo").await.unwrap();
+    ///     println!("foo uses about {:?} bytes", usage);
+    /// }
+    /// ```
+    pub async fn memory_usage(
+        &mut self,
+        key: &str,
+    ) -> Result<Option<u64>, MiniRedisConnectionError> {
+        let frame = Memory::usage(key).into_frame();
+        debug!("memory usage request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(usage) if usage >= 0 => Ok(Some(usage as u64)),
+            Frame::Null => Ok(None),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 设置哈希表 `key` 中的字段，返回新增的字段数量，已有字段的值会被覆盖。
+    ///
+    /// 如果 `key` 不存在，则先创建一个空的哈希表。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let added = client
+    ///         .hset("user", &[("name", "alice".into()), ("age", "30".into())])
+    ///         .await
+    ///         .unwrap();
+    ///     assert_eq!(added, 2);
+    /// }
+    /// ```
+    pub async fn hset(
+        &mut self,
+        key: &str,
+        pairs: &[(&str, Bytes)],
+    ) -> Result<u64, MiniRedisConnectionError> {
+        let pairs = pairs
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.clone()))
+            .collect();
+        let frame = HSet::new(key, pairs).into_frame();
+        debug!("hset request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(added) if added >= 0 => Ok(added as u64),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 获取哈希表 `key` 中字段 `field` 的值。
+    ///
+    /// 如果 `key` 或者字段不存在，则返回 `None`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.hset("user", &[("name", "alice".into())]).await.unwrap();
+    ///
+    ///     let name = client.hget("user", "name").await.unwrap();
+    ///     assert_eq!(name, Some("alice".into()));
+    /// }
+    /// ```
+    pub async fn hget(
+        &mut self,
+        key: &str,
+        field: &str,
+    ) -> Result<Option<Bytes>, MiniRedisConnectionError> {
+        let frame = HGet::new(key, field).into_frame();
+        debug!("hget request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(Some(value)),
+            Frame::Null => Ok(None),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 获取哈希表 `key` 中所有的字段和值。
+    ///
+    /// 如果 `key` 不存在，则返回空的 `HashMap`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.hset("user", &[("name", "alice".into())]).await.unwrap();
+    ///
+    ///     let user = client.hgetall("user").await.unwrap();
+    ///     assert_eq!(user["name"], "alice");
+    /// }
+    /// ```
+    pub async fn hgetall(
+        &mut self,
+        key: &str,
+    ) -> Result<HashMap<String, Bytes>, MiniRedisConnectionError> {
+        let frame = HGetAll::new(key).into_frame();
+        debug!("hgetall request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        // RESP2 下服务器回复字段和值交替排列的数组，RESP3 下回复 Map
+        let entries = match self.read_response().await? {
+            Frame::Array(frames) if frames.len() % 2 == 0 => {
+                let mut entries = Vec::with_capacity(frames.len() / 2);
+                let mut frames = frames.into_iter();
+                while let (Some(field), Some(value)) = (frames.next(), frames.next()) {
+                    entries.push((field, value));
+                }
+                entries
+            }
+            Frame::Map(entries) => entries,
+            frame => return Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        };
+
+        entries
+            .into_iter()
+            .map(|entry| match entry {
+                (Frame::Bulk(field), Frame::Bulk(value)) => {
+                    Ok((String::from_utf8_lossy(&field).into_owned(), value))
+                }
+                (field, value) => Err(MiniRedisConnectionError::CommandExecute(format!(
+                    "{} {}",
+                    field, value
+                ))),
+            })
+            .collect()
+    }
+
+    /// 删除哈希表 `key` 中的字段，返回实际删除的字段数量。
+    ///
+    /// 哈希表中最后一个字段被删除后，`key` 也会被删除。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.hset("user", &[("name", "alice".into())]).await.unwrap();
+    ///
+    ///     let removed = client
+    ///         .hdel("user", &["name".to_string(), "age".to_string()])
+    ///         .await
+    ///         .unwrap();
+    ///     assert_eq!(removed, 1);
+    /// }
+    /// ```
+    pub async fn hdel(
+        &mut self,
+        key: &str,
+        fields: &[String],
+    ) -> Result<u64, MiniRedisConnectionError> {
+        let frame = HDel::new(key, fields).into_frame();
+        debug!("hdel request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(removed) if removed >= 0 => Ok(removed as u64),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 判断哈希表 `key` 中是否存在字段 `field`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.hset("user", &[("name", "alice".into())]).await.unwrap();
+    ///
+    ///     assert!(client.hexists("user", "name").await.unwrap());
+    ///     assert!(!client.hexists("user", "age").await.unwrap());
+    /// }
+    /// ```
+    pub async fn hexists(
+        &mut self,
+        key: &str,
+        field: &str,
+    ) -> Result<bool, MiniRedisConnectionError> {
+        let frame = HExists::new(key, field).into_frame();
+        debug!("hexists request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(exists) => Ok(exists == 1),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 返回哈希表 `key` 中字段的数量，`key` 不存在时返回 0。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.hset("user", &[("name", "alice".into())]).await.unwrap();
+    ///
+    ///     let len = client.hlen("user").await.unwrap();
+    ///     assert_eq!(len, 1);
+    /// }
+    /// ```
+    pub async fn hlen(&mut self, key: &str) -> Result<u64, MiniRedisConnectionError> {
+        let frame = HLen::new(key).into_frame();
+        debug!("hlen request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(len) if len >= 0 => Ok(len as u64),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 向集合 `key` 中添加成员，返回新增的成员数量。
+    ///
+    /// 已经在集合中的成员不会计入返回值。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let added = client
+    ///         .sadd("tags", &["a".into(), "b".into(), "a".into()])
+    ///         .await
+    ///         .unwrap();
+    ///     assert_eq!(added, 2);
+    /// }
+    /// ```
+    pub async fn sadd(
+        &mut self,
+        key: &str,
+        members: &[Bytes],
+    ) -> Result<u64, MiniRedisConnectionError> {
+        let frame = SAdd::new(key, members).into_frame();
+        debug!("sadd request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(added) if added >= 0 => Ok(added as u64),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 获取集合 `key` 中所有的成员，顺序不确定。
+    ///
+    /// 如果 `key` 不存在，则返回空的 `Vec`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.sadd("tags", &["a".into()]).await.unwrap();
+    ///
+    ///     let members = client.smembers("tags").await.unwrap();
+    ///     assert_eq!(members, vec!["a"]);
+    /// }
+    /// ```
+    pub async fn smembers(&mut self, key: &str) -> Result<Vec<Bytes>, MiniRedisConnectionError> {
+        let frame = SMembers::new(key).into_frame();
+        debug!("smembers request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(frames) => frames
+                .into_iter()
+                .map(|frame| match frame {
+                    Frame::Bulk(member) => Ok(member),
+                    frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+                })
+                .collect(),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 判断 `member` 是否在集合 `key` 中。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.sadd("tags", &["a".into()]).await.unwrap();
+    ///
+    ///     assert!(client.sismember("tags", "a".into()).await.unwrap());
+    ///     assert!(!client.sismember("tags", "b".into()).await.unwrap());
+    /// }
+    /// ```
+    pub async fn sismember(
+        &mut self,
+        key: &str,
+        member: Bytes,
+    ) -> Result<bool, MiniRedisConnectionError> {
+        let frame = SIsMember::new(key, member).into_frame();
+        debug!("sismember request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(exists) => Ok(exists == 1),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 将 `value` 追加到 `key` 原有的值之后，返回追加后值的长度。
+    ///
+    /// 如果 `key` 不存在，则等同于 `set`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "hello".into()).await.unwrap();
+    ///     let len = client.append("foo", " world".into()).await.unwrap();
+    ///     assert_eq!(len, 11);
+    /// }
+    /// ```
+    pub async fn append(
+        &mut self,
+        key: &str,
+        value: Bytes,
+    ) -> Result<u64, MiniRedisConnectionError> {
+        let frame = Append::new(key, value).into_frame();
+        debug!("append request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(len) if len >= 0 => Ok(len as u64),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 从 `offset` 开始用 `value` 覆盖 `key` 的字符串值的一部分，返回修改后字符串的长度。
+    ///
+    /// 字符串不够长时用 0 补齐，`key` 不存在时视为空字符串。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "Hello World".into()).await.unwrap();
+    ///
+    ///     let len = client.setrange("foo", 6, "Redis".into()).await.unwrap();
+    ///     assert_eq!(len, 11);
+    /// }
+    /// ```
+    pub async fn setrange(
+        &mut self,
+        key: &str,
+        offset: u64,
+        value: Bytes,
+    ) -> Result<u64, MiniRedisConnectionError> {
+        let frame = SetRange::new(key, offset, value).into_frame();
+        debug!("setrange request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(len) if len >= 0 => Ok(len as u64),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 将 `key` 的字符串值中第 `offset` 位设置为 `value`，返回这一位原来的值。
+    ///
+    /// 位从第一个字节的最高位开始编号，字符串不够长时用 0 补齐。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let old = client.setbit("foo", 7, true).await.unwrap();
+    ///     assert!(!old);
+    /// }
+    /// ```
+    pub async fn setbit(
+        &mut self,
+        key: &str,
+        offset: u64,
+        value: bool,
+    ) -> Result<bool, MiniRedisConnectionError> {
+        let frame = SetBit::new(key, offset, value).into_frame();
+        debug!("setbit request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(bit) => Ok(bit == 1),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 返回 `key` 的字符串值中第 `offset` 位的值，超出字符串长度时返回 `false`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.setbit("foo", 7, true).await.unwrap();
+    ///     assert!(client.getbit("foo", 7).await.unwrap());
+    /// }
+    /// ```
+    pub async fn getbit(
+        &mut self,
+        key: &str,
+        offset: u64,
+    ) -> Result<bool, MiniRedisConnectionError> {
+        let frame = GetBit::new(key, offset).into_frame();
+        debug!("getbit request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(bit) => Ok(bit == 1),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 将 `key` 的值解析为浮点数并加上 `increment`，返回相加之后的值。
+    ///
+    /// 如果 `key` 不存在，则视为 0。`increment` 可以是负数。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "10.5".into()).await.unwrap();
+    ///     let value = client.incrbyfloat("foo", 0.1).await.unwrap();
+    ///     assert_eq!(value, 10.6);
+    /// }
+    /// ```
+    pub async fn incrbyfloat(
+        &mut self,
+        key: &str,
+        increment: f64,
+    ) -> Result<f64, MiniRedisConnectionError> {
+        let frame = IncrByFloat::new(key, increment).into_frame();
+        debug!("incrbyfloat request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        let response = self.read_response().await?;
+        let value = response.into_string()?;
+        value
+            .parse()
+            .map_err(|_| MiniRedisConnectionError::CommandExecute(value))
+    }
+
+    /// 将 `key` 重命名为 `newkey`，值和剩余生存时间一起移动，`newkey` 原有的值会被覆盖。
+    ///
+    /// 如果 `key` 不存在，返回错误。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///     client.rename("foo", "baz").await.unwrap();
+    ///
+    ///     let val = client.get("baz").await.unwrap().unwrap();
+    ///     assert_eq!(val, "bar");
+    /// }
+    /// ```
+    pub async fn rename(
+        &mut self,
+        key: &str,
+        newkey: &str,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let frame = Rename::new(key, newkey).into_frame();
+        debug!("rename request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 序列化 `key` 的值，`key` 不存在时返回 `None`。
+    ///
+    /// 返回的数据可以交给另一个服务器的 [`Client::restore`]，用来迁移这个键。数据中不包含过期时间。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut src = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///     let mut dst = mini_redis::client::connect("localhost:6380").await.unwrap();
+    ///
+    ///     src.set("foo", "bar".into()).await.unwrap();
+    ///
+    ///     let payload = src.dump("foo").await.unwrap().unwrap();
+    ///     dst.restore("foo", None, payload, false).await.unwrap();
+    /// }
+    /// ```
+    pub async fn dump(&mut self, key: &str) -> Result<Option<Bytes>, MiniRedisConnectionError> {
+        let frame = Dump::new(key).into_frame();
+        debug!("dump request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(payload) => Ok(Some(payload)),
+            Frame::Null => Ok(None),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 使用 [`Client::dump`] 返回的数据创建 `key`，`ttl` 为 `None` 时不过期。
+    ///
+    /// `key` 已经存在时返回 `BUSYKEY` 错误，`replace` 为 `true` 时覆盖原有的值。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///     let payload = client.dump("foo").await.unwrap().unwrap();
+    ///
+    ///     client
+    ///         .restore("copy", Some(Duration::from_secs(10)), payload, false)
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub async fn restore(
+        &mut self,
+        key: &str,
+        ttl: Option<Duration>,
+        payload: Bytes,
+        replace: bool,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let frame = Restore::new(key, ttl, payload, replace).into_frame();
+        debug!("restore request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 设置键的值。该值在 `expiration` 之后过期。
+    ///
+    /// 该值与键关联，直到以下之一：
+    /// - 它过期。
+    /// - 它被下次调用 `set` 覆盖。
+    /// - 它被移除。
+    ///
+    /// 如果键已经持有一个值，它将被覆盖。成功的 SET 操作将丢弃与键相关的任何先前的生存时间。
+    ///
+    /// # 示例
+    ///
+    /// 演示基本用法。此示例不能 **保证** 始终有效，因为它依赖于基于时间的逻辑并假设客户端和服务器在时间上保持相对同步。
+    /// 现实世界往往不会那么有利。
+    ///
+    /// ```no_run
+    /// use tokio::time;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let ttl = Duration::from_millis(500);
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set_expire("foo", "bar".into(), ttl).await.unwrap();
+    ///
+    ///     // 立即获取值
+    ///     let val = client.get("foo").await.unwrap().unwrap();
+    ///     assert_eq!(val, "bar");
+    ///
+    ///     // 等待 TTL 过期
+    ///     time::sleep(ttl).await;
+    ///
+    ///     let val = client.get("foo").await.unwrap();
+    ///     assert!(val.is_none());
+    /// }
+    /// ```
+    pub async fn set_expire(
+        &mut self,
+        key: &str,
+        value: Bytes,
+        epxiration: Duration,
+    ) -> Result<(), MiniRedisConnectionError> {
+        // 创建一个 `Set` 命令并传递给 `set_cmd`。一个单独的方法用于设置带有过期时间的值。
+        // 两个函数的共同部分由 `set_cmd` 实现。
+        self.set_cmd(Set::new(key, value, Some(epxiration))).await
+    }
+
+    /// 使用 `SETEX` 设置 `key` 保存 `value`，同时设置生存时间，精确到秒。
+    ///
+    /// 生存时间不足一秒时服务器返回 `invalid expire time` 错误。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.setex("foo", Duration::from_secs(10), "bar".into()).await.unwrap();
+    /// }
+    /// ```
+    pub async fn setex(
+        &mut self,
+        key: &str,
+        expiration: Duration,
+        value: Bytes,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let frame = SetEx::new(key, expiration, value).into_frame();
+        debug!("setex request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 为已存在的 `key` 设置生存时间，精确到秒，覆盖原有的过期时间。
+    ///
+    /// 返回 `key` 是否存在。生存时间为零时 `key` 会被立即删除。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///
+    ///     let updated = client.expire("foo", Duration::from_secs(10)).await.unwrap();
+    ///     assert!(updated);
+    /// }
+    /// ```
+    pub async fn expire(
+        &mut self,
+        key: &str,
+        expiration: Duration,
+    ) -> Result<bool, MiniRedisConnectionError> {
+        let frame = Expire::new(key, expiration.as_secs() as i64).into_frame();
+        debug!("expire request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(updated) => Ok(updated == 1),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 将已存在的 `key` 的过期时间设置为时间点 `when`，精确到秒，覆盖原有的过期时间。
+    ///
+    /// 返回 `key` 是否存在。已经过去的时间点会让 `key` 被立即删除。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///
+    ///     let when = SystemTime::now() + Duration::from_secs(10);
+    ///     let updated = client.expireat("foo", when).await.unwrap();
+    ///     assert!(updated);
+    /// }
+    /// ```
+    pub async fn expireat(
+        &mut self,
+        key: &str,
+        when: SystemTime,
+    ) -> Result<bool, MiniRedisConnectionError> {
+        // 早于 Unix 纪元的时间点同样已经过去
+        let timestamp = when
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs() as i64)
+            .unwrap_or(0);
+        let frame = ExpireAt::new(key, timestamp).into_frame();
+        debug!("expireat request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(updated) => Ok(updated == 1),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 向指定的 `channel` 发布 `message`，同时返回服务器上已经没有订阅者的频道数量。
+    ///
+    /// 订阅者断开之后频道仍然保留在服务器上，可以用 `stale_channels` 发现这些失效的频道。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let details = client.publish_detailed("foo", "bar".into()).await.unwrap();
+    ///     println!("delivered to {} subscribers", details.receivers);
+    /// }
+    /// ```
+    pub async fn publish_detailed(
+        &mut self,
+        channel: &str,
+        message: Bytes,
+    ) -> Result<PublishDetails, MiniRedisConnectionError> {
+        let frame = Publish::detailed(channel, message).into_frame();
+        debug!("publish request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        let reply = self.read_response().await?.into_array()?;
+        match reply[..] {
+            [Frame::Integer(receivers), Frame::Integer(stale_channels)]
+                if receivers >= 0 && stale_channels >= 0 =>
+            {
+                Ok(PublishDetails {
+                    receivers: receivers as u64,
+                    stale_channels: stale_channels as u64,
+                })
+            }
+            _ => Err(MiniRedisConnectionError::CommandExecute(
+                Frame::Array(reply).to_string(),
+            )),
+        }
+    }
+
+    /// 向指定的 `channel` 发布 `message`。
+    ///
+    /// 返回当前在频道上收听的订阅者数量。不能保证这些订阅者会收到消息，因为他们可能随时断开连接。
+    ///
+    /// # 示例
+    ///
+    /// 演示基本用法。
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let val = client.publish("foo", "bar".into()).await.unwrap();
+    ///     println!("获得 = {:?}", val);
+    /// }
+    /// ```
+    pub async fn publish(
+        &mut self,
+        channel: &str,
+        message: Bytes,
+    ) -> Result<u64, MiniRedisConnectionError> {
+        // 将 `Publish` 命令转换为帧
+        let frame = Publish::new(channel, message).into_frame();
+        debug!("publish request: {:?}", frame);
+        // 将帧写入套接字
+        self.conn.write_frame(&frame).await?;
+        // 读取响应，订阅者数量不会是负数
+        match self.read_response().await?.into_integer()? {
+            receivers if receivers >= 0 => Ok(receivers as u64),
+            receivers => Err(MiniRedisConnectionError::CommandExecute(
+                receivers.to_string(),
+            )),
+        }
+    }
+
+    /// 订阅客户端到指定的频道。
+    ///
+    /// 一旦客户端发出订阅命令，它不能再发出任何非发布/订阅命令。该函数消耗 `self` 并返回一个 `Subscriber`。
+    ///
+    /// `Subscriber` 值用于接收消息以及管理客户端订阅的频道列表。
+    pub async fn subscribe(
+        mut self,
+        channels: Vec<String>,
+    ) -> Result<Subscriber, MiniRedisConnectionError> {
+        // 向服务器发出订阅命令并等待确认。
+        // 然后客户端将被转换为“订阅者”状态，从那时起只能发出发布/订阅命令。
+        self.subscribe_cmd(&channels).await?;
+        Ok(Subscriber {
+            client: self,
+            subscribed_channels: channels,
+        })
+    }
+
+    /// 删除一个或多个键，返回实际删除的键的数量。
+    ///
+    /// 与 `del` 相同，但服务器在后台释放被删除的值，适合删除很大的值。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///
+    ///     let unlinked = client.unlink(&["foo".into(), "missing".into()]).await.unwrap();
+    ///     assert_eq!(unlinked, 1);
+    /// }
+    /// ```
+    pub async fn unlink(&mut self, keys: &[String]) -> Result<u64, MiniRedisConnectionError> {
+        let frame = Unlink::new(keys).into_frame();
+        debug!("unlink request: {:?}", frame);
+
+        self.conn.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(unlinked) if unlinked >= 0 => Ok(unlinked as u64),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    /// 删除指定的键。  
+    ///  
+    /// 如果键不存在，则此操作无效。  
+    ///  
+    /// # 示例  
+    ///  
+    /// 演示基本用法。  
+    ///  
+    /// ```no_run  
+    /// #[tokio::main]  
+    /// async fn main() {  
+    ///     let mut client = mini_redis::client::connect("localhost:6379").await.unwrap();  
+    ///  
+    ///     client.set("foo", "bar".into()).await.unwrap();  
+    ///     client.del("foo").await.unwrap();  
+    ///  
+    ///     let val = client.get("foo").await.unwrap();  
+    ///     assert!(val.is_none());  
+    /// }  
+    /// ```  
+    pub async fn del(&mut self, key: &str) -> Result<u64, MiniRedisConnectionError> {
+        // 构造 DEL 命令的帧
+        let frame = Del::new(key).into_frame();
+        debug!("del request: {:?}", frame);
+
+        // 将帧写入套接字
+        self.conn.write_frame(&frame).await?;
+
+        // 读取响应
+        match self.read_response().await? {
+            Frame::Integer(deleted_cnt) if deleted_cnt >= 0 => Ok(deleted_cnt as u64),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
 }
@@ -9,6 +9,9 @@ pub mod cli;
 pub mod cmd;
 mod subscriber; // 订阅者模块，内部使用，因此不公开
 
+pub use crate::connection::frame::Frame;
+pub use subscriber::{ResilientSubscriber, SubscriberEvent};
+
 // 定义异步函数 connect，用于创建与 Redis 服务器的连接
 // 泛型参数T必须实现 ToSocketAddrs 特质，允许传入多种类型的地址
 pub async fn connect<T: ToSocketAddrs>(addr: T) -> Result<Client, MiniRedisConnectionError> {
@@ -21,4 +24,4 @@ pub async fn connect<T: ToSocketAddrs>(addr: T) -> Result<Client, MiniRedisConne
 
     // 返回Client实例，包含已建立的连接
     Ok(Client { conn })
-}
\ No newline at end of file
+}
@@ -1,9 +1,14 @@
+use std::time::Duration;
+
 use async_stream::try_stream;
 use bytes::Bytes;
-use log::{debug, error};
+use log::{debug, error, warn};
+use tokio::time;
 use tokio_stream::Stream;
 
 use crate::client::cli::Client;
+use crate::client::connect;
+use crate::cmd::reset::Reset;
 use crate::cmd::unsubscribe::Unsubscribe;
 use crate::connection::frame::Frame;
 use crate::error::MiniRedisConnectionError;
@@ -26,6 +31,38 @@ pub struct Message {
     pub content: Bytes,
 }
 
+/// 断线后自动重连的订阅者。
+///
+/// 读取消息时如果发现连接已断开，会按指数退避重新连接服务器，并重新订阅之前订阅的所有频道。
+/// 每次重连成功后先返回一个 `SubscriberEvent::Reconnected`，提醒调用者断开期间发布的消息可能已经丢失。
+pub struct ResilientSubscriber {
+    /// 服务器地址，重连时使用
+    addr: String,
+    /// 当前的订阅连接，连接断开后为 `None`
+    subscriber: Option<Subscriber>,
+    /// 订阅的频道集合，重连后会重新订阅这些频道
+    subscribed_channels: Vec<String>,
+    /// 第一次重试前的等待时间
+    initial_backoff: Duration,
+    /// 两次重试之间的最长等待时间
+    max_backoff: Duration,
+}
+
+/// `ResilientSubscriber` 收到的事件
+#[derive(Debug, Clone)]
+pub enum SubscriberEvent {
+    /// 在订阅的频道上收到的消息
+    Message(Message),
+    /// 连接断开后已经重新连接并恢复了订阅，断开期间发布的消息可能已经丢失
+    Reconnected,
+}
+
+/// 重连时第一次重试前的默认等待时间
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// 重连时两次重试之间的默认最长等待时间
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
 impl Subscriber {
     /// 订阅新的频道列表
     pub async fn subscribe(&mut self, channels: &[String]) -> Result<(), MiniRedisConnectionError> {
@@ -60,7 +97,7 @@ impl Subscriber {
                         })),
                         _ => {
                             error!("invalid message, frame: {:?}", frame);
-                            return Err(MiniRedisConnectionError::InvalidFrameType);
+                            Err(MiniRedisConnectionError::InvalidFrameType)
                         }
                     },
                     frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
@@ -70,6 +107,17 @@ impl Subscriber {
         }
     }
 
+    /// 接收在订阅的频道上发布的下一条消息，最多等待 `timeout`。
+    ///
+    /// 如果在 `timeout` 内没有收到消息，返回 `MiniRedisConnectionError::Timeout`。`None` 表示订阅已终止。
+    /// 超时不会丢失数据，之后仍然可以继续接收消息。
+    pub async fn next_message_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Option<Message>, MiniRedisConnectionError> {
+        time::timeout(timeout, self.next_message()).await?
+    }
+
     /// 将订阅者转换为 `Stream`，返回在订阅的频道上发布的新消息。
     ///
     /// `Subscriber` 自身不实现 stream，因为使用安全代码实现这个功能并非易事。
@@ -84,12 +132,26 @@ impl Subscriber {
         }
     }
 
+    /// 与 `into_stream` 相同，但如果在 `timeout` 内没有收到新消息，stream 返回一个超时错误并结束。
+    ///
+    /// 每收到一条消息都会重新开始计时，因此 `timeout` 限制的是两条消息之间的最长空闲时间。
+    pub fn into_stream_with_timeout(
+        mut self,
+        timeout: Duration,
+    ) -> impl Stream<Item = Result<Message, MiniRedisConnectionError>> {
+        try_stream! {
+            while let Some(message) = self.next_message_timeout(timeout).await? {
+                yield message;
+            }
+        }
+    }
+
     /// 取消订阅新的频道列表
     pub async fn unsubscribe(
         &mut self,
         channels: &[String],
     ) -> Result<(), MiniRedisConnectionError> {
-        let frame = Unsubscribe::new(channels).into_frame()?;
+        let frame = Unsubscribe::new(channels).into_frame();
         debug!("unsubscribe request: {:?}", frame);
         self.client.conn.write_frame(&frame).await?;
 
@@ -135,4 +197,200 @@ impl Subscriber {
 
         Ok(())
     }
+
+    /// 取消所有订阅并退出订阅状态，返回可以执行普通命令的 `Client`。
+    ///
+    /// 服务器收到 `RESET` 之前发布的消息仍然会先到达，这些消息被丢弃。
+    pub async fn reset(mut self) -> Result<Client, MiniRedisConnectionError> {
+        let frame = Reset::new().into_frame();
+        debug!("reset request: {:?}", frame);
+        self.client.conn.write_frame(&frame).await?;
+
+        loop {
+            let response = self.client.read_response().await?;
+            match response {
+                Frame::Simple(ref reset) if reset == "RESET" => return Ok(self.client),
+                Frame::Array(ref frame) => match frame.as_slice() {
+                    [message, ..] if *message == "message" => {
+                        debug!("drop message received before reset: {}", response);
+                    }
+                    _ => return Err(MiniRedisConnectionError::InvalidFrameType),
+                },
+                frame => return Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+            }
+        }
+    }
+}
+
+impl ResilientSubscriber {
+    /// 连接服务器并订阅指定的频道。
+    ///
+    /// 第一次连接失败时直接返回错误，之后的断线才会自动重连。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::client::{ResilientSubscriber, SubscriberEvent};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut subscriber = ResilientSubscriber::connect("localhost:6379", vec!["news".into()])
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     loop {
+    ///         match subscriber.next_message().await.unwrap() {
+    ///             SubscriberEvent::Message(msg) => println!("{}: {:?}", msg.channel, msg.content),
+    ///             SubscriberEvent::Reconnected => println!("reconnected, messages may be lost"),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn connect(
+        addr: impl Into<String>,
+        channels: Vec<String>,
+    ) -> Result<ResilientSubscriber, MiniRedisConnectionError> {
+        let addr = addr.into();
+        let subscriber = connect(addr.as_str())
+            .await?
+            .subscribe(channels.clone())
+            .await?;
+
+        Ok(ResilientSubscriber {
+            addr,
+            subscriber: Some(subscriber),
+            subscribed_channels: channels,
+            initial_backoff: INITIAL_BACKOFF,
+            max_backoff: MAX_BACKOFF,
+        })
+    }
+
+    /// 设置重连的退避时间：第一次重试前等待 `initial`，之后每次翻倍，最长不超过 `max`。
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> ResilientSubscriber {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// 订阅新的频道列表
+    ///
+    /// 如果此时连接已断开，频道会被记录下来，在重连后订阅。
+    pub async fn subscribe(&mut self, channels: &[String]) -> Result<(), MiniRedisConnectionError> {
+        self.subscribed_channels
+            .extend(channels.iter().map(Clone::clone));
+
+        if let Some(subscriber) = self.subscriber.as_mut() {
+            match subscriber.subscribe(channels).await {
+                Err(err) if is_disconnect(&err) => self.subscriber = None,
+                res => res?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 取消订阅新的频道列表，列表为空时取消订阅所有频道
+    pub async fn unsubscribe(
+        &mut self,
+        channels: &[String],
+    ) -> Result<(), MiniRedisConnectionError> {
+        if let Some(subscriber) = self.subscriber.as_mut() {
+            match subscriber.unsubscribe(channels).await {
+                Err(err) if is_disconnect(&err) => self.subscriber = None,
+                res => res?,
+            }
+        }
+
+        if channels.is_empty() {
+            self.subscribed_channels.clear();
+        } else {
+            self.subscribed_channels.retain(|c| !channels.contains(c));
+        }
+
+        Ok(())
+    }
+
+    /// 返回当前订阅的频道集合
+    pub fn get_subscribed(&self) -> &[String] {
+        &self.subscribed_channels
+    }
+
+    /// 接收下一个事件，必要时等待。
+    ///
+    /// 连接断开时会一直重试直到重连成功，调用者可以用 `tokio::time::timeout` 限制等待时间。
+    pub async fn next_message(&mut self) -> Result<SubscriberEvent, MiniRedisConnectionError> {
+        loop {
+            let subscriber = match self.subscriber.as_mut() {
+                Some(subscriber) => subscriber,
+                None => {
+                    self.reconnect().await?;
+                    return Ok(SubscriberEvent::Reconnected);
+                }
+            };
+
+            match subscriber.next_message().await {
+                Ok(Some(message)) => return Ok(SubscriberEvent::Message(message)),
+                // 服务器关闭了连接
+                Ok(None) => {}
+                Err(err) if is_disconnect(&err) => {}
+                Err(err) => return Err(err),
+            }
+
+            warn!("subscriber disconnected from {}, reconnecting", self.addr);
+            self.subscriber = None;
+        }
+    }
+
+    /// 将订阅者转换为 `Stream`，返回收到的事件。
+    ///
+    /// 只有遇到无法通过重连恢复的错误时 stream 才会结束。
+    pub fn into_stream(
+        mut self,
+    ) -> impl Stream<Item = Result<SubscriberEvent, MiniRedisConnectionError>> {
+        try_stream! {
+            loop {
+                yield self.next_message().await?;
+            }
+        }
+    }
+
+    /// 按指数退避重新连接服务器并重新订阅所有频道
+    async fn reconnect(&mut self) -> Result<(), MiniRedisConnectionError> {
+        let mut backoff = self.initial_backoff;
+
+        loop {
+            match self.try_reconnect().await {
+                Ok(subscriber) => {
+                    debug!("subscriber reconnected to {}", self.addr);
+                    self.subscriber = Some(subscriber);
+                    return Ok(());
+                }
+                Err(err) if is_disconnect(&err) => {
+                    debug!(
+                        "reconnect to {} failed: {}, retry in {:?}",
+                        self.addr, err, backoff
+                    );
+                }
+                Err(err) => return Err(err),
+            }
+
+            time::sleep(backoff).await;
+            backoff = (backoff * 2).min(self.max_backoff);
+        }
+    }
+
+    async fn try_reconnect(&self) -> Result<Subscriber, MiniRedisConnectionError> {
+        connect(self.addr.as_str())
+            .await?
+            .subscribe(self.subscribed_channels.clone())
+            .await
+    }
+}
+
+/// 错误是否表示连接已经不可用，可以通过重连恢复
+fn is_disconnect(err: &MiniRedisConnectionError) -> bool {
+    matches!(
+        err,
+        MiniRedisConnectionError::Disconnect | MiniRedisConnectionError::IoError(_)
+    )
 }
@@ -0,0 +1,96 @@
+use std::sync::RwLock;
+
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::cmd::{max_value_len, value_too_large};
+use crate::config::ServerConfig;
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 将 `value` 追加到 `key` 原有的值之后。
+///
+/// 如果 `key` 不存在，则等同于 `SET key value`。键原有的生存时间保持不变。
+/// 返回追加之后值的长度。追加之后的长度超过 `max-value-size` 时回复错误，不做修改。
+#[derive(Debug)]
+pub struct Append {
+    /// 键
+    key: String,
+    /// 要追加的值
+    value: Bytes,
+}
+
+impl Append {
+    /// 创建一个新的 `APPEND` 命令。
+    pub fn new(key: impl ToString, value: Bytes) -> Append {
+        Append {
+            key: key.to_string(),
+            value,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取 `value`。
+    pub fn value(&self) -> &Bytes {
+        &self.value
+    }
+
+    /// 从接收到的帧中解析 `Append` 实例。
+    ///
+    /// `APPEND` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个条目的数组帧。
+    ///
+    /// ```text
+    /// APPEND key value
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Append, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let value = parse.next_bytes()?;
+
+        Ok(Append { key, value })
+    }
+
+    /// 将 `Append` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        config: &RwLock<ServerConfig>,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let (max_len, limit) = max_value_len(config);
+        let response = match db.append(self.key, self.value, max_len) {
+            Ok(Some(len)) => Frame::Integer(len as i64),
+            Ok(None) => value_too_large(limit),
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("append cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `APPEND` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new()
+            .bulk("append")
+            .bulk(self.key)
+            .bulk(self.value)
+            .build()
+    }
+}
@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::select;
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::server::shutdown::Shutdown;
+use crate::storage::db::Db;
+
+/// 移除并返回第一个非空列表头部或尾部的值，所有列表都为空时阻塞等待。
+///
+/// `BLPOP` 从头部移除，`BRPOP` 从尾部移除。按键的顺序检查列表，返回值所在的键和值；
+/// 等待超过 `timeout` 仍然没有值时返回 nil，`timeout` 为 0 表示一直等待。
+///
+/// 服务器目前没有复制功能。以后加入复制时，不能把阻塞命令原样传给副本，否则副本也会阻塞；
+/// 应当在取到值之后传播确定的效果，即对取到值的那个键执行的 `LPOP` 或 `RPOP`。
+#[derive(Debug)]
+pub struct BPop {
+    /// 列表的键
+    keys: Vec<String>,
+    /// 最长的等待时间，0 表示一直等待
+    timeout: Duration,
+    /// `true` 表示从头部移除（`BLPOP`）
+    front: bool,
+}
+
+impl BPop {
+    /// 创建一个新的 `BLPOP` 命令。
+    pub fn left(keys: &[String], timeout: Duration) -> BPop {
+        BPop {
+            keys: keys.to_vec(),
+            timeout,
+            front: true,
+        }
+    }
+
+    /// 创建一个新的 `BRPOP` 命令。
+    pub fn right(keys: &[String], timeout: Duration) -> BPop {
+        BPop {
+            keys: keys.to_vec(),
+            timeout,
+            front: false,
+        }
+    }
+
+    /// 获取 `keys`。
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// 获取最长的等待时间。
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// 从接收到的帧中解析 `BPop` 实例。
+    ///
+    /// `BLPOP` 或 `BRPOP` 字符串已被使用，`front` 表示是哪一个。
+    /// 与 Redis 一样，超时时间以秒为单位，可以是小数，不能是负数。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个或更多条目的数组帧，最后一个条目是超时时间。
+    ///
+    /// ```text
+    /// BLPOP key [key ...] timeout
+    /// BRPOP key [key ...] timeout
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse, front: bool) -> Result<BPop, MiniRedisParseError> {
+        let mut keys = vec![parse.next_string()?];
+        loop {
+            match parse.next_string() {
+                Ok(s) => keys.push(s),
+                Err(MiniRedisParseError::EndOfStream) => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        // 至少有一个键和超时时间
+        if keys.len() < 2 {
+            let name = if front { "blpop" } else { "brpop" };
+            return Err(MiniRedisParseError::InvalidCommand(format!(
+                "wrong number of arguments for '{}' command",
+                name
+            )));
+        }
+        let timeout = parse_timeout(&keys.pop().unwrap())?;
+
+        Ok(BPop {
+            keys,
+            timeout,
+            front,
+        })
+    }
+
+    /// 将 `BPop` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。等待期间服务器关闭时不回复，直接返回。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+        shutdown: &mut Shutdown,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let timeout = if self.timeout.is_zero() {
+            None
+        } else {
+            Some(self.timeout)
+        };
+
+        let res = select! {
+            res = db.blocking_pop(&self.keys, self.front, timeout) => res,
+            _ = shutdown.recv() => {
+                debug!("server shutdown, stop waiting for {:?}", self.keys);
+                return Ok(());
+            }
+        };
+
+        let response = match res {
+            Ok(Some((key, value))) => {
+                Frame::Array(vec![Frame::Bulk(Bytes::from(key)), Frame::Bulk(value)])
+            }
+            // 超时
+            Ok(None) => Frame::Null,
+            // 键的值不是列表
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("bpop cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `BLPOP` 或 `BRPOP` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        let name = if self.front { "blpop" } else { "brpop" };
+
+        FrameBuilder::new()
+            .bulk(name)
+            .bulks(self.keys)
+            .bulk(self.timeout.as_secs_f64().to_string())
+            .build()
+    }
+}
+
+// 解析以秒为单位的超时时间
+fn parse_timeout(timeout: &str) -> Result<Duration, MiniRedisParseError> {
+    let secs: f64 = timeout.parse().map_err(|_| out_of_range())?;
+    if secs < 0.0 {
+        return Err(MiniRedisParseError::InvalidCommand(
+            "timeout is negative".into(),
+        ));
+    }
+    Duration::try_from_secs_f64(secs).map_err(|_| out_of_range())
+}
+
+fn out_of_range() -> MiniRedisParseError {
+    MiniRedisParseError::InvalidCommand("timeout is not a float or out of range".into())
+}
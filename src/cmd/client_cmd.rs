@@ -0,0 +1,159 @@
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::server::clients::ClientHandle;
+
+/// 读取或者修改当前连接的状态。
+///
+/// 支持以下子命令：
+///
+/// - `CLIENT SETNAME name` 为连接设置一个名称，回复 `OK`。名称不能包含空格、换行等特殊字符，
+///   空字符串表示清除名称。
+/// - `CLIENT GETNAME` 返回连接的名称，没有设置时返回空字符串。
+/// - `CLIENT ID` 返回连接的 id。id 在服务器中唯一，按照连接的先后顺序递增。
+/// - `CLIENT LIST` 返回所有已连接的客户端，每个客户端一行，
+///   格式为 `id=<id> addr=<addr> name=<name> age=<秒>`。
+///
+/// 名称只属于这个连接，连接关闭之后就不存在了。
+#[derive(Debug)]
+pub struct ClientCmd {
+    /// 子命令
+    subcommand: Subcommand,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Subcommand {
+    /// 新的名称，`None` 表示清除名称
+    SetName(Option<String>),
+    GetName,
+    Id,
+    List,
+}
+
+impl ClientCmd {
+    /// 创建一个新的 `CLIENT SETNAME` 命令，`name` 为空时清除名称。
+    pub fn set_name(name: impl ToString) -> ClientCmd {
+        let name = name.to_string();
+        ClientCmd {
+            subcommand: Subcommand::SetName(Some(name).filter(|name| !name.is_empty())),
+        }
+    }
+
+    /// 创建一个新的 `CLIENT GETNAME` 命令。
+    pub fn get_name() -> ClientCmd {
+        ClientCmd {
+            subcommand: Subcommand::GetName,
+        }
+    }
+
+    /// 创建一个新的 `CLIENT ID` 命令。
+    pub fn id() -> ClientCmd {
+        ClientCmd {
+            subcommand: Subcommand::Id,
+        }
+    }
+
+    /// 创建一个新的 `CLIENT LIST` 命令。
+    pub fn list() -> ClientCmd {
+        ClientCmd {
+            subcommand: Subcommand::List,
+        }
+    }
+
+    /// 从接收到的帧中解析 `ClientCmd` 实例。
+    ///
+    /// `CLIENT` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// ```text
+    /// CLIENT SETNAME name
+    /// CLIENT GETNAME
+    /// CLIENT ID
+    /// CLIENT LIST
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<ClientCmd, MiniRedisParseError> {
+        let subcommand = match &parse.next_string()?.to_lowercase()[..] {
+            "setname" => match parse.next_string() {
+                Ok(name) if !is_valid_name(&name) => {
+                    return Err(MiniRedisParseError::InvalidCommand(
+                        "Client names cannot contain spaces, newlines or special characters."
+                            .into(),
+                    ))
+                }
+                Ok(name) => Subcommand::SetName(Some(name).filter(|name| !name.is_empty())),
+                Err(MiniRedisParseError::EndOfStream) => {
+                    return Err(MiniRedisParseError::InvalidCommand(
+                        "wrong number of arguments for 'client|setname' command".into(),
+                    ))
+                }
+                Err(err) => return Err(err),
+            },
+            "getname" if parse.remaining() == 0 => Subcommand::GetName,
+            "id" if parse.remaining() == 0 => Subcommand::Id,
+            "list" if parse.remaining() == 0 => Subcommand::List,
+            subcommand @ ("getname" | "id" | "list") => {
+                return Err(MiniRedisParseError::InvalidCommand(format!(
+                    "wrong number of arguments for 'client|{}' command",
+                    subcommand
+                )))
+            }
+            subcommand => {
+                return Err(MiniRedisParseError::InvalidCommand(format!(
+                    "unknown subcommand '{}'",
+                    subcommand
+                )))
+            }
+        };
+
+        Ok(ClientCmd { subcommand })
+    }
+
+    /// 应用 `ClientCmd` 命令，读取或者修改当前连接 `client` 的状态。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        client: &ClientHandle,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match self.subcommand {
+            Subcommand::SetName(name) => {
+                client.set_name(name);
+                Frame::Simple("OK".to_string())
+            }
+            Subcommand::GetName => Frame::Bulk(client.name().map(Bytes::from).unwrap_or_default()),
+            Subcommand::Id => Frame::Integer(client.id() as i64),
+            Subcommand::List => Frame::Bulk(Bytes::from(client.clients().list())),
+        };
+
+        debug!("client cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `CLIENT` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        let frame = FrameBuilder::new().bulk("client");
+        let frame = match self.subcommand {
+            Subcommand::SetName(name) => frame.bulk("setname").bulk(name.unwrap_or_default()),
+            Subcommand::GetName => frame.bulk("getname"),
+            Subcommand::Id => frame.bulk("id"),
+            Subcommand::List => frame.bulk("list"),
+        };
+
+        frame.build()
+    }
+}
+
+// 与 Redis 一样，名称只能包含 `!` 到 `~` 之间的可见 ASCII 字符
+fn is_valid_name(name: &str) -> bool {
+    name.bytes().all(|b| (b'!'..=b'~').contains(&b))
+}
@@ -0,0 +1,94 @@
+use std::sync::RwLock;
+
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::config::ServerConfig;
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+
+/// 读取服务器的配置。
+///
+/// 目前只支持 `CONFIG GET`，返回配置项的名称和当前值组成的 Map，RESP2 连接收到的是数组。
+/// 未知的配置项返回空的 Map。
+#[derive(Debug)]
+pub struct Config {
+    /// 要读取的配置项名称，与配置文件中的名称相同
+    parameter: String,
+}
+
+impl Config {
+    /// 创建一个新的 `CONFIG GET` 命令，读取 `parameter` 的值。
+    pub fn get(parameter: impl ToString) -> Config {
+        Config {
+            parameter: parameter.to_string(),
+        }
+    }
+
+    /// 获取要读取的配置项名称。
+    pub fn parameter(&self) -> &str {
+        &self.parameter
+    }
+
+    /// 从接收到的帧中解析 `Config` 实例。
+    ///
+    /// `CONFIG` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个条目的数组帧。
+    ///
+    /// ```text
+    /// CONFIG GET parameter
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Config, MiniRedisParseError> {
+        let subcommand = parse.next_string()?.to_lowercase();
+        if subcommand != "get" {
+            return Err(MiniRedisParseError::InvalidCommand(format!(
+                "unknown subcommand '{}'",
+                subcommand
+            )));
+        }
+
+        match parse.next_string() {
+            Ok(parameter) => Ok(Config { parameter }),
+            Err(MiniRedisParseError::EndOfStream) => Err(MiniRedisParseError::InvalidCommand(
+                "wrong number of arguments for 'config|get' command".into(),
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 将 `Config` 命令应用于服务器配置。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        config: &RwLock<ServerConfig>,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let value = config.read().unwrap().get(&self.parameter);
+
+        let response = match value {
+            Some(value) => Frame::Map(vec![(
+                Frame::Bulk(Bytes::from(self.parameter.to_lowercase())),
+                Frame::Bulk(Bytes::from(value)),
+            )]),
+            None => Frame::Map(vec![]),
+        };
+
+        debug!("config cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `CONFIG GET` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("config", ["get".to_string(), self.parameter])
+    }
+}
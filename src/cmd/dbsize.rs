@@ -0,0 +1,58 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+
+/// 返回数据库中键的数量。
+///
+/// 读取数据库维护的键计数，不需要获取任何分片的锁。已经过期但还没有被清理的键也计算在内。
+#[derive(Debug, Default)]
+pub struct DbSize;
+
+impl DbSize {
+    /// 创建一个新的 `DBSIZE` 命令。
+    pub fn new() -> DbSize {
+        DbSize
+    }
+
+    /// 从接收到的帧中解析 `DbSize` 实例。
+    ///
+    /// `DBSIZE` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个只包含一个条目的数组帧。
+    ///
+    /// ```text
+    /// DBSIZE
+    /// ```
+    pub(crate) fn parse_frame(_parse: &mut Parse) -> Result<DbSize, MiniRedisParseError> {
+        Ok(DbSize)
+    }
+
+    /// 将 `DbSize` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = Frame::Integer(db.len() as i64);
+
+        debug!("dbsize cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `DBSIZE` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new().bulk("dbsize").build()
+    }
+}
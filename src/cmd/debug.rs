@@ -0,0 +1,108 @@
+use std::sync::RwLock;
+use std::time::Duration;
+
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::time;
+
+use crate::config::ServerConfig;
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+
+/// 用于测试和诊断的命令，只有配置中开启了 `debug_commands` 时才能执行。
+///
+/// 目前只支持一个子命令：
+///
+/// - `DEBUG SLEEP seconds` 让处理这个连接的任务等待指定的秒数后回复 `OK`，秒数可以是小数。
+///   等待期间连接不会处理其他命令，也不会响应服务器的关闭信号，可以用来测试客户端超时和服务器关闭。
+#[derive(Debug)]
+pub struct Debug {
+    /// 子命令
+    subcommand: Subcommand,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Subcommand {
+    Sleep(Duration),
+}
+
+impl Debug {
+    /// 创建一个新的 `DEBUG SLEEP` 命令。
+    pub fn sleep(duration: Duration) -> Debug {
+        Debug {
+            subcommand: Subcommand::Sleep(duration),
+        }
+    }
+
+    /// 从接收到的帧中解析 `Debug` 实例。
+    ///
+    /// `DEBUG` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个条目的数组帧。
+    ///
+    /// ```text
+    /// DEBUG SLEEP seconds
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Debug, MiniRedisParseError> {
+        let subcommand = match &parse.next_string()?.to_lowercase()[..] {
+            "sleep" => {
+                let secs = parse.next_double()?;
+                let duration = Duration::try_from_secs_f64(secs).map_err(|_| {
+                    MiniRedisParseError::InvalidCommand("value is not a valid float".into())
+                })?;
+                Subcommand::Sleep(duration)
+            }
+            subcommand => {
+                return Err(MiniRedisParseError::InvalidCommand(format!(
+                    "unknown subcommand '{}'",
+                    subcommand
+                )))
+            }
+        };
+
+        Ok(Debug { subcommand })
+    }
+
+    /// 应用 `Debug` 命令。
+    ///
+    /// 没有开启 `debug_commands` 时回复错误。将响应写入 `dst`。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        config: &RwLock<ServerConfig>,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        if !config.read().unwrap().debug_commands {
+            let response = Frame::error(
+                ErrorKind::Err,
+                "DEBUG command not allowed, set 'enable-debug-command' to 'yes' in the config file",
+            );
+            dst.write_frame(&response).await?;
+            return Ok(());
+        }
+
+        match self.subcommand {
+            Subcommand::Sleep(duration) => time::sleep(duration).await,
+        }
+        let response = Frame::Simple("OK".to_string());
+
+        debug!("debug cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `DEBUG` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        match self.subcommand {
+            Subcommand::Sleep(duration) => Frame::cmd(
+                "debug",
+                ["sleep".to_string(), duration.as_secs_f64().to_string()],
+            ),
+        }
+    }
+}
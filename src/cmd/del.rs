@@ -1,12 +1,11 @@
-use bytes::Bytes;
 use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
 
 use crate::connection::connect::Connection;
 use crate::connection::frame::Frame;
 use crate::connection::parse::Parse;
 use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
-use crate::storage::db::Db;
-use crate::storage::traits::KvStore;
+use crate::storage::traits::AsyncKvStore;
 
 /// 删除指定的键及其关联的值。  
 ///  
@@ -56,18 +55,18 @@ impl Del {
         Ok(Del { key })
     }
 
-    /// 将 `Del` 命令应用于指定的 `Db` 实例。  
+    /// 将 `Del` 命令应用于指定的存储，服务器使用内存中的 `Db`，也可以是其他实现了 `AsyncKvStore` 的存储。  
     ///  
     /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。  
-    pub(crate) async fn apply(
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
         self,
-        db: &Db,
-        dst: &mut Connection,
+        db: &impl AsyncKvStore,
+        dst: &mut Connection<S>,
     ) -> Result<(), MiniRedisConnectionError> {
         // 在 db 中删除 key
-        let delete_cnt = db.del(self.key);
+        let delete_cnt = db.del(self.key).await;
 
-        let response = Frame::Integer(delete_cnt as u64);
+        let response = Frame::Integer(delete_cnt as i64);
 
         debug!("del cmd applied response: {:?}", response);
 
@@ -81,11 +80,8 @@ impl Del {
     /// # 返回值  
     ///  
     /// 返回一个表示 `DEL` 请求的 `Frame` 实例。  
-    pub(crate) fn into_frame(self) -> Result<Frame, MiniRedisParseError> {
-        let mut frame = Frame::array();
-        frame.push_bulk(Bytes::from("del".as_bytes()))?;
-        frame.push_bulk(Bytes::from(self.key.into_bytes()))?;
-        Ok(frame)
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("del", [self.key])
     }
 
     /// 获取 `key`。  
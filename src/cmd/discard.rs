@@ -0,0 +1,52 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::cmd::multi::Transaction;
+use crate::cmd::watch::WatchedKeys;
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+
+/// 放弃 `MULTI` 之后排队的所有命令并取消监视所有的键，连接回到普通的命令模式。
+#[derive(Debug)]
+pub struct Discard;
+
+impl Discard {
+    /// 从接收到的帧中解析 `Discard` 实例。
+    ///
+    /// `DISCARD` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个只包含一个条目的数组帧。
+    ///
+    /// ```text
+    /// DISCARD
+    /// ```
+    pub(crate) fn parse_frame(_parse: &mut Parse) -> Result<Discard, MiniRedisParseError> {
+        Ok(Discard)
+    }
+
+    /// 应用 `Discard` 命令，清空 `transaction` 和 `watched` 并回复 `OK`；不在事务中时回复错误。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        transaction: &mut Option<Transaction>,
+        watched: &mut WatchedKeys,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match transaction.take() {
+            Some(_) => {
+                watched.clear();
+                Frame::Simple("OK".to_string())
+            }
+            None => Frame::error(ErrorKind::Err, "DISCARD without MULTI"),
+        };
+
+        debug!("discard cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
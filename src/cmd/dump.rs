@@ -0,0 +1,75 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 序列化 key 的值。
+///
+/// 返回的数据只能交给 `RESTORE` 使用，格式见 `storage::persistence`。
+/// 数据中不包含过期时间，迁移键时需要另外用 `PTTL` 读取。如果 key 不存在，则返回 nil。
+#[derive(Debug)]
+pub struct Dump {
+    /// 要序列化的键
+    key: String,
+}
+
+impl Dump {
+    /// 创建一个新的 `DUMP` 命令。
+    pub fn new(key: impl ToString) -> Dump {
+        Dump {
+            key: key.to_string(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 从接收到的帧中解析 `Dump` 实例。
+    ///
+    /// `DUMP` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含两个条目的数组帧。
+    ///
+    /// ```text
+    /// DUMP key
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Dump, MiniRedisParseError> {
+        let key = parse.next_string()?;
+
+        Ok(Dump { key })
+    }
+
+    /// 将 `Dump` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.dump(&self.key) {
+            Some(payload) => Frame::Bulk(payload),
+            None => Frame::Null,
+        };
+
+        debug!("dump cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `DUMP` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("dump", [self.key])
+    }
+}
@@ -0,0 +1,101 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::metrics::MetricsRecorder;
+use crate::server::shutdown::Shutdown;
+use crate::server::state::{ConnState, Shared};
+use crate::storage::traits::AsyncKvStore;
+
+/// 依次执行 `MULTI` 之后排队的命令，以数组回复每个命令的结果。
+///
+/// 某个命令执行失败时，它在数组中的回复是错误，其他命令仍然执行。排队时出现过错误的事务不会执行，
+/// 回复 `EXECABORT`。`WATCH` 监视的键在这之后被修改过时同样不会执行，回复 nil。
+/// 命令按顺序执行，但不会锁住整个数据库，其他连接的命令可能穿插在它们之间执行，
+/// 包括检查监视的键和执行第一个命令之间。
+#[derive(Debug)]
+pub struct Exec;
+
+impl Exec {
+    /// 从接收到的帧中解析 `Exec` 实例。
+    ///
+    /// `EXEC` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个只包含一个条目的数组帧。
+    ///
+    /// ```text
+    /// EXEC
+    /// ```
+    pub(crate) fn parse_frame(_parse: &mut Parse) -> Result<Exec, MiniRedisParseError> {
+        Ok(Exec)
+    }
+
+    /// 应用 `Exec` 命令，执行 `state` 的事务中排队的命令。
+    ///
+    /// 每个命令各自把回复写入 `dst`，这里只需要先写入数组的头部。
+    /// 只要事务结束，无论是否执行，`state` 中监视的键都会被清空。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin, K: AsyncKvStore>(
+        self,
+        shared: &Shared<'_, K>,
+        dst: &mut Connection<S>,
+        shutdown: &mut Shutdown,
+        state: &mut ConnState,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let transaction = match state.transaction.take() {
+            Some(transaction) if transaction.is_aborted() => {
+                state.watched.clear();
+                let response = Frame::error(
+                    ErrorKind::ExecAbort,
+                    "Transaction discarded because of previous errors.",
+                );
+                return dst.write_frame(&response).await;
+            }
+            Some(transaction) => transaction,
+            None => {
+                let response = Frame::error(ErrorKind::Err, "EXEC without MULTI");
+                return dst.write_frame(&response).await;
+            }
+        };
+
+        // 被监视的键在 WATCH 之后被修改过，放弃事务
+        let dirty = state.watched.is_dirty(shared.db);
+        state.watched.clear();
+        if dirty {
+            debug!("watched keys modified, exec aborted");
+            return dst.write_frame(&Frame::Null).await;
+        }
+
+        let commands = transaction.into_commands();
+        debug!("exec {} queued commands", commands.len());
+
+        dst.write_array_header(commands.len()).await?;
+        for cmd in commands {
+            shared.metrics.incr_command(cmd.get_name());
+            // 排队之后内存可能已经超过 `maxmemory`，与单独执行时一样拒绝可能占用更多内存的命令，
+            // 它在数组中的回复是错误，其他命令仍然执行
+            if cmd.flags().deny_oom() && shared.db.over_max_memory(shared.config) {
+                debug!(
+                    "queued command {} rejected, used memory over maxmemory",
+                    cmd.get_name()
+                );
+                let response = Frame::error(
+                    ErrorKind::Oom,
+                    "command not allowed when used memory > 'maxmemory'",
+                );
+                dst.write_frame_buffered(&response).await?;
+                continue;
+            }
+            // 队列中没有 MULTI、EXEC、DISCARD 和 QUIT，执行时不会再修改事务状态，也不会要求关闭连接
+            let _ = Box::pin(cmd.apply(shared, dst, shutdown, state)).await?;
+        }
+        // 事务为空时只写入了数组的头部
+        dst.flush().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::cmd::check_expire;
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::store::ExpireCondition;
+use crate::storage::traits::KvStore;
+
+/// 为 key 设置以秒为单位的生存时间，覆盖原有的过期时间。
+///
+/// 设置成功时返回 1，key 不存在时返回 0。与 Redis 一样，生存时间不是正数时 key 会被立即删除。
+///
+/// 可以指定 `NX`、`XX`、`GT` 或 `LT` 中的一个选项，只在满足条件时设置：
+/// `NX` 要求 key 没有过期时间，`XX` 要求 key 已经有过期时间，
+/// `GT` 和 `LT` 要求新的过期时间晚于或者早于原有的过期时间，没有过期时间视为永不过期。
+/// 条件不满足时返回 0，key 保持不变。
+#[derive(Debug)]
+pub struct Expire {
+    /// 要设置过期时间的键
+    key: String,
+    /// 从现在开始计算的生存时间，单位为秒
+    seconds: i64,
+    /// 是否覆盖原有过期时间的条件
+    condition: ExpireCondition,
+}
+
+impl Expire {
+    /// 创建一个新的 `EXPIRE` 命令。
+    pub fn new(key: impl ToString, seconds: i64) -> Expire {
+        Expire {
+            key: key.to_string(),
+            seconds,
+            condition: ExpireCondition::Always,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取生存时间的秒数。
+    pub fn seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    /// 从接收到的帧中解析 `Expire` 实例。
+    ///
+    /// `EXPIRE` 字符串已被使用。生存时间过大，计算过期时间点会溢出时返回错误。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个或四个条目的数组帧。
+    ///
+    /// ```text
+    /// EXPIRE key seconds [NX | XX | GT | LT]
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Expire, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let seconds = parse.next_signed_int()?;
+
+        let condition = match parse.next_string() {
+            Ok(option) => match &option.to_uppercase()[..] {
+                "NX" => ExpireCondition::Nx,
+                "XX" => ExpireCondition::Xx,
+                "GT" => ExpireCondition::Gt,
+                "LT" => ExpireCondition::Lt,
+                _ => return Err(syntax_error()),
+            },
+            Err(MiniRedisParseError::EndOfStream) => ExpireCondition::Always,
+            Err(err) => return Err(err),
+        };
+        // 只支持一个选项
+        match parse.next_string() {
+            Err(MiniRedisParseError::EndOfStream) => {}
+            Ok(_) => return Err(syntax_error()),
+            Err(err) => return Err(err),
+        }
+
+        if seconds > 0 {
+            check_expire(Duration::from_secs(seconds as u64), "expire")?;
+        }
+
+        Ok(Expire {
+            key,
+            seconds,
+            condition,
+        })
+    }
+
+    /// 将 `Expire` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        // 不是正数的生存时间表示已经过期，满足条件时键被删除
+        let expire = Duration::from_secs(self.seconds.max(0) as u64);
+        let updated = db.expire(&self.key, expire, self.condition);
+
+        let response = Frame::Integer(updated as i64);
+
+        debug!("expire cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `EXPIRE` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        let option = match self.condition {
+            ExpireCondition::Always => None,
+            ExpireCondition::Nx => Some("nx"),
+            ExpireCondition::Xx => Some("xx"),
+            ExpireCondition::Gt => Some("gt"),
+            ExpireCondition::Lt => Some("lt"),
+        };
+
+        FrameBuilder::new()
+            .bulk("expire")
+            .bulk(self.key)
+            .bulk(self.seconds.to_string())
+            .bulks(option)
+            .build()
+    }
+}
+
+// 语法错误会作为错误帧回复给客户端，连接保持打开
+fn syntax_error() -> MiniRedisParseError {
+    MiniRedisParseError::InvalidCommand("syntax error".into())
+}
@@ -0,0 +1,92 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 将 key 的过期时间设置为以秒为单位的 Unix 时间戳，覆盖原有的过期时间。
+///
+/// 设置成功时返回 1，key 不存在时返回 0。与 Redis 一样，已经过去的时间戳会让 key 被立即删除。
+#[derive(Debug)]
+pub struct ExpireAt {
+    /// 要设置过期时间的键
+    key: String,
+    /// 过期的 Unix 时间戳，单位为秒
+    timestamp: i64,
+}
+
+impl ExpireAt {
+    /// 创建一个新的 `EXPIREAT` 命令。
+    pub fn new(key: impl ToString, timestamp: i64) -> ExpireAt {
+        ExpireAt {
+            key: key.to_string(),
+            timestamp,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取过期的 Unix 时间戳。
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    /// 从接收到的帧中解析 `ExpireAt` 实例。
+    ///
+    /// `EXPIREAT` 字符串已被使用。时间戳换算为毫秒后超出 `i64` 的范围时返回错误。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个条目的数组帧。
+    ///
+    /// ```text
+    /// EXPIREAT key unix-time-seconds
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<ExpireAt, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let timestamp = parse.next_signed_int()?;
+
+        if timestamp > i64::MAX / 1000 {
+            return Err(MiniRedisParseError::InvalidCommand(
+                "invalid expire time in 'expireat' command".into(),
+            ));
+        }
+
+        Ok(ExpireAt { key, timestamp })
+    }
+
+    /// 将 `ExpireAt` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        // 负数的时间戳同样早于现在
+        let when = UNIX_EPOCH + Duration::from_secs(self.timestamp.max(0) as u64);
+        let updated = db.expire_at(&self.key, when);
+
+        let response = Frame::Integer(updated as i64);
+
+        debug!("expireat cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `EXPIREAT` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("expireat", [self.key, self.timestamp.to_string()])
+    }
+}
@@ -0,0 +1,60 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+
+/// 删除所有的键。
+///
+/// 服务器只有一个数据库，清空它的所有分片。发布订阅的频道和订阅者不受影响。
+#[derive(Debug, Default)]
+pub struct FlushAll;
+
+impl FlushAll {
+    /// 创建一个新的 `FLUSHALL` 命令。
+    pub fn new() -> FlushAll {
+        FlushAll
+    }
+
+    /// 从接收到的帧中解析 `FlushAll` 实例。
+    ///
+    /// `FLUSHALL` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个只包含一个条目的数组帧。
+    ///
+    /// ```text
+    /// FLUSHALL
+    /// ```
+    pub(crate) fn parse_frame(_parse: &mut Parse) -> Result<FlushAll, MiniRedisParseError> {
+        Ok(FlushAll)
+    }
+
+    /// 将 `FlushAll` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        db.flush_all();
+
+        let response = Frame::Simple("OK".to_string());
+
+        debug!("flushall cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `FLUSHALL` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new().bulk("flushall").build()
+    }
+}
@@ -1,12 +1,11 @@
-use bytes::Bytes;
 use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
 
 use crate::connection::connect::Connection;
 use crate::connection::frame::Frame;
 use crate::connection::parse::Parse;
-use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
-use crate::storage::db::Db;
-use crate::storage::traits::KvStore;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::traits::AsyncKvStore;
 
 /// 获取 key 的值。
 ///
@@ -53,21 +52,22 @@ impl Get {
         Ok(Get { key })
     }
 
-    /// 将 `Get` 命令应用于指定的 `Db` 实例。
+    /// 将 `Get` 命令应用于指定的存储，服务器使用内存中的 `Db`，也可以是其他实现了 `AsyncKvStore` 的存储。
     ///
     /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
-    pub(crate) async fn apply(
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
         self,
-        db: &Db,
-        dst: &mut Connection,
+        db: &impl AsyncKvStore,
+        dst: &mut Connection<S>,
     ) -> Result<(), MiniRedisConnectionError> {
         // 从共享数据库状态中获取值
-        let response = if let Some(value) = db.get(&self.key) {
+        let response = match db.get(&self.key).await {
             // 如果有值，则以 "bulk" 格式写入客户端。
-            Frame::Bulk(value)
-        } else {
+            Ok(Some(value)) => Frame::Bulk(value),
             // 如果没有值，则写入 `Null`。
-            Frame::Null
+            Ok(None) => Frame::Null,
+            // 键的值不是字符串
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
         };
 
         debug!("get cmd applied response: {:?}", response);
@@ -82,11 +82,7 @@ impl Get {
     /// # 返回值
     ///
     /// 返回一个表示 `GET` 请求的 `Frame` 实例。
-    pub(crate) fn into_frame(self) -> Result<Frame, MiniRedisParseError> {
-        let mut frame = Frame::array();
-        frame.push_bulk(Bytes::from("get".as_bytes()))?;
-        frame.push_bulk(Bytes::from(self.key.into_bytes()))?;
-
-        Ok(frame)
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("get", [self.key])
     }
 }
@@ -0,0 +1,84 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::cmd::setbit::parse_bit_offset;
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 返回 `key` 的字符串值中第 `offset` 位的值。
+///
+/// 超出字符串长度的位和不存在的键都返回 0。
+#[derive(Debug)]
+pub struct GetBit {
+    /// 键
+    key: String,
+    /// 位偏移量
+    offset: u64,
+}
+
+impl GetBit {
+    /// 创建一个新的 `GETBIT` 命令。
+    pub fn new(key: impl ToString, offset: u64) -> GetBit {
+        GetBit {
+            key: key.to_string(),
+            offset,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取位偏移量。
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// 从接收到的帧中解析 `GetBit` 实例。
+    ///
+    /// `GETBIT` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个条目的数组帧。
+    ///
+    /// ```text
+    /// GETBIT key offset
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<GetBit, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let offset = parse_bit_offset(parse)?;
+
+        Ok(GetBit { key, offset })
+    }
+
+    /// 将 `GetBit` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.getbit(&self.key, self.offset as usize) {
+            Ok(bit) => Frame::Integer(bit as i64),
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("getbit cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `GETBIT` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("getbit", [self.key, self.offset.to_string()])
+    }
+}
@@ -0,0 +1,75 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 获取 key 的值并删除该 key。
+///
+/// 获取和删除是一个原子操作。如果 key 不存在，则返回特殊值 nil。
+#[derive(Debug)]
+pub struct GetDel {
+    /// 要获取并删除的键的名称
+    key: String,
+}
+
+impl GetDel {
+    /// 创建一个新的 `GETDEL` 命令。
+    pub fn new(key: impl ToString) -> GetDel {
+        GetDel {
+            key: key.to_string(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 从接收到的帧中解析 `GetDel` 实例。
+    ///
+    /// `GETDEL` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含两个条目的数组帧。
+    ///
+    /// ```text
+    /// GETDEL key
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<GetDel, MiniRedisParseError> {
+        let key = parse.next_string()?;
+
+        Ok(GetDel { key })
+    }
+
+    /// 将 `GetDel` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.get_del(&self.key) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("getdel cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `GETDEL` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("getdel", [self.key])
+    }
+}
@@ -0,0 +1,97 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 删除哈希表中的一个或多个字段。
+///
+/// 不存在的字段会被忽略，返回实际删除的字段数量。哈希表中最后一个字段被删除后，key 也会被删除。
+#[derive(Debug)]
+pub struct HDel {
+    /// 哈希表的键
+    key: String,
+    /// 要删除的字段
+    fields: Vec<String>,
+}
+
+impl HDel {
+    /// 创建一个新的 `HDEL` 命令。
+    pub fn new(key: impl ToString, fields: &[String]) -> HDel {
+        HDel {
+            key: key.to_string(),
+            fields: fields.to_vec(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取要删除的字段。
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// 从接收到的帧中解析 `HDel` 实例。
+    ///
+    /// `HDEL` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个或更多条目的数组帧。
+    ///
+    /// ```text
+    /// HDEL key field [field ...]
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<HDel, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let mut fields = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(field) => fields.push(field),
+                // 所有的字段都已读取
+                Err(MiniRedisParseError::EndOfStream) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(HDel { key, fields })
+    }
+
+    /// 将 `HDel` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.hdel(&self.key, &self.fields) {
+            Ok(removed) => Frame::Integer(removed as i64),
+            // 键的值不是哈希表
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("hdel cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `HDEL` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new()
+            .bulk("hdel")
+            .bulk(self.key)
+            .bulks(self.fields)
+            .build()
+    }
+}
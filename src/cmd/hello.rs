@@ -0,0 +1,95 @@
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::consts::{RESP2, RESP3};
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+
+/// 协商连接使用的协议版本，并返回服务器信息。
+///
+/// 不指定 `protover` 时只返回服务器信息，协议版本保持不变。
+/// 服务器信息是一个 Map，RESP2 连接收到的是键值交替排列的数组。
+#[derive(Debug, Default)]
+pub struct Hello {
+    /// 请求的协议版本
+    protover: Option<u8>,
+}
+
+impl Hello {
+    /// 创建一个新的 `HELLO` 命令，请求切换到 `protover` 版本的协议。
+    pub fn new(protover: Option<u8>) -> Hello {
+        Hello { protover }
+    }
+
+    /// 获取请求的协议版本。
+    pub fn protover(&self) -> Option<u8> {
+        self.protover
+    }
+
+    /// 从接收到的帧中解析 `Hello` 实例。
+    ///
+    /// `HELLO` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含一个或两个条目的数组帧。
+    ///
+    /// ```text
+    /// HELLO [protover]
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Hello, MiniRedisParseError> {
+        let protover = match parse.next_string() {
+            Ok(protover) => protover,
+            // 没有指定协议版本
+            Err(MiniRedisParseError::EndOfStream) => return Ok(Hello::default()),
+            Err(e) => return Err(e),
+        };
+
+        match protover.parse() {
+            Ok(protover) => Ok(Hello::new(Some(protover))),
+            Err(_) => Err(MiniRedisParseError::InvalidCommand(
+                "Protocol version is not an integer or out of range".into(),
+            )),
+        }
+    }
+
+    /// 应用 `Hello` 命令，切换连接的协议版本。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        if let Some(protover) = self.protover {
+            if !(RESP2..=RESP3).contains(&protover) {
+                let response = Frame::error(ErrorKind::NoProto, "unsupported protocol version");
+                debug!("hello cmd applied response: {:?}", response);
+                dst.write_frame(&response).await?;
+                return Ok(());
+            }
+
+            dst.set_protocol(protover);
+        }
+
+        let response = Frame::Map(vec![
+            (bulk("server"), bulk("mini-redis")),
+            (bulk("version"), bulk(env!("CARGO_PKG_VERSION"))),
+            (bulk("proto"), Frame::Integer(dst.protocol() as i64)),
+            (bulk("mode"), bulk("standalone")),
+            (bulk("role"), bulk("master")),
+        ]);
+
+        debug!("hello cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+fn bulk(value: &'static str) -> Frame {
+    Frame::Bulk(Bytes::from_static(value.as_bytes()))
+}
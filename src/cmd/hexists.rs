@@ -0,0 +1,84 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 判断哈希表中是否存在字段。
+///
+/// 存在时返回 1，key 或者字段不存在时返回 0。
+#[derive(Debug)]
+pub struct HExists {
+    /// 哈希表的键
+    key: String,
+    /// 字段名
+    field: String,
+}
+
+impl HExists {
+    /// 创建一个新的 `HEXISTS` 命令。
+    pub fn new(key: impl ToString, field: impl ToString) -> HExists {
+        HExists {
+            key: key.to_string(),
+            field: field.to_string(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取 `field`。
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// 从接收到的帧中解析 `HExists` 实例。
+    ///
+    /// `HEXISTS` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个条目的数组帧。
+    ///
+    /// ```text
+    /// HEXISTS key field
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<HExists, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let field = parse.next_string()?;
+
+        Ok(HExists { key, field })
+    }
+
+    /// 将 `HExists` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.hexists(&self.key, &self.field) {
+            Ok(exists) => Frame::Integer(exists as i64),
+            // 键的值不是哈希表
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("hexists cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `HEXISTS` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("hexists", [self.key, self.field])
+    }
+}
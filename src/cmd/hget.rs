@@ -0,0 +1,85 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 获取哈希表中字段的值。
+///
+/// 如果 key 或者字段不存在，则返回特殊值 nil。
+#[derive(Debug)]
+pub struct HGet {
+    /// 哈希表的键
+    key: String,
+    /// 字段名
+    field: String,
+}
+
+impl HGet {
+    /// 创建一个新的 `HGET` 命令。
+    pub fn new(key: impl ToString, field: impl ToString) -> HGet {
+        HGet {
+            key: key.to_string(),
+            field: field.to_string(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取 `field`。
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// 从接收到的帧中解析 `HGet` 实例。
+    ///
+    /// `HGET` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个条目的数组帧。
+    ///
+    /// ```text
+    /// HGET key field
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<HGet, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let field = parse.next_string()?;
+
+        Ok(HGet { key, field })
+    }
+
+    /// 将 `HGet` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.hget(&self.key, &self.field) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            // 键的值不是哈希表
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("hget cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `HGET` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("hget", [self.key, self.field])
+    }
+}
@@ -0,0 +1,82 @@
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 获取哈希表中所有的字段和值。
+///
+/// RESP3 连接收到的是 Map，RESP2 连接收到的是字段和值交替排列的数组。字段的顺序不确定。
+/// 如果 key 不存在，则返回空的 Map。
+#[derive(Debug)]
+pub struct HGetAll {
+    /// 哈希表的键
+    key: String,
+}
+
+impl HGetAll {
+    /// 创建一个新的 `HGETALL` 命令。
+    pub fn new(key: impl ToString) -> HGetAll {
+        HGetAll {
+            key: key.to_string(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 从接收到的帧中解析 `HGetAll` 实例。
+    ///
+    /// `HGETALL` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含两个条目的数组帧。
+    ///
+    /// ```text
+    /// HGETALL key
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<HGetAll, MiniRedisParseError> {
+        let key = parse.next_string()?;
+
+        Ok(HGetAll { key })
+    }
+
+    /// 将 `HGetAll` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.hgetall(&self.key) {
+            Ok(pairs) => Frame::Map(
+                pairs
+                    .into_iter()
+                    .map(|(field, value)| (Frame::Bulk(Bytes::from(field)), Frame::Bulk(value)))
+                    .collect(),
+            ),
+            // 键的值不是哈希表
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("hgetall cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `HGETALL` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("hgetall", [self.key])
+    }
+}
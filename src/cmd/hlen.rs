@@ -0,0 +1,75 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 返回哈希表中字段的数量。
+///
+/// 如果 key 不存在，则返回 0。
+#[derive(Debug)]
+pub struct HLen {
+    /// 哈希表的键
+    key: String,
+}
+
+impl HLen {
+    /// 创建一个新的 `HLEN` 命令。
+    pub fn new(key: impl ToString) -> HLen {
+        HLen {
+            key: key.to_string(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 从接收到的帧中解析 `HLen` 实例。
+    ///
+    /// `HLEN` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含两个条目的数组帧。
+    ///
+    /// ```text
+    /// HLEN key
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<HLen, MiniRedisParseError> {
+        let key = parse.next_string()?;
+
+        Ok(HLen { key })
+    }
+
+    /// 将 `HLen` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.hlen(&self.key) {
+            Ok(len) => Frame::Integer(len as i64),
+            // 键的值不是哈希表
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("hlen cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `HLEN` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("hlen", [self.key])
+    }
+}
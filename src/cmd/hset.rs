@@ -0,0 +1,114 @@
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 设置哈希表中一个或多个字段的值。
+///
+/// 已有字段的值会被覆盖。如果 key 不存在，则先创建一个空的哈希表。返回新增的字段数量。
+#[derive(Debug)]
+pub struct HSet {
+    /// 哈希表的键
+    key: String,
+    /// 要设置的字段和值
+    pairs: Vec<(String, Bytes)>,
+}
+
+impl HSet {
+    /// 创建一个新的 `HSET` 命令。
+    pub fn new(key: impl ToString, pairs: Vec<(String, Bytes)>) -> HSet {
+        HSet {
+            key: key.to_string(),
+            pairs,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取要设置的字段和值。
+    pub fn pairs(&self) -> &[(String, Bytes)] {
+        &self.pairs
+    }
+
+    /// 从接收到的帧中解析 `HSet` 实例。
+    ///
+    /// `HSET` 字符串已被使用。参数个数不匹配时返回错误，不会写入任何字段。
+    ///
+    /// # 格式
+    ///
+    /// 期望数组帧包含至少 4 个条目，并且字段和值成对出现。
+    ///
+    /// ```text
+    /// HSET key field value [field value ...]
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<HSet, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let mut pairs = vec![];
+
+        loop {
+            let field = match parse.next_string() {
+                Ok(field) => field,
+                // 所有字段都已读取
+                Err(MiniRedisParseError::EndOfStream) => break,
+                Err(e) => return Err(e),
+            };
+
+            match parse.next_bytes() {
+                Ok(value) => pairs.push((field, value)),
+                // 最后一个字段缺少对应的值
+                Err(MiniRedisParseError::EndOfStream) => return Err(wrong_arity()),
+                Err(e) => return Err(e),
+            }
+        }
+
+        if pairs.is_empty() {
+            return Err(wrong_arity());
+        }
+
+        Ok(HSet { key, pairs })
+    }
+
+    /// 将 `HSet` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.hset(self.key, self.pairs) {
+            Ok(added) => Frame::Integer(added as i64),
+            // 键的值不是哈希表
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("hset cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `HSET` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = FrameBuilder::new().bulk("hset").bulk(self.key);
+        for (field, value) in self.pairs {
+            frame = frame.bulk(field).bulk(value);
+        }
+
+        frame.build()
+    }
+}
+
+fn wrong_arity() -> MiniRedisParseError {
+    MiniRedisParseError::InvalidCommand("wrong number of arguments for 'hset' command".into())
+}
@@ -0,0 +1,88 @@
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::store::IncrError;
+use crate::storage::traits::KvStore;
+
+/// 将 `key` 的值解析为浮点数并加上 `increment`，返回相加之后的值。
+///
+/// 如果 `key` 不存在，则视为 0。结果以没有多余 0 的十进制写法保存为字符串，
+/// 键原有的生存时间保持不变。原有的值不是合法的浮点数，或者结果是无穷大时回复错误。
+#[derive(Debug)]
+pub struct IncrByFloat {
+    /// 键
+    key: String,
+    /// 增量，可以是负数
+    increment: f64,
+}
+
+impl IncrByFloat {
+    /// 创建一个新的 `INCRBYFLOAT` 命令。
+    pub fn new(key: impl ToString, increment: f64) -> IncrByFloat {
+        IncrByFloat {
+            key: key.to_string(),
+            increment,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取增量。
+    pub fn increment(&self) -> f64 {
+        self.increment
+    }
+
+    /// 从接收到的帧中解析 `IncrByFloat` 实例。
+    ///
+    /// `INCRBYFLOAT` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个条目的数组帧。
+    ///
+    /// ```text
+    /// INCRBYFLOAT key increment
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<IncrByFloat, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let increment = parse.next_double()?;
+
+        Ok(IncrByFloat { key, increment })
+    }
+
+    /// 将 `IncrByFloat` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        // 与 Redis 一样以 Bulk 字符串回复，内容就是保存的值
+        let response = match db.incr_by_float(self.key, self.increment) {
+            Ok(value) => Frame::Bulk(Bytes::from(value.to_string())),
+            Err(err @ IncrError::WrongType) => Frame::error(ErrorKind::WrongType, err),
+            Err(err) => Frame::error(ErrorKind::Err, err),
+        };
+
+        debug!("incrbyfloat cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `INCRBYFLOAT` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("incrbyfloat", [self.key, self.increment.to_string()])
+    }
+}
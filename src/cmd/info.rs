@@ -0,0 +1,91 @@
+use std::fmt::Write;
+
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::server::metrics::Metrics;
+use crate::storage::db::Db;
+
+/// 返回服务器的运行信息和统计数据。
+///
+/// 响应是一个 bulk 字符串，每行一个 `key:value`，以 `# ` 开头的行是分组标题，与 Redis 的格式一致。
+#[derive(Debug, Default)]
+pub struct Info;
+
+impl Info {
+    /// 创建一个新的 `INFO` 命令。
+    pub fn new() -> Info {
+        Info
+    }
+
+    /// 从接收到的帧中解析 `Info` 实例。
+    ///
+    /// `INFO` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个只包含一个条目的数组帧。
+    ///
+    /// ```text
+    /// INFO
+    /// ```
+    pub(crate) fn parse_frame(_parse: &mut Parse) -> Result<Info, MiniRedisParseError> {
+        Ok(Info)
+    }
+
+    /// 将 `Info` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        metrics: &Metrics,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let recorded = metrics.recorded();
+        let mut info = String::new();
+        // 写入 String 不会失败
+        let _ = write!(
+            info,
+            "# Server\r\n\
+             uptime_in_seconds:{}\r\n\
+             \r\n\
+             # Clients\r\n\
+             connected_clients:{}\r\n\
+             \r\n\
+             # Stats\r\n\
+             total_connections_received:{}\r\n\
+             total_commands_processed:{}\r\n\
+             \r\n\
+             # Memory\r\n\
+             used_memory:{}\r\n\
+             \r\n\
+             # Keyspace\r\n\
+             keys:{}\r\n",
+            metrics.uptime().as_secs(),
+            recorded.gauge("connected_clients").unwrap_or(0),
+            recorded.gauge("total_connections_received").unwrap_or(0),
+            recorded.total_commands(),
+            db.used_memory(),
+            db.len(),
+        );
+
+        let response = Frame::Bulk(Bytes::from(info));
+
+        debug!("info cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `INFO` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new().bulk("info").build()
+    }
+}
@@ -0,0 +1,75 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 返回列表的长度。
+///
+/// 如果 key 不存在，则返回 0。
+#[derive(Debug)]
+pub struct LLen {
+    /// 列表的键
+    key: String,
+}
+
+impl LLen {
+    /// 创建一个新的 `LLEN` 命令。
+    pub fn new(key: impl ToString) -> LLen {
+        LLen {
+            key: key.to_string(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 从接收到的帧中解析 `LLen` 实例。
+    ///
+    /// `LLEN` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含两个条目的数组帧。
+    ///
+    /// ```text
+    /// LLEN key
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<LLen, MiniRedisParseError> {
+        let key = parse.next_string()?;
+
+        Ok(LLen { key })
+    }
+
+    /// 将 `LLen` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.llen(&self.key) {
+            Ok(len) => Frame::Integer(len as i64),
+            // 键的值不是列表
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("llen cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `LLEN` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("llen", [self.key])
+    }
+}
@@ -0,0 +1,76 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 移除并返回列表头部的值。
+///
+/// 如果 key 不存在，则返回特殊值 nil。列表中最后一个值被移除后，key 也会被删除。
+#[derive(Debug)]
+pub struct LPop {
+    /// 列表的键
+    key: String,
+}
+
+impl LPop {
+    /// 创建一个新的 `LPOP` 命令。
+    pub fn new(key: impl ToString) -> LPop {
+        LPop {
+            key: key.to_string(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 从接收到的帧中解析 `LPop` 实例。
+    ///
+    /// `LPOP` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含两个条目的数组帧。
+    ///
+    /// ```text
+    /// LPOP key
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<LPop, MiniRedisParseError> {
+        let key = parse.next_string()?;
+
+        Ok(LPop { key })
+    }
+
+    /// 将 `LPop` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.lpop(&self.key) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            // 键的值不是列表
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("lpop cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `LPOP` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("lpop", [self.key])
+    }
+}
@@ -0,0 +1,87 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 返回列表中下标从 `start` 到 `stop` 的值，包含两端。
+///
+/// 下标从 0 开始，负数下标从列表尾部开始计算，`-1` 表示最后一个值。
+/// 超出范围的下标不会报错，如果 key 不存在，则返回空数组。
+#[derive(Debug)]
+pub struct LRange {
+    /// 列表的键
+    key: String,
+    /// 起始下标
+    start: i64,
+    /// 结束下标
+    stop: i64,
+}
+
+impl LRange {
+    /// 创建一个新的 `LRANGE` 命令。
+    pub fn new(key: impl ToString, start: i64, stop: i64) -> LRange {
+        LRange {
+            key: key.to_string(),
+            start,
+            stop,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 从接收到的帧中解析 `LRange` 实例。
+    ///
+    /// `LRANGE` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含四个条目的数组帧。
+    ///
+    /// ```text
+    /// LRANGE key start stop
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<LRange, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let start = parse.next_signed_int()?;
+        let stop = parse.next_signed_int()?;
+
+        Ok(LRange { key, start, stop })
+    }
+
+    /// 将 `LRange` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.lrange(&self.key, self.start, self.stop) {
+            Ok(values) => Frame::Array(values.into_iter().map(Frame::Bulk).collect()),
+            // 键的值不是列表
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("lrange cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `LRANGE` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd(
+            "lrange",
+            [self.key, self.start.to_string(), self.stop.to_string()],
+        )
+    }
+}
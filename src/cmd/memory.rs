@@ -0,0 +1,90 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 估算键占用的内存。
+///
+/// 目前只支持 `MEMORY USAGE key`，返回键名和值的长度加上每个键固定的开销，单位是字节。
+/// 与 `maxmemory` 统计的一样只是一个近似值，不包括哈希表等数据结构本身的开销。
+///
+/// `MEMORY` 本身不会更新键的访问时间。如果 key 不存在，则返回特殊值 nil。
+#[derive(Debug)]
+pub struct Memory {
+    /// 要估算的键
+    key: String,
+}
+
+impl Memory {
+    /// 创建一个新的 `MEMORY USAGE` 命令。
+    pub fn usage(key: impl ToString) -> Memory {
+        Memory {
+            key: key.to_string(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 从接收到的帧中解析 `Memory` 实例。
+    ///
+    /// `MEMORY` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个条目的数组帧。
+    ///
+    /// ```text
+    /// MEMORY USAGE key
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Memory, MiniRedisParseError> {
+        match &parse.next_string()?.to_lowercase()[..] {
+            "usage" => {}
+            subcommand => {
+                return Err(MiniRedisParseError::InvalidCommand(format!(
+                    "unknown subcommand '{}'",
+                    subcommand
+                )))
+            }
+        }
+        let key = parse.next_string()?;
+
+        Ok(Memory { key })
+    }
+
+    /// 将 `Memory` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.memory_usage(&self.key) {
+            Some(usage) => Frame::Integer(usage as i64),
+            None => Frame::Null,
+        };
+
+        debug!("memory cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `MEMORY` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new()
+            .bulk("memory")
+            .bulk("usage")
+            .bulk(self.key)
+            .build()
+    }
+}
@@ -1,27 +1,135 @@
+use crate::cmd::append::Append;
+use crate::cmd::bpop::BPop;
+use crate::cmd::client_cmd::ClientCmd;
+use crate::cmd::config::Config;
+use crate::cmd::dbsize::DbSize;
+use crate::cmd::debug::Debug;
+use crate::cmd::del::Del;
+use crate::cmd::discard::Discard;
+use crate::cmd::dump::Dump;
+use crate::cmd::exec::Exec;
+use crate::cmd::expire::Expire;
+use crate::cmd::expireat::ExpireAt;
+use crate::cmd::flushall::FlushAll;
 use crate::cmd::get::Get;
+use crate::cmd::getbit::GetBit;
+use crate::cmd::getdel::GetDel;
+use crate::cmd::hdel::HDel;
+use crate::cmd::hello::Hello;
+use crate::cmd::hexists::HExists;
+use crate::cmd::hget::HGet;
+use crate::cmd::hgetall::HGetAll;
+use crate::cmd::hlen::HLen;
+use crate::cmd::hset::HSet;
+use crate::cmd::incrbyfloat::IncrByFloat;
+use crate::cmd::info::Info;
+use crate::cmd::llen::LLen;
+use crate::cmd::lpop::LPop;
+use crate::cmd::lpush::LPush;
+use crate::cmd::lrange::LRange;
+use crate::cmd::memory::Memory;
+use crate::cmd::mset::MSet;
+use crate::cmd::multi::Multi;
+use crate::cmd::object::Object;
 use crate::cmd::ping::Ping;
 use crate::cmd::publish::Publish;
+use crate::cmd::quit::Quit;
+use crate::cmd::ready::Ready;
+use crate::cmd::rename::Rename;
+use crate::cmd::reset::Reset;
+use crate::cmd::restore::Restore;
+use crate::cmd::rpop::RPop;
+use crate::cmd::rpush::RPush;
+use crate::cmd::sadd::SAdd;
+use crate::cmd::scan::Scan;
 use crate::cmd::set::Set;
+use crate::cmd::setbit::SetBit;
+use crate::cmd::setex::SetEx;
+use crate::cmd::setrange::SetRange;
+use crate::cmd::sismember::SIsMember;
+use crate::cmd::slowlog::SlowLog;
+use crate::cmd::smembers::SMembers;
 use crate::cmd::subscribe::Subscribe;
+use crate::cmd::touch::Touch;
+use crate::cmd::ttl::Ttl;
 use crate::cmd::unknown::Unknown;
+use crate::cmd::unlink::Unlink;
 use crate::cmd::unsubscribe::Unsubscribe;
-use crate::cmd::del::Del;
+use crate::cmd::watch::{Unwatch, Watch};
 
+pub(crate) mod append;
+pub(crate) mod bpop;
+pub(crate) mod client_cmd;
+pub(crate) mod config;
+pub(crate) mod dbsize;
+pub(crate) mod debug;
+pub(crate) mod del;
+pub(crate) mod discard;
+pub(crate) mod dump;
+pub(crate) mod exec;
+pub(crate) mod expire;
+pub(crate) mod expireat;
+pub(crate) mod flushall;
 pub(crate) mod get;
+pub(crate) mod getbit;
+pub(crate) mod getdel;
+pub(crate) mod hdel;
+pub(crate) mod hello;
+pub(crate) mod hexists;
+pub(crate) mod hget;
+pub(crate) mod hgetall;
+pub(crate) mod hlen;
+pub(crate) mod hset;
+pub(crate) mod incrbyfloat;
+pub(crate) mod info;
+pub(crate) mod llen;
+pub(crate) mod lpop;
+pub(crate) mod lpush;
+pub(crate) mod lrange;
+pub(crate) mod memory;
+pub(crate) mod mset;
+pub(crate) mod multi;
+pub(crate) mod object;
 pub(crate) mod ping;
 pub(crate) mod publish;
+pub(crate) mod quit;
+pub(crate) mod ready;
+pub(crate) mod rename;
+pub(crate) mod reset;
+pub(crate) mod restore;
+pub(crate) mod rpop;
+pub(crate) mod rpush;
+pub(crate) mod sadd;
+pub(crate) mod scan;
 pub(crate) mod set;
+pub(crate) mod setbit;
+pub(crate) mod setex;
+pub(crate) mod setrange;
+pub(crate) mod sismember;
+pub(crate) mod slowlog;
+pub(crate) mod smembers;
 pub(crate) mod subscribe;
+pub(crate) mod touch;
+pub(crate) mod ttl;
 pub(crate) mod unknown;
+pub(crate) mod unlink;
 pub(crate) mod unsubscribe;
-pub(crate) mod del;
+pub(crate) mod watch;
 
+use std::ops::ControlFlow;
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::config::ServerConfig;
 use crate::connection::connect::Connection;
 use crate::connection::frame::Frame;
 use crate::connection::parse::Parse;
-use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
 use crate::server::shutdown::Shutdown;
-use crate::storage::db::Db;
+use crate::server::state::{ConnState, Shared};
+use crate::storage::traits::AsyncKvStore;
 
 /// 支持的 Redis 命令枚举。
 ///
@@ -36,6 +144,59 @@ pub enum Command {
     Unsubscribe(Unsubscribe),
     Unknown(Unknown),
     Del(Del),
+    Ttl(Ttl),
+    Pttl(Ttl),
+    MSet(MSet),
+    Rename(Rename),
+    Append(Append),
+    GetDel(GetDel),
+    Touch(Touch),
+    Info(Info),
+    Hello(Hello),
+    Config(Config),
+    Quit(Quit),
+    Scan(Scan),
+    LPush(LPush),
+    RPush(RPush),
+    LPop(LPop),
+    RPop(RPop),
+    LRange(LRange),
+    LLen(LLen),
+    Object(Object),
+    HSet(HSet),
+    HGet(HGet),
+    HGetAll(HGetAll),
+    HDel(HDel),
+    HExists(HExists),
+    HLen(HLen),
+    Expire(Expire),
+    DbSize(DbSize),
+    Unlink(Unlink),
+    SetEx(SetEx),
+    ExpireAt(ExpireAt),
+    Ready(Ready),
+    BLPop(BPop),
+    BRPop(BPop),
+    Debug(Debug),
+    SetBit(SetBit),
+    GetBit(GetBit),
+    IncrByFloat(IncrByFloat),
+    Reset(Reset),
+    Multi(Multi),
+    Exec(Exec),
+    Discard(Discard),
+    SAdd(SAdd),
+    SMembers(SMembers),
+    SIsMember(SIsMember),
+    Dump(Dump),
+    Restore(Restore),
+    SetRange(SetRange),
+    FlushAll(FlushAll),
+    SlowLog(SlowLog),
+    Memory(Memory),
+    Watch(Watch),
+    Unwatch(Unwatch),
+    Client(ClientCmd),
 }
 
 impl Command {
@@ -57,6 +218,17 @@ impl Command {
         // 读取名称并转换为小写，以便进行区分大小写的匹配
         let cmd_name = parse.next_string()?.to_lowercase();
 
+        // 先统一检查参数个数，参数个数不对时回复错误而不是在解析中途失败并关闭连接
+        if let Some((min, max)) = arity(&cmd_name) {
+            let args = parse.remaining();
+            if args < min || max.is_some_and(|max| args > max) {
+                return Err(MiniRedisParseError::InvalidCommand(format!(
+                    "wrong number of arguments for '{}' command",
+                    cmd_name
+                )));
+            }
+        }
+
         // 匹配命令名称，将其余的解析委托给特定的命令
         let cmd = match &cmd_name[..] {
             "get" => Command::Get(Get::parse_frame(&mut parse)?),
@@ -66,6 +238,59 @@ impl Command {
             "subscribe" => Command::Subscribe(Subscribe::parse_frame(&mut parse)?),
             "unsubscribe" => Command::Unsubscribe(Unsubscribe::parse_frame(&mut parse)?),
             "del" => Command::Del(Del::parse_frame(&mut parse)?),
+            "ttl" => Command::Ttl(Ttl::parse_frame(&mut parse, false)?),
+            "pttl" => Command::Pttl(Ttl::parse_frame(&mut parse, true)?),
+            "mset" => Command::MSet(MSet::parse_frame(&mut parse)?),
+            "rename" => Command::Rename(Rename::parse_frame(&mut parse)?),
+            "append" => Command::Append(Append::parse_frame(&mut parse)?),
+            "getdel" => Command::GetDel(GetDel::parse_frame(&mut parse)?),
+            "touch" => Command::Touch(Touch::parse_frame(&mut parse)?),
+            "info" => Command::Info(Info::parse_frame(&mut parse)?),
+            "hello" => Command::Hello(Hello::parse_frame(&mut parse)?),
+            "config" => Command::Config(Config::parse_frame(&mut parse)?),
+            "quit" => Command::Quit(Quit::parse_frame(&mut parse)?),
+            "scan" => Command::Scan(Scan::parse_frame(&mut parse)?),
+            "lpush" => Command::LPush(LPush::parse_frame(&mut parse)?),
+            "rpush" => Command::RPush(RPush::parse_frame(&mut parse)?),
+            "lpop" => Command::LPop(LPop::parse_frame(&mut parse)?),
+            "rpop" => Command::RPop(RPop::parse_frame(&mut parse)?),
+            "lrange" => Command::LRange(LRange::parse_frame(&mut parse)?),
+            "llen" => Command::LLen(LLen::parse_frame(&mut parse)?),
+            "object" => Command::Object(Object::parse_frame(&mut parse)?),
+            "hset" => Command::HSet(HSet::parse_frame(&mut parse)?),
+            "hget" => Command::HGet(HGet::parse_frame(&mut parse)?),
+            "hgetall" => Command::HGetAll(HGetAll::parse_frame(&mut parse)?),
+            "hdel" => Command::HDel(HDel::parse_frame(&mut parse)?),
+            "hexists" => Command::HExists(HExists::parse_frame(&mut parse)?),
+            "hlen" => Command::HLen(HLen::parse_frame(&mut parse)?),
+            "expire" => Command::Expire(Expire::parse_frame(&mut parse)?),
+            "dbsize" => Command::DbSize(DbSize::parse_frame(&mut parse)?),
+            "unlink" => Command::Unlink(Unlink::parse_frame(&mut parse)?),
+            "setex" => Command::SetEx(SetEx::parse_frame(&mut parse)?),
+            "expireat" => Command::ExpireAt(ExpireAt::parse_frame(&mut parse)?),
+            "ready" => Command::Ready(Ready::parse_frame(&mut parse)?),
+            "blpop" => Command::BLPop(BPop::parse_frame(&mut parse, true)?),
+            "brpop" => Command::BRPop(BPop::parse_frame(&mut parse, false)?),
+            "debug" => Command::Debug(Debug::parse_frame(&mut parse)?),
+            "setbit" => Command::SetBit(SetBit::parse_frame(&mut parse)?),
+            "getbit" => Command::GetBit(GetBit::parse_frame(&mut parse)?),
+            "incrbyfloat" => Command::IncrByFloat(IncrByFloat::parse_frame(&mut parse)?),
+            "reset" => Command::Reset(Reset::parse_frame(&mut parse)?),
+            "multi" => Command::Multi(Multi::parse_frame(&mut parse)?),
+            "exec" => Command::Exec(Exec::parse_frame(&mut parse)?),
+            "discard" => Command::Discard(Discard::parse_frame(&mut parse)?),
+            "sadd" => Command::SAdd(SAdd::parse_frame(&mut parse)?),
+            "smembers" => Command::SMembers(SMembers::parse_frame(&mut parse)?),
+            "sismember" => Command::SIsMember(SIsMember::parse_frame(&mut parse)?),
+            "dump" => Command::Dump(Dump::parse_frame(&mut parse)?),
+            "restore" => Command::Restore(Restore::parse_frame(&mut parse)?),
+            "setrange" => Command::SetRange(SetRange::parse_frame(&mut parse)?),
+            "flushall" => Command::FlushAll(FlushAll::parse_frame(&mut parse)?),
+            "slowlog" => Command::SlowLog(SlowLog::parse_frame(&mut parse)?),
+            "memory" => Command::Memory(Memory::parse_frame(&mut parse)?),
+            "watch" => Command::Watch(Watch::parse_frame(&mut parse)?),
+            "unwatch" => Command::Unwatch(Unwatch::parse_frame(&mut parse)?),
+            "client" => Command::Client(ClientCmd::parse_frame(&mut parse)?),
             _ => {
                 // 命令未被识别，返回一个 `Unknown` 命令。
                 //
@@ -82,30 +307,110 @@ impl Command {
         Ok(cmd)
     }
 
-    /// 将命令应用于指定的 `Db` 实例。
+    /// 将命令应用于 `shared` 中的数据库。
     ///
     /// 响应被写入 `dst`。服务器调用此方法以执行接收到的命令。
-    pub(crate) async fn apply(
+    /// `GET`、`SET` 和 `DEL` 使用 `shared.store`，其他命令使用内存中的 `shared.db`。
+    ///
+    /// `state` 是连接自己的状态：`MULTI`、`EXEC`、`DISCARD` 和 `RESET` 修改其中的事务；
+    /// `WATCH` 添加监视的键，`EXEC` 检查，`UNWATCH`、`EXEC`、`DISCARD` 和 `RESET` 清空；
+    /// `CLIENT` 读取或者修改连接在已连接的客户端中的登记。
+    ///
+    /// 返回 `ControlFlow::Break` 表示命令要求关闭连接，例如 `QUIT`，调用者应当停止处理这个连接。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin, K: AsyncKvStore>(
         self,
-        db: &Db,
-        dst: &mut Connection,
+        shared: &Shared<'_, K>,
+        dst: &mut Connection<S>,
         shutdown: &mut Shutdown,
-    ) -> Result<(), MiniRedisConnectionError> {
+        state: &mut ConnState,
+    ) -> Result<ControlFlow<()>, MiniRedisConnectionError> {
         use Command::*;
 
-        match self {
-            Get(cmd) => cmd.apply(db, dst).await,
+        let Shared {
+            db,
+            store,
+            metrics,
+            config,
+        } = *shared;
+
+        let res = match self {
+            Get(cmd) => cmd.apply(store, dst).await,
             Ping(cmd) => cmd.apply(dst).await,
             Publish(cmd) => cmd.apply(db, dst).await,
-            Set(cmd) => cmd.apply(db, dst).await,
-            Subscribe(cmd) => cmd.apply(db, dst, shutdown).await,
-             // `Unsubscribe` 不能被应用。它只能在 `Subscribe` 命令的上下文中接收
-            Unsubscribe(_) => Err(MiniRedisConnectionError::CommandExecute(
-                "`Unsubscribe` is unsupported in this context".into(),
-            )),
+            Set(cmd) => cmd.apply(store, config, dst).await,
+            Subscribe(cmd) => cmd.apply(db, dst, shutdown, state).await,
+            // `Unsubscribe` 不能被应用。它只能在 `Subscribe` 命令的上下文中接收，
+            // 在其他地方收到时回复错误，连接保持打开
+            Unsubscribe(_) => {
+                let response = Frame::error(
+                    ErrorKind::Err,
+                    "'unsubscribe' is only allowed in the context of a subscription",
+                );
+                dst.write_frame(&response).await?;
+                Ok(())
+            }
             Unknown(cmd) => cmd.apply(dst).await,
-            Del(cmd) => cmd.apply(db, dst).await,
-        }
+            Del(cmd) => cmd.apply(store, dst).await,
+            Ttl(cmd) | Pttl(cmd) => cmd.apply(db, dst).await,
+            MSet(cmd) => cmd.apply(db, config, dst).await,
+            Rename(cmd) => cmd.apply(db, dst).await,
+            Append(cmd) => cmd.apply(db, config, dst).await,
+            GetDel(cmd) => cmd.apply(db, dst).await,
+            Touch(cmd) => cmd.apply(db, dst).await,
+            Info(cmd) => cmd.apply(db, metrics, dst).await,
+            Hello(cmd) => cmd.apply(dst).await,
+            Config(cmd) => cmd.apply(config, dst).await,
+            Scan(cmd) => cmd.apply(db, dst).await,
+            LPush(cmd) => cmd.apply(db, dst).await,
+            RPush(cmd) => cmd.apply(db, dst).await,
+            LPop(cmd) => cmd.apply(db, dst).await,
+            RPop(cmd) => cmd.apply(db, dst).await,
+            LRange(cmd) => cmd.apply(db, dst).await,
+            LLen(cmd) => cmd.apply(db, dst).await,
+            Object(cmd) => cmd.apply(db, dst).await,
+            HSet(cmd) => cmd.apply(db, dst).await,
+            HGet(cmd) => cmd.apply(db, dst).await,
+            HGetAll(cmd) => cmd.apply(db, dst).await,
+            HDel(cmd) => cmd.apply(db, dst).await,
+            HExists(cmd) => cmd.apply(db, dst).await,
+            HLen(cmd) => cmd.apply(db, dst).await,
+            Expire(cmd) => cmd.apply(db, dst).await,
+            DbSize(cmd) => cmd.apply(db, dst).await,
+            Unlink(cmd) => cmd.apply(db, dst).await,
+            SetEx(cmd) => cmd.apply(db, config, dst).await,
+            ExpireAt(cmd) => cmd.apply(db, dst).await,
+            Ready(cmd) => cmd.apply(db, dst).await,
+            BLPop(cmd) | BRPop(cmd) => cmd.apply(db, dst, shutdown).await,
+            Debug(cmd) => cmd.apply(config, dst).await,
+            SetBit(cmd) => cmd.apply(db, config, dst).await,
+            GetBit(cmd) => cmd.apply(db, dst).await,
+            IncrByFloat(cmd) => cmd.apply(db, dst).await,
+            Reset(cmd) => cmd.apply(state, dst).await,
+            Multi(cmd) => cmd.apply(&mut state.transaction, dst).await,
+            Exec(cmd) => cmd.apply(shared, dst, shutdown, state).await,
+            Discard(cmd) => {
+                cmd.apply(&mut state.transaction, &mut state.watched, dst)
+                    .await
+            }
+            SAdd(cmd) => cmd.apply(db, dst).await,
+            SMembers(cmd) => cmd.apply(db, dst).await,
+            SIsMember(cmd) => cmd.apply(db, dst).await,
+            Dump(cmd) => cmd.apply(db, dst).await,
+            Restore(cmd) => cmd.apply(db, dst).await,
+            SetRange(cmd) => cmd.apply(db, config, dst).await,
+            FlushAll(cmd) => cmd.apply(db, dst).await,
+            SlowLog(cmd) => cmd.apply(metrics, dst).await,
+            Memory(cmd) => cmd.apply(db, dst).await,
+            Watch(cmd) => cmd.apply(db, &mut state.watched, dst).await,
+            Unwatch(cmd) => cmd.apply(&mut state.watched, dst).await,
+            Client(cmd) => cmd.apply(&state.client, dst).await,
+            Quit(cmd) => {
+                cmd.apply(dst).await?;
+                return Ok(ControlFlow::Break(()));
+            }
+        };
+
+        res.map(|()| ControlFlow::Continue(()))
     }
 
     /// 根据自身枚举类型返回命令名称，方便识别命令类型。
@@ -119,6 +424,184 @@ impl Command {
             Command::Unsubscribe(_) => "unsubscribe",
             Command::Unknown(cmd) => cmd.get_name(),
             Command::Del(_) => "del",
+            Command::Ttl(_) => "ttl",
+            Command::Pttl(_) => "pttl",
+            Command::MSet(_) => "mset",
+            Command::Rename(_) => "rename",
+            Command::Append(_) => "append",
+            Command::GetDel(_) => "getdel",
+            Command::Touch(_) => "touch",
+            Command::Info(_) => "info",
+            Command::Hello(_) => "hello",
+            Command::Config(_) => "config",
+            Command::Quit(_) => "quit",
+            Command::Scan(_) => "scan",
+            Command::LPush(_) => "lpush",
+            Command::RPush(_) => "rpush",
+            Command::LPop(_) => "lpop",
+            Command::RPop(_) => "rpop",
+            Command::LRange(_) => "lrange",
+            Command::LLen(_) => "llen",
+            Command::Object(_) => "object",
+            Command::HSet(_) => "hset",
+            Command::HGet(_) => "hget",
+            Command::HGetAll(_) => "hgetall",
+            Command::HDel(_) => "hdel",
+            Command::HExists(_) => "hexists",
+            Command::HLen(_) => "hlen",
+            Command::Expire(_) => "expire",
+            Command::DbSize(_) => "dbsize",
+            Command::Unlink(_) => "unlink",
+            Command::SetEx(_) => "setex",
+            Command::ExpireAt(_) => "expireat",
+            Command::Ready(_) => "ready",
+            Command::BLPop(_) => "blpop",
+            Command::BRPop(_) => "brpop",
+            Command::Debug(_) => "debug",
+            Command::SetBit(_) => "setbit",
+            Command::GetBit(_) => "getbit",
+            Command::IncrByFloat(_) => "incrbyfloat",
+            Command::Reset(_) => "reset",
+            Command::Multi(_) => "multi",
+            Command::Exec(_) => "exec",
+            Command::Discard(_) => "discard",
+            Command::SAdd(_) => "sadd",
+            Command::SMembers(_) => "smembers",
+            Command::SIsMember(_) => "sismember",
+            Command::Dump(_) => "dump",
+            Command::Restore(_) => "restore",
+            Command::SetRange(_) => "setrange",
+            Command::FlushAll(_) => "flushall",
+            Command::SlowLog(_) => "slowlog",
+            Command::Memory(_) => "memory",
+            Command::Watch(_) => "watch",
+            Command::Unwatch(_) => "unwatch",
+            Command::Client(_) => "client",
+        }
+    }
+
+    /// 返回命令的分类，服务器据此决定命令在特定状态下是否可以执行。
+    pub(crate) fn flags(&self) -> CommandFlags {
+        use Command::*;
+
+        match self {
+            Set(_) | MSet(_) | Append(_) | LPush(_) | RPush(_) | HSet(_) | SetEx(_) | SetBit(_)
+            | IncrByFloat(_) | SAdd(_) | Restore(_) | SetRange(_) => CommandFlags::WRITE,
+            // 这些命令修改键空间，但只会删除数据或者修改元数据，不会占用更多内存
+            Del(_) | Unlink(_) | GetDel(_) | LPop(_) | RPop(_) | HDel(_) | Rename(_)
+            | Expire(_) | ExpireAt(_) | BLPop(_) | BRPop(_) => CommandFlags::WRITE_FREE,
+            FlushAll(_) => CommandFlags::WRITE_FREE,
+            // 只读取键空间或者不访问键空间的命令
+            Get(_) | Ttl(_) | Pttl(_) | Touch(_) | Scan(_) | LRange(_) | LLen(_) | Object(_)
+            | HGet(_) | HGetAll(_) | HExists(_) | HLen(_) | DbSize(_) | Ping(_) | Publish(_)
+            | Subscribe(_) | Unsubscribe(_) | Unknown(_) | Info(_) | Hello(_) | Config(_)
+            | Quit(_) | Ready(_) | Debug(_) | GetBit(_) | Reset(_) | Multi(_) | Discard(_)
+            | SMembers(_) | SIsMember(_) | Dump(_) | SlowLog(_) | Memory(_) | Watch(_)
+            | Unwatch(_) | Client(_) => CommandFlags::READONLY,
+            // 排队的命令在排队时已经各自检查过
+            Exec(_) => CommandFlags::READONLY,
         }
     }
 }
+
+/// 命令的分类，对应 Redis 命令表中的 `write` 和 `denyoom` 等标志。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CommandFlags {
+    /// 命令会修改键空间。
+    write: bool,
+    /// 命令只会删除数据或者修改过期时间等元数据。
+    frees: bool,
+}
+
+impl CommandFlags {
+    /// 不修改键空间的命令。
+    const READONLY: CommandFlags = CommandFlags {
+        write: false,
+        frees: false,
+    };
+
+    /// 可能写入新数据的命令。
+    const WRITE: CommandFlags = CommandFlags {
+        write: true,
+        frees: false,
+    };
+
+    /// 修改键空间但不会占用更多内存的命令，例如 `DEL`。
+    const WRITE_FREE: CommandFlags = CommandFlags {
+        write: true,
+        frees: true,
+    };
+
+    /// 命令是否可能占用更多内存，超过 `maxmemory` 时应当拒绝执行。
+    pub(crate) fn deny_oom(&self) -> bool {
+        self.write && !self.frees
+    }
+}
+
+/// 检查从现在开始计算的过期时间是否有效。
+///
+/// 与 Redis 一样，过期时间点换算为 Unix 毫秒时间戳后必须在 `i64` 的范围内，
+/// 否则回复 `invalid expire time` 错误，而不是在计算过期时间点时溢出。
+pub(crate) fn check_expire(expire: Duration, cmd: &str) -> Result<Duration, MiniRedisParseError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    if now.as_millis() + expire.as_millis() > i64::MAX as u128 {
+        return Err(MiniRedisParseError::InvalidCommand(format!(
+            "invalid expire time in '{}' command",
+            cmd
+        )));
+    }
+
+    Ok(expire)
+}
+
+/// 返回字符串值允许的最大长度和对应的配置名，即 `proto-max-bulk-len` 和 `max-value-size` 中较小的一个。
+///
+/// 写入字符串的命令在分配内存之前检查结果的长度，超过时回复 [`value_too_large`]，不做修改。
+pub(crate) fn max_value_len(config: &RwLock<ServerConfig>) -> (usize, &'static str) {
+    let config = config.read().unwrap();
+    match config.max_value_size {
+        Some(max_value_size) if max_value_size < config.proto_max_bulk_len => {
+            (max_value_size, "max-value-size")
+        }
+        _ => (config.proto_max_bulk_len, "proto-max-bulk-len"),
+    }
+}
+
+/// 字符串超过 [`max_value_len`] 时回复的错误，`limit` 是对应的配置名。
+pub(crate) fn value_too_large(limit: &str) -> Frame {
+    Frame::error(
+        ErrorKind::Err,
+        format!("string exceeds maximum allowed size ({})", limit),
+    )
+}
+
+// 返回命令的参数个数范围 `(最少, 最多)`，不包括命令名称，最多为 `None` 表示没有上限。
+// 未知的命令返回 `None`，由 `Unknown` 回复错误。
+// 这里只检查个数，参数需要成对出现等更细的规则仍然由各个命令自己检查
+fn arity(name: &str) -> Option<(usize, Option<usize>)> {
+    let arity = match name {
+        "dbsize" | "discard" | "exec" | "flushall" | "info" | "multi" | "quit" | "ready"
+        | "reset" | "unwatch" => (0, Some(0)),
+        "hello" | "ping" => (0, Some(1)),
+        "unsubscribe" => (0, None),
+        "del" | "dump" | "get" | "getdel" | "hgetall" | "hlen" | "llen" | "lpop" | "pttl"
+        | "rpop" | "smembers" | "ttl" => (1, Some(1)),
+        "client" | "config" | "slowlog" => (1, Some(2)),
+        "scan" | "subscribe" | "touch" | "unlink" | "watch" => (1, None),
+        "append" | "debug" | "expireat" | "getbit" | "hexists" | "hget" | "incrbyfloat"
+        | "memory" | "object" | "rename" | "sismember" => (2, Some(2)),
+        "publish" => (2, Some(3)),
+        "blpop" | "brpop" | "expire" | "hdel" | "lpush" | "mset" | "rpush" | "sadd" | "set" => {
+            (2, None)
+        }
+        "lrange" | "setbit" | "setex" | "setrange" => (3, Some(3)),
+        "restore" => (3, Some(4)),
+        "hset" => (3, None),
+        _ => return None,
+    };
+
+    Some(arity)
+}
@@ -0,0 +1,117 @@
+use std::sync::RwLock;
+
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::cmd::{max_value_len, value_too_large};
+use crate::config::ServerConfig;
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 一次设置多个键值对。
+///
+/// 与 `SET` 一样，已存在的值会被覆盖，之前的生存时间会被丢弃。
+/// 所有键值对会被原子地写入，其他客户端不会看到只设置了一部分键的状态。
+/// 任何一个值的长度超过 `max-value-size` 时回复错误，所有的键都不会被修改。
+#[derive(Debug)]
+pub struct MSet {
+    /// 要设置的键值对
+    pairs: Vec<(String, Bytes)>,
+}
+
+impl MSet {
+    /// 创建一个新的 `MSET` 操作实例。
+    ///
+    /// # 参数
+    ///
+    /// * `pairs` - 要设置的键值对。
+    pub fn new(pairs: Vec<(String, Bytes)>) -> MSet {
+        MSet { pairs }
+    }
+
+    /// 获取要设置的键值对。
+    pub fn pairs(&self) -> &[(String, Bytes)] {
+        &self.pairs
+    }
+
+    /// 从接收到的帧中解析 `MSet` 实例。
+    ///
+    /// `MSET` 字符串已被使用。整个命令会在执行前解析完成，
+    /// 参数个数不匹配时返回错误，不会写入任何数据。
+    ///
+    /// # 格式
+    ///
+    /// 期望数组帧包含至少 3 个条目，并且键值成对出现。
+    ///
+    /// ```text
+    /// MSET key value [key value ...]
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<MSet, MiniRedisParseError> {
+        let mut pairs = vec![];
+
+        loop {
+            let key = match parse.next_string() {
+                Ok(key) => key,
+                // 所有键值对都已读取
+                Err(MiniRedisParseError::EndOfStream) => break,
+                Err(e) => return Err(e),
+            };
+
+            match parse.next_bytes() {
+                Ok(value) => pairs.push((key, value)),
+                // 最后一个键缺少对应的值
+                Err(MiniRedisParseError::EndOfStream) => return Err(wrong_arity()),
+                Err(e) => return Err(e),
+            }
+        }
+
+        if pairs.is_empty() {
+            return Err(wrong_arity());
+        }
+
+        Ok(MSet { pairs })
+    }
+
+    /// 将 `MSet` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        config: &RwLock<ServerConfig>,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let (max_len, limit) = max_value_len(config);
+        let response = if self.pairs.iter().any(|(_, value)| value.len() > max_len) {
+            value_too_large(limit)
+        } else {
+            db.mset(self.pairs);
+            Frame::Simple("OK".to_string())
+        };
+
+        debug!("mset cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `MSET` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = FrameBuilder::new().bulk("mset");
+        for (key, value) in self.pairs {
+            frame = frame.bulk(key).bulk(value);
+        }
+
+        frame.build()
+    }
+}
+
+fn wrong_arity() -> MiniRedisParseError {
+    MiniRedisParseError::InvalidCommand("wrong number of arguments for 'mset' command".into())
+}
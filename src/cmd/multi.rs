@@ -0,0 +1,108 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::cmd::Command;
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+
+/// 开始一个事务。
+///
+/// 之后的命令不会立即执行，而是放入队列并回复 `QUEUED`，直到收到 `EXEC` 时依次执行，
+/// 或者收到 `DISCARD` 时放弃。事务不能嵌套。
+#[derive(Debug)]
+pub struct Multi;
+
+impl Multi {
+    /// 从接收到的帧中解析 `Multi` 实例。
+    ///
+    /// `MULTI` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个只包含一个条目的数组帧。
+    ///
+    /// ```text
+    /// MULTI
+    /// ```
+    pub(crate) fn parse_frame(_parse: &mut Parse) -> Result<Multi, MiniRedisParseError> {
+        Ok(Multi)
+    }
+
+    /// 应用 `Multi` 命令，让连接进入事务状态并回复 `OK`。
+    ///
+    /// 事务中再次收到的 `MULTI` 在排队时就被拒绝，见 [`Transaction::queue`]。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        transaction: &mut Option<Transaction>,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        *transaction = Some(Transaction::default());
+        let response = Frame::Simple("OK".to_string());
+
+        debug!("multi cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 一个连接的事务状态，保存 `MULTI` 之后排队的命令。
+#[derive(Debug, Default)]
+pub(crate) struct Transaction {
+    /// 排队的命令，按收到的顺序执行
+    commands: Vec<Command>,
+    /// 排队时出现了错误，`EXEC` 时放弃整个事务
+    aborted: bool,
+}
+
+impl Transaction {
+    /// 标记排队时出现了错误，例如命令的参数不合法。
+    pub(crate) fn abort(&mut self) {
+        self.aborted = true;
+    }
+
+    /// 排队时是否出现过错误。
+    pub(crate) fn is_aborted(&self) -> bool {
+        self.aborted
+    }
+
+    /// 取出排队的命令。
+    pub(crate) fn into_commands(self) -> Vec<Command> {
+        self.commands
+    }
+
+    /// 将 `cmd` 放入队列并回复 `QUEUED`。
+    ///
+    /// 未知的命令、嵌套的 `MULTI`、`WATCH` 以及会一直阻塞或者进入订阅状态的命令不能在事务中执行，
+    /// 这时回复错误，并且整个事务在 `EXEC` 时被放弃。
+    pub(crate) async fn queue<S: AsyncRead + AsyncWrite + Unpin>(
+        &mut self,
+        cmd: Command,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match cmd {
+            Command::Unknown(cmd) => {
+                self.abort();
+                return cmd.apply(dst).await;
+            }
+            Command::Multi(_) => {
+                self.abort();
+                Frame::error(ErrorKind::Err, "MULTI calls can not be nested")
+            }
+            Command::Subscribe(_) | Command::BLPop(_) | Command::BRPop(_) | Command::Watch(_) => {
+                self.abort();
+                Frame::error(ErrorKind::Err, "Command not allowed inside a transaction")
+            }
+            cmd => {
+                debug!("queued command {}", cmd.get_name());
+                self.commands.push(cmd);
+                Frame::Simple("QUEUED".to_string())
+            }
+        };
+
+        dst.write_frame(&response).await
+    }
+}
@@ -0,0 +1,142 @@
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 查看键的内部信息，用于调优缓存。
+///
+/// 支持三个子命令：
+///
+/// - `OBJECT ENCODING key` 返回值的编码名称，能解析为整数的字符串是 `int`，
+///   短字符串是 `embstr`，其他字符串是 `raw`，列表是 `quicklist`。
+/// - `OBJECT IDLETIME key` 返回键距离上次被访问经过的秒数。
+/// - `OBJECT REFCOUNT key` 返回值的引用计数。值不会在键之间共享，因此总是 1。
+///
+/// `OBJECT` 本身不会更新键的访问时间。如果 key 不存在，则返回特殊值 nil。
+#[derive(Debug)]
+pub struct Object {
+    /// 子命令
+    subcommand: Subcommand,
+    /// 要查看的键
+    key: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Subcommand {
+    Encoding,
+    IdleTime,
+    RefCount,
+}
+
+impl Object {
+    /// 创建一个新的 `OBJECT ENCODING` 命令。
+    pub fn encoding(key: impl ToString) -> Object {
+        Object {
+            subcommand: Subcommand::Encoding,
+            key: key.to_string(),
+        }
+    }
+
+    /// 创建一个新的 `OBJECT IDLETIME` 命令。
+    pub fn idle_time(key: impl ToString) -> Object {
+        Object {
+            subcommand: Subcommand::IdleTime,
+            key: key.to_string(),
+        }
+    }
+
+    /// 创建一个新的 `OBJECT REFCOUNT` 命令。
+    pub fn refcount(key: impl ToString) -> Object {
+        Object {
+            subcommand: Subcommand::RefCount,
+            key: key.to_string(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 从接收到的帧中解析 `Object` 实例。
+    ///
+    /// `OBJECT` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个条目的数组帧。
+    ///
+    /// ```text
+    /// OBJECT ENCODING key
+    /// OBJECT IDLETIME key
+    /// OBJECT REFCOUNT key
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Object, MiniRedisParseError> {
+        let subcommand = match &parse.next_string()?.to_lowercase()[..] {
+            "encoding" => Subcommand::Encoding,
+            "idletime" => Subcommand::IdleTime,
+            "refcount" => Subcommand::RefCount,
+            subcommand => {
+                return Err(MiniRedisParseError::InvalidCommand(format!(
+                    "unknown subcommand '{}'",
+                    subcommand
+                )))
+            }
+        };
+        let key = parse.next_string()?;
+
+        Ok(Object { subcommand, key })
+    }
+
+    /// 将 `Object` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match self.subcommand {
+            Subcommand::Encoding => match db.object_encoding(&self.key) {
+                Some(encoding) => Frame::Bulk(Bytes::from_static(encoding.as_bytes())),
+                None => Frame::Null,
+            },
+            Subcommand::IdleTime => match db.object_idle_time(&self.key) {
+                Some(idle) => Frame::Integer(idle.as_secs() as i64),
+                None => Frame::Null,
+            },
+            // 借用 `object_encoding` 判断键是否存在，它不会更新访问时间
+            Subcommand::RefCount => match db.object_encoding(&self.key) {
+                Some(_) => Frame::Integer(1),
+                None => Frame::Null,
+            },
+        };
+
+        debug!("object cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `OBJECT` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        let subcommand = match self.subcommand {
+            Subcommand::Encoding => "encoding",
+            Subcommand::IdleTime => "idletime",
+            Subcommand::RefCount => "refcount",
+        };
+
+        FrameBuilder::new()
+            .bulk("object")
+            .bulk(subcommand)
+            .bulk(self.key)
+            .build()
+    }
+}
@@ -1,9 +1,10 @@
 use bytes::Bytes; // 引入 bytes 库中的 Bytes 类型，用于处理原始二进制数据
 use log::debug; // 引入 log 库的 debug 宏，用于输出调试信息
+use tokio::io::{AsyncRead, AsyncWrite}; // 命令可以写入任何字节流上的连接
 
 // 引入本项目内的模块，用于处理连接、帧解析和错误处理
 use crate::connection::connect::Connection;
-use crate::connection::frame::Frame;
+use crate::connection::frame::{Frame, FrameBuilder};
 use crate::connection::parse::Parse;
 use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
 
@@ -53,7 +54,10 @@ impl Ping {
     /// 应用 `Ping` 命令并返回消息。
     ///
     /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
-    pub(crate) async fn apply(self, dst: &mut Connection) -> Result<(), MiniRedisConnectionError> {
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
         let response = match self.msg {
             Some(msg) => Frame::Bulk(Bytes::from(msg)), // 如果有消息，创建一个 bulk frame
             None => Frame::Simple("PONG".to_string()),  // 没有消息，创建一个 PONG frame
@@ -67,17 +71,31 @@ impl Ping {
         Ok(())
     }
 
+    /// 在订阅模式下应用 `Ping` 命令。
+    ///
+    /// 订阅模式下的响应是一个数组帧 `["pong", msg]`，没有提供消息时 `msg` 为空字符串。
+    pub(crate) async fn apply_subscribed<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = FrameBuilder::new()
+            .bulk("pong")
+            .bulk(self.msg.unwrap_or_default())
+            .build();
+
+        debug!("ping cmd applied in subscribe mode response: {}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
     /// 将 `PING` 命令转换为用于网络传输的 `Frame` 格式，主要为客户端使用。
     ///
     /// # 返回值
     ///
     /// 返回一个表示 `PING` 请求的 `Frame` 实例。
-    pub(crate) fn into_frame(self) -> Result<Frame, MiniRedisParseError> {
-        let mut frame = Frame::array();
-        frame.push_bulk(Bytes::from("ping".as_bytes()))?;
-        if let Some(msg) = self.msg {
-            frame.push_bulk(Bytes::from(msg))?;
-        }
-        Ok(frame)
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("ping", self.msg)
     }
 }
@@ -1,8 +1,9 @@
 use bytes::Bytes;
 use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
 
 use crate::connection::connect::Connection;
-use crate::connection::frame::Frame;
+use crate::connection::frame::{Frame, FrameBuilder};
 use crate::connection::parse::Parse;
 use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
 use crate::storage::db::Db;
@@ -14,12 +15,17 @@ use crate::storage::traits::KvStore;
 /// 消费者可以订阅频道以接收消息。
 ///
 /// 频道名称与键值命名空间没有关系。在名为 "foo" 的频道上发布与设置 "foo" 键没有关系。
+///
+/// 指定 `DETAILED` 选项时，回复一个包含两个整数的数组：收到消息的订阅者数量，
+/// 以及已经没有任何订阅者的频道数量。
 #[derive(Debug)]
 pub struct Publish {
     /// 频道名称
     channel: String,
     /// 消息内容，使用 `Bytes` 类型以支持二进制数据
     message: Bytes,
+    /// 是否同时回复没有订阅者的频道数量
+    detailed: bool,
 }
 
 impl Publish {
@@ -37,6 +43,15 @@ impl Publish {
         Publish {
             channel: channel.to_string(),
             message,
+            detailed: false,
+        }
+    }
+
+    /// 创建一个带有 `DETAILED` 选项的 `Publish` 实例。
+    pub(crate) fn detailed(channel: impl ToString, message: Bytes) -> Self {
+        Publish {
+            detailed: true,
+            ..Publish::new(channel, message)
         }
     }
 
@@ -53,17 +68,30 @@ impl Publish {
     ///
     /// # 格式
     ///
-    /// 期望数组帧包含三个条目。
+    /// 期望数组帧包含三个条目，或者带有 `DETAILED` 选项的四个条目。
+    /// 缺少消息或者多出 `DETAILED` 以外的参数时返回参数个数错误。
     ///
     /// ```text
-    /// PUBLISH channel message
+    /// PUBLISH channel message [DETAILED]
     /// ```
     pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Publish, MiniRedisParseError> {
+        // 参数个数已经由 `arity` 检查过，这里只有两个或者三个参数
         // `channel` 必须是有效的字符串
         let channel = parse.next_string()?;
-         // `message` 是任意字节
+        // `message` 是任意字节
         let message = parse.next_bytes()?;
-        Ok(Publish { channel, message })
+        // 第三个参数只能是 `DETAILED` 选项
+        let detailed = match parse.next_string() {
+            Ok(option) if option.eq_ignore_ascii_case("detailed") => true,
+            Ok(_) => return Err(syntax_error()),
+            Err(MiniRedisParseError::EndOfStream) => false,
+            Err(err) => return Err(err),
+        };
+        Ok(Publish {
+            channel,
+            message,
+            detailed,
+        })
     }
 
     /// 将 `Publish` 操作应用到数据库，并将响应写入连接。
@@ -76,19 +104,28 @@ impl Publish {
     /// # 返回值
     ///
     /// 成功时返回 `Ok(())`，失败时返回 `MiniRedisConnectionError`。
-    pub(crate) async fn apply(
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
         self,
         db: &Db,
-        dst: &mut Connection,
+        dst: &mut Connection<S>,
     ) -> Result<(), MiniRedisConnectionError> {
         // 共享状态包含所有活跃频道的 `tokio::sync::broadcast::Sender`。
         // 调用 `db.publish` 将消息发送到适当的频道。
         //
         // 返回当前监听频道的订阅者数量。这并不意味着 `num_subscriber` 频道将接收消息。
         // 订阅者可能在接收消息之前退出。鉴于此，`num_subscribers` 仅应作为“提示”使用。
-        let num_subscribers = db.publish(&self.channel, self.message);
-        // 订阅者数量作为发布请求的响应返回
-        let response = Frame::Integer(num_subscribers as u64);
+        let response = if self.detailed {
+            let (num_subscribers, stale_channels) =
+                db.publish_detailed(&self.channel, self.message);
+            Frame::Array(vec![
+                Frame::Integer(num_subscribers as i64),
+                Frame::Integer(stale_channels as i64),
+            ])
+        } else {
+            let num_subscribers = db.publish(&self.channel, self.message);
+            // 订阅者数量作为发布请求的响应返回
+            Frame::Integer(num_subscribers as i64)
+        };
         debug!("apply cmd applied response: {}", response);
         dst.write_frame(&response).await?;
         Ok(())
@@ -99,12 +136,23 @@ impl Publish {
     /// # 返回值
     ///
     /// 成功时返回 `Frame` 实例，失败时返回 `MiniRedisParseError`。
-    pub(crate) fn into_frame(self) -> Result<Frame, MiniRedisParseError> {
-        let mut frame = Frame::array();
-        frame.push_bulk(Bytes::from("publish".as_bytes()))?;
-        frame.push_bulk(Bytes::from(self.channel.into_bytes()))?;
-        // frame.push_bulk(Bytes::from(self.message))?;
-        frame.push_bulk(self.message)?;
-        Ok(frame)
+    pub(crate) fn into_frame(self) -> Frame {
+        let detailed = if self.detailed {
+            Some("detailed")
+        } else {
+            None
+        };
+
+        FrameBuilder::new()
+            .bulk("publish")
+            .bulk(self.channel)
+            .bulk(self.message)
+            .bulks(detailed)
+            .build()
     }
 }
+
+// 语法错误会作为错误帧回复给客户端，连接保持打开
+fn syntax_error() -> MiniRedisParseError {
+    MiniRedisParseError::InvalidCommand("syntax error".into())
+}
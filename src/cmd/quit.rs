@@ -0,0 +1,56 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+
+/// 请求服务器关闭连接。
+///
+/// 服务器回复 `OK` 之后关闭连接。
+#[derive(Debug, Default)]
+pub struct Quit;
+
+impl Quit {
+    /// 创建一个新的 `QUIT` 命令。
+    pub fn new() -> Quit {
+        Quit
+    }
+
+    /// 从接收到的帧中解析 `Quit` 实例。
+    ///
+    /// `QUIT` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个只包含一个条目的数组帧。
+    ///
+    /// ```text
+    /// QUIT
+    /// ```
+    pub(crate) fn parse_frame(_parse: &mut Parse) -> Result<Quit, MiniRedisParseError> {
+        Ok(Quit)
+    }
+
+    /// 应用 `Quit` 命令，回复 `OK`。
+    ///
+    /// 连接由调用者在此之后关闭，见 `Command::apply`。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = Frame::Simple("OK".to_string());
+
+        debug!("quit cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `QUIT` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new().bulk("quit").build()
+    }
+}
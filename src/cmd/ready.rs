@@ -0,0 +1,63 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+
+/// 就绪检查，供编排系统判断服务器是否可以接收请求。
+///
+/// 与 `PING` 不同，它还会检查数据库的后台清理任务没有被关闭：
+/// 就绪时回复 `+READY`，否则回复 `-NOTREADY` 错误。
+#[derive(Debug, Default)]
+pub struct Ready;
+
+impl Ready {
+    /// 创建一个新的 `READY` 命令。
+    pub fn new() -> Ready {
+        Ready
+    }
+
+    /// 从接收到的帧中解析 `Ready` 实例。
+    ///
+    /// `READY` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个只包含一个条目的数组帧。
+    ///
+    /// ```text
+    /// READY
+    /// ```
+    pub(crate) fn parse_frame(_parse: &mut Parse) -> Result<Ready, MiniRedisParseError> {
+        Ok(Ready)
+    }
+
+    /// 将 `Ready` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = if db.is_ready() {
+            Frame::Simple("READY".to_string())
+        } else {
+            Frame::error(ErrorKind::NotReady, "background tasks are shut down")
+        };
+
+        debug!("ready cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `READY` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new().bulk("ready").build()
+    }
+}
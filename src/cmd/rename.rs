@@ -0,0 +1,85 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 将 `key` 重命名为 `newkey`。
+///
+/// 值和剩余生存时间一起移动到 `newkey`，`newkey` 原有的值会被覆盖。
+/// 如果 `key` 不存在，返回错误。
+#[derive(Debug)]
+pub struct Rename {
+    /// 原来的键
+    key: String,
+    /// 新的键
+    newkey: String,
+}
+
+impl Rename {
+    /// 创建一个新的 `RENAME` 命令，将 `key` 重命名为 `newkey`。
+    pub fn new(key: impl ToString, newkey: impl ToString) -> Rename {
+        Rename {
+            key: key.to_string(),
+            newkey: newkey.to_string(),
+        }
+    }
+
+    /// 获取原来的键。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取新的键。
+    pub fn newkey(&self) -> &str {
+        &self.newkey
+    }
+
+    /// 从接收到的帧中解析 `Rename` 实例。
+    ///
+    /// `RENAME` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个条目的数组帧。
+    ///
+    /// ```text
+    /// RENAME key newkey
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Rename, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let newkey = parse.next_string()?;
+
+        Ok(Rename { key, newkey })
+    }
+
+    /// 将 `Rename` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = if db.rename(&self.key, self.newkey) {
+            Frame::Simple("OK".to_string())
+        } else {
+            Frame::error(ErrorKind::Err, "no such key")
+        };
+
+        debug!("rename cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `RENAME` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("rename", [self.key, self.newkey])
+    }
+}
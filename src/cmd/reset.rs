@@ -0,0 +1,65 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::consts::RESP2;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::server::state::ConnState;
+
+/// 将连接恢复到刚建立时的状态，回复 `RESET`。
+///
+/// 协议版本恢复为 RESP2，放弃正在排队的事务，取消监视所有的键并清除 `CLIENT SETNAME` 设置的名称；处于订阅状态时取消所有订阅并回到普通的命令模式，
+/// 与 Redis 一样不会为取消的频道发送 `unsubscribe` 消息。
+/// 服务器目前没有 `AUTH` 和 `SELECT`，以后加入时它们的状态也应当在这里重置。
+#[derive(Debug, Default)]
+pub struct Reset;
+
+impl Reset {
+    /// 创建一个新的 `RESET` 命令。
+    pub fn new() -> Reset {
+        Reset
+    }
+
+    /// 从接收到的帧中解析 `Reset` 实例。
+    ///
+    /// `RESET` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个只包含一个条目的数组帧。
+    ///
+    /// ```text
+    /// RESET
+    /// ```
+    pub(crate) fn parse_frame(_parse: &mut Parse) -> Result<Reset, MiniRedisParseError> {
+        Ok(Reset)
+    }
+
+    /// 应用 `Reset` 命令，重置连接的状态并回复 `RESET`。
+    ///
+    /// 订阅状态由 `Subscribe::apply` 在调用此方法之后退出。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        state: &mut ConnState,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        state.transaction = None;
+        state.watched.clear();
+        state.client.set_name(None);
+        dst.set_protocol(RESP2);
+        let response = Frame::Simple("RESET".to_string());
+
+        debug!("reset cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `RESET` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new().bulk("reset").build()
+    }
+}
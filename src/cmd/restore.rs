@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::cmd::check_expire;
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::persistence;
+use crate::storage::traits::KvStore;
+
+/// 使用 `DUMP` 得到的数据创建 key。
+///
+/// `ttl` 是以毫秒为单位的过期时间，0 表示不过期。key 已经存在时回复 `BUSYKEY` 错误，
+/// 指定 `REPLACE` 时覆盖原有的值。数据损坏或者版本不兼容时回复错误，不会创建 key。
+#[derive(Debug)]
+pub struct Restore {
+    /// 要创建的键
+    key: String,
+    /// 过期时间，`None` 表示不过期
+    ttl: Option<Duration>,
+    /// `DUMP` 返回的数据
+    payload: Bytes,
+    /// 是否覆盖已经存在的键
+    replace: bool,
+}
+
+impl Restore {
+    /// 创建一个新的 `RESTORE` 命令。
+    pub fn new(
+        key: impl ToString,
+        ttl: Option<Duration>,
+        payload: Bytes,
+        replace: bool,
+    ) -> Restore {
+        Restore {
+            key: key.to_string(),
+            ttl,
+            payload,
+            replace,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取过期时间。
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// 从接收到的帧中解析 `Restore` 实例。
+    ///
+    /// `RESTORE` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含四个或五个条目的数组帧。
+    ///
+    /// ```text
+    /// RESTORE key ttl serialized-value [REPLACE]
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Restore, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let ttl = parse.next_signed_int()?;
+        let payload = parse.next_bytes()?;
+
+        let mut replace = false;
+        loop {
+            match parse.next_string() {
+                Ok(option) if option.eq_ignore_ascii_case("replace") => replace = true,
+                Ok(_) => return Err(MiniRedisParseError::InvalidCommand("syntax error".into())),
+                Err(MiniRedisParseError::EndOfStream) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        let ttl = match ttl {
+            0 => None,
+            ttl if ttl < 0 => {
+                return Err(MiniRedisParseError::InvalidCommand(
+                    "Invalid TTL value, must be >= 0".into(),
+                ))
+            }
+            ttl => Some(check_expire(Duration::from_millis(ttl as u64), "restore")?),
+        };
+
+        Ok(Restore {
+            key,
+            ttl,
+            payload,
+            replace,
+        })
+    }
+
+    /// 将 `Restore` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match persistence::decode(&self.payload) {
+            Ok(value) => {
+                if db.restore(self.key, value, self.ttl, self.replace) {
+                    Frame::Simple("OK".to_string())
+                } else {
+                    Frame::error(ErrorKind::BusyKey, "Target key name already exists.")
+                }
+            }
+            Err(err) => Frame::error(ErrorKind::Err, err),
+        };
+
+        debug!("restore cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `RESTORE` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        let ttl = self.ttl.map_or(0, |ttl| ttl.as_millis());
+
+        FrameBuilder::new()
+            .bulk("restore")
+            .bulk(self.key)
+            .bulk(ttl.to_string())
+            .bulk(self.payload)
+            .bulks(if self.replace { Some("replace") } else { None })
+            .build()
+    }
+}
@@ -0,0 +1,93 @@
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 将一个或多个值依次插入列表的尾部。
+///
+/// 如果 key 不存在，则先创建一个空列表。返回插入后列表的长度。
+#[derive(Debug)]
+pub struct RPush {
+    /// 列表的键
+    key: String,
+    /// 要插入的值
+    values: Vec<Bytes>,
+}
+
+impl RPush {
+    /// 创建一个新的 `RPUSH` 命令。
+    pub fn new(key: impl ToString, values: Vec<Bytes>) -> RPush {
+        RPush {
+            key: key.to_string(),
+            values,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 从接收到的帧中解析 `RPush` 实例。
+    ///
+    /// `RPUSH` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个或更多条目的数组帧。
+    ///
+    /// ```text
+    /// RPUSH key value [value ...]
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<RPush, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let mut values = vec![parse.next_bytes()?];
+
+        loop {
+            match parse.next_bytes() {
+                Ok(value) => values.push(value),
+                // 所有的值都已读取
+                Err(MiniRedisParseError::EndOfStream) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(RPush { key, values })
+    }
+
+    /// 将 `RPush` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.rpush(self.key, self.values) {
+            Ok(len) => Frame::Integer(len as i64),
+            // 键的值不是列表
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("rpush cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `RPUSH` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new()
+            .bulk("rpush")
+            .bulk(self.key)
+            .bulks(self.values)
+            .build()
+    }
+}
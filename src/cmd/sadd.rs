@@ -0,0 +1,104 @@
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 向集合中添加一个或多个成员。
+///
+/// 已经在集合中的成员会被忽略。如果 key 不存在，则先创建一个空的集合。返回新增的成员数量。
+#[derive(Debug)]
+pub struct SAdd {
+    /// 集合的键
+    key: String,
+    /// 要添加的成员
+    members: Vec<Bytes>,
+}
+
+impl SAdd {
+    /// 创建一个新的 `SADD` 命令。
+    pub fn new(key: impl ToString, members: &[Bytes]) -> SAdd {
+        SAdd {
+            key: key.to_string(),
+            members: members.to_vec(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取要添加的成员。
+    pub fn members(&self) -> &[Bytes] {
+        &self.members
+    }
+
+    /// 从接收到的帧中解析 `SAdd` 实例。
+    ///
+    /// `SADD` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个或更多条目的数组帧。
+    ///
+    /// ```text
+    /// SADD key member [member ...]
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<SAdd, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let mut members = vec![];
+
+        loop {
+            match parse.next_bytes() {
+                Ok(member) => members.push(member),
+                // 所有成员都已读取
+                Err(MiniRedisParseError::EndOfStream) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        if members.is_empty() {
+            return Err(MiniRedisParseError::InvalidCommand(
+                "wrong number of arguments for 'sadd' command".into(),
+            ));
+        }
+
+        Ok(SAdd { key, members })
+    }
+
+    /// 将 `SAdd` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.sadd(self.key, self.members) {
+            Ok(added) => Frame::Integer(added as i64),
+            // 键的值不是集合
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("sadd cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `SADD` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new()
+            .bulk("sadd")
+            .bulk(self.key)
+            .bulks(self.members)
+            .build()
+    }
+}
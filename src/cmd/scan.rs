@@ -0,0 +1,204 @@
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 未指定 `COUNT` 时每次遍历的键数量，与 Redis 相同。
+const DEFAULT_COUNT: u64 = 10;
+
+/// 以游标的方式增量遍历键空间。
+///
+/// 每次调用返回下一次调用使用的游标和一批键，游标为 0 表示遍历已经完成。
+/// 遍历期间一直存在的键一定会被返回，期间被修改的键可能会被返回多次。
+#[derive(Debug)]
+pub struct Scan {
+    /// 上一次遍历返回的游标，从头开始遍历时为 0
+    cursor: u64,
+    /// 只返回匹配这个 glob 模式的键
+    pattern: Option<String>,
+    /// 每次遍历的键数量，只是一个提示
+    count: Option<u64>,
+}
+
+impl Scan {
+    /// 创建一个新的 `SCAN` 命令。
+    pub fn new(cursor: u64, pattern: Option<String>, count: Option<u64>) -> Scan {
+        Scan {
+            cursor,
+            pattern,
+            count,
+        }
+    }
+
+    /// 获取游标。
+    pub fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    /// 获取匹配模式。
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    /// 从接收到的帧中解析 `Scan` 实例。
+    ///
+    /// `SCAN` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// ```text
+    /// SCAN cursor [MATCH pattern] [COUNT count]
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Scan, MiniRedisParseError> {
+        let cursor = parse
+            .next_string()?
+            .parse()
+            .map_err(|_| MiniRedisParseError::InvalidCommand("invalid cursor".into()))?;
+        let mut pattern = None;
+        let mut count = None;
+
+        loop {
+            let option = match parse.next_string() {
+                Ok(s) => s.to_uppercase(),
+                // 选项已经读完
+                Err(MiniRedisParseError::EndOfStream) => break,
+                Err(e) => return Err(e),
+            };
+
+            match &option[..] {
+                "MATCH" => pattern = Some(parse.next_string()?),
+                "COUNT" => match parse.next_int()? {
+                    0 => return Err(syntax_error()),
+                    n => count = Some(n),
+                },
+                _ => {
+                    debug!("invalid SCAN option: {}", option);
+                    return Err(syntax_error());
+                }
+            }
+        }
+
+        Ok(Scan {
+            cursor,
+            pattern,
+            count,
+        })
+    }
+
+    /// 将 `Scan` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 回复一个包含两个元素的数组：下一次遍历的游标和这一批中匹配的键。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let count = self.count.unwrap_or(DEFAULT_COUNT) as usize;
+        let (cursor, keys) = db.scan(self.cursor, count);
+
+        // 与 Redis 一样，先遍历再过滤，因此一批中可能没有匹配的键，但游标仍然会前进
+        let batch = FrameBuilder::new()
+            .bulks(keys.into_iter().filter(|key| match &self.pattern {
+                Some(pattern) => glob_match(pattern.as_bytes(), key.as_bytes()),
+                None => true,
+            }))
+            .build();
+
+        let response = Frame::Array(vec![Frame::Bulk(Bytes::from(cursor.to_string())), batch]);
+
+        debug!("scan cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `SCAN` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = FrameBuilder::new()
+            .bulk("scan")
+            .bulk(self.cursor.to_string());
+        if let Some(pattern) = self.pattern {
+            frame = frame.bulk("match").bulk(pattern);
+        }
+        if let Some(count) = self.count {
+            frame = frame.bulk("count").int(count as i64);
+        }
+
+        frame.build()
+    }
+}
+
+// 语法错误会作为错误帧回复给客户端，连接保持打开
+fn syntax_error() -> MiniRedisParseError {
+    MiniRedisParseError::InvalidCommand("syntax error".into())
+}
+
+// 判断 string 是否匹配 glob 模式 pattern。
+// 支持 `*`、`?`、`[abc]`、`[^abc]`、`[a-z]` 以及用 `\` 转义特殊字符，与 Redis 的 `KEYS`/`SCAN` 一致。
+fn glob_match(pattern: &[u8], string: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => string.is_empty(),
+        Some((b'*', rest)) => {
+            // `*` 匹配任意长度，依次尝试让它吃掉 0 个、1 个……字符
+            (0..=string.len()).any(|skip| glob_match(rest, &string[skip..]))
+        }
+        Some((&c, rest)) => {
+            let (s, string) = match string.split_first() {
+                Some(split) => split,
+                None => return false,
+            };
+
+            match c {
+                b'?' => glob_match(rest, string),
+                b'[' => match match_class(rest, *s) {
+                    Some((true, rest)) => glob_match(rest, string),
+                    Some((false, _)) => false,
+                    // 没有闭合的 `[` 按普通字符处理
+                    None => *s == b'[' && glob_match(rest, string),
+                },
+                b'\\' if !rest.is_empty() => *s == rest[0] && glob_match(&rest[1..], string),
+                c => *s == c && glob_match(rest, string),
+            }
+        }
+    }
+}
+
+// 匹配 `[` 之后的字符集合，返回是否匹配和 `]` 之后剩余的模式，没有 `]` 时返回 None
+fn match_class(pattern: &[u8], c: u8) -> Option<(bool, &[u8])> {
+    let (negate, mut pattern) = match pattern.split_first() {
+        Some((b'^', rest)) => (true, rest),
+        _ => (false, pattern),
+    };
+    let mut matched = false;
+
+    loop {
+        match pattern {
+            [] => return None,
+            [b']', rest @ ..] => return Some((matched != negate, rest)),
+            [b'\\', escaped, rest @ ..] => {
+                matched |= *escaped == c;
+                pattern = rest;
+            }
+            [start, b'-', end, rest @ ..] if *end != b']' => {
+                let (lo, hi) = if start <= end {
+                    (*start, *end)
+                } else {
+                    (*end, *start)
+                };
+                matched |= lo <= c && c <= hi;
+                pattern = rest;
+            }
+            [other, rest @ ..] => {
+                matched |= *other == c;
+                pattern = rest;
+            }
+        }
+    }
+}
@@ -1,19 +1,23 @@
-use std::time::Duration;
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use bytes::Bytes;
-use log::{debug, warn};
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
 
+use crate::cmd::{check_expire, max_value_len, value_too_large};
+use crate::config::ServerConfig;
 use crate::connection::connect::Connection;
-use crate::connection::frame::Frame;
+use crate::connection::frame::{Frame, FrameBuilder};
 use crate::connection::parse::Parse;
 use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
-use crate::storage::db::Db;
-use crate::storage::traits::KvStore;
+use crate::storage::traits::AsyncKvStore;
 
 /// 设置 `key` 来保存字符串 `value`。
 ///
 /// 如果 `key` 已经保存了一个值，则无论其类型如何，该值都会被覆盖。
 /// 成功执行 SET 操作后，与该键相关联的任何先前生存时间都将被丢弃。
+/// 值的长度超过 `max-value-size` 时回复错误，不做修改。
 ///
 /// # 选项
 ///
@@ -21,6 +25,11 @@ use crate::storage::traits::KvStore;
 ///
 /// * EX `seconds` -- 设置指定的过期时间，以秒为单位。
 /// * PX `milliseconds` -- 设置指定的过期时间，以毫秒为单位。
+/// * EXAT `timestamp-seconds` -- 设置过期的 Unix 时间戳，以秒为单位。
+/// * PXAT `timestamp-milliseconds` -- 设置过期的 Unix 时间戳，以毫秒为单位。
+/// * KEEPTTL -- 保留键原有的过期时间。
+///
+/// 以上选项最多只能指定一个，同时指定多个时返回语法错误。
 #[derive(Debug)]
 pub struct Set {
     /// 键
@@ -29,6 +38,8 @@ pub struct Set {
     value: Bytes,
     /// 可选的过期时间
     expire: Option<Duration>,
+    /// 是否保留键原有的过期时间
+    keep_ttl: bool,
 }
 
 impl Set {
@@ -48,6 +59,7 @@ impl Set {
             key: key.to_string(),
             value,
             expire,
+            keep_ttl: false,
         }
     }
 
@@ -66,54 +78,80 @@ impl Set {
     /// 期望数组帧包含至少 3 个条目。
     ///
     /// ```text
-    /// SET key value [EX seconds|PX milliseconds]
+    /// SET key value [EX seconds|PX milliseconds|EXAT timestamp|PXAT milliseconds-timestamp|KEEPTTL]
     /// ```
     pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Set, MiniRedisParseError> {
         let key = parse.next_string()?; // 读取 key
         let value = parse.next_bytes()?; // 读取 value
-        let mut expire = None; // 因为 expire 是可选的， 下面使用 match 语句处理
-
-        match parse.next_string() {
-            // seconds
-            Ok(s) if s.to_uppercase() == "EX" => {
-                let seconds = parse.next_int()?;
-                expire = Some(Duration::from_secs(seconds));
-            }
-            // milliseconds
-            Ok(s) if s.to_uppercase() == "PX" => {
-                let millis = parse.next_int()?;
-                expire = Some(Duration::from_millis(millis));
-            }
-            // invalid，暂时不支持其他类型
-            Ok(s) => {
-                warn!("invalid set command argument: {}", s);
-                return Err(MiniRedisParseError::Parse(
-                    "currently `SET` only support the expiration option".into(),
-                ));
+        let mut expire = None; // 因为 expire 是可选的， 下面循环处理所有选项
+        let mut keep_ttl = false;
+
+        loop {
+            let option = match parse.next_string() {
+                Ok(s) => s.to_uppercase(),
+                // `EndOfStream` 错误表示没有进一步的数据需要解析。在这种情况下，这是正常的运行时情况，表示选项已经读完。
+                Err(MiniRedisParseError::EndOfStream) => break,
+                // 其他错误
+                Err(e) => return Err(e),
+            };
+
+            // 过期相关的选项最多只能出现一个，与 Redis 一样拒绝冲突的组合，而不是默默使用最后一个
+            if expire.is_some() || keep_ttl {
+                debug!("conflicting SET option: {}", option);
+                return Err(syntax_error());
             }
-            // `EndOfStream` 错误表示没有进一步的数据需要解析。在这种情况下，这是正常的运行时情况，并且表示没有指定的 `SET` 选项。
-            Err(MiniRedisParseError::EndOfStream) => {
-                debug!("no extra SET option");
+
+            match &option[..] {
+                // seconds
+                "EX" => expire = Some(Duration::from_secs(parse.next_int()?)),
+                // milliseconds
+                "PX" => expire = Some(Duration::from_millis(parse.next_int()?)),
+                // unix time in seconds
+                "EXAT" => expire = Some(until(Duration::from_secs(parse.next_int()?))),
+                // unix time in milliseconds
+                "PXAT" => expire = Some(until(Duration::from_millis(parse.next_int()?))),
+                "KEEPTTL" => keep_ttl = true,
+                _ => {
+                    debug!("invalid SET option: {}", option);
+                    return Err(syntax_error());
+                }
             }
-            // 其他错误
-            Err(e) => return Err(e),
         }
 
-        Ok(Set { key, value, expire })
+        // 过大的过期时间会让计算过期时间点时溢出
+        let expire = expire
+            .map(|expire| check_expire(expire, "set"))
+            .transpose()?;
+
+        Ok(Set {
+            key,
+            value,
+            expire,
+            keep_ttl,
+        })
     }
 
-    /// 将 `Set` 命令应用于指定的 `Db` 实例。
+    /// 将 `Set` 命令应用于指定的存储，服务器使用内存中的 `Db`，也可以是其他实现了 `AsyncKvStore` 的存储。
     ///
     /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
-    pub(crate) async fn apply(
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
         self,
-        db: &Db,
-        dst: &mut Connection,
+        db: &impl AsyncKvStore,
+        config: &RwLock<ServerConfig>,
+        dst: &mut Connection<S>,
     ) -> Result<(), MiniRedisConnectionError> {
-        // 在 db 中设置 key-value
-        db.set(self.key, self.value, self.expire);
-
-        let response = Frame::Simple("OK".to_string());
+        let (max_len, limit) = max_value_len(config);
+        let response = if self.value.len() > max_len {
+            value_too_large(limit)
+        } else {
+            // 在 db 中设置 key-value
+            if self.keep_ttl {
+                db.set_keep_ttl(self.key, self.value).await;
+            } else {
+                db.set(self.key, self.value, self.expire).await;
+            }
+            Frame::Simple("OK".to_string())
+        };
 
         debug!("set cmd applied response: {:?}", response);
 
@@ -127,19 +165,22 @@ impl Set {
     /// # 返回值
     ///
     /// 返回一个表示 `SET` 请求的 `Frame` 实例。
-    pub(crate) fn into_frame(self) -> Result<Frame, MiniRedisParseError> {
-        let mut frame = Frame::array();
-        frame.push_bulk(Bytes::from("set".as_bytes()))?;
-        frame.push_bulk(Bytes::from(self.key.into_bytes()))?;
-        frame.push_bulk(self.value)?;
+    pub(crate) fn into_frame(self) -> Frame {
+        let frame = FrameBuilder::new()
+            .bulk("set")
+            .bulk(self.key)
+            .bulk(self.value);
 
-        if let Some(ms) = self.expire {
+        let frame = if let Some(ms) = self.expire {
             // 我们选择 PX 选项，因为它提供了更多的精度
-            frame.push_bulk(Bytes::from("px".as_bytes()))?;
-            frame.push_int(ms.as_millis() as u64)?;
-        }
-
-        Ok(frame)
+            frame.bulk("px").int(ms.as_millis() as i64)
+        } else if self.keep_ttl {
+            frame.bulk("keepttl")
+        } else {
+            frame
+        };
+
+        frame.build()
     }
 
     /// 获取 `key`。
@@ -157,3 +198,16 @@ impl Set {
         self.expire
     }
 }
+
+// 语法错误会作为错误帧回复给客户端，连接保持打开
+fn syntax_error() -> MiniRedisParseError {
+    MiniRedisParseError::InvalidCommand("syntax error".into())
+}
+
+// 将 Unix 时间戳转换为距离现在的时长，已经过去的时间戳转换为零，即立即过期
+fn until(timestamp: Duration) -> Duration {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    timestamp.saturating_sub(now)
+}
@@ -0,0 +1,137 @@
+use std::sync::RwLock;
+
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::cmd::{max_value_len, value_too_large};
+use crate::config::ServerConfig;
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 与 Redis 一样，位偏移量不能超过 2^32 - 1，即字符串最长 512MB。
+const MAX_BIT_OFFSET: u64 = (1 << 32) - 1;
+
+/// 将 `key` 的字符串值中第 `offset` 位设置为 0 或 1。
+///
+/// 位从字符串第一个字节的最高位开始编号。字符串不够长时用 0 补齐，
+/// 键不存在时创建新的字符串。键原有的生存时间保持不变。返回这一位原来的值。
+/// 补齐后的长度超过 `proto-max-bulk-len` 或者 `max-value-size` 时回复错误，不做修改。
+#[derive(Debug)]
+pub struct SetBit {
+    /// 键
+    key: String,
+    /// 位偏移量
+    offset: u64,
+    /// 要设置的值
+    value: bool,
+}
+
+impl SetBit {
+    /// 创建一个新的 `SETBIT` 命令。
+    pub fn new(key: impl ToString, offset: u64, value: bool) -> SetBit {
+        SetBit {
+            key: key.to_string(),
+            offset,
+            value,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取位偏移量。
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// 获取要设置的值。
+    pub fn value(&self) -> bool {
+        self.value
+    }
+
+    /// 从接收到的帧中解析 `SetBit` 实例。
+    ///
+    /// `SETBIT` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含四个条目的数组帧。
+    ///
+    /// ```text
+    /// SETBIT key offset value
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<SetBit, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let offset = parse_bit_offset(parse)?;
+        let value = match parse.next_int() {
+            Ok(0) => false,
+            Ok(1) => true,
+            Ok(_) | Err(MiniRedisParseError::InvalidCommand(_)) => {
+                return Err(MiniRedisParseError::InvalidCommand(
+                    "bit is not an integer or out of range".into(),
+                ))
+            }
+            Err(err) => return Err(err),
+        };
+
+        Ok(SetBit { key, offset, value })
+    }
+
+    /// 将 `SetBit` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        config: &RwLock<ServerConfig>,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let (max_len, limit) = max_value_len(config);
+
+        // 与 SETRANGE 一样，在补齐字符串之前检查长度
+        let response = if self.offset / 8 + 1 > max_len as u64 {
+            value_too_large(limit)
+        } else {
+            match db.setbit(self.key, self.offset as usize, self.value) {
+                Ok(old) => Frame::Integer(old as i64),
+                Err(err) => Frame::error(ErrorKind::WrongType, err),
+            }
+        };
+
+        debug!("setbit cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `SETBIT` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new()
+            .bulk("setbit")
+            .bulk(self.key)
+            .bulk(self.offset.to_string())
+            .bulk(if self.value { "1" } else { "0" })
+            .build()
+    }
+}
+
+/// 解析 `SETBIT` 和 `GETBIT` 的位偏移量。
+pub(crate) fn parse_bit_offset(parse: &mut Parse) -> Result<u64, MiniRedisParseError> {
+    match parse.next_int() {
+        Ok(offset) if offset <= MAX_BIT_OFFSET => Ok(offset),
+        Ok(_) | Err(MiniRedisParseError::InvalidCommand(_)) => {
+            Err(MiniRedisParseError::InvalidCommand(
+                "bit offset is not an integer or out of range".into(),
+            ))
+        }
+        Err(err) => Err(err),
+    }
+}
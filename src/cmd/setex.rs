@@ -0,0 +1,115 @@
+use std::sync::RwLock;
+use std::time::Duration;
+
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::cmd::{check_expire, max_value_len, value_too_large};
+use crate::config::ServerConfig;
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 设置 `key` 保存字符串 `value`，同时设置以秒为单位的生存时间。
+///
+/// 与 `SET key value EX seconds` 相同，但生存时间位于值之前，并且必须是正数。
+/// 值的长度超过 `max-value-size` 时回复错误，不做修改。
+#[derive(Debug)]
+pub struct SetEx {
+    /// 键
+    key: String,
+    /// 生存时间
+    expire: Duration,
+    /// 值
+    value: Bytes,
+}
+
+impl SetEx {
+    /// 创建一个新的 `SETEX` 命令，生存时间按秒发送，不足一秒的部分被舍去。
+    pub fn new(key: impl ToString, expire: Duration, value: Bytes) -> SetEx {
+        SetEx {
+            key: key.to_string(),
+            expire,
+            value,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取生存时间。
+    pub fn expire(&self) -> Duration {
+        self.expire
+    }
+
+    /// 获取 `value`。
+    pub fn value(&self) -> &Bytes {
+        &self.value
+    }
+
+    /// 从接收到的帧中解析 `SetEx` 实例。
+    ///
+    /// `SETEX` 字符串已被使用。生存时间不是正数或者过大时返回 `invalid expire time` 错误。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含四个条目的数组帧，注意生存时间位于值之前。
+    ///
+    /// ```text
+    /// SETEX key seconds value
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<SetEx, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let seconds = parse.next_signed_int()?;
+        let value = parse.next_bytes()?;
+
+        if seconds <= 0 {
+            return Err(MiniRedisParseError::InvalidCommand(
+                "invalid expire time in 'setex' command".into(),
+            ));
+        }
+        let expire = check_expire(Duration::from_secs(seconds as u64), "setex")?;
+
+        Ok(SetEx { key, expire, value })
+    }
+
+    /// 将 `SetEx` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        config: &RwLock<ServerConfig>,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let (max_len, limit) = max_value_len(config);
+        let response = if self.value.len() > max_len {
+            value_too_large(limit)
+        } else {
+            db.set(self.key, self.value, Some(self.expire));
+            Frame::Simple("OK".to_string())
+        };
+
+        debug!("setex cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `SETEX` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new()
+            .bulk("setex")
+            .bulk(self.key)
+            .bulk(self.expire.as_secs().to_string())
+            .bulk(self.value)
+            .build()
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::RwLock;
+
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::cmd::{max_value_len, value_too_large};
+use crate::config::ServerConfig;
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 从 `offset` 开始用 `value` 覆盖 `key` 的字符串值的一部分。
+///
+/// 字符串不够长时用 0 补齐，键不存在时视为空字符串。键原有的生存时间保持不变。
+/// 返回修改后字符串的长度。修改后的长度超过 `proto-max-bulk-len` 或者 `max-value-size` 时回复错误，不做修改。
+#[derive(Debug)]
+pub struct SetRange {
+    /// 键
+    key: String,
+    /// 开始覆盖的字节偏移量
+    offset: u64,
+    /// 要写入的数据
+    value: Bytes,
+}
+
+impl SetRange {
+    /// 创建一个新的 `SETRANGE` 命令。
+    pub fn new(key: impl ToString, offset: u64, value: Bytes) -> SetRange {
+        SetRange {
+            key: key.to_string(),
+            offset,
+            value,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取偏移量。
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// 获取要写入的数据。
+    pub fn value(&self) -> &Bytes {
+        &self.value
+    }
+
+    /// 从接收到的帧中解析 `SetRange` 实例。
+    ///
+    /// `SETRANGE` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含四个条目的数组帧。
+    ///
+    /// ```text
+    /// SETRANGE key offset value
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<SetRange, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let offset = parse.next_signed_int()?;
+        if offset < 0 {
+            return Err(MiniRedisParseError::InvalidCommand(
+                "offset is out of range".into(),
+            ));
+        }
+        let value = parse.next_bytes()?;
+
+        Ok(SetRange {
+            key,
+            offset: offset as u64,
+            value,
+        })
+    }
+
+    /// 将 `SetRange` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        config: &RwLock<ServerConfig>,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let (max_len, limit) = max_value_len(config);
+
+        // 空的 value 不会修改字符串，因此不检查长度
+        let response =
+            if !self.value.is_empty() && self.offset + self.value.len() as u64 > max_len as u64 {
+                value_too_large(limit)
+            } else {
+                match db.setrange(self.key, self.offset as usize, self.value) {
+                    Ok(len) => Frame::Integer(len as i64),
+                    Err(err) => Frame::error(ErrorKind::WrongType, err),
+                }
+            };
+
+        debug!("setrange cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `SETRANGE` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new()
+            .bulk("setrange")
+            .bulk(self.key)
+            .bulk(self.offset.to_string())
+            .bulk(self.value)
+            .build()
+    }
+}
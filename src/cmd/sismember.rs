@@ -0,0 +1,89 @@
+use bytes::Bytes;
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 判断成员是否在集合中。
+///
+/// 存在时返回 1，key 或者成员不存在时返回 0。
+#[derive(Debug)]
+pub struct SIsMember {
+    /// 集合的键
+    key: String,
+    /// 要查找的成员
+    member: Bytes,
+}
+
+impl SIsMember {
+    /// 创建一个新的 `SISMEMBER` 命令。
+    pub fn new(key: impl ToString, member: Bytes) -> SIsMember {
+        SIsMember {
+            key: key.to_string(),
+            member,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取 `member`。
+    pub fn member(&self) -> &Bytes {
+        &self.member
+    }
+
+    /// 从接收到的帧中解析 `SIsMember` 实例。
+    ///
+    /// `SISMEMBER` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含三个条目的数组帧。
+    ///
+    /// ```text
+    /// SISMEMBER key member
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<SIsMember, MiniRedisParseError> {
+        let key = parse.next_string()?;
+        let member = parse.next_bytes()?;
+
+        Ok(SIsMember { key, member })
+    }
+
+    /// 将 `SIsMember` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.sismember(&self.key, &self.member) {
+            Ok(exists) => Frame::Integer(exists as i64),
+            // 键的值不是集合
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("sismember cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `SISMEMBER` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        FrameBuilder::new()
+            .bulk("sismember")
+            .bulk(self.key)
+            .bulk(self.member)
+            .build()
+    }
+}
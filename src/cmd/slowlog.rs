@@ -0,0 +1,112 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::{Frame, FrameBuilder};
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::server::metrics::Metrics;
+
+// `SLOWLOG GET` 没有指定数量时返回的记录数
+const DEFAULT_COUNT: usize = 10;
+
+/// 读取或者清空慢查询日志。
+///
+/// 支持两个子命令：
+///
+/// - `SLOWLOG GET [count]` 返回最新的 `count` 条记录，默认为 10 条，-1 表示返回所有记录。
+///   每条记录是一个数组：编号、完成的时间（UNIX 时间戳，秒）、耗时（微秒）以及命令名称和参数组成的数组。
+/// - `SLOWLOG RESET` 清空所有记录，回复 `OK`。
+///
+/// 记录的条件见 `ServerConfig::slowlog_threshold`。
+#[derive(Debug)]
+pub struct SlowLog {
+    /// 子命令
+    subcommand: Subcommand,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Subcommand {
+    /// 返回的记录数量，`None` 表示返回所有记录
+    Get(Option<usize>),
+    Reset,
+}
+
+impl SlowLog {
+    /// 从接收到的帧中解析 `SlowLog` 实例。
+    ///
+    /// `SLOWLOG` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// ```text
+    /// SLOWLOG GET [count]
+    /// SLOWLOG RESET
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<SlowLog, MiniRedisParseError> {
+        let subcommand = match &parse.next_string()?.to_lowercase()[..] {
+            "get" => match parse.next_signed_int() {
+                Ok(-1) => Subcommand::Get(None),
+                Ok(count) if count >= 0 => Subcommand::Get(Some(count as usize)),
+                Ok(_) => {
+                    return Err(MiniRedisParseError::InvalidCommand(
+                        "count should be greater than or equal to -1".into(),
+                    ))
+                }
+                Err(MiniRedisParseError::EndOfStream) => Subcommand::Get(Some(DEFAULT_COUNT)),
+                Err(err) => return Err(err),
+            },
+            "reset" if parse.remaining() == 0 => Subcommand::Reset,
+            "reset" => {
+                return Err(MiniRedisParseError::InvalidCommand(
+                    "wrong number of arguments for 'slowlog|reset' command".into(),
+                ))
+            }
+            subcommand => {
+                return Err(MiniRedisParseError::InvalidCommand(format!(
+                    "unknown subcommand '{}'",
+                    subcommand
+                )))
+            }
+        };
+
+        Ok(SlowLog { subcommand })
+    }
+
+    /// 应用 `SlowLog` 命令，读取或者清空 `metrics` 中的慢查询日志。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        metrics: &Metrics,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match self.subcommand {
+            Subcommand::Get(count) => {
+                let entries = metrics.slowlog().get(count.unwrap_or(usize::MAX));
+                let entries = entries
+                    .into_iter()
+                    .map(|entry| {
+                        FrameBuilder::new()
+                            .int(entry.id as i64)
+                            .int(entry.timestamp as i64)
+                            .int(entry.duration.as_micros() as i64)
+                            .frame(FrameBuilder::new().bulks(entry.args).build())
+                            .build()
+                    })
+                    .collect();
+                Frame::Array(entries)
+            }
+            Subcommand::Reset => {
+                metrics.slowlog().reset();
+                Frame::Simple("OK".to_string())
+            }
+        };
+
+        debug!("slowlog cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 获取集合中所有的成员。
+///
+/// 成员的顺序不确定。如果 key 不存在，则返回空数组。
+#[derive(Debug)]
+pub struct SMembers {
+    /// 集合的键
+    key: String,
+}
+
+impl SMembers {
+    /// 创建一个新的 `SMEMBERS` 命令。
+    pub fn new(key: impl ToString) -> SMembers {
+        SMembers {
+            key: key.to_string(),
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 从接收到的帧中解析 `SMembers` 实例。
+    ///
+    /// `SMEMBERS` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含两个条目的数组帧。
+    ///
+    /// ```text
+    /// SMEMBERS key
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<SMembers, MiniRedisParseError> {
+        let key = parse.next_string()?;
+
+        Ok(SMembers { key })
+    }
+
+    /// 将 `SMembers` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = match db.smembers(&self.key) {
+            Ok(members) => Frame::Array(members.into_iter().map(Frame::Bulk).collect()),
+            // 键的值不是集合
+            Err(err) => Frame::error(ErrorKind::WrongType, err),
+        };
+
+        debug!("smembers cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `SMEMBERS` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("smembers", [self.key])
+    }
+}
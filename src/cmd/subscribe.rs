@@ -1,7 +1,10 @@
+use std::future;
+use std::ops::ControlFlow;
 use std::pin::Pin; // 提供一个安全的机制来防止被 Pin 的值被移动
 
 use bytes::Bytes;
 use log::{debug, warn};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::select;
 use tokio_stream::{Stream, StreamExt, StreamMap}; // 提供 tokio 中的流处理功能
 
@@ -9,10 +12,11 @@ use crate::cmd::unknown::Unknown;
 use crate::cmd::unsubscribe::make_unsubscribe_frame;
 use crate::cmd::Command;
 use crate::connection::connect::Connection;
-use crate::connection::frame::Frame;
+use crate::connection::frame::{Frame, FrameBuilder};
 use crate::connection::parse::Parse;
-use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
 use crate::server::shutdown::Shutdown;
+use crate::server::state::ConnState;
 use crate::storage::db::Db;
 use crate::storage::traits::KvStore;
 
@@ -21,10 +25,14 @@ use crate::storage::traits::KvStore;
 /// 由于 `stream!` 值不能被命名，因此我们使用特征对象来装箱流。
 type Messages = Pin<Box<dyn Stream<Item = Bytes> + Send>>;
 
+// 每次被唤醒时最多连续转发的消息数量，之后刷新并回到 `select!`，避免消息很多时迟迟不处理客户端的命令
+const MAX_MESSAGE_BATCH: usize = 64;
+
 /// 订阅客户端到一个或多个频道。
 ///
 /// 一旦客户端进入订阅状态，除了额外的 SUBSCRIBE, PSUBSCRIBE, UNSUBSCRIBE,
-/// PUNSUBSCRIBE, PING 和 QUIT 命令之外，不应发出任何其他命令。
+/// PUNSUBSCRIBE, PING, RESET 和 QUIT 命令之外，不应发出任何其他命令。
+/// `RESET` 取消所有订阅，连接回到普通的命令模式。
 #[derive(Debug)]
 pub struct Subscribe {
     /// 订阅的频道列表。
@@ -89,15 +97,17 @@ impl Subscribe {
     /// * `db` - 数据库实例的引用。
     /// * `dst` - 连接实例的可变引用。
     /// * `shutdown` - 服务器关闭信号的可变引用。
+    /// * `state` - 连接自己的状态，订阅状态下收到 `RESET` 时重置它。
     ///
     /// # 返回值
     ///
     /// 成功时返回 `Ok(())`，失败时返回连接错误。
-    pub(crate) async fn apply(
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
         mut self,
         db: &Db,
-        dst: &mut Connection,
+        dst: &mut Connection<S>,
         shutdown: &mut Shutdown,
+        state: &mut ConnState,
     ) -> Result<(), MiniRedisConnectionError> {
         // 每个单独的频道订阅都使用 `sync::broadcast` 频道来处理。
         // 然后消息被分发给当前订阅这些频道的所有客户端。
@@ -121,7 +131,10 @@ impl Subscribe {
             // - 服务器关闭信号。
             select! {
                 Some((channel_name, msg)) = subscriptions.next() => {
-                    dst.write_frame(&make_message_frame(channel_name, msg)?).await?;
+                    // 把已经到达的消息一起写入缓冲区，最后只刷新一次
+                    dst.write_frame_buffered(&make_message_frame(channel_name, msg)).await?;
+                    forward_ready_messages(&mut subscriptions, dst).await?;
+                    dst.flush().await?;
                 }
 
                 res = dst.read_frame() => {
@@ -133,12 +146,19 @@ impl Subscribe {
                         }
                     };
 
-                    handle_command(
+                    let flow = handle_command(
                         frame,
                         &mut self.channels,
                         &mut subscriptions,
+                        state,
                         dst,
                     ).await?;
+
+                    // `RESET` 已经取消所有订阅，退出订阅状态
+                    if flow.is_break() {
+                        debug!("subscribe reset, back to normal command mode");
+                        return Ok(());
+                    }
                 }
 
                 _ = shutdown.recv() => {
@@ -154,13 +174,8 @@ impl Subscribe {
     /// # 返回值
     ///
     /// 成功时返回帧，失败时返回解析错误。
-    pub(crate) fn into_frame(self) -> Result<Frame, MiniRedisParseError> {
-        let mut frame = Frame::array();
-        frame.push_bulk(Bytes::from("subscribe".as_bytes()))?;
-        for channel in self.channels {
-            frame.push_bulk(Bytes::from(channel.into_bytes()))?;
-        }
-        Ok(frame)
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("subscribe", self.channels)
     }
 
     /// 订阅指定频道。
@@ -175,12 +190,24 @@ impl Subscribe {
     /// # 返回值
     ///
     /// 成功时返回 `Ok(())`，失败时返回连接错误。
-    async fn subscribe_to_channel(
+    async fn subscribe_to_channel<S: AsyncRead + AsyncWrite + Unpin>(
         channel_name: String,
         subscriptions: &mut StreamMap<String, Messages>,
         db: &Db,
-        dst: &mut Connection,
+        dst: &mut Connection<S>,
     ) -> Result<(), MiniRedisConnectionError> {
+        // 已经订阅的频道只回复确认，订阅数量不变。
+        // 不替换原有的接收者，否则其中已经缓存但还未转发的消息会丢失。
+        if subscriptions.contains_key(&channel_name) {
+            debug!("already subscribed to channel: {}", channel_name);
+            let response = make_subscribe_frame(channel_name, subscriptions.len());
+            dst.write_frame(&response).await?;
+            return Ok(());
+        }
+
+        // `db.subscribe` 返回时接收者已经注册到广播频道上，之后发布的消息都会被缓存在 `rx` 中，
+        // 即使它还没有被插入 `subscriptions`。确认帧在这之后才写给客户端，
+        // 因此客户端收到确认之后发布的消息不会丢失。
         let mut rx = db.subscribe(channel_name.clone());
         // 订阅频道
         let rx = Box::pin(async_stream::stream! {
@@ -201,7 +228,7 @@ impl Subscribe {
         // 在此客户端的订阅集中跟踪订阅
         subscriptions.insert(channel_name.clone(), rx);
         debug!("subscribed to channel success: {}", channel_name);
-        let response = make_subscribe_frame(channel_name, subscriptions.len())?;
+        let response = make_subscribe_frame(channel_name, subscriptions.len());
         dst.write_frame(&response).await?;
 
         Ok(())
@@ -213,42 +240,90 @@ impl Subscribe {
 /// 所有这些函数都将 `channel_name` 作为 `String` 而不是 `&str`，因为
 /// `Bytes::from` 可以重用 `String` 中的分配，而使用 `&str` 会要求复制数据。
 /// 这允许调用者决定是否克隆频道名称。
-fn make_subscribe_frame(
-    channel_name: String,
-    num_subs: usize,
-) -> Result<Frame, MiniRedisParseError> {
-    let mut response = Frame::array();
-    response.push_bulk(Bytes::from_static(b"subscribe"))?;
-    response.push_bulk(Bytes::from(channel_name))?;
-    response.push_int(num_subs as u64)?;
-    Ok(response)
+fn make_subscribe_frame(channel_name: String, num_subs: usize) -> Frame {
+    FrameBuilder::new()
+        .bulk("subscribe")
+        .bulk(channel_name)
+        .int(num_subs as i64)
+        .build()
 }
 
 /// 创建一个消息，通知客户端关于其订阅频道的新消息。
-fn make_message_frame(channel_name: String, msg: Bytes) -> Result<Frame, MiniRedisParseError> {
-    let mut response = Frame::array();
-    response.push_bulk(Bytes::from_static(b"message"))?;
-    response.push_bulk(Bytes::from(channel_name))?;
-    response.push_bulk(msg)?;
-    Ok(response)
+///
+/// `msg` 是从广播频道收到的 `Bytes`，与发布者发送的消息共享同一块内存，这里直接放入帧中而不复制。
+/// 因此向大量订阅者发布一条消息时，消息体只有一份，每个订阅者只多出一个引用计数。
+fn make_message_frame(channel_name: String, msg: Bytes) -> Frame {
+    FrameBuilder::new()
+        .bulk("message")
+        .bulk(channel_name)
+        .bulk(msg)
+        .build()
+}
+
+/// 轮流从每个频道取出已经到达的消息写入 `dst` 的缓冲区，不会等待，也不会刷新。
+///
+/// 每一轮从每个频道最多取一条消息，因此一个频道的消息很多时，其他频道的消息最多等待一轮，
+/// 而不是排在它的所有消息之后。所有频道都没有消息，或者已经写入 `MAX_MESSAGE_BATCH` 条消息时返回。
+async fn forward_ready_messages<S: AsyncRead + AsyncWrite + Unpin>(
+    subscriptions: &mut StreamMap<String, Messages>,
+    dst: &mut Connection<S>,
+) -> Result<(), MiniRedisConnectionError> {
+    let mut forwarded = 0;
+    while forwarded < MAX_MESSAGE_BATCH {
+        let mut round = 0;
+        for (channel_name, messages) in subscriptions.iter_mut() {
+            if let Some(msg) = ready_message(messages).await {
+                let frame = make_message_frame(channel_name.clone(), msg);
+                dst.write_frame_buffered(&frame).await?;
+                round += 1;
+            }
+        }
+        if round == 0 {
+            break;
+        }
+        forwarded += round;
+    }
+    Ok(())
 }
 
-/// 处理在 `Subscribe::apply` 内接收到的命令。只有订阅和取消订阅命令在此上下文中被允许。
+/// 返回频道中已经到达的下一条消息，没有立即可用的消息时返回 `None`，不会等待。
+async fn ready_message(messages: &mut Messages) -> Option<Bytes> {
+    // `biased` 保证先检查频道，只有频道暂时没有消息时才会选择第二个分支
+    select! {
+        biased;
+        message = messages.next() => message,
+        _ = future::ready(()) => None,
+    }
+}
+
+/// 处理在 `Subscribe::apply` 内接收到的命令。只有订阅、取消订阅、`PING` 和 `RESET` 命令在此上下文中被允许。
 ///
 /// 新的订阅将被添加到 `subscribe_to` 中，而不是修改 `subscriptions`。
-async fn handle_command(
+/// 返回 `ControlFlow::Break` 表示收到了 `RESET`，需要退出订阅状态。
+async fn handle_command<S: AsyncRead + AsyncWrite + Unpin>(
     frame: Frame,
     subscribe_to: &mut Vec<String>,
     subscriptions: &mut StreamMap<String, Messages>,
-    dst: &mut Connection,
-) -> Result<(), MiniRedisConnectionError> {
+    state: &mut ConnState,
+    dst: &mut Connection<S>,
+) -> Result<ControlFlow<()>, MiniRedisConnectionError> {
     // 从客户端接收到一个命令。
     //
-    // 在此上下文中只允许 `SUBSCRIBE` 和 `UNSUBSCRIBE` 命令。
-    match Command::from_frame(frame)? {
+    // 在此上下文中只允许 `SUBSCRIBE`、`UNSUBSCRIBE`、`PING` 和 `RESET` 命令。
+    let command = match Command::from_frame(frame) {
+        Ok(command) => command,
+        // 参数错误的命令只回复错误，不影响当前的订阅
+        Err(MiniRedisParseError::InvalidCommand(msg)) => {
+            dst.write_frame(&Frame::error(ErrorKind::Err, msg)).await?;
+            return Ok(ControlFlow::Continue(()));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    match command {
         Command::Subscribe(subscirbe) => {
             // `apply` 方法将订阅我们添加到这个向量中的频道
-            subscribe_to.extend(subscirbe.channels.into_iter());
+            subscribe_to.extend(subscirbe.channels);
         }
 
         Command::Unsubscribe(mut unsubscirbe) => {
@@ -264,16 +339,89 @@ async fn handle_command(
             for channel_name in unsubscirbe.channels {
                 debug!("begin unsubscribe: {}", channel_name);
                 subscriptions.remove(&channel_name);
-                let response = make_unsubscribe_frame(channel_name, subscriptions.len())?;
+                let response = make_unsubscribe_frame(channel_name, subscriptions.len());
                 dst.write_frame(&response).await?;
                 debug!("unsubscribe success: {}", response);
             }
         }
 
+        Command::Ping(ping) => {
+            // 订阅模式下的 `PING` 以数组帧响应，不影响当前的订阅
+            ping.apply_subscribed(dst).await?;
+        }
+
+        Command::Reset(reset) => {
+            // 在回复之前取消订阅，客户端收到回复之后发布的消息不会再发给这个连接
+            subscriptions.clear();
+            subscribe_to.clear();
+            // 订阅之前监视的键和设置的名称仍然需要重置
+            reset.apply(state, dst).await?;
+            return Ok(ControlFlow::Break(()));
+        }
+
         command => {
             let cmd = Unknown::new(command.get_name());
             cmd.apply(dst).await?;
         }
     }
-    Ok(())
+    Ok(ControlFlow::Continue(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::ServerConfig;
+    use crate::connection::connect::pair;
+
+    use super::*;
+
+    // 一个频道有大量消息时，另一个频道的消息在第一轮就被转发
+    #[tokio::test]
+    async fn quiet_channel_not_starved_by_flood() {
+        let (client, mut server) = pair();
+        let mut client = Connection::with_stream(client);
+
+        let flood = vec![Bytes::from("flood"); 10 * MAX_MESSAGE_BATCH];
+        let mut subscriptions: StreamMap<String, Messages> = StreamMap::new();
+        subscriptions.insert("flood".into(), Box::pin(tokio_stream::iter(flood)));
+        subscriptions.insert(
+            "quiet".into(),
+            Box::pin(tokio_stream::iter(vec![Bytes::from("quiet")])),
+        );
+
+        forward_ready_messages(&mut subscriptions, &mut server)
+            .await
+            .unwrap();
+        server.flush().await.unwrap();
+
+        // 第一轮每个频道各转发一条消息
+        let mut channels = Vec::new();
+        for _ in 0..2 {
+            match client.read_frame().await.unwrap() {
+                Some(Frame::Array(parts)) => channels.push(parts[1].clone()),
+                frame => panic!("unexpected frame {:?}", frame),
+            }
+        }
+        assert!(channels.contains(&Frame::Bulk(Bytes::from("quiet"))));
+    }
+
+    // 向大量订阅者发布一条大消息，每个订阅者的消息帧都引用同一块内存，消息体没有被复制
+    #[tokio::test]
+    async fn fan_out_shares_message_body() {
+        let db = Db::new(&ServerConfig::default());
+        let mut receivers: Vec<_> = (0..1_000).map(|_| db.subscribe("news".into())).collect();
+
+        let msg = Bytes::from(vec![b'x'; 1024 * 1024]);
+        assert_eq!(1_000, db.publish("news", msg.clone()));
+
+        for rx in &mut receivers {
+            let frame = make_message_frame("news".into(), rx.try_recv().unwrap());
+            match &frame {
+                Frame::Array(parts) => match &parts[2] {
+                    Frame::Bulk(body) => assert_eq!(msg.as_ptr(), body.as_ptr()),
+                    part => panic!("unexpected message body {:?}", part),
+                },
+                frame => panic!("unexpected message frame {:?}", frame),
+            }
+        }
+    }
 }
@@ -0,0 +1,82 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 访问一个或多个键但不返回它们的值。
+///
+/// 返回其中存在的键的数量，可以用于缓存预热等场景。
+#[derive(Debug)]
+pub struct Touch {
+    /// 要访问的键
+    keys: Vec<String>,
+}
+
+impl Touch {
+    /// 创建一个新的 `TOUCH` 命令。
+    pub fn new(keys: &[String]) -> Touch {
+        Touch {
+            keys: keys.to_vec(),
+        }
+    }
+
+    /// 获取要访问的键。
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// 从接收到的帧中解析 `Touch` 实例。
+    ///
+    /// `TOUCH` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含两个或更多条目的数组帧。
+    ///
+    /// ```text
+    /// TOUCH key [key ...]
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Touch, MiniRedisParseError> {
+        let mut keys = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(key) => keys.push(key),
+                // 所有的键都已读取
+                Err(MiniRedisParseError::EndOfStream) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(Touch { keys })
+    }
+
+    /// 将 `Touch` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let touched = self.keys.iter().filter(|key| db.touch(key)).count();
+
+        let response = Frame::Integer(touched as i64);
+
+        debug!("touch cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `TOUCH` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("touch", self.keys)
+    }
+}
@@ -0,0 +1,97 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 返回 key 的剩余生存时间。
+///
+/// `TTL` 以秒为单位返回，`PTTL` 以毫秒为单位返回。
+/// 如果 key 不存在，返回 `-2`；如果 key 存在但没有设置过期时间，返回 `-1`。
+#[derive(Debug)]
+pub struct Ttl {
+    /// 要查询的键的名称
+    key: String,
+    /// `true` 表示以毫秒为单位返回（`PTTL`）
+    millis: bool,
+}
+
+impl Ttl {
+    /// 创建一个新的 `TTL` 命令，以秒为单位查询 `key` 的剩余生存时间。
+    pub fn new(key: impl ToString) -> Ttl {
+        Ttl {
+            key: key.to_string(),
+            millis: false,
+        }
+    }
+
+    /// 创建一个新的 `PTTL` 命令，以毫秒为单位查询 `key` 的剩余生存时间。
+    pub fn millis(key: impl ToString) -> Ttl {
+        Ttl {
+            key: key.to_string(),
+            millis: true,
+        }
+    }
+
+    /// 获取 `key`。
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 从接收到的帧中解析 `Ttl` 实例。
+    ///
+    /// `TTL` 或 `PTTL` 字符串已被使用，`millis` 表示是哪一个。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含两个条目的数组帧。
+    ///
+    /// ```text
+    /// TTL key
+    /// PTTL key
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse, millis: bool) -> Result<Ttl, MiniRedisParseError> {
+        let key = parse.next_string()?;
+
+        Ok(Ttl { key, millis })
+    }
+
+    /// 将 `Ttl` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let ttl = match db.ttl(&self.key) {
+            // 键不存在
+            None => -2,
+            // 键没有设置过期时间
+            Some(None) => -1,
+            // 毫秒直接返回剩余时间
+            Some(Some(remaining)) if self.millis => remaining.as_millis() as i64,
+            // 秒向上取整，与 Redis 保持一致
+            Some(Some(remaining)) => remaining.as_millis().div_ceil(1000) as i64,
+        };
+
+        let response = Frame::Integer(ttl);
+
+        debug!("ttl cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `TTL`/`PTTL` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        let name = if self.millis { "pttl" } else { "ttl" };
+
+        Frame::cmd(name, [self.key])
+    }
+}
@@ -1,8 +1,9 @@
 use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
 
 use crate::connection::connect::Connection;
 use crate::connection::frame::Frame;
-use crate::error::MiniRedisConnectionError;
+use crate::error::{ErrorKind, MiniRedisConnectionError};
 
 /// 表示一个“未知”命令。这不是一个真正的 `Redis` 命令。
 #[derive(Debug)]
@@ -47,8 +48,14 @@ impl Unknown {
     ///
     /// 成功时返回 `Ok(())`，表示响应已成功发送。
     /// 失败时返回 `MiniRedisConnectionError`，表示发送过程中出现了错误。
-    pub(crate) async fn apply(self, dst: &mut Connection) -> Result<(), MiniRedisConnectionError> {
-        let response = Frame::Error(format!("err unknown command '{}'", self.cmd_name));
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let response = Frame::error(
+            ErrorKind::Err,
+            format!("unknown command '{}'", self.cmd_name),
+        );
         debug!("apply unknown command resp: '{:?}'", response);
         dst.write_frame(&response).await?;
         Ok(())
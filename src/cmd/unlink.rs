@@ -0,0 +1,85 @@
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+use crate::storage::traits::KvStore;
+
+/// 删除一个或多个键，与 `DEL` 相同，但值在后台释放。
+///
+/// 键在持有锁时被删除并立即返回删除的数量，值的释放交给单独的任务，
+/// 因此删除很大的值不会阻塞当前连接和同一分片上的其他命令。不存在的键会被忽略。
+#[derive(Debug)]
+pub struct Unlink {
+    /// 要删除的键
+    keys: Vec<String>,
+}
+
+impl Unlink {
+    /// 创建一个新的 `UNLINK` 命令。
+    pub fn new(keys: &[String]) -> Unlink {
+        Unlink {
+            keys: keys.to_vec(),
+        }
+    }
+
+    /// 获取要删除的键。
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// 从接收到的帧中解析 `Unlink` 实例。
+    ///
+    /// `UNLINK` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含两个或更多条目的数组帧。
+    ///
+    /// ```text
+    /// UNLINK key [key ...]
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Unlink, MiniRedisParseError> {
+        let mut keys = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(key) => keys.push(key),
+                // 所有的键都已读取
+                Err(MiniRedisParseError::EndOfStream) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(Unlink { keys })
+    }
+
+    /// 将 `Unlink` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 将响应写入 `dst`。服务器调用此方法以便执行收到的命令。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        let values = db.unlink(&self.keys);
+        let response = Frame::Integer(values.len() as i64);
+
+        // 值在后台任务中被丢弃
+        tokio::spawn(async move { drop(values) });
+
+        debug!("unlink cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 将 `UNLINK` 操作转换为用于网络传输的 `Frame` 格式。
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("unlink", self.keys)
+    }
+}
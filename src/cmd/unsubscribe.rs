@@ -1,6 +1,4 @@
-use bytes::Bytes;
-
-use crate::connection::frame::Frame;
+use crate::connection::frame::{Frame, FrameBuilder};
 use crate::connection::parse::Parse;
 use crate::error::MiniRedisParseError;
 
@@ -58,26 +56,16 @@ impl Unsubscribe {
     /// 将命令转换为等效的 `Frame`。
     ///
     /// 当客户端编码要发送到服务器的 `Unsubscribe` 命令时会调用此函数。
-    pub(crate) fn into_frame(self) -> Result<Frame, MiniRedisParseError> {
-        let mut frame = Frame::array();
-        frame.push_bulk(Bytes::from("unsubscribe".as_bytes()))?;
-
-        for channel in self.channels {
-            frame.push_bulk(Bytes::from(channel.into_bytes()))?;
-        }
-
-        Ok(frame)
+    pub(crate) fn into_frame(self) -> Frame {
+        Frame::cmd("unsubscribe", self.channels)
     }
 }
 
 /// 创建取消订阅请求的响应。
-pub(crate) fn make_unsubscribe_frame(
-    channel_name: String,
-    num_subs: usize,
-) -> Result<Frame, MiniRedisParseError> {
-    let mut response = Frame::array();
-    response.push_bulk(Bytes::from_static(b"unsubscribe"))?;
-    response.push_bulk(Bytes::from(channel_name))?;
-    response.push_int(num_subs as u64)?;
-    Ok(response)
+pub(crate) fn make_unsubscribe_frame(channel_name: String, num_subs: usize) -> Frame {
+    FrameBuilder::new()
+        .bulk("unsubscribe")
+        .bulk(channel_name)
+        .int(num_subs as i64)
+        .build()
 }
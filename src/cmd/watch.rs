@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use log::debug;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::connection::connect::Connection;
+use crate::connection::frame::Frame;
+use crate::connection::parse::Parse;
+use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
+use crate::storage::db::Db;
+
+/// 监视一个或多个键，为之后的 `MULTI` 事务提供乐观锁。
+///
+/// 从 `WATCH` 到 `EXEC` 之间，只要有一个被监视的键被修改、删除或者过期，`EXEC` 就放弃事务并回复 nil。
+/// 执行 `EXEC`、`DISCARD`、`UNWATCH` 或者 `RESET` 之后不再监视任何键。
+/// 不能在事务中执行 `WATCH`。
+#[derive(Debug)]
+pub struct Watch {
+    /// 要监视的键
+    keys: Vec<String>,
+}
+
+impl Watch {
+    /// 从接收到的帧中解析 `Watch` 实例。
+    ///
+    /// `WATCH` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个包含两个或更多条目的数组帧。
+    ///
+    /// ```text
+    /// WATCH key [key ...]
+    /// ```
+    pub(crate) fn parse_frame(parse: &mut Parse) -> Result<Watch, MiniRedisParseError> {
+        let mut keys = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(key) => keys.push(key),
+                // 所有的键都已读取
+                Err(MiniRedisParseError::EndOfStream) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(Watch { keys })
+    }
+
+    /// 应用 `Watch` 命令，记下每个键当前的版本号并回复 `OK`。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        db: &Db,
+        watched: &mut WatchedKeys,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        for key in self.keys {
+            watched.watch(db, key);
+        }
+        let response = Frame::Simple("OK".to_string());
+
+        debug!("watch cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 取消监视所有的键，回复 `OK`。
+#[derive(Debug)]
+pub struct Unwatch;
+
+impl Unwatch {
+    /// 从接收到的帧中解析 `Unwatch` 实例。
+    ///
+    /// `UNWATCH` 字符串已被使用。
+    ///
+    /// # 格式
+    ///
+    /// 期望一个只包含一个条目的数组帧。
+    ///
+    /// ```text
+    /// UNWATCH
+    /// ```
+    pub(crate) fn parse_frame(_parse: &mut Parse) -> Result<Unwatch, MiniRedisParseError> {
+        Ok(Unwatch)
+    }
+
+    /// 应用 `Unwatch` 命令，清空监视的键并回复 `OK`。
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        self,
+        watched: &mut WatchedKeys,
+        dst: &mut Connection<S>,
+    ) -> Result<(), MiniRedisConnectionError> {
+        watched.clear();
+        let response = Frame::Simple("OK".to_string());
+
+        debug!("unwatch cmd applied response: {:?}", response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 一个连接监视的键，以及 `WATCH` 时它们的版本号。
+///
+/// 版本号为 `None` 表示监视时键不存在，之后创建这个键同样视为修改。
+#[derive(Debug, Default)]
+pub(crate) struct WatchedKeys {
+    versions: HashMap<String, Option<u64>>,
+}
+
+impl WatchedKeys {
+    /// 监视 `key`，已经在监视的键保留第一次监视时的版本号。
+    pub(crate) fn watch(&mut self, db: &Db, key: String) {
+        self.versions
+            .entry(key)
+            .or_insert_with_key(|key| db.version(key));
+    }
+
+    /// 是否有被监视的键在 `WATCH` 之后被修改过。
+    pub(crate) fn is_dirty(&self, db: &Db) -> bool {
+        self.versions
+            .iter()
+            .any(|(key, version)| db.version(key) != *version)
+    }
+
+    /// 不再监视任何键。
+    pub(crate) fn clear(&mut self) {
+        self.versions.clear();
+    }
+}
@@ -1,2 +1,664 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::consts::{
+    COMPACT_INTERVAL, DEFAULT_BIND, DEFAULT_PORT, DEFAULT_SHARDS, MAX_CONNECTIONS, MAX_INLINE_LEN,
+    PROTO_MAX_BULK_LEN, SHUTDOWN_TIMEOUT, SLOWLOG_MAX_LEN,
+};
+use log::{info, warn, LevelFilter};
+
+use crate::error::MiniRedisConfigError;
+use crate::metrics::{MetricsRecorder, NoopMetrics};
+
 /// Logger level
 pub static LOG_LEVEL: &str = "LOG_LEVEL";
+
+/// 服务器的运行时配置。
+///
+/// 通过 `server::run_with_config` 传入；`server::run` 使用 `ServerConfig::default()`。
+/// 服务器运行期间配置由所有连接共享，在 unix 上收到 SIGHUP 时会重新读取 `config_file`，
+/// 并应用其中可以在运行时修改的部分，见 [`ServerConfig::reload`]。
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// 监听的地址。
+    ///
+    /// `run_with_config` 使用调用者传入的 `TcpListener`，不读取这个字段；
+    /// 服务器可执行文件用它和 `port` 绑定监听的套接字。
+    pub bind: String,
+
+    /// 监听的端口，与 `bind` 一样只由服务器可执行文件使用。
+    pub port: u16,
+
+    /// 指标记录器，处理命令、接受连接和修改数据时会调用它上报指标，默认不做任何记录。
+    ///
+    /// 服务器总是在内存中另外保存一份指标供 `INFO` 读取，这里只用于转发到外部系统。
+    pub metrics: Arc<dyn MetricsRecorder>,
+
+    /// 单行请求的最大长度。
+    ///
+    /// 如果客户端发送的一行数据超过这个长度仍然没有 `\r\n` 结尾，服务器会将其视为协议错误并关闭连接，
+    /// 而不是无限制地缓存数据。
+    pub max_inline_len: usize,
+
+    /// 字符串值的最大长度，默认为 512MB。
+    ///
+    /// `SETRANGE` 等由客户端指定偏移量的命令不能把字符串扩展到超过这个长度，
+    /// 否则回复错误，避免一条命令就分配大量的内存。
+    /// 请求中声明的 Bulk 字符串或者数组长度超过它时视为协议错误并关闭连接，
+    /// 修改只对新的连接生效。
+    pub proto_max_bulk_len: usize,
+
+    /// 字符串值允许的最大长度，默认为 `None`，即只受 `proto_max_bulk_len` 限制。
+    ///
+    /// `SET`、`SETEX`、`MSET`、`APPEND`、`SETRANGE` 和 `SETBIT` 在写入之前检查结果的长度，
+    /// 超过这个长度或者 `proto_max_bulk_len` 时回复错误，不做修改。与 `proto_max_bulk_len` 不同，
+    /// 它不限制请求本身，只限制保存的值，修改之后立即生效。
+    pub max_value_size: Option<usize>,
+
+    /// 收到关闭信号后等待现有连接处理完成的最长时间。
+    ///
+    /// 超时后 `run` 会记录警告并直接返回，不再等待仍未结束的连接。
+    pub shutdown_timeout: Duration,
+
+    /// 键空间的分片数量，每个分片有独立的锁，至少为 1。
+    ///
+    /// 分片越多，不同键上的写入并发度越高，但跨多个键的命令需要锁住的分片也越多。
+    pub shards: usize,
+
+    /// 最大并发连接数。
+    pub max_connections: usize,
+
+    /// 后台整理键空间的间隔，默认为 60 秒，`None` 表示不整理。
+    ///
+    /// 大量的键被删除之后，存储键的哈希表的容量不会自动缩小。整理任务每隔这么长时间检查一次每个分片，
+    /// 在容量远大于键的数量时释放多余的内存。正在被其他命令使用的分片会被跳过，留到下一次再整理。
+    pub compact_interval: Option<Duration>,
+
+    /// 键和值最多占用的字节数，默认为 `None`，即不限制。
+    ///
+    /// 超过之后，执行可能占用更多内存的命令之前先按照 `maxmemory_policy` 删除键，直到回到限制以内。
+    /// 没有可以删除的键时拒绝命令，回复 `-OOM` 错误；读取命令以及 `DEL`、`UNLINK`、`EXPIRE`
+    /// 等释放内存的命令仍然可以执行。统计的只是键和值本身的长度，不包括数据结构和连接的开销。
+    pub max_memory: Option<usize>,
+
+    /// 超过 `max_memory` 时删除哪些键，默认为 `MaxmemoryPolicy::NoEviction`，即不删除任何键。
+    pub maxmemory_policy: MaxmemoryPolicy,
+
+    /// 客户端空闲多久之后服务器关闭连接，默认为 `None`，即不关闭空闲连接。
+    ///
+    /// 空闲连接会一直占用一个连接数名额。处于订阅状态的客户端不受这个限制，它们在等待消息时本来就不会发送命令。
+    pub client_idle_timeout: Option<Duration>,
+
+    /// 单个命令最长的执行时间，默认为 `None`，即不限制。
+    ///
+    /// 超时的命令被取消，服务器回复 `-ERR command timed out` 后继续处理这个连接的下一个命令。
+    /// 订阅状态会一直持续到客户端取消订阅，不受这个限制。
+    pub command_timeout: Option<Duration>,
+
+    /// 每个连接每秒最多执行的命令数，默认为 `None`，即不限制。
+    ///
+    /// 每个连接有一个容量为一秒配额的令牌桶，空闲的连接可以突发执行这么多条命令，之后被限制在这个速率，
+    /// 超过时按照 `rate_limit_action` 处理。订阅状态下推送给客户端的消息不计入限制。
+    pub max_commands_per_sec: Option<u32>,
+
+    /// 超过 `max_commands_per_sec` 时如何处理，默认为 `RateLimitAction::Delay`。
+    pub rate_limit_action: RateLimitAction,
+
+    /// 执行时间超过多久的命令被记录到慢查询日志，默认为 `None`，即不记录。
+    ///
+    /// 记录中包括命令的参数、耗时和完成的时间，可以用 `SLOWLOG GET` 读取。
+    /// 耗时只包括执行命令的时间，不包括读取请求和等待排队的时间。订阅状态不会被记录。
+    pub slowlog_threshold: Option<Duration>,
+
+    /// 慢查询日志最多保存的记录数，默认为 128，超过之后丢弃最早的记录。
+    pub slowlog_max_len: usize,
+
+    /// 连接数达到 `max_connections` 时是否拒绝新的连接。
+    ///
+    /// 默认为 `false`：服务器暂停接受新连接，直到有连接断开，新的客户端会一直等待。
+    /// 设置为 `true` 时，服务器仍然接受新连接，回复 `-ERR max number of clients reached` 后立即关闭，
+    /// 与 Redis 的 `maxclients` 行为一致。
+    pub reject_on_full: bool,
+
+    /// 是否允许执行 `DEBUG` 命令，默认为 `false`。
+    ///
+    /// `DEBUG` 只用于测试和诊断，例如 `DEBUG SLEEP` 会让连接长时间不响应，因此默认关闭，
+    /// 关闭时执行它会收到错误。只在启动时生效。
+    pub debug_commands: bool,
+
+    /// 是否记录访问日志，默认为 `false`。
+    ///
+    /// 打开时，每执行一条命令都会以 `info` 级别记录一行日志，包括命令名称、参数个数、耗时和回复的类型，
+    /// 设置了 `on_access` 时还会调用它。排队到事务中的命令不单独记录，由 `EXEC` 记录一行。
+    pub access_log: bool,
+
+    /// 访问日志的回调，默认不设置，只在 `access_log` 打开时调用。
+    ///
+    /// 可以用来把访问日志写入日志文件以外的地方，例如发送给日志收集系统。
+    pub on_access: Option<AccessLogCallback>,
+
+    /// 日志级别。默认为 `None`，即使用 `LOG_LEVEL` 环境变量设置的级别。
+    ///
+    /// 日志级别是整个进程共享的，启动服务器时不会修改它，由调用者决定是否调用
+    /// [`ServerConfig::apply_log_level`]，服务器可执行文件在启动时会调用它。
+    pub log_level: Option<LevelFilter>,
+
+    /// 读取配置的文件，由 [`parse_config_file`] 设置。
+    ///
+    /// 设置之后，在 unix 上服务器收到 SIGHUP 时会重新读取这个文件。
+    pub config_file: Option<PathBuf>,
+
+    /// 键过期时的回调，默认不设置。
+    ///
+    /// 后台清理任务删除过期键之后，或者命令访问到已经过期但还未被清理的键并删除它之后调用，
+    /// 可以用来通知下游缓存失效或记录日志。服务器启动之后可以用 [`DbHandle::on_expire`] 替换。
+    ///
+    /// [`DbHandle::on_expire`]: crate::server::DbHandle::on_expire
+    pub on_expire: Option<ExpireCallback>,
+
+    /// 是否发布键空间事件，默认为 `false`。
+    ///
+    /// 开启之后，`SET` 和 `MSET` 向频道 `__keyevent@0__:set` 发布被设置的键名，`DEL` 和 `UNLINK`
+    /// 向频道 `__keyevent@0__:del` 发布每个被删除的键名，客户端可以用 `SUBSCRIBE` 接收。
+    /// 配置文件中与 Redis 一样写作类型标志组成的字符串，例如 `notify-keyspace-events KEA`，
+    /// 只有键事件（`E`）一种频道，因此包含 `E` 和至少一个事件类型（`g`、`$` 或 `A` 等）时开启，
+    /// 空字符串 `""` 表示关闭；也可以写作 `yes` 或 `no`。收到 SIGHUP 重新加载之后立即生效。
+    pub notify_keyspace_events: bool,
+
+    /// TLS 证书和私钥，默认为 `None`，即不使用 TLS。
+    ///
+    /// 设置之后，服务器在 TCP 连接上先完成 TLS 握手再处理命令，unix 域套接字连接不受影响。
+    /// 证书或私钥无法读取时服务器不会启动。只在启动时生效。
+    pub tls: Option<TlsConfig>,
+}
+
+/// 服务器使用的 TLS 证书和私钥，见 [`ServerConfig::tls`]。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// PEM 格式的证书链文件，第一个证书是服务器自己的证书。
+    pub cert_path: PathBuf,
+    /// PEM 格式的私钥文件，支持 PKCS#1、PKCS#8 和 SEC1 格式。
+    pub key_path: PathBuf,
+}
+
+impl ServerConfig {
+    /// 返回配置项 `name` 的当前值，配置项的名称与配置文件中的相同。
+    ///
+    /// 未知的配置项返回 `None`。
+    pub fn get(&self, name: &str) -> Option<String> {
+        let value = match &name.to_lowercase()[..] {
+            "bind" => self.bind.clone(),
+            "port" => self.port.to_string(),
+            "maxclients" => self.max_connections.to_string(),
+            "shutdown-timeout" => self.shutdown_timeout.as_secs().to_string(),
+            "compact-interval" => self
+                .compact_interval
+                .map_or(0, |interval| interval.as_secs())
+                .to_string(),
+            "maxmemory" => self.max_memory.unwrap_or(0).to_string(),
+            "maxmemory-policy" => self.maxmemory_policy.as_str().to_string(),
+            "proto-max-bulk-len" => self.proto_max_bulk_len.to_string(),
+            "max-value-size" => self.max_value_size.unwrap_or(0).to_string(),
+            "enable-debug-command" => yes_no(self.debug_commands).to_string(),
+            "access-log" => yes_no(self.access_log).to_string(),
+            "notify-keyspace-events" => yes_no(self.notify_keyspace_events).to_string(),
+            "timeout" => self
+                .client_idle_timeout
+                .map_or(0, |timeout| timeout.as_secs())
+                .to_string(),
+            "command-timeout" => self
+                .command_timeout
+                .map_or(0, |timeout| timeout.as_millis())
+                .to_string(),
+            "slowlog-threshold" => self
+                .slowlog_threshold
+                .map_or(0, |threshold| threshold.as_millis())
+                .to_string(),
+            "slowlog-max-len" => self.slowlog_max_len.to_string(),
+            "max-commands-per-sec" => self.max_commands_per_sec.unwrap_or(0).to_string(),
+            "rate-limit-action" => self.rate_limit_action.as_str().to_string(),
+            "tls-cert-file" => self
+                .tls
+                .as_ref()
+                .map_or_else(String::new, |tls| tls.cert_path.display().to_string()),
+            "tls-key-file" => self
+                .tls
+                .as_ref()
+                .map_or_else(String::new, |tls| tls.key_path.display().to_string()),
+            "loglevel" => self
+                .log_level
+                .unwrap_or_else(log::max_level)
+                .to_string()
+                .to_lowercase(),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    /// 使用重新读取的配置 `new` 更新可以在运行时修改的配置项。
+    ///
+    /// 目前可以修改的是 `shutdown_timeout`、`client_idle_timeout`、`command_timeout`、`max_memory`、
+    /// `maxmemory_policy`、`proto_max_bulk_len`、`max_value_size`、`slowlog_threshold`、`slowlog_max_len`、
+    /// `max_commands_per_sec`、`rate_limit_action`、`access_log`、`notify_keyspace_events` 和 `log_level`。
+    /// 监听地址、端口、最大连接数和 TLS 证书只在启动时生效，它们的变化会被记录并忽略。
+    pub(crate) fn reload(&mut self, new: ServerConfig) {
+        if new.bind != self.bind || new.port != self.port {
+            warn!("config reload: bind and port can't be changed at runtime, ignored");
+        }
+        if new.max_connections != self.max_connections {
+            warn!("config reload: maxclients can't be changed at runtime, ignored");
+        }
+        if new.tls != self.tls {
+            warn!("config reload: tls can't be changed at runtime, ignored");
+        }
+
+        self.shutdown_timeout = new.shutdown_timeout;
+        self.client_idle_timeout = new.client_idle_timeout;
+        self.command_timeout = new.command_timeout;
+        self.max_memory = new.max_memory;
+        self.maxmemory_policy = new.maxmemory_policy;
+        self.proto_max_bulk_len = new.proto_max_bulk_len;
+        self.max_value_size = new.max_value_size;
+        self.slowlog_threshold = new.slowlog_threshold;
+        self.slowlog_max_len = new.slowlog_max_len;
+        self.max_commands_per_sec = new.max_commands_per_sec;
+        self.rate_limit_action = new.rate_limit_action;
+        self.access_log = new.access_log;
+        self.notify_keyspace_events = new.notify_keyspace_events;
+        self.log_level = new.log_level;
+        self.apply_log_level();
+
+        info!("config reloaded");
+    }
+
+    /// 如果设置了 `log_level`，将它应用到全局的日志级别。
+    pub fn apply_log_level(&self) {
+        if let Some(level) = self.log_level {
+            log::set_max_level(level);
+        }
+    }
+}
+
+/// 超过 `maxmemory` 时选择删除哪些键，名称与 Redis 的 `maxmemory-policy` 相同。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaxmemoryPolicy {
+    /// 不删除任何键，拒绝可能占用更多内存的命令。
+    #[default]
+    NoEviction,
+    /// 在所有键中删除最久没有被访问的键。
+    AllKeysLru,
+    /// 在所有键中随机删除。
+    AllKeysRandom,
+    /// 只在设置了过期时间的键中删除最久没有被访问的键。
+    VolatileLru,
+    /// 只在设置了过期时间的键中删除最早过期的键。
+    VolatileTtl,
+}
+
+impl MaxmemoryPolicy {
+    /// 策略在配置文件中的名称，例如 `allkeys-lru`。
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MaxmemoryPolicy::NoEviction => "noeviction",
+            MaxmemoryPolicy::AllKeysLru => "allkeys-lru",
+            MaxmemoryPolicy::AllKeysRandom => "allkeys-random",
+            MaxmemoryPolicy::VolatileLru => "volatile-lru",
+            MaxmemoryPolicy::VolatileTtl => "volatile-ttl",
+        }
+    }
+}
+
+impl FromStr for MaxmemoryPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<MaxmemoryPolicy, ()> {
+        match &s.to_lowercase()[..] {
+            "noeviction" => Ok(MaxmemoryPolicy::NoEviction),
+            "allkeys-lru" => Ok(MaxmemoryPolicy::AllKeysLru),
+            "allkeys-random" => Ok(MaxmemoryPolicy::AllKeysRandom),
+            "volatile-lru" => Ok(MaxmemoryPolicy::VolatileLru),
+            "volatile-ttl" => Ok(MaxmemoryPolicy::VolatileTtl),
+            _ => Err(()),
+        }
+    }
+}
+
+/// 连接执行命令的速率超过 `max-commands-per-sec` 时的处理方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RateLimitAction {
+    /// 等待到有令牌时再执行命令，客户端看到的是响应变慢。
+    #[default]
+    Delay,
+    /// 不执行命令，回复 `-ERR rate limit exceeded`。
+    Reject,
+}
+
+impl RateLimitAction {
+    /// 处理方式在配置文件中的名称，例如 `delay`。
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RateLimitAction::Delay => "delay",
+            RateLimitAction::Reject => "reject",
+        }
+    }
+}
+
+impl FromStr for RateLimitAction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<RateLimitAction, ()> {
+        match &s.to_lowercase()[..] {
+            "delay" => Ok(RateLimitAction::Delay),
+            "reject" => Ok(RateLimitAction::Reject),
+            _ => Err(()),
+        }
+    }
+}
+
+/// 键过期时调用的回调，参数是过期的键。
+///
+/// 回调在后台清理任务或者惰性删除过期键的命令中、释放存储锁之后调用，因此可以在回调中再次访问数据库。
+/// 回调应当尽量轻量，不要阻塞。
+#[derive(Clone)]
+pub struct ExpireCallback(Arc<dyn Fn(&str) + Send + Sync>);
+
+impl ExpireCallback {
+    /// 使用 `callback` 创建一个过期回调。
+    pub fn new(callback: impl Fn(&str) + Send + Sync + 'static) -> ExpireCallback {
+        ExpireCallback(Arc::new(callback))
+    }
+
+    /// 通知 `key` 已经过期。
+    pub(crate) fn call(&self, key: &str) {
+        (self.0)(key)
+    }
+}
+
+impl fmt::Debug for ExpireCallback {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("ExpireCallback")
+    }
+}
+
+/// 一条访问日志，描述一次命令的执行。
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessLogEntry<'a> {
+    /// 命令的名称，例如 `get`。
+    pub command: &'a str,
+    /// 参数的个数，不包括命令名称本身。
+    pub args: usize,
+    /// 执行命令的耗时，不包括读取请求的时间。
+    pub latency: Duration,
+    /// 回复的类型，例如 `bulk`、`error`，命令没有回复时为 `none`。
+    pub reply: &'static str,
+}
+
+impl fmt::Display for AccessLogEntry<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "cmd={} args={} latency_us={} reply={}",
+            self.command,
+            self.args,
+            self.latency.as_micros(),
+            self.reply
+        )
+    }
+}
+
+/// 访问日志的回调，参数是刚刚执行完的命令的访问日志。
+///
+/// 回调在处理连接的任务中调用，执行期间这个连接不会处理下一个命令，因此回调应当尽量轻量，不要阻塞。
+#[derive(Clone)]
+pub struct AccessLogCallback(Arc<dyn Fn(&AccessLogEntry<'_>) + Send + Sync>);
+
+impl AccessLogCallback {
+    /// 使用 `callback` 创建一个访问日志回调。
+    pub fn new(
+        callback: impl Fn(&AccessLogEntry<'_>) + Send + Sync + 'static,
+    ) -> AccessLogCallback {
+        AccessLogCallback(Arc::new(callback))
+    }
+
+    /// 上报一条访问日志。
+    pub(crate) fn call(&self, entry: &AccessLogEntry<'_>) {
+        (self.0)(entry)
+    }
+}
+
+impl fmt::Debug for AccessLogCallback {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("AccessLogCallback")
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> ServerConfig {
+        ServerConfig {
+            bind: DEFAULT_BIND.to_string(),
+            port: DEFAULT_PORT,
+            metrics: Arc::new(NoopMetrics),
+            max_inline_len: MAX_INLINE_LEN,
+            proto_max_bulk_len: PROTO_MAX_BULK_LEN,
+            max_value_size: None,
+            shutdown_timeout: SHUTDOWN_TIMEOUT,
+            shards: DEFAULT_SHARDS,
+            max_connections: MAX_CONNECTIONS,
+            compact_interval: Some(COMPACT_INTERVAL),
+            max_memory: None,
+            maxmemory_policy: MaxmemoryPolicy::NoEviction,
+            client_idle_timeout: None,
+            command_timeout: None,
+            max_commands_per_sec: None,
+            rate_limit_action: RateLimitAction::Delay,
+            slowlog_threshold: None,
+            slowlog_max_len: SLOWLOG_MAX_LEN,
+            reject_on_full: false,
+            debug_commands: false,
+            access_log: false,
+            on_access: None,
+            log_level: None,
+            config_file: None,
+            on_expire: None,
+            notify_keyspace_events: false,
+            tls: None,
+        }
+    }
+}
+
+/// 读取 `redis.conf` 格式的配置文件。
+///
+/// 文件中没有出现的配置项使用默认值。格式见 [`parse_config`]。
+pub fn parse_config_file(path: impl AsRef<Path>) -> Result<ServerConfig, MiniRedisConfigError> {
+    let contents = fs::read_to_string(path.as_ref())?;
+    let mut config = parse_config(&contents)?;
+    config.config_file = Some(path.as_ref().to_path_buf());
+    Ok(config)
+}
+
+/// 解析 `redis.conf` 格式的配置。
+///
+/// 每行一条配置，配置名和参数以空白分隔，配置名不区分大小写，以 `#` 开头的行和空行会被忽略。
+/// 只支持已经实现的功能对应的配置：
+///
+/// ```text
+/// bind 127.0.0.1
+/// port 6379
+/// maxclients 1024
+/// shutdown-timeout 30
+/// compact-interval 60
+/// maxmemory 0
+/// maxmemory-policy noeviction
+/// proto-max-bulk-len 536870912
+/// max-value-size 0
+/// enable-debug-command no
+/// access-log no
+/// notify-keyspace-events ""
+/// timeout 0
+/// command-timeout 0
+/// slowlog-threshold 0
+/// slowlog-max-len 128
+/// max-commands-per-sec 0
+/// rate-limit-action delay
+/// tls-cert-file /path/to/cert.pem
+/// tls-key-file /path/to/key.pem
+/// loglevel info
+/// ```
+///
+/// 未知的配置名或者不合法的参数会返回错误，而不是被忽略。
+pub fn parse_config(contents: &str) -> Result<ServerConfig, MiniRedisConfigError> {
+    let mut config = ServerConfig::default();
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        // 非空行至少有一个部分
+        let name = parts.next().unwrap().to_lowercase();
+        let args: Vec<&str> = parts.collect();
+
+        apply_directive(&mut config, &name, &args)
+            .map_err(|msg| MiniRedisConfigError::Invalid { line: i + 1, msg })?;
+    }
+
+    Ok(config)
+}
+
+// 将一条配置应用到 config 上，出错时返回错误描述
+fn apply_directive(config: &mut ServerConfig, name: &str, args: &[&str]) -> Result<(), String> {
+    let arg = match args {
+        [arg] => *arg,
+        _ => return Err(format!("wrong number of arguments for '{}'", name)),
+    };
+
+    match name {
+        "bind" => config.bind = arg.to_string(),
+        "port" => config.port = parse_arg(name, arg)?,
+        "maxclients" => config.max_connections = parse_arg(name, arg)?,
+        "shutdown-timeout" => config.shutdown_timeout = Duration::from_secs(parse_arg(name, arg)?),
+        "compact-interval" => {
+            // 0 表示不整理
+            let secs = parse_arg(name, arg)?;
+            config.compact_interval = match secs {
+                0 => None,
+                secs => Some(Duration::from_secs(secs)),
+            };
+        }
+        "maxmemory" => {
+            // 与 Redis 一致，0 表示不限制
+            config.max_memory = match parse_arg(name, arg)? {
+                0 => None,
+                bytes => Some(bytes),
+            };
+        }
+        "maxmemory-policy" => config.maxmemory_policy = parse_arg(name, arg)?,
+        "proto-max-bulk-len" => config.proto_max_bulk_len = parse_arg(name, arg)?,
+        "max-value-size" => {
+            // 0 表示不限制
+            config.max_value_size = match parse_arg(name, arg)? {
+                0 => None,
+                bytes => Some(bytes),
+            };
+        }
+        "timeout" => {
+            // 与 Redis 一致，0 表示不关闭空闲连接
+            let secs = parse_arg(name, arg)?;
+            config.client_idle_timeout = match secs {
+                0 => None,
+                secs => Some(Duration::from_secs(secs)),
+            };
+        }
+        "command-timeout" => {
+            // 以毫秒为单位，0 表示不限制
+            let millis = parse_arg(name, arg)?;
+            config.command_timeout = match millis {
+                0 => None,
+                millis => Some(Duration::from_millis(millis)),
+            };
+        }
+        "slowlog-threshold" => {
+            // 以毫秒为单位，0 表示不记录
+            let millis = parse_arg(name, arg)?;
+            config.slowlog_threshold = match millis {
+                0 => None,
+                millis => Some(Duration::from_millis(millis)),
+            };
+        }
+        "slowlog-max-len" => config.slowlog_max_len = parse_arg(name, arg)?,
+        "max-commands-per-sec" => {
+            // 0 表示不限制
+            config.max_commands_per_sec = match parse_arg(name, arg)? {
+                0 => None,
+                rate => Some(rate),
+            };
+        }
+        "rate-limit-action" => config.rate_limit_action = parse_arg(name, arg)?,
+        "tls-cert-file" => {
+            config.tls.get_or_insert_with(TlsConfig::default).cert_path = PathBuf::from(arg)
+        }
+        "tls-key-file" => {
+            config.tls.get_or_insert_with(TlsConfig::default).key_path = PathBuf::from(arg)
+        }
+        "enable-debug-command" => config.debug_commands = parse_yes_no(name, arg)?,
+        "access-log" => config.access_log = parse_yes_no(name, arg)?,
+        "notify-keyspace-events" => {
+            config.notify_keyspace_events = parse_keyspace_events(name, arg)?
+        }
+        "loglevel" => config.log_level = Some(parse_arg(name, arg)?),
+        _ => return Err(format!("unknown directive '{}'", name)),
+    }
+
+    Ok(())
+}
+
+// 解析布尔配置项，只接受 yes 和 no
+fn parse_yes_no(name: &str, arg: &str) -> Result<bool, String> {
+    match &arg.to_lowercase()[..] {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(format!("invalid argument '{}' for '{}'", arg, name)),
+    }
+}
+
+// 解析 notify-keyspace-events 的参数，返回是否开启键空间事件
+// 参数是 Redis 的类型标志组成的字符串，可以用引号包围，空字符串表示关闭；为了兼容也接受 yes 和 no
+fn parse_keyspace_events(name: &str, arg: &str) -> Result<bool, String> {
+    if let Ok(enabled) = parse_yes_no(name, arg) {
+        return Ok(enabled);
+    }
+
+    let flags = ["\"", "'"]
+        .iter()
+        .find_map(|quote| arg.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(arg);
+    if !flags.chars().all(|flag| "KEg$lshzxetmdnA".contains(flag)) {
+        return Err(format!("invalid argument '{}' for '{}'", arg, name));
+    }
+
+    // 只发布键事件，需要 E 和至少一个事件类型
+    let classes = flags.chars().any(|flag| !matches!(flag, 'K' | 'E'));
+    Ok(flags.contains('E') && classes)
+}
+
+// 布尔配置项在配置文件中的写法
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+// 解析配置的参数
+fn parse_arg<T: std::str::FromStr>(name: &str, arg: &str) -> Result<T, String> {
+    arg.parse()
+        .map_err(|_| format!("invalid argument '{}' for '{}'", arg, name))
+}
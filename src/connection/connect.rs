@@ -1,28 +1,53 @@
 use std::io::Cursor;
 
-use bytes::{Buf, BytesMut};
-use log::warn;
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
 
-use crate::connection::frame::Frame;
+use crate::connection::frame::{format_double, Frame};
+use crate::consts::{MAX_INLINE_LEN, PROTO_MAX_BULK_LEN, RESP2, RESP3};
 use crate::error::{MiniRedisConnectionError, MiniRedisParseError};
 
-//// 从远程对等方发送和接收 `Frame` 值。
+// 写缓冲区超过这个长度时，`write_frame_buffered` 先把数据写入流中
+const WRITE_BUF_LEN: usize = 8 * 1024;
+
+/// 从远程对等方发送和接收 `Frame` 值。
+///
+/// 在实现网络协议时，协议中的消息通常由几个较小的消息组成，称为帧。`Connection` 的目的是在底层的字节流上读取和写入帧。
 ///
-/// 在实现网络协议时，协议中的消息通常由几个较小的消息组成，称为帧。`Connection` 的目的是在底层的 `TcpStream` 上读取和写入帧。
+/// 字节流可以是任何实现了 `AsyncRead` 和 `AsyncWrite` 的类型，默认为 `TcpStream`。服务器在 TCP、TLS
+/// 和 unix 域套接字连接上使用同一个 `Connection`，测试中也可以使用 `tokio::io::duplex` 创建的内存管道。
 ///
 /// 为了读取帧，`Connection` 使用内部缓冲区，直到有足够的字节来创建一个完整的帧。一旦完成，`Connection` 创建帧并将其返回给调用者。
 ///
 /// 当发送帧时，帧首先被编码到写缓冲区中。然后，写缓冲区的内容被写入到套接字中。
 #[derive(Debug)]
-pub struct Connection {
-    /// `TcpStream`。它被 `BufWriter` 装饰，提供写入级别的缓冲。
-    /// Tokio 提供的 `BufWriter` 实现满足我们的需求。
-    stream: BufWriter<TcpStream>,
+pub struct Connection<S = TcpStream> {
+    /// 底层的字节流。
+    stream: S,
 
     /// 读取帧的缓冲区。
     buffer: BytesMut,
+
+    /// 写缓冲区，帧先编码到这里，刷新时再写入字节流。
+    write_buf: BytesMut,
+
+    /// 暂存回复时写缓冲区的起始位置，见 [`Connection::hold_replies`]。
+    held: Option<usize>,
+
+    /// 单行数据的最大长度，超过这个长度仍未读到 `\r\n` 时视为协议错误。
+    max_inline_len: usize,
+
+    /// Bulk 字符串和数组的最大长度，声明的长度超过它时视为协议错误，不会为其分配缓冲区。
+    max_bulk_len: usize,
+
+    /// 通过 `HELLO` 协商的协议版本，默认为 RESP2。
+    ///
+    /// 决定写入 `Map` 和 `Boolean` 帧时使用 RESP3 类型，还是退化为 RESP2 的数组和整数。
+    protocol: u8,
+
+    /// 上次取出之后写入的第一个回复的类型，由 [`Connection::take_reply_kind`] 取出，用于访问日志。
+    reply_kind: Option<&'static str>,
 }
 
 impl Connection {
@@ -34,14 +59,60 @@ impl Connection {
     /// # 返回
     /// 返回一个包含缓冲区和流的 `Connection` 实例。
     pub fn new(socket: TcpStream) -> Connection {
+        Connection::with_stream(socket)
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Connection<S> {
+    /// 在任意的字节流上创建一个新的 `Connection` 实例。
+    ///
+    /// # 参数
+    /// * `stream` - 一个已经建立的连接，例如 TLS 连接或者 `tokio::io::duplex` 创建的内存管道。
+    pub fn with_stream(stream: S) -> Connection<S> {
+        Connection::with_limits(stream, MAX_INLINE_LEN, PROTO_MAX_BULK_LEN)
+    }
+
+    /// 创建一个新的 `Connection` 实例，并指定单行数据和 Bulk 字符串的最大长度。
+    ///
+    /// # 参数
+    /// * `socket` - 一个已经建立的连接。
+    /// * `max_inline_len` - 单行数据的最大长度。
+    /// * `max_bulk_len` - Bulk 字符串和数组的最大长度。
+    pub(crate) fn with_limits(
+        socket: S,
+        max_inline_len: usize,
+        max_bulk_len: usize,
+    ) -> Connection<S> {
         Connection {
-            // 使用 BufWriter 包装 TcpStream 以提供写缓冲功能。
-            stream: BufWriter::new(socket),
+            stream: socket,
             // 初始化一个 4KB 的缓冲区用于读取数据。
             buffer: BytesMut::with_capacity(4 * 1024),
+            write_buf: BytesMut::with_capacity(WRITE_BUF_LEN),
+            held: None,
+            max_inline_len,
+            max_bulk_len,
+            protocol: RESP2,
+            reply_kind: None,
         }
     }
 
+    /// 返回连接当前使用的协议版本。
+    pub(crate) fn protocol(&self) -> u8 {
+        self.protocol
+    }
+
+    /// 设置连接使用的协议版本，之后写入的帧按照新的版本编码。
+    pub(crate) fn set_protocol(&mut self, protocol: u8) {
+        self.protocol = protocol;
+    }
+
+    /// 取出上次取出之后写入的第一个回复的类型，这段时间没有写入回复时返回 `None`。
+    ///
+    /// 只记录第一个帧，因此 `EXEC` 的回复是数组，而不是其中最后一个命令的回复。
+    pub(crate) fn take_reply_kind(&mut self) -> Option<&'static str> {
+        self.reply_kind.take()
+    }
+
     /// 异步读取数据并解析为 `Frame`。
     ///
     /// # 返回
@@ -70,29 +141,65 @@ impl Connection {
     /// # 返回
     /// 如果成功，返回解析出的 `Frame`；如果数据不足，返回 `None`。
     fn parse_frame(&mut self) -> Result<Option<Frame>, MiniRedisConnectionError> {
-        // 创建一个 Cursor 以便在缓冲区中移动和读取数据。
-        let mut buf = Cursor::new(&self.buffer[..]);
+        loop {
+            // 记录是否是内联命令，内联命令中的空行需要跳过。
+            let inline = self
+                .buffer
+                .first()
+                .map(|first| Frame::is_inline(*first))
+                .unwrap_or(false);
+
+            // 创建一个 Cursor 以便在缓冲区中移动和读取数据。
+            let mut buf = Cursor::new(&self.buffer[..]);
 
-        // 调用 Frame::check 检查缓冲区中是否有完整的帧。
-        match Frame::check(&mut buf) {
-            Ok(_) => {
-                // 获取当前 buf 的位置，表示帧的长度。
-                let len = buf.position() as usize;
+            // 调用 Frame::check 检查缓冲区中是否有完整的帧。
+            match Frame::check(&mut buf, self.max_inline_len, self.max_bulk_len) {
+                Ok(_) => {
+                    // 获取当前 buf 的位置，表示帧的长度。
+                    let len = buf.position() as usize;
 
-                // 将 cursor 的位置重置为起始位置。
-                buf.set_position(0);
+                    // 将 cursor 的位置重置为起始位置。
+                    buf.set_position(0);
 
-                // 调用 Frame::parse 解析帧。
-                let frame = Frame::parse(&mut buf)?;
+                    // 调用 Frame::parse 解析帧。
+                    // check 已经确认缓冲区中有一个完整的帧，parse 不应该再返回 Incomplete，
+                    // 也应该消耗相同的字节数，否则说明两者的实现不一致。
+                    let frame = match Frame::parse(&mut buf) {
+                        Ok(frame) => frame,
+                        Err(MiniRedisParseError::Incomplete) => {
+                            debug_assert!(false, "frame check passed but parse is incomplete");
+                            return Err(MiniRedisParseError::CheckParseMismatch(format!(
+                                "parse incomplete after check accepted {} bytes",
+                                len
+                            ))
+                            .into());
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
+                    if buf.position() as usize != len {
+                        debug_assert!(false, "frame check and parse consumed different lengths");
+                        return Err(MiniRedisParseError::CheckParseMismatch(format!(
+                            "check consumed {} bytes, parse consumed {}",
+                            len,
+                            buf.position()
+                        ))
+                        .into());
+                    }
 
-                // 移动缓冲区的起始位置，丢弃已经解析的数据。
-                self.buffer.advance(len);
-                Ok(Some(frame))
+                    // 移动缓冲区的起始位置，丢弃已经解析的数据。
+                    self.buffer.advance(len);
+
+                    // 内联命令的空行不包含任何参数，跳过后继续解析下一帧。
+                    if inline && matches!(&frame, Frame::Array(parts) if parts.is_empty()) {
+                        continue;
+                    }
+                    return Ok(Some(frame));
+                }
+                // 如果数据不足以构成一个完整的帧，则返回 None。
+                Err(MiniRedisParseError::Incomplete) => return Ok(None),
+                // 其他错误则直接返回。
+                Err(e) => return Err(e.into()),
             }
-            // 如果数据不足以构成一个完整的帧，则返回 None。
-            Err(MiniRedisParseError::Incomplete) => Ok(None),
-            // 其他错误则直接返回。
-            Err(e) => Err(e.into()),
         }
     }
 
@@ -104,123 +211,321 @@ impl Connection {
     /// # 返回
     /// 如果成功，返回 `Ok(())`。
     pub async fn write_frame(&mut self, frame: &Frame) -> Result<(), MiniRedisConnectionError> {
-        // 根据帧的类型进行处理。
-        match frame {
-            // 如果是数组类型
-            Frame::Array(val) => {
-                // 写入数组类型的标识符 `*`
-                self.stream.write_u8(b'*').await?;
+        self.reply_kind.get_or_insert(frame.kind());
+        self.write_value(frame);
 
-                // 写入数组的长度
-                self.write_decimal(val.len() as u64).await?;
+        // 刷新缓冲区，将数据真正发送到网络中。
+        self.flush().await
+    }
 
-                // 遍历数组中的每个元素并写入
-                for entry in val {
-                    self.write_value(entry).await?;
-                }
-            }
-            // 其他类型的帧
-            _ => self.write_value(frame).await?,
+    /// 异步写入 `Frame` 数据到写缓冲区，但不刷新。
+    ///
+    /// 连续写入多个帧时只需要在最后调用一次 [`Connection::flush`]，减少系统调用的次数。
+    /// 缓冲区写满时数据仍然会被写入流中，因此不会无限制地占用内存；
+    /// 但写完之后必须调用 `flush`，否则最后一部分数据可能一直留在缓冲区中。
+    pub async fn write_frame_buffered(
+        &mut self,
+        frame: &Frame,
+    ) -> Result<(), MiniRedisConnectionError> {
+        self.reply_kind.get_or_insert(frame.kind());
+        self.write_value(frame);
+        if self.held.is_none() && self.write_buf.len() >= WRITE_BUF_LEN {
+            self.write_out().await?;
         }
+        Ok(())
+    }
 
-        // 刷新缓冲区，将数据真正发送到网络中。
+    /// 写入数组帧的头部，之后需要再写入 `len` 个帧作为数组的元素。不刷新。
+    ///
+    /// `EXEC` 使用它把每个命令各自写入的回复组成一个数组。
+    pub(crate) async fn write_array_header(
+        &mut self,
+        len: usize,
+    ) -> Result<(), MiniRedisConnectionError> {
+        self.reply_kind.get_or_insert("array");
+        self.write_buf.put_u8(b'*');
+        self.write_decimal(len as i64);
+        Ok(())
+    }
+
+    /// 将写缓冲区中的数据全部发送到网络中。
+    ///
+    /// 调用 [`Connection::hold_replies`] 之后什么也不做，数据留在缓冲区中，
+    /// 直到 [`Connection::release_replies`] 时才发送。
+    pub async fn flush(&mut self) -> Result<(), MiniRedisConnectionError> {
+        if self.held.is_some() {
+            return Ok(());
+        }
+        self.write_out().await?;
         self.stream.flush().await.map_err(|e| e.into())
     }
 
-    /// 根据 `Frame` 类型写入具体数据。
+    /// 暂存之后写入的回复，不发送到网络中。
+    ///
+    /// 服务器限制命令的执行时间时使用：执行期间的回复只写入内存，超时时由
+    /// [`Connection::discard_replies`] 丢弃，不会把半个回复留在连接上；
+    /// 执行完成后由 [`Connection::release_replies`] 在限制之外发送，客户端读得慢时不会被算作超时。
+    pub(crate) fn hold_replies(&mut self) {
+        self.held.get_or_insert(self.write_buf.len());
+    }
+
+    /// 发送 [`Connection::hold_replies`] 之后暂存的回复，并恢复正常的写入。
+    pub(crate) async fn release_replies(&mut self) -> Result<(), MiniRedisConnectionError> {
+        self.held = None;
+        self.flush().await
+    }
+
+    /// 丢弃 [`Connection::hold_replies`] 之后暂存的回复，并恢复正常的写入。
+    pub(crate) fn discard_replies(&mut self) {
+        if let Some(held) = self.held.take() {
+            self.write_buf.truncate(held);
+        }
+    }
+
+    // 将写缓冲区中的数据写入流中，不刷新流
+    async fn write_out(&mut self) -> Result<(), MiniRedisConnectionError> {
+        self.stream.write_all(&self.write_buf).await?;
+        self.write_buf.clear();
+        Ok(())
+    }
+
+    /// 根据 `Frame` 类型把数据编码到写缓冲区。
     ///
     /// # 参数
     /// * `frame` - 要写入的 `Frame` 数据。
-    ///
-    /// # 返回
-    /// 如果成功，返回 `Ok(())`。
-    async fn write_value(&mut self, frame: &Frame) -> Result<(), MiniRedisConnectionError> {
+    fn write_value(&mut self, frame: &Frame) {
         // 使用 match 语句根据 frame 的类型进行处理
         match frame {
             // 写入简单字符串
             Frame::Simple(val) => {
                 // 写入简单字符串类型的标识符 `+`
-                self.stream.write_u8(b'+').await?;
+                self.write_buf.put_u8(b'+');
                 // 写入字符串的内容
-                self.stream.write_all(val.as_bytes()).await?;
+                self.write_buf.put_slice(val.as_bytes());
                 // 写入结尾标识 `\r\n`
-                self.stream.write_all(b"\r\n").await?;
+                self.write_buf.put_slice(b"\r\n");
             }
             // 写入错误信息
             Frame::Error(val) => {
                 // 写入错误信息类型的标识符 `-`
-                self.stream.write_u8(b'-').await?;
+                self.write_buf.put_u8(b'-');
                 // 写入错误信息的内容
-                self.stream.write_all(val.as_bytes()).await?;
+                self.write_buf.put_slice(val.as_bytes());
                 // 写入结尾标识 `\r\n`
-                self.stream.write_all(b"\r\n").await?;
+                self.write_buf.put_slice(b"\r\n");
             }
             // 写入整数
             Frame::Integer(val) => {
                 // 写入整数类型的标识符 `:`
-                self.stream.write_u8(b':').await?;
+                self.write_buf.put_u8(b':');
                 // 写入整数值
-                self.write_decimal(*val).await?;
+                self.write_decimal(*val);
             }
             // 写入空值
             Frame::Null => {
                 // 写入表示空值的特殊标识 `$-1\r\n`
-                self.stream.write_all(b"$-1\r\n").await?;
+                self.write_buf.put_slice(b"$-1\r\n");
             }
             // 写入批量字符串
             Frame::Bulk(val) => {
                 // 获取字符串的长度
                 let len = val.len();
                 // 写入批量字符串类型的标识符 `$`
-                self.stream.write_u8(b'$').await?;
+                self.write_buf.put_u8(b'$');
                 // 写入字符串的长度
-                self.write_decimal(len as u64).await?;
+                self.write_decimal(len as i64);
                 // 写入字符串的内容
-                self.stream.write_all(val).await?;
+                self.write_buf.put_slice(val);
                 // 写入结尾标识 `\r\n`
-                self.stream.write_all(b"\r\n").await?;
+                self.write_buf.put_slice(b"\r\n");
             }
-            // 数组类型目前不支持递归写入，直接返回未实现错误
-            Frame::Array(_val) => {
-                // 记录警告信息
-                warn!("unreachable code: recursive write_value: {:?}", _val);
-                // 返回未实现错误
-                return Err(MiniRedisParseError::Unimplemented.into());
+            // 写入布尔值
+            Frame::Boolean(val) => {
+                let val = *val;
+                if self.protocol >= RESP3 {
+                    // 写入布尔值类型的标识符 `#` 和 `t` 或 `f`
+                    self.write_buf
+                        .put_slice(if val { b"#t\r\n" } else { b"#f\r\n" });
+                } else {
+                    // RESP2 没有布尔值类型，写为整数 1 或 0
+                    self.write_buf.put_u8(b':');
+                    self.write_decimal(val as i64);
+                }
             }
-        }
+            // 写入浮点数
+            Frame::Double(val) => {
+                let val = format_double(*val);
+                if self.protocol >= RESP3 {
+                    // 写入浮点数类型的标识符 `,` 和数值
+                    self.write_buf.put_u8(b',');
+                    self.write_buf.put_slice(val.as_bytes());
+                    self.write_buf.put_slice(b"\r\n");
+                } else {
+                    // RESP2 没有浮点数类型，与 Redis 一样写为 Bulk 字符串
+                    self.write_value(&Frame::Bulk(Bytes::from(val)));
+                }
+            }
+            // 写入数组，元素可以是嵌套的数组或 Map
+            Frame::Array(val) => {
+                // 写入数组类型的标识符 `*`
+                self.write_buf.put_u8(b'*');
 
-        // 所有写入操作成功后，返回 Ok(())
-        Ok(())
+                // 写入数组的长度
+                self.write_decimal(val.len() as i64);
+
+                // 遍历数组中的每个元素并写入
+                for entry in val {
+                    self.write_value(entry);
+                }
+            }
+            // 写入 Map
+            Frame::Map(entries) => {
+                if self.protocol >= RESP3 {
+                    // 写入 Map 类型的标识符 `%` 和条目数量
+                    self.write_buf.put_u8(b'%');
+                    self.write_decimal(entries.len() as i64);
+                } else {
+                    // RESP2 没有 Map 类型，写为键值交替排列的数组
+                    self.write_buf.put_u8(b'*');
+                    self.write_decimal(entries.len() as i64 * 2);
+                }
+
+                for (key, value) in entries {
+                    self.write_value(key);
+                    self.write_value(value);
+                }
+            }
+        }
     }
 
-    /// 异步地将十进制数值写入 TCP 流。
+    /// 将十进制数值和结尾标识符 `\r\n` 写入写缓冲区。
     ///
     /// # 参数
     /// * `val` - 要写入的十进制数值。
-    ///
-    /// # 返回
-    /// 如果成功，返回 `Ok(())`。
-    async fn write_decimal(&mut self, val: u64) -> Result<(), MiniRedisConnectionError> {
-        // 引入 std::io::Write trait 以便使用其提供的写入方法。
-        use std::io::Write;
-
-        // 创建一个长度为20的字节数组缓冲区，用于存储转换后的字符串。
-        let mut buf = [0u8; 20];
-        // 将缓冲区包装成 Cursor，以便在其上执行写入操作。
-        let mut buf = Cursor::new(&mut buf[..]);
-
-        // 将十进制数值转换为字符串并写入缓冲区。
-        // write! 宏用于格式化输出，将 val 作为十进制数值写入 buf 中。
-        write!(&mut buf, "{}", val)?;
-
-        // 获取当前 Cursor 的位置，该位置表示写入的数据长度。
-        let pos = buf.position() as usize;
-        // 将缓冲区中的有效内容（从起始位置到当前 Cursor 位置）写入到 TCP 流中。
-        self.stream.write_all(&buf.get_ref()[..pos]).await?;
-        // 写入结尾标识符 `\r\n` 到 TCP 流中，以表示结束。
-        self.stream.write_all(b"\r\n").await?;
-
-        // 返回 Ok(()) 表示写入操作成功完成。
-        Ok(())
+    fn write_decimal(&mut self, val: i64) {
+        // 引入 std::fmt::Write trait 以便直接格式化到 `BytesMut` 中。
+        use std::fmt::Write;
+
+        // 写入 `BytesMut` 只会在内存不足时失败，这里不会出错。
+        let _ = write!(self.write_buf, "{}\r\n", val);
+    }
+}
+
+/// 测试使用的一对相连的内存管道，返回客户端一侧的原始字节流和服务端一侧的 `Connection`。
+///
+/// 管道的缓冲区为 64 KiB，写入更多的数据时需要另一侧同时读取。
+#[cfg(test)]
+pub(crate) fn pair() -> (tokio::io::DuplexStream, Connection<tokio::io::DuplexStream>) {
+    let (client, server) = tokio::io::duplex(64 * 1024);
+    (client, Connection::with_stream(server))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    // 空的 bulk 字符串经过写入和读取之后保持不变
+    #[tokio::test]
+    async fn empty_bulk_round_trip() {
+        let (client, mut server) = pair();
+        let mut client = Connection::with_stream(client);
+
+        let frames = [
+            Frame::Bulk(Bytes::new()),
+            Frame::Array(vec![
+                Frame::Bulk(Bytes::from("GET")),
+                Frame::Bulk(Bytes::new()),
+            ]),
+        ];
+        for frame in frames {
+            client.write_frame(&frame).await.unwrap();
+            assert_eq!(Some(frame), server.read_frame().await.unwrap());
+        }
+    }
+
+    // 空的 bulk 字符串分成多次到达时，等到结尾的 \r\n 到达之后才返回帧
+    #[tokio::test]
+    async fn empty_bulk_split_across_reads() {
+        let (mut client, mut server) = pair();
+
+        client
+            .write_all(b"*2\r\n$3\r\nGET\r\n$0\r\n")
+            .await
+            .unwrap();
+        let read = tokio::spawn(async move { server.read_frame().await.unwrap() });
+        client.write_all(b"\r").await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let expected = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("GET")),
+            Frame::Bulk(Bytes::new()),
+        ]);
+        assert_eq!(Some(expected), read.await.unwrap());
     }
-}
\ No newline at end of file
+
+    // 在内存管道上读写帧，不需要监听 TCP 端口
+    #[tokio::test]
+    async fn duplex_round_trip() {
+        let (client, server) = tokio::io::duplex(64);
+        let mut client = Connection::with_stream(client);
+        let mut server = Connection::with_stream(server);
+
+        let frame = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("SET")),
+            Frame::Bulk(Bytes::from("hello")),
+            Frame::Bulk(Bytes::from("world")),
+        ]);
+        client.write_frame(&frame).await.unwrap();
+        assert_eq!(Some(frame), server.read_frame().await.unwrap());
+
+        server
+            .write_frame(&Frame::Simple("OK".into()))
+            .await
+            .unwrap();
+        assert_eq!(
+            Some(Frame::Simple("OK".into())),
+            client.read_frame().await.unwrap()
+        );
+
+        // 一端关闭之后另一端读到 `None`
+        drop(server);
+        assert_eq!(None, client.read_frame().await.unwrap());
+    }
+
+    // RESP3 连接以 `,` 类型写入浮点数，读取之后保持不变
+    // 3.14 只是一个普通的小数，不是 PI 的近似值
+    #[allow(clippy::approx_constant)]
+    #[tokio::test]
+    async fn double_round_trip() {
+        let (client, mut server) = pair();
+        let mut client = Connection::with_stream(client);
+        client.set_protocol(RESP3);
+
+        for value in [3.14, f64::INFINITY, f64::NEG_INFINITY] {
+            let frame = Frame::Double(value);
+            client.write_frame(&frame).await.unwrap();
+            assert_eq!(Some(frame), server.read_frame().await.unwrap());
+        }
+    }
+
+    // RESP2 连接把浮点数写为 Bulk 字符串
+    #[allow(clippy::approx_constant)]
+    #[tokio::test]
+    async fn double_as_bulk_in_resp2() {
+        let (client, mut server) = pair();
+        let mut client = Connection::with_stream(client);
+
+        let doubles = [
+            (3.14, "3.14"),
+            (f64::INFINITY, "inf"),
+            (f64::NEG_INFINITY, "-inf"),
+        ];
+        for (value, expected) in doubles {
+            client.write_frame(&Frame::Double(value)).await.unwrap();
+            let frame = server.read_frame().await.unwrap();
+            assert_eq!(Some(Frame::Bulk(Bytes::from(expected))), frame);
+        }
+    }
+}
@@ -4,24 +4,30 @@
 //! Redis serialization protocol (RESP) specification:
 //!  https://redis.io/docs/reference/protocol-spec/
 
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::io::Cursor;
 use std::str;
 
 use bytes::{Buf, Bytes};
 
-use crate::error::MiniRedisParseError;
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError};
 
 // 定义 RESP 协议中的不同帧类型
-#[derive(Clone, Debug)]
+// 实现 PartialEq 以便直接比较整个帧，例如在测试中断言嵌套的数组
+#[derive(Clone, Debug, PartialEq)]
 pub enum Frame {
     Simple(String),
     Error(String),
-    Integer(u64),
+    Integer(i64),
     Bulk(Bytes),
     Null,
     Array(Vec<Frame>),
+    // RESP3 类型，只有协商了 RESP3 的连接才会以这些类型发送，RESP2 连接会退化为数组和整数
+    Map(Vec<(Frame, Frame)>),
+    Boolean(bool),
+    // RESP2 连接会退化为 Bulk 字符串
+    Double(f64),
 }
 
 // 允许 Frame 和 &str 类型比较，主要用于测试和某些特定逻辑判断
@@ -53,7 +59,7 @@ impl fmt::Display for Frame {
             // 对于 Error 类型，输出格式为 "error: " 后跟错误消息
             Frame::Error(msg) => write!(fmt, "error: {}", msg),
 
-            // 对于 Integer 类型，直接调用 u64 的 fmt 方法来输出
+            // 对于 Integer 类型，直接调用 i64 的 fmt 方法来输出
             Frame::Integer(num) => num.fmt(fmt),
 
             // 对于 Bulk 类型，尝试将 Bytes 解码为 UTF-8 字符串并输出
@@ -76,58 +82,112 @@ impl fmt::Display for Frame {
                 }
                 Ok(()) // 当所有元素都处理完毕后，返回 Ok
             }
+
+            // 对于 Map 类型，依次输出每个键和值，以空格分隔
+            Frame::Map(entries) => {
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(fmt, " ")?;
+                    }
+                    write!(fmt, "{} {}", key, value)?;
+                }
+                Ok(())
+            }
+
+            // 对于 Boolean 类型，输出 true 或 false
+            Frame::Boolean(value) => value.fmt(fmt),
+
+            // 对于 Double 类型，按 RESP3 的写法输出，例如 3.14、inf 和 nan
+            Frame::Double(value) => format_double(*value).fmt(fmt),
         }
     }
 }
 
 impl Frame {
-    // 创建一个空的 Array 类型的 Frame
-    pub(crate) fn array() -> Frame {
-        Frame::Array(vec![])
+    // 创建一个 Error 类型的 Frame，格式为 "<错误码> <错误信息>"
+    pub(crate) fn error(kind: ErrorKind, msg: impl fmt::Display) -> Frame {
+        Frame::Error(format!("{} {}", kind, msg))
     }
 
-    // 向 Array 类型的 Frame 添加 Bulk 类型的数据
-    pub(crate) fn push_bulk(&mut self, bytes: Bytes) -> Result<(), MiniRedisParseError> {
+    // 构造命令请求帧，命令名称和每个参数都编码为 Bulk 字符串
+    // 参数类型不一致或者需要整数参数时使用 FrameBuilder
+    pub(crate) fn cmd<I>(name: &'static str, args: I) -> Frame
+    where
+        I: IntoIterator,
+        I::Item: Into<Bytes>,
+    {
+        FrameBuilder::new().bulk(name).bulks(args).build()
+    }
+
+    // 取出 Simple 或 Bulk 类型的 Frame 中的数据，其他类型返回 CommandExecute 错误
+    pub(crate) fn into_bytes(self) -> Result<Bytes, MiniRedisConnectionError> {
         match self {
-            Frame::Array(vec) => {
-                // 向数组中添加一个新的 Bulk 类型帧
-                vec.push(Frame::Bulk(bytes));
-                Ok(())
-            }
-            // 当前帧不是 Array 类型时返回错误
-            _ => Err(MiniRedisParseError::ParseArrayFrame),
+            Frame::Simple(s) => Ok(Bytes::from(s)),
+            Frame::Bulk(data) => Ok(data),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
         }
     }
 
-    // 向 Array 类型的 Frame 添加 Integer 类型的数据
-    pub(crate) fn push_int(&mut self, value: u64) -> Result<(), MiniRedisParseError> {
+    // 与 into_bytes 相同，但要求数据是合法的 UTF-8 字符串
+    pub(crate) fn into_string(self) -> Result<String, MiniRedisConnectionError> {
+        let data = self.into_bytes()?;
+        Ok(String::from_utf8(data.to_vec()).map_err(MiniRedisParseError::from)?)
+    }
+
+    // 取出 Integer 类型的 Frame 中的整数，其他类型返回 CommandExecute 错误
+    pub(crate) fn into_integer(self) -> Result<i64, MiniRedisConnectionError> {
         match self {
-            Frame::Array(vec) => {
-                // 向数组中添加一个新的 Integer 类型帧
-                vec.push(Frame::Integer(value));
-                Ok(())
-            }
-            // 当前帧不是 Array 类型时返回错误
-            _ => Err(MiniRedisParseError::ParseArrayFrame),
+            Frame::Integer(value) => Ok(value),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    // 取出 Array 类型的 Frame 中的元素，其他类型返回 CommandExecute 错误
+    pub(crate) fn into_array(self) -> Result<Vec<Frame>, MiniRedisConnectionError> {
+        match self {
+            Frame::Array(frames) => Ok(frames),
+            frame => Err(MiniRedisConnectionError::CommandExecute(frame.to_string())),
+        }
+    }
+
+    // 帧类型的名称，用于访问日志等记录回复的种类
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Frame::Simple(_) => "simple",
+            Frame::Error(_) => "error",
+            Frame::Integer(_) => "integer",
+            Frame::Bulk(_) => "bulk",
+            Frame::Null => "null",
+            Frame::Array(_) => "array",
+            Frame::Map(_) => "map",
+            Frame::Boolean(_) => "boolean",
+            Frame::Double(_) => "double",
         }
     }
 
     // 检查 src 中的数据是否可以解析为合法的 Frame
-    pub fn check(src: &mut Cursor<&[u8]>) -> Result<(), MiniRedisParseError> {
+    // 如果某一行数据超过 max_inline_len 字节仍然没有 \r\n 结尾，返回协议错误
+    // 如果 Bulk 字符串、数组或者 Map 声明的长度超过 max_bulk_len，返回 FrameTooLarge，
+    // 不再等待这么多数据到达。parse 只在 check 通过后调用，因此不需要重复检查
+    pub fn check(
+        src: &mut Cursor<&[u8]>,
+        max_inline_len: usize,
+        max_bulk_len: usize,
+    ) -> Result<(), MiniRedisParseError> {
         match get_u8(src)? {
             // '+' 开头表示简单字符串 Simple Strings
             b'+' => {
-                get_line(src)?;
+                get_bounded(src, max_inline_len, get_line)?;
                 Ok(())
             }
             // '-' 开头表示错误信息 Errors
             b'-' => {
-                get_line(src)?;
+                get_bounded(src, max_inline_len, get_line)?;
                 Ok(())
             }
             // ':' 开头表示整数 Integers
             b':' => {
-                let _ = get_decimal(src)?;
+                let _ = get_bounded(src, max_inline_len, get_integer)?;
                 Ok(())
             }
             // '$' 开头表示Bulk String
@@ -135,33 +195,67 @@ impl Frame {
             // 此类型数据可以包含任意字符，包括换行符和空字节（null 字符）。
             // Bulk String 是 Redis 协议的一部分，主要用于表示二进制数据或较长的字符串。
             b'$' => {
-                // 处理 Null Bulk String
+                // 处理 Null Bulk String，与 parse 一样读取整行并校验，
+                // 保证两者消耗的字节数一致
                 if b'-' == peek_u8(src)? {
-                    skip(src, 4)?;
+                    let line = get_bounded(src, max_inline_len, get_line)?;
+                    if line != b"-1" {
+                        return Err(MiniRedisParseError::Parse(
+                            "protocol error; invalid frame format".into(),
+                        ));
+                    }
                 } else {
-                    let len: usize = get_decimal(src)?.try_into()?; // 读取并解析数据长度
+                    // 读取并解析数据长度
+                    let len = get_length(src, max_inline_len, max_bulk_len)?;
                     skip(src, len + 2)?; // 跳过数据和结尾的 \r\n
                 }
                 Ok(())
             }
             // '*' 开头表示数组 Arrays
             b'*' => {
-                let len = get_decimal(src)?; // 读取数组长度
+                let len = get_length(src, max_inline_len, max_bulk_len)?; // 读取数组长度
                 for _ in 0..len {
-                    Frame::check(src)?; // 递归检查每个元素
+                    check_element_type(src)?;
+                    Frame::check(src, max_inline_len, max_bulk_len)?; // 递归检查每个元素
                 }
                 Ok(())
             }
-            // 其他情况为非法类型
-            actual => Err(MiniRedisParseError::Parse(format!(
-                "protocol error; invalid frame type byte `{}`",
-                actual
-            ))),
+            // '%' 开头表示 RESP3 Map，每个条目由一个键和一个值两个帧组成
+            b'%' => {
+                let len = get_length(src, max_inline_len, max_bulk_len)?;
+                for _ in 0..len * 2 {
+                    check_element_type(src)?;
+                    Frame::check(src, max_inline_len, max_bulk_len)?;
+                }
+                Ok(())
+            }
+            // '#' 开头表示 RESP3 Boolean
+            b'#' => {
+                let _ = get_bounded(src, max_inline_len, get_boolean)?;
+                Ok(())
+            }
+            // ',' 开头表示 RESP3 Double
+            b',' => {
+                let _ = get_bounded(src, max_inline_len, get_double)?;
+                Ok(())
+            }
+            // 其他字节开头表示内联命令，例如通过 telnet 发送的 `PING\r\n`
+            _ => {
+                // 类型字节也是命令的一部分，回退一个字节后读取整行
+                src.set_position(src.position() - 1);
+                get_bounded(src, max_inline_len, get_line)?;
+                Ok(())
+            }
         }
     }
 
+    // 判断以 first 开头的数据是否是内联命令，即不以 RESP 类型字节开头
+    pub(crate) fn is_inline(first: u8) -> bool {
+        !matches!(first, b'+' | b'-' | b':' | b'$' | b'*' | b'%' | b'#' | b',')
+    }
+
     // 解析 src 中的数据为 Frame
-    // 解析 src 中的数据为 Frame
+    // 内联命令按空白分割为参数，解析为由 Bulk 组成的 Array，空行解析为空的 Array
     pub fn parse(src: &mut Cursor<&[u8]>) -> Result<Frame, MiniRedisParseError> {
         // 从字节流中读取一个字节，并根据该字节确定 RESP 类型
         match get_u8(src)? {
@@ -185,8 +279,8 @@ impl Frame {
             }
             // ':' 表示 RESP 整数
             b':' => {
-                // 解析整行数据为整数
-                let num = get_decimal(src)?;
+                // 解析整行数据为有符号整数
+                let num = get_integer(src)?;
                 // 返回 Frame::Integer 类型
                 Ok(Frame::Integer(num))
             }
@@ -226,17 +320,109 @@ impl Frame {
                 let mut out = Vec::with_capacity(len);
                 // 递归解析每个数组元素
                 for _ in 0..len {
+                    check_element_type(src)?;
                     out.push(Frame::parse(src)?);
                 }
                 // 返回 Frame::Array 类型
                 Ok(Frame::Array(out))
             }
-            // 其他情况为非法类型
-            _ => Err(MiniRedisParseError::Unimplemented),
+            // '%' 表示 RESP3 Map
+            b'%' => {
+                let len = get_decimal(src)?.try_into()?;
+                let mut out = Vec::with_capacity(len);
+                for _ in 0..len {
+                    check_element_type(src)?;
+                    let key = Frame::parse(src)?;
+                    check_element_type(src)?;
+                    let value = Frame::parse(src)?;
+                    out.push((key, value));
+                }
+                Ok(Frame::Map(out))
+            }
+            // '#' 表示 RESP3 Boolean
+            b'#' => Ok(Frame::Boolean(get_boolean(src)?)),
+            // ',' 表示 RESP3 Double
+            b',' => Ok(Frame::Double(get_double(src)?)),
+            // 其他情况为内联命令
+            _ => {
+                // 回退类型字节，读取整行
+                src.set_position(src.position() - 1);
+                let line = get_line(src)?;
+                // 按空白分割为参数
+                let parts = line
+                    .split(|b| b.is_ascii_whitespace())
+                    .filter(|part| !part.is_empty())
+                    .map(|part| Frame::Bulk(Bytes::copy_from_slice(part)))
+                    .collect();
+                Ok(Frame::Array(parts))
+            }
         }
     }
 }
 
+/// 逐个追加元素来构造 Array 类型的 Frame，每个方法都返回构造器本身，可以链式调用。
+///
+/// 构造器只生成数组，追加元素不会失败。
+#[derive(Debug, Default)]
+pub(crate) struct FrameBuilder {
+    parts: Vec<Frame>,
+}
+
+impl FrameBuilder {
+    // 创建一个空的构造器
+    pub(crate) fn new() -> FrameBuilder {
+        FrameBuilder::default()
+    }
+
+    // 追加一个 Bulk 字符串，String 和 &'static str 转换为 Bytes 时不会复制数据
+    pub(crate) fn bulk(mut self, data: impl Into<Bytes>) -> FrameBuilder {
+        self.parts.push(Frame::Bulk(data.into()));
+        self
+    }
+
+    // 依次追加多个 Bulk 字符串
+    pub(crate) fn bulks<I>(mut self, data: I) -> FrameBuilder
+    where
+        I: IntoIterator,
+        I::Item: Into<Bytes>,
+    {
+        self.parts
+            .extend(data.into_iter().map(|data| Frame::Bulk(data.into())));
+        self
+    }
+
+    // 追加一个整数
+    pub(crate) fn int(mut self, value: i64) -> FrameBuilder {
+        self.parts.push(Frame::Integer(value));
+        self
+    }
+
+    // 追加一个任意的帧，例如嵌套的数组
+    pub(crate) fn frame(mut self, frame: Frame) -> FrameBuilder {
+        self.parts.push(frame);
+        self
+    }
+
+    // 追加一个简单字符串，只有测试使用
+    #[cfg(test)]
+    pub(crate) fn simple(mut self, value: &str) -> FrameBuilder {
+        self.parts.push(Frame::Simple(value.to_string()));
+        self
+    }
+
+    // 追加一个 Null，只有测试使用
+    #[cfg(test)]
+    pub(crate) fn null(mut self) -> FrameBuilder {
+        self.parts.push(Frame::Null);
+        self
+    }
+
+    // 返回构造好的 Array 类型的 Frame
+    pub(crate) fn build(self) -> Frame {
+        Frame::Array(self.parts)
+    }
+}
+
 // 跳过 n 个字节
 fn skip(src: &mut Cursor<&[u8]>, n: usize) -> Result<(), MiniRedisParseError> {
     // 检查是否有足够的字节可供跳过
@@ -250,6 +436,18 @@ fn skip(src: &mut Cursor<&[u8]>, n: usize) -> Result<(), MiniRedisParseError> {
     Ok(())
 }
 
+// 数组和 Map 的元素必须以 RESP 类型字节开头，只有顶层的帧可以是内联命令
+fn check_element_type(src: &mut Cursor<&[u8]>) -> Result<(), MiniRedisParseError> {
+    let first = peek_u8(src)?;
+    if Frame::is_inline(first) {
+        return Err(MiniRedisParseError::Parse(format!(
+            "protocol error; expected '$', got '{}'",
+            first.escape_ascii()
+        )));
+    }
+    Ok(())
+}
+
 // 查看但不消耗当前字节
 fn peek_u8(src: &mut Cursor<&[u8]>) -> Result<u8, MiniRedisParseError> {
     // 检查缓冲区是否还有数据
@@ -284,10 +482,86 @@ fn get_decimal(src: &mut Cursor<&[u8]>) -> Result<u64, MiniRedisParseError> {
     })
 }
 
+// 读取 Bulk 字符串、数组或者 Map 的长度，超过 max_len 时返回 FrameTooLarge
+fn get_length(
+    src: &mut Cursor<&[u8]>,
+    max_inline_len: usize,
+    max_len: usize,
+) -> Result<usize, MiniRedisParseError> {
+    let len = get_bounded(src, max_inline_len, get_decimal)?;
+    match usize::try_from(len) {
+        Ok(len) if len <= max_len => Ok(len),
+        _ => Err(MiniRedisParseError::FrameTooLarge),
+    }
+}
+
+// 解析有符号整数值，用于 RESP 整数类型
+fn get_integer(src: &mut Cursor<&[u8]>) -> Result<i64, MiniRedisParseError> {
+    use atoi::atoi;
+    let line = get_line(src)?;
+    atoi::<i64>(line).ok_or_else(|| {
+        MiniRedisParseError::Parse("protocol error; invalid frame format to get integer".into())
+    })
+}
+
+// 解析 RESP3 布尔值，`t` 表示 true，`f` 表示 false
+fn get_boolean(src: &mut Cursor<&[u8]>) -> Result<bool, MiniRedisParseError> {
+    match get_line(src)? {
+        b"t" => Ok(true),
+        b"f" => Ok(false),
+        _ => Err(MiniRedisParseError::Parse(
+            "protocol error; invalid frame format to get boolean".into(),
+        )),
+    }
+}
+
+// 解析 RESP3 浮点数，除了普通的小数和指数形式，还可以是 `inf`、`-inf` 和 `nan`
+fn get_double(src: &mut Cursor<&[u8]>) -> Result<f64, MiniRedisParseError> {
+    let line = get_line(src)?;
+    str::from_utf8(line)
+        .ok()
+        .and_then(|line| line.parse().ok())
+        .ok_or_else(|| {
+            MiniRedisParseError::Parse("protocol error; invalid frame format to get double".into())
+        })
+}
+
+// 将浮点数格式化为 RESP3 的写法，无穷大和 NaN 分别写为 `inf`、`-inf` 和 `nan`，
+// RESP2 连接收到的 Bulk 字符串使用同样的写法
+pub(crate) fn format_double(value: f64) -> String {
+    if value.is_nan() {
+        "nan".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+// 使用 read 读取一行数据，如果剩余数据超过 max_len 字节仍然没有读到完整的一行，
+// 返回协议错误而不是继续等待更多数据，避免无限制地缓存一行数据
+fn get_bounded<'a, T>(
+    src: &mut Cursor<&'a [u8]>,
+    max_len: usize,
+    read: fn(&mut Cursor<&'a [u8]>) -> Result<T, MiniRedisParseError>,
+) -> Result<T, MiniRedisParseError> {
+    match read(src) {
+        // 读取失败时光标位置不变，剩余的数据就是这一行已经收到的部分
+        Err(MiniRedisParseError::Incomplete) if src.remaining() > max_len => Err(
+            MiniRedisParseError::Parse("protocol error; too big inline request".into()),
+        ),
+        res => res,
+    }
+}
+
 // 获取一行数据，以 \r\n 结尾
 fn get_line<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], MiniRedisParseError> {
     // 记录当前光标位置
     let start = src.position() as usize;
+    // 剩余的数据放不下 \r\n 时不可能是完整的一行，同时避免下面计算 end 时下溢
+    if src.get_ref().len() < start + 2 {
+        return Err(MiniRedisParseError::Incomplete);
+    }
     // 获取缓冲区的长度减1（预留检查 \r\n 的位置）
     let end = src.get_ref().len() - 1;
     // 遍历缓冲区寻找 \r\n
@@ -302,3 +576,250 @@ fn get_line<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], MiniRedisParseEr
     // 如果没有找到 \r\n，返回错误
     Err(MiniRedisParseError::Incomplete)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::cmd::set::Set;
+    use crate::consts::{MAX_INLINE_LEN, PROTO_MAX_BULK_LEN};
+
+    // 完整的帧，包括边界情况：空 bulk、包含 \r\n 的 bulk、负数、嵌套数组和内联命令
+    const FRAMES: &[&[u8]] = &[
+        b"+OK\r\n",
+        b"-ERR oops\r\n",
+        b":0\r\n",
+        b":-42\r\n",
+        b"$0\r\n\r\n",
+        b"$5\r\nhe\r\no\r\n",
+        b"$-1\r\n",
+        b"*0\r\n",
+        b"*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\n",
+        b"*3\r\n*1\r\n:1\r\n$-1\r\n+x\r\n",
+        b"%0\r\n",
+        b"%2\r\n+a\r\n:1\r\n$1\r\nb\r\n*1\r\n#t\r\n",
+        b"#f\r\n",
+        b",3.14\r\n",
+        b",-1.5e10\r\n",
+        b",inf\r\n",
+        b",nan\r\n",
+        b"GET foo\r\n",
+        b"\r\n",
+    ];
+
+    // 格式错误的帧，check 和 parse 都应该拒绝
+    const INVALID: &[&[u8]] = &[
+        b"$-10\r\n+OK\r\n",
+        b"$-2\r\n",
+        b":abc\r\n",
+        b"*-1\r\n",
+        b"#x\r\n",
+        b",abc\r\n",
+        b"%-1\r\n",
+        b"*1\r\nGET\r\n",
+        b"%1\r\n+a\r\n!\r\n",
+    ];
+
+    fn check(src: &[u8]) -> Result<u64, MiniRedisParseError> {
+        let mut cursor = Cursor::new(src);
+        Frame::check(&mut cursor, MAX_INLINE_LEN, PROTO_MAX_BULK_LEN).map(|_| cursor.position())
+    }
+
+    fn parse(src: &[u8]) -> Result<u64, MiniRedisParseError> {
+        let mut cursor = Cursor::new(src);
+        Frame::parse(&mut cursor).map(|_| cursor.position())
+    }
+
+    #[test]
+    fn check_and_parse_agree_on_complete_frames() {
+        for frame in FRAMES {
+            // 后面紧跟下一帧时，两者都只消耗当前帧
+            let mut src = frame.to_vec();
+            src.extend_from_slice(b"+next\r\n");
+
+            let checked = check(&src).unwrap();
+            let parsed = parse(&src).unwrap();
+            assert_eq!(frame.len() as u64, checked, "{:?}", frame);
+            assert_eq!(checked, parsed, "{:?}", frame);
+        }
+    }
+
+    #[test]
+    fn check_reports_incomplete_for_every_prefix() {
+        for frame in FRAMES {
+            for end in 0..frame.len() {
+                assert!(
+                    matches!(check(&frame[..end]), Err(MiniRedisParseError::Incomplete)),
+                    "{:?}",
+                    &frame[..end]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn short_buffers_are_incomplete() {
+        for src in [&b""[..], b"+", b"G", b"\r"] {
+            assert!(
+                matches!(check(src), Err(MiniRedisParseError::Incomplete)),
+                "{:?}",
+                src
+            );
+            assert!(
+                matches!(
+                    get_line(&mut Cursor::new(src)),
+                    Err(MiniRedisParseError::Incomplete)
+                ),
+                "{:?}",
+                src
+            );
+        }
+    }
+
+    #[test]
+    fn parse_nested_frames() {
+        let src = b"*4\r\n$3\r\nfoo\r\n$-1\r\n:-1\r\n%1\r\n+a\r\n*1\r\n#t\r\n";
+        let frame = Frame::parse(&mut Cursor::new(&src[..])).unwrap();
+
+        let expected = Frame::Array(vec![
+            Frame::Bulk(Bytes::from("foo")),
+            Frame::Null,
+            Frame::Integer(-1),
+            Frame::Map(vec![(
+                Frame::Simple("a".into()),
+                Frame::Array(vec![Frame::Boolean(true)]),
+            )]),
+        ]);
+        assert_eq!(expected, frame);
+        assert_ne!(Frame::Bulk(Bytes::from("foo")), Frame::Simple("foo".into()));
+    }
+
+    #[allow(clippy::approx_constant)]
+    #[test]
+    fn display_doubles() {
+        assert_eq!("3.14", Frame::Double(3.14).to_string());
+        assert_eq!("-2", Frame::Double(-2.0).to_string());
+        assert_eq!("inf", Frame::Double(f64::INFINITY).to_string());
+        assert_eq!("-inf", Frame::Double(f64::NEG_INFINITY).to_string());
+        assert_eq!("nan", Frame::Double(f64::NAN).to_string());
+    }
+
+    #[test]
+    fn typed_accessors() {
+        assert_eq!(
+            Bytes::from("OK"),
+            Frame::Simple("OK".into()).into_bytes().unwrap()
+        );
+        assert_eq!(
+            Bytes::from("foo"),
+            Frame::Bulk("foo".into()).into_bytes().unwrap()
+        );
+        assert_eq!("foo", Frame::Bulk("foo".into()).into_string().unwrap());
+        assert_eq!(-1, Frame::Integer(-1).into_integer().unwrap());
+        assert_eq!(
+            vec![Frame::Null],
+            Frame::Array(vec![Frame::Null]).into_array().unwrap()
+        );
+    }
+
+    #[test]
+    fn typed_accessors_reject_other_variants() {
+        let mismatch = |res: Result<_, MiniRedisConnectionError>| {
+            matches!(res, Err(MiniRedisConnectionError::CommandExecute(_)))
+        };
+        assert!(mismatch(Frame::Null.into_bytes().map(drop)));
+        assert!(mismatch(Frame::Integer(1).into_string().map(drop)));
+        assert!(mismatch(Frame::Bulk("1".into()).into_integer().map(drop)));
+        assert!(mismatch(Frame::Map(vec![]).into_array().map(drop)));
+
+        // 不是合法 UTF-8 的数据不能转换为字符串
+        let invalid = Frame::Bulk(Bytes::from_static(b"\xff")).into_string();
+        assert!(matches!(
+            invalid,
+            Err(MiniRedisConnectionError::ParseFrame(_))
+        ));
+    }
+
+    #[test]
+    fn check_and_parse_reject_invalid_frames() {
+        for frame in INVALID {
+            assert!(
+                !matches!(check(frame), Ok(_) | Err(MiniRedisParseError::Incomplete)),
+                "{:?}",
+                frame
+            );
+            assert!(parse(frame).is_err(), "{:?}", frame);
+        }
+    }
+
+    #[test]
+    fn check_rejects_lengths_over_limit() {
+        // 只收到了长度，数据还没有到达，也不应该等待
+        for frame in [
+            &b"$6\r\n"[..],
+            b"*6\r\n",
+            b"%6\r\n",
+            b"$18446744073709551615\r\n",
+        ] {
+            let mut cursor = Cursor::new(frame);
+            assert!(
+                matches!(
+                    Frame::check(&mut cursor, MAX_INLINE_LEN, 5),
+                    Err(MiniRedisParseError::FrameTooLarge)
+                ),
+                "{:?}",
+                frame
+            );
+        }
+        // 等于上限的长度仍然接受
+        let mut cursor = Cursor::new(&b"*1\r\n$5\r\nhello\r\n"[..]);
+        assert!(Frame::check(&mut cursor, MAX_INLINE_LEN, 5).is_ok());
+    }
+
+    #[test]
+    fn builder_matches_hand_rolled_frame() {
+        let expected = Frame::Array(vec![
+            Frame::Bulk(Bytes::from_static(b"set")),
+            Frame::Bulk(Bytes::from_static(b"hello")),
+            Frame::Bulk(Bytes::from_static(b"world")),
+            Frame::Bulk(Bytes::from_static(b"px")),
+            Frame::Integer(100),
+        ]);
+        let built = FrameBuilder::new()
+            .bulk("set")
+            .bulk(String::from("hello"))
+            .bulk(Bytes::from("world"))
+            .bulk("px")
+            .int(100)
+            .build();
+        assert_eq!(expected, built);
+
+        // 与 `SET` 命令自己编码出的帧相同
+        let set = Set::new(
+            "hello",
+            Bytes::from("world"),
+            Some(Duration::from_millis(100)),
+        );
+        assert_eq!(expected, set.into_frame());
+        let set = Set::new("hello", Bytes::from("world"), None);
+        assert_eq!(Frame::cmd("set", ["hello", "world"]), set.into_frame());
+
+        let mixed = FrameBuilder::new()
+            .simple("OK")
+            .null()
+            .bulks(vec!["a", "b"])
+            .build();
+        assert_eq!(
+            Frame::Array(vec![
+                Frame::Simple("OK".into()),
+                Frame::Null,
+                Frame::Bulk(Bytes::from_static(b"a")),
+                Frame::Bulk(Bytes::from_static(b"b")),
+            ]),
+            mixed
+        );
+        assert_eq!(Frame::Array(vec![]), FrameBuilder::new().build());
+    }
+}
@@ -1,3 +1,4 @@
 pub mod connect;
 pub mod frame;
-pub mod parse;
\ No newline at end of file
+pub mod parse;
+pub(crate) mod stream;
@@ -1,3 +1,4 @@
+use std::convert::TryFrom;
 use std::vec;
 
 use bytes::Bytes;
@@ -89,19 +90,17 @@ impl Parse {
 
     /// 返回下一个条目作为整数。
     /// 包括 `Simple`、`Bulk` 和 `Integer` 类型的帧，`Simple` 和 `Bulk` 类型需要解析。
-    /// 如果下一个条目不能表示为整数，则返回错误。
+    /// 如果下一个条目不能表示为整数，则返回 `value is not an integer` 错误，作为错误帧回复给客户端。
     pub(crate) fn next_int(&mut self) -> Result<u64, MiniRedisParseError> {
         use atoi::atoi; // 使用 atoi 库来转换字符串为整数
 
         match self.next()? {
-            // 如果下一个帧本身就是整数类型，直接返回这个整数
-            Frame::Integer(v) => Ok(v),
+            // 如果下一个帧本身就是整数类型，负数不是合法的无符号整数
+            Frame::Integer(v) => u64::try_from(v).map_err(|_| not_an_integer()),
 
             // 如果下一个帧是简单字符串或批量字符串类型，尝试解析为整数
-            Frame::Simple(data) => atoi::<u64>(data.as_bytes())
-                .ok_or_else(|| MiniRedisParseError::Parse("protocol error; invalid number".into())),
-            Frame::Bulk(data) => atoi::<u64>(&data)
-                .ok_or_else(|| MiniRedisParseError::Parse("protocol error; invalid number".into())),
+            Frame::Simple(data) => atoi::<u64>(data.as_bytes()).ok_or_else(not_an_integer),
+            Frame::Bulk(data) => atoi::<u64>(&data).ok_or_else(not_an_integer),
 
             // 如果下一个帧不是期望的整数、简单字符串或批量字符串类型，返回错误
             frame => Err(MiniRedisParseError::Parse(format!(
@@ -111,6 +110,53 @@ impl Parse {
         }
     }
 
+    /// 返回下一个条目作为有符号整数，例如 `LRANGE` 中可以为负数的下标。
+    /// 与 `next_int` 一样接受 `Simple`、`Bulk` 和 `Integer` 类型的帧。
+    pub(crate) fn next_signed_int(&mut self) -> Result<i64, MiniRedisParseError> {
+        use atoi::atoi;
+
+        match self.next()? {
+            Frame::Integer(v) => Ok(v),
+            Frame::Simple(data) => atoi::<i64>(data.as_bytes()).ok_or_else(not_an_integer),
+            Frame::Bulk(data) => atoi::<i64>(&data).ok_or_else(not_an_integer),
+            frame => Err(MiniRedisParseError::Parse(format!(
+                "protocol error; expected int frame but got {:?}",
+                frame
+            ))),
+        }
+    }
+
+    /// 返回下一个条目作为浮点数，例如 `INCRBYFLOAT` 的增量。
+    /// 接受 `Simple`、`Bulk`、`Integer` 和 `Double` 类型的帧，字符串可以是 `inf` 和 `-inf`。
+    /// 与 Redis 一样，NaN 不是合法的浮点数。
+    pub(crate) fn next_double(&mut self) -> Result<f64, MiniRedisParseError> {
+        let value = match self.next()? {
+            Frame::Double(v) => v,
+            Frame::Integer(v) => v as f64,
+            Frame::Simple(data) => data.parse().map_err(|_| not_a_float())?,
+            Frame::Bulk(data) => std::str::from_utf8(&data)
+                .ok()
+                .and_then(|data| data.parse().ok())
+                .ok_or_else(not_a_float)?,
+            frame => {
+                return Err(MiniRedisParseError::Parse(format!(
+                    "protocol error; expected double frame but got {:?}",
+                    frame
+                )))
+            }
+        };
+
+        if value.is_nan() {
+            return Err(not_a_float());
+        }
+        Ok(value)
+    }
+
+    /// 返回数组中还没有读取的条目数量
+    pub(crate) fn remaining(&self) -> usize {
+        self.parts.len()
+    }
+
     /// 确保数组中没有更多条目
     pub(crate) fn finish(&mut self) -> Result<(), MiniRedisParseError> {
         // 尝试从迭代器中获取下一个帧
@@ -125,3 +171,13 @@ impl Parse {
         }
     }
 }
+
+// 参数不是合法的整数时与 Redis 一样回复错误，连接保持打开
+fn not_an_integer() -> MiniRedisParseError {
+    MiniRedisParseError::InvalidCommand("value is not an integer or out of range".into())
+}
+
+// 参数不是合法的浮点数时与 Redis 一样回复错误，连接保持打开
+fn not_a_float() -> MiniRedisParseError {
+    MiniRedisParseError::InvalidCommand("value is not a valid float".into())
+}
@@ -1,10 +1,62 @@
+use std::time::Duration;
+
 /// Default port that a redis server listens on.
 ///
 /// Used if no port is specified.
 pub const DEFAULT_PORT: u16 = 6379;
 
+/// Default address that the redis server binary listens on.
+pub const DEFAULT_BIND: &str = "0.0.0.0";
+
 /// Maximum number of concurrent connections the redis server will accept.
 ///
 /// When this limit is reached, the server will stop accepting connections until
-/// an active connection terminates.
+/// an active connection terminates, unless `ServerConfig::reject_on_full` is
+/// set.
 pub const MAX_CONNECTIONS: usize = 1024;
+
+/// Default number of shards the key space is split into.
+///
+/// Commands on keys in different shards don't contend on the same lock.
+pub const DEFAULT_SHARDS: usize = 16;
+
+/// How long the server waits for in-flight connections to finish after a
+/// shutdown signal before giving up on them.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default interval between runs of the background task that releases memory
+/// left over after many keys have been deleted.
+pub const COMPACT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// RESP2 protocol version, used by every connection until it negotiates
+/// another version with `HELLO`.
+pub const RESP2: u8 = 2;
+
+/// RESP3 protocol version, which adds map and boolean reply types.
+pub const RESP3: u8 = 3;
+
+/// Default maximum length of a string value, in bytes.
+///
+/// Commands that grow a string to a caller-chosen length, like `SETRANGE` with
+/// a large offset, are rejected beyond this instead of allocating it. Matches
+/// Redis' default `proto-max-bulk-len` of 512MB.
+pub const PROTO_MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+
+/// Maximum length of a single protocol line, in bytes.
+///
+/// A line that grows beyond this without a terminating `\r\n` is rejected with
+/// a protocol error instead of being buffered forever. Matches Redis' 64KB
+/// inline request limit.
+pub const MAX_INLINE_LEN: usize = 64 * 1024;
+
+/// Default maximum number of entries kept in the slow log.
+///
+/// Once full, the oldest entry is dropped for each new one. Matches Redis'
+/// default `slowlog-max-len`.
+pub const SLOWLOG_MAX_LEN: usize = 128;
+
+/// Maximum time a client gets to complete the TLS handshake.
+///
+/// A connection that hasn't finished the handshake by then is closed, so a
+/// client that connects and never speaks TLS doesn't hold a connection slot.
+pub const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
@@ -1,6 +1,8 @@
+use std::fmt;
 use std::io;
 
 use thiserror::Error;
+use tokio::time::error::Elapsed;
 
 #[derive(Error, Debug)]
 pub enum MiniRedisServerError {
@@ -12,6 +14,9 @@ pub enum MiniRedisServerError {
 
     #[error(transparent)]
     Parse(#[from] MiniRedisParseError),
+
+    #[error(transparent)]
+    Config(#[from] MiniRedisConfigError),
 }
 
 #[derive(Error, Debug)]
@@ -23,10 +28,72 @@ pub enum MiniRedisClientError {
     Parse(#[from] MiniRedisParseError),
 }
 
+/// Error encountered while loading a `redis.conf`-style config file.
+#[derive(Error, Debug)]
+pub enum MiniRedisConfigError {
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+
+    /// A directive is unknown or has invalid arguments. `line` is 1-based.
+    #[error("config file line {line}: {msg}")]
+    Invalid { line: usize, msg: String },
+
+    /// The TLS certificate or private key can't be loaded.
+    #[error("tls: {0}")]
+    Tls(String),
+}
+
+/// The error code at the start of an error reply, e.g. `ERR` in
+/// `-ERR unknown command 'foo'`.
+///
+/// Clients can match on the code without parsing the rest of the message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Generic error.
+    Err,
+    /// The operation was applied to a key holding the wrong kind of value.
+    WrongType,
+    /// The requested protocol version is not supported.
+    NoProto,
+    /// The server is running but cannot serve requests, e.g. its background
+    /// tasks have shut down.
+    NotReady,
+    /// The command would use more memory while the server is over its
+    /// `maxmemory` limit.
+    Oom,
+    /// `EXEC` discarded the transaction because a command failed while it was
+    /// being queued.
+    ExecAbort,
+    /// `RESTORE` targeted a key that already exists and `REPLACE` was not
+    /// given.
+    BusyKey,
+}
+
+impl ErrorKind {
+    /// Returns the error code as sent on the wire.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Err => "ERR",
+            ErrorKind::WrongType => "WRONGTYPE",
+            ErrorKind::NoProto => "NOPROTO",
+            ErrorKind::NotReady => "NOTREADY",
+            ErrorKind::Oom => "OOM",
+            ErrorKind::ExecAbort => "EXECABORT",
+            ErrorKind::BusyKey => "BUSYKEY",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Error encountered while parsing a frame.
 ///
-/// Only `EndOfStream` errors are handled at runtime. All other errors result in
-/// the connection being terminated.
+/// Only `EndOfStream` and `InvalidCommand` errors are handled at runtime. All
+/// other errors result in the connection being terminated.
 #[derive(Error, Debug)]
 pub enum MiniRedisParseError {
     #[error("invalid message encoding, parse failed")]
@@ -40,11 +107,26 @@ pub enum MiniRedisParseError {
     #[error("not enough data is available to parse a message")]
     Incomplete,
 
+    /// A bulk string or array declared a length larger than
+    /// `proto-max-bulk-len`. The connection is closed without buffering
+    /// the payload.
+    #[error("protocol error; invalid bulk or multibulk length")]
+    FrameTooLarge,
+
     #[error("unimplemented command")]
     Unimplemented,
 
-    #[error("not an array frame")]
-    ParseArrayFrame,
+    /// The command is well-formed at the protocol level but its arguments are
+    /// invalid. The message is sent back to the client as an `ERR` error
+    /// frame and the connection stays open.
+    #[error("{0}")]
+    InvalidCommand(String),
+
+    /// `Frame::check` accepted a frame that `Frame::parse` then failed to
+    /// read the same way. This is a bug in the parser rather than bad input
+    /// from the peer.
+    #[error("internal error; frame check and parse disagree: {0}")]
+    CheckParseMismatch(String),
 
     #[error(transparent)]
     ParseInt(#[from] std::num::TryFromIntError),
@@ -66,9 +148,39 @@ pub enum MiniRedisConnectionError {
     #[error("command execute error")]
     CommandExecute(String),
 
+    /// The server replied with a `WRONGTYPE` error: the command was applied to
+    /// a key holding a different kind of value, e.g. `LPUSH` on a string.
+    #[error("{0}")]
+    WrongType(String),
+
     #[error("received next message failed, invalid frame type")]
     InvalidFrameType,
 
     #[error("invalid argument")]
     InvalidArgument(String),
+
+    /// An operation did not complete within its deadline, e.g. waiting for a
+    /// subscription message or an idle client connection.
+    #[error("operation timed out")]
+    Timeout(#[from] Elapsed),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future;
+
+    use tokio::time::{self, Duration};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn timeout_from_elapsed() {
+        let elapsed = time::timeout(Duration::ZERO, future::pending::<()>())
+            .await
+            .unwrap_err();
+
+        let err = MiniRedisConnectionError::from(elapsed);
+        assert!(matches!(err, MiniRedisConnectionError::Timeout(_)));
+        assert_eq!("operation timed out", err.to_string());
+    }
 }
@@ -5,5 +5,6 @@ pub(crate) mod connection;
 pub mod consts;
 pub mod error;
 pub mod logger;
+pub mod metrics;
 pub mod server;
 mod storage;
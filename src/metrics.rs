@@ -0,0 +1,96 @@
+//! 服务器指标的记录接口。
+//!
+//! 服务器通过 `MetricsRecorder` trait 上报指标，而不是直接依赖某个具体的后端，
+//! 因此可以把指标转发到 StatsD、OpenTelemetry 等外部系统。
+//! 服务器总是用 `InMemoryMetrics` 在进程内保存一份指标供 `INFO` 读取，
+//! 同时把指标转发给 `ServerConfig::metrics` 配置的记录器。
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 指标记录器。
+///
+/// 所有方法都有默认的空实现，实现者只需覆盖关心的指标。
+/// 这些方法会在处理命令的热路径上被调用，实现应当尽量轻量，不要阻塞。
+pub trait MetricsRecorder: Debug + Send + Sync {
+    /// 命令 `name` 被执行了一次。
+    fn incr_command(&self, _name: &str) {}
+
+    /// 命令 `name` 执行完成，耗时 `latency`。
+    fn observe_latency(&self, _name: &str, _latency: Duration) {}
+
+    /// 将仪表 `name` 设置为 `value`，例如当前的键数量。
+    fn set_gauge(&self, _name: &str, _value: u64) {}
+}
+
+/// 不做任何记录的指标记录器。
+#[derive(Debug, Default)]
+pub struct NoopMetrics;
+
+impl MetricsRecorder for NoopMetrics {}
+
+/// 将指标保存在内存中的记录器，服务器用它保存 `INFO` 读取的指标。
+#[derive(Debug, Default)]
+pub struct InMemoryMetrics {
+    inner: Mutex<MetricsState>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    // 每个命令的执行次数
+    commands: HashMap<String, u64>,
+    // 每个命令的累计耗时
+    latencies: HashMap<String, Duration>,
+    // 仪表的当前值
+    gauges: HashMap<String, u64>,
+}
+
+impl InMemoryMetrics {
+    /// 创建一个空的 `InMemoryMetrics`。
+    pub fn new() -> InMemoryMetrics {
+        InMemoryMetrics::default()
+    }
+
+    /// 返回命令 `name` 的执行次数。
+    pub fn command_count(&self, name: &str) -> u64 {
+        let state = self.inner.lock().unwrap();
+        state.commands.get(name).copied().unwrap_or(0)
+    }
+
+    /// 返回所有命令的执行次数之和。
+    pub fn total_commands(&self) -> u64 {
+        let state = self.inner.lock().unwrap();
+        state.commands.values().sum()
+    }
+
+    /// 返回命令 `name` 的累计耗时。
+    pub fn total_latency(&self, name: &str) -> Duration {
+        let state = self.inner.lock().unwrap();
+        state.latencies.get(name).copied().unwrap_or_default()
+    }
+
+    /// 返回仪表 `name` 的当前值，如果从未设置过则返回 `None`。
+    pub fn gauge(&self, name: &str) -> Option<u64> {
+        let state = self.inner.lock().unwrap();
+        state.gauges.get(name).copied()
+    }
+}
+
+impl MetricsRecorder for InMemoryMetrics {
+    fn incr_command(&self, name: &str) {
+        let mut state = self.inner.lock().unwrap();
+        *state.commands.entry(name.to_string()).or_insert(0) += 1;
+    }
+
+    fn observe_latency(&self, name: &str, latency: Duration) {
+        let mut state = self.inner.lock().unwrap();
+        *state.latencies.entry(name.to_string()).or_default() += latency;
+    }
+
+    fn set_gauge(&self, name: &str, value: u64) {
+        let mut state = self.inner.lock().unwrap();
+        state.gauges.insert(name.to_string(), value);
+    }
+}
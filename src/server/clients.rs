@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::metrics::MetricsRecorder;
+
+/// 所有已连接的客户端，`CLIENT LIST` 从这里读取。
+///
+/// `Listener` 持有一份，每个连接在创建 `Handler` 时登记，连接关闭时注销。
+#[derive(Debug)]
+pub(crate) struct Clients {
+    // 按照连接 id 排序，`CLIENT LIST` 按照连接的先后顺序输出
+    clients: Mutex<BTreeMap<u64, ClientInfo>>,
+    // 指标记录器，用于上报当前的连接数
+    metrics: Arc<dyn MetricsRecorder>,
+}
+
+// 一个客户端的信息
+#[derive(Debug)]
+struct ClientInfo {
+    // 客户端的地址
+    addr: String,
+    // 由 `CLIENT SETNAME` 设置的名称
+    name: Option<String>,
+    // 连接的时间
+    connected_at: Instant,
+}
+
+impl Clients {
+    /// 创建一个空的客户端表，连接数的变化上报给 `metrics`。
+    pub(crate) fn new(metrics: Arc<dyn MetricsRecorder>) -> Clients {
+        Clients {
+            clients: Mutex::new(BTreeMap::new()),
+            metrics,
+        }
+    }
+
+    /// 登记一个 id 为 `id`、地址为 `addr` 的客户端，返回的 `ClientHandle` 被 drop 时注销。
+    pub(crate) fn register(self: &Arc<Self>, id: u64, addr: String) -> ClientHandle {
+        let info = ClientInfo {
+            addr,
+            name: None,
+            connected_at: Instant::now(),
+        };
+        let mut clients = self.clients.lock().unwrap();
+        clients.insert(id, info);
+        // 持有锁时上报，上报的顺序与连接数变化的顺序一致
+        self.metrics
+            .set_gauge("connected_clients", clients.len() as u64);
+        drop(clients);
+        ClientHandle {
+            id,
+            clients: self.clone(),
+        }
+    }
+
+    /// 每个客户端一行，格式为 `id=<id> addr=<addr> name=<name> age=<秒>`。
+    pub(crate) fn list(&self) -> String {
+        let clients = self.clients.lock().unwrap();
+        let mut list = String::new();
+        for (id, info) in clients.iter() {
+            let _ = writeln!(
+                list,
+                "id={} addr={} name={} age={}",
+                id,
+                info.addr,
+                info.name.as_deref().unwrap_or(""),
+                info.connected_at.elapsed().as_secs()
+            );
+        }
+        list
+    }
+}
+
+/// 一个连接在 `Clients` 中的登记，drop 时注销。
+#[derive(Debug)]
+pub(crate) struct ClientHandle {
+    id: u64,
+    clients: Arc<Clients>,
+}
+
+impl ClientHandle {
+    /// 连接的 id，在服务器中唯一，按照连接的先后顺序递增。
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 连接的名称，`None` 表示没有名称。
+    pub(crate) fn name(&self) -> Option<String> {
+        let clients = self.clients.clients.lock().unwrap();
+        clients.get(&self.id).and_then(|info| info.name.clone())
+    }
+
+    /// 修改连接的名称，`None` 表示清除名称。
+    pub(crate) fn set_name(&self, name: Option<String>) {
+        let mut clients = self.clients.clients.lock().unwrap();
+        if let Some(info) = clients.get_mut(&self.id) {
+            info.name = name;
+        }
+    }
+
+    /// 所有已连接的客户端。
+    pub(crate) fn clients(&self) -> &Clients {
+        &self.clients
+    }
+}
+
+impl Drop for ClientHandle {
+    fn drop(&mut self) {
+        let mut clients = self.clients.clients.lock().unwrap();
+        clients.remove(&self.id);
+        self.clients
+            .metrics
+            .set_gauge("connected_clients", clients.len() as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::NoopMetrics;
+
+    // 客户端按照 id 的顺序列出，drop 之后注销
+    #[test]
+    fn lists_registered_clients() {
+        let clients = Arc::new(Clients::new(Arc::new(NoopMetrics)));
+        let second = clients.register(2, "127.0.0.1:2".to_string());
+        let first = clients.register(1, "127.0.0.1:1".to_string());
+        first.set_name(Some("worker".to_string()));
+        assert_eq!(Some("worker".to_string()), first.name());
+        assert_eq!(None, second.name());
+        assert_eq!(
+            "id=1 addr=127.0.0.1:1 name=worker age=0\nid=2 addr=127.0.0.1:2 name= age=0\n",
+            clients.list()
+        );
+
+        drop(first);
+        assert_eq!("id=2 addr=127.0.0.1:2 name= age=0\n", clients.list());
+    }
+}
@@ -1,26 +1,47 @@
-use log::debug; // 用于日志记录
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use bytes::Bytes; // 慢查询日志中保存的参数
+use log::{debug, info}; // 用于日志记录
+use tokio::io::{AsyncRead, AsyncWrite}; // 连接底层的字节流
 use tokio::sync::mpsc; // 异步消息传递
+use tokio::time::{self, Instant}; // 用于统计命令耗时和空闲超时
 
 use crate::cmd::Command; // 命令处理模块
+use crate::config::{AccessLogEntry, RateLimitAction, ServerConfig}; // 服务器配置模块
 use crate::connection::connect::Connection; // 连接处理模块
-use crate::error::MiniRedisConnectionError; // 错误处理模块
+use crate::connection::frame::Frame; // 帧处理模块
+use crate::connection::stream::Stream; // 底层的字节流
+use crate::error::{ErrorKind, MiniRedisConnectionError, MiniRedisParseError}; // 错误处理模块
+use crate::metrics::MetricsRecorder; // 指标记录模块
+use crate::server::metrics::Metrics; // 服务器统计模块
+use crate::server::ratelimit::RateLimiter; // 命令速率限制
 use crate::server::shutdown::Shutdown; // 服务器关闭处理模块
+use crate::server::state::{ConnState, Shared}; // 连接自己的状态和共享的服务器状态
 use crate::storage::db::Db; // 数据库处理模块
+use crate::storage::traits::AsyncKvStore; // GET、SET 和 DEL 使用的存储
 
 /// 每个连接的处理器。从 `connection` 读取请求并将命令应用到 `db`。
+///
+/// 服务器使用 `Stream`，即 TCP、TLS 或者 unix 域套接字连接；`S` 也可以是其他实现了
+/// `AsyncRead` 和 `AsyncWrite` 的字节流，例如测试中的 `tokio::io::duplex`。
+/// `K` 是 `GET`、`SET` 和 `DEL` 使用的存储，默认是内存中的 `Db`。
 #[derive(Debug)]
-pub(crate) struct Handler {
+pub(crate) struct Handler<S = Stream, K = Db> {
     /// 共享的数据库句柄。
     ///
     /// 当从 `connection` 收到命令时，它会使用 `db` 应用。
     /// 命令的实现位于 `cmd` 模块中。每个命令都需要与 `db` 交互以完成工作。
     pub(crate) db: Db,
 
-    /// 使用 redis 协议编码器/解码器装饰的 TCP 连接，通过缓冲的 `TcpStream` 实现。
+    /// `GET`、`SET` 和 `DEL` 使用的存储，所有连接共享。没有指定时与 `db` 是同一个数据库。
+    pub(crate) store: Arc<K>,
+
+    /// 使用 redis 协议编码器/解码器装饰的连接，可以是 TCP、TLS 或者 unix 域套接字连接。
     ///
-    /// 当 `Listener` 收到一个入站连接时，`TcpStream` 会被传递给 `Connection::new`，
+    /// 当 `Listener` 收到一个入站连接时，连接会被传递给 `Connection::with_limits`，
     /// 它会初始化相关的缓冲区。`Connection` 允许处理器在 "帧" 级别操作，并将字节级别的协议解析细节封装在 `Connection` 中。
-    pub(crate) conn: Connection,
+    pub(crate) conn: Connection<S>,
 
     /// 监听关闭通知。
     ///
@@ -31,9 +52,21 @@ pub(crate) struct Handler {
 
     /// 服务器关闭完成通知的发送端
     pub(crate) _shutdown_complete: mpsc::Sender<()>,
+
+    /// 指标记录器和服务器运行统计，所有连接共享。每执行一条命令都会记录命令次数和耗时。
+    pub(crate) metrics: Arc<Metrics>,
+
+    /// 服务器配置，所有连接共享，`CONFIG GET` 从这里读取。
+    pub(crate) config: Arc<RwLock<ServerConfig>>,
+
+    /// 连接自己的事务、监视的键和在已连接的客户端中的登记。
+    pub(crate) state: ConnState,
+
+    /// 这个连接的令牌桶，设置了 `max_commands_per_sec` 时每执行一条命令取出一个令牌。
+    pub(crate) rate_limiter: RateLimiter,
 }
 
-impl Handler {
+impl<S: AsyncRead + AsyncWrite + Unpin, K: AsyncKvStore> Handler<S, K> {
     /// 异步运行处理器。
     ///
     /// 循环等待并处理来自连接的命令，直到接收到关闭信号。
@@ -44,9 +77,23 @@ impl Handler {
     pub(crate) async fn run(&mut self) -> Result<(), MiniRedisConnectionError> {
         // 当未接收到关闭信号时循环
         while !self.shutdown.is_shutdown() {
+            // 每次读取前重新读取配置，这样重新加载的超时对已有的连接也生效。
+            // 订阅状态下的读取在 `Subscribe::apply` 中进行，不受空闲超时的限制。
+            let idle_timeout = self.config.read().unwrap().client_idle_timeout;
+
             // 异步等待读取帧或接收关闭信号
             let maybe_frame = tokio::select! {
-                res = self.conn.read_frame() => res?, // 读取帧
+                res = read_frame_with_timeout(&mut self.conn, idle_timeout) => match res {
+                    Ok(frame) => frame, // 读取帧
+                    Err(MiniRedisConnectionError::Timeout(_)) => { // 空闲超时，关闭连接以释放 permit
+                        debug!("client idle for more than {:?}, closing", idle_timeout.unwrap());
+                        return Ok(());
+                    }
+                    Err(MiniRedisConnectionError::ParseFrame(err)) => {
+                        return self.protocol_error(err).await;
+                    }
+                    Err(e) => return Err(e),
+                },
                 _ = self.shutdown.recv() => { // 接收关闭信号
                     return Ok(());
                 }
@@ -61,15 +108,241 @@ impl Handler {
                 }
             };
 
+            // 超过每秒的命令数时，按照配置等待令牌或者拒绝命令
+            let (rate, action) = {
+                let config = self.config.read().unwrap();
+                (config.max_commands_per_sec, config.rate_limit_action)
+            };
+            if let Some(rate) = rate {
+                match action {
+                    RateLimitAction::Delay => {
+                        while let Err(wait) = self.rate_limiter.try_acquire(rate, Instant::now()) {
+                            tokio::select! {
+                                _ = time::sleep(wait) => {}
+                                _ = self.shutdown.recv() => return Ok(()),
+                            }
+                        }
+                    }
+                    RateLimitAction::Reject => {
+                        if self.rate_limiter.try_acquire(rate, Instant::now()).is_err() {
+                            debug!("rate limit exceeded, command rejected");
+                            // 与参数错误一样，事务中被拒绝的命令让整个事务在 EXEC 时被放弃
+                            if let Some(transaction) = &mut self.state.transaction {
+                                transaction.abort();
+                            }
+                            self.conn
+                                .write_frame(&Frame::error(ErrorKind::Err, "rate limit exceeded"))
+                                .await?;
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            // 解析会消耗帧，先记下参数个数供访问日志使用
+            let args = match &frame {
+                Frame::Array(parts) => parts.len().saturating_sub(1),
+                _ => 0,
+            };
+            // 开启了慢查询日志时同样先保存命令的参数，执行时间超过阈值时记录
+            let slowlog = self
+                .config
+                .read()
+                .unwrap()
+                .slowlog_threshold
+                .map(|threshold| (threshold, command_args(&frame)));
+
             // 从帧中解析命令
-            let cmd = Command::from_frame(frame)?;
+            // 参数错误的命令只回复错误，不关闭连接
+            let cmd = match Command::from_frame(frame) {
+                Ok(cmd) => cmd,
+                Err(MiniRedisParseError::InvalidCommand(msg)) => {
+                    debug!("invalid command: {}", msg);
+                    // 事务中的命令参数错误时，整个事务在 EXEC 时被放弃
+                    if let Some(transaction) = &mut self.state.transaction {
+                        transaction.abort();
+                    }
+                    self.conn
+                        .write_frame(&Frame::error(ErrorKind::Err, msg))
+                        .await?;
+                    continue;
+                }
+                Err(err) => return self.protocol_error(err).await,
+            };
             // 记录接收到的命令
             debug!("received command: {:?}", cmd);
+
+            // 超过 `maxmemory` 时先按照策略删除键，仍然超过时只拒绝可能占用更多内存的命令，
+            // 读取和删除仍然可以执行，客户端可以通过删除键释放内存
+            if cmd.flags().deny_oom() && self.db.over_max_memory(&self.config) {
+                debug!(
+                    "command {} rejected, used memory over maxmemory",
+                    cmd.get_name()
+                );
+                if let Some(transaction) = &mut self.state.transaction {
+                    transaction.abort();
+                }
+                let response = Frame::error(
+                    ErrorKind::Oom,
+                    "command not allowed when used memory > 'maxmemory'",
+                );
+                self.conn.write_frame(&response).await?;
+                continue;
+            }
+
+            // 事务中的命令只排队，EXEC 时再执行；结束事务的命令以及 QUIT 和 RESET 立即执行
+            if let Some(transaction) = &mut self.state.transaction {
+                if !matches!(
+                    cmd,
+                    Command::Exec(_) | Command::Discard(_) | Command::Quit(_) | Command::Reset(_)
+                ) {
+                    transaction.queue(cmd, &mut self.conn).await?;
+                    continue;
+                }
+            }
+
+            // `apply` 会消耗命令，因此先取出命令名称。
+            // 在执行之前计数，这样客户端收到响应时计数已经可见。
+            let name = cmd.get_name().to_string();
+            self.metrics.incr_command(&name);
+            // 丢弃之前的命令留下的回复类型，之后取到的就是这个命令的回复
+            self.conn.take_reply_kind();
+            let start = Instant::now();
+            // 订阅会一直持续到客户端取消订阅，不限制执行时间。
+            // EXEC 中途取消会只执行事务中的一部分命令，因此也不限制
+            let command_timeout = match cmd {
+                Command::Subscribe(_) | Command::Exec(_) => None,
+                _ => self.config.read().unwrap().command_timeout,
+            };
+            // 订阅状态持续的时间不是执行命令的耗时，不记录到慢查询日志
+            let slowlog = match cmd {
+                Command::Subscribe(_) => None,
+                _ => slowlog,
+            };
+            // 只限制执行的时间：执行期间的回复先暂存在内存中，执行完成之后再发送，
+            // 客户端读取回复的快慢不会导致命令超时
+            if command_timeout.is_some() {
+                self.conn.hold_replies();
+            }
             // 应用命令到数据库和连接
-            cmd.apply(&self.db, &mut self.conn, &mut self.shutdown)
-                .await?;
+            let shared = Shared {
+                db: &self.db,
+                store: &*self.store,
+                metrics: &self.metrics,
+                config: &self.config,
+            };
+            let apply = cmd.apply(&shared, &mut self.conn, &mut self.shutdown, &mut self.state);
+            let flow = match command_timeout {
+                Some(command_timeout) => match time::timeout(command_timeout, apply).await {
+                    Ok(res) => {
+                        let flow = res?;
+                        self.conn.release_replies().await?;
+                        flow
+                    }
+                    // 命令被取消，丢弃已经暂存的回复，回复错误后继续处理下一个命令
+                    Err(_) => {
+                        debug!("command {} timed out after {:?}", name, command_timeout);
+                        self.conn.discard_replies();
+                        self.conn.take_reply_kind();
+                        self.conn
+                            .write_frame(&Frame::error(ErrorKind::Err, "command timed out"))
+                            .await?;
+                        self.access_log(&name, args, start.elapsed());
+                        self.slow_log(slowlog, start.elapsed());
+                        continue;
+                    }
+                },
+                None => apply.await?,
+            };
+            let latency = start.elapsed();
+            self.metrics.observe_latency(&name, latency);
+            self.access_log(&name, args, latency);
+            self.slow_log(slowlog, latency);
+
+            // 命令要求关闭连接，返回后连接被丢弃
+            if flow.is_break() {
+                debug!("command {} closes the connection", name);
+                return Ok(());
+            }
         }
 
         Ok(())
     }
+
+    /// 访问日志打开时，记录刚刚执行完的命令。
+    fn access_log(&mut self, command: &str, args: usize, latency: Duration) {
+        let config = self.config.read().unwrap();
+        if !config.access_log {
+            return;
+        }
+
+        let entry = AccessLogEntry {
+            command,
+            args,
+            latency,
+            reply: self.conn.take_reply_kind().unwrap_or("none"),
+        };
+        info!(target: "mini_redis::access", "{}", entry);
+        if let Some(on_access) = &config.on_access {
+            on_access.call(&entry);
+        }
+    }
+
+    /// 命令的执行时间超过 `slowlog` 中的阈值时，将命令的参数记录到慢查询日志。
+    fn slow_log(&self, slowlog: Option<(Duration, Vec<Bytes>)>, latency: Duration) {
+        if let Some((threshold, args)) = slowlog {
+            if latency > threshold {
+                let max_len = self.config.read().unwrap().slowlog_max_len;
+                self.metrics.slowlog().push(args, latency, max_len);
+            }
+        }
+    }
+
+    /// 回复协议错误并关闭连接。
+    ///
+    /// 与 Redis 一样以 `-ERR Protocol error: <detail>` 告诉客户端连接为什么被关闭，
+    /// 之后缓冲区中的数据已经无法可靠地划分为帧，只能关闭连接。
+    async fn protocol_error(
+        &mut self,
+        err: MiniRedisParseError,
+    ) -> Result<(), MiniRedisConnectionError> {
+        debug!("protocol error: {:?}, closing", err);
+
+        let msg = match err {
+            MiniRedisParseError::Parse(msg) => msg,
+            err => err.to_string(),
+        };
+        let detail = msg.strip_prefix("protocol error; ").unwrap_or(&msg);
+        self.conn
+            .write_frame(&Frame::error(
+                ErrorKind::Err,
+                format!("Protocol error: {}", detail),
+            ))
+            .await
+    }
+}
+
+// 返回请求中的命令名称和参数，参数通常是 bulk 字符串，其他类型转换为字符串
+fn command_args(frame: &Frame) -> Vec<Bytes> {
+    match frame {
+        Frame::Array(parts) => parts
+            .iter()
+            .map(|part| match part {
+                Frame::Bulk(data) => data.clone(),
+                part => Bytes::from(part.to_string()),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// 读取一个帧，设置了 `timeout` 时超时返回 `MiniRedisConnectionError::Timeout`
+async fn read_frame_with_timeout<S: AsyncRead + AsyncWrite + Unpin>(
+    conn: &mut Connection<S>,
+    timeout: Option<Duration>,
+) -> Result<Option<Frame>, MiniRedisConnectionError> {
+    match timeout {
+        Some(timeout) => time::timeout(timeout, conn.read_frame()).await?,
+        None => conn.read_frame().await,
+    }
 }
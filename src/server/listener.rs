@@ -1,31 +1,113 @@
 // 使用标准库中的 Arc 和 Duration
-use std::sync::Arc;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
-use log::{error, info}; // 使用 log 库记录信息和错误
-use tokio::net::{TcpListener, TcpStream}; // 使用 tokio 异步网络编程库中的 TcpListener 和 TcpStream
+use log::{debug, error, info}; // 使用 log 库记录信息和错误
+use tokio::net::TcpListener; // 使用 tokio 异步网络编程库中的 TcpListener
+#[cfg(unix)]
+use tokio::net::UnixListener; // unix 域套接字监听器
 use tokio::sync::{broadcast, mpsc, Semaphore}; // 使用 tokio 同步原语：broadcast（广播），mpsc（消息传递），Semaphore（信号量）
 use tokio::time; // 使用 tokio 的时间处理工具
+use tokio_rustls::{rustls, TlsAcceptor}; // TLS 握手
 
 // 引入项目内部模块
+use crate::config::ServerConfig; // 服务器配置
 use crate::connection::connect::Connection; // 连接处理
-use crate::error::MiniRedisConnectionError; // 错误定义
+use crate::connection::frame::Frame; // 协议帧
+use crate::connection::stream::Stream; // TCP、TLS 或 unix 域套接字连接
+use crate::consts::TLS_HANDSHAKE_TIMEOUT; // TLS 握手超时
+use crate::error::{ErrorKind, MiniRedisConnectionError}; // 错误定义
+use crate::metrics::MetricsRecorder; // 指标记录
+use crate::server::clients::Clients; // 已连接的客户端
 use crate::server::handler::Handler; // 连接处理器
+use crate::server::metrics::Metrics; // 服务器统计
+use crate::server::ratelimit::RateLimiter; // 命令速率限制
 use crate::server::shutdown::Shutdown; // 优雅关闭处理
-use crate::storage::db::DbDropGuard; // 数据库守护
+use crate::server::state::ConnState; // 连接自己的状态
+use crate::storage::db::{Db, DbDropGuard}; // 数据库和数据库守护
+use crate::storage::traits::AsyncKvStore; // GET、SET 和 DEL 使用的存储
+
+/// 服务器监听的套接字，可以是 TCP 套接字或者 unix 域套接字。
+///
+/// `server::run`、`server::run_with_config` 和 `server::run_with_store` 接受任何可以转换为 `ServerListener` 的类型，
+/// 因此可以直接传入 `TcpListener` 或者 `UnixListener`。
+#[derive(Debug)]
+pub enum ServerListener {
+    /// 监听 TCP 连接
+    Tcp(TcpListener),
+    /// 监听 unix 域套接字连接
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl ServerListener {
+    /// 返回监听的地址，用于日志。unix 域套接字返回套接字文件的路径。
+    pub(crate) fn local_addr(&self) -> String {
+        match self {
+            ServerListener::Tcp(listener) => match listener.local_addr() {
+                Ok(addr) => addr.to_string(),
+                Err(err) => err.to_string(),
+            },
+            #[cfg(unix)]
+            ServerListener::Unix(listener) => match listener.local_addr() {
+                Ok(addr) => match addr.as_pathname() {
+                    Some(path) => format!("unix:{}", path.display()),
+                    None => "unix:(unnamed)".to_string(),
+                },
+                Err(err) => err.to_string(),
+            },
+        }
+    }
+
+    /// 接受一个入站连接，同时返回客户端的地址。unix 域套接字连接返回监听的套接字文件的路径。
+    async fn accept(&self) -> std::io::Result<(Stream, String)> {
+        match self {
+            ServerListener::Tcp(listener) => listener
+                .accept()
+                .await
+                .map(|(s, addr)| (s.into(), addr.to_string())),
+            #[cfg(unix)]
+            ServerListener::Unix(listener) => listener
+                .accept()
+                .await
+                .map(|(s, _)| (s.into(), self.local_addr())),
+        }
+    }
+}
+
+impl From<TcpListener> for ServerListener {
+    fn from(listener: TcpListener) -> ServerListener {
+        ServerListener::Tcp(listener)
+    }
+}
+
+#[cfg(unix)]
+impl From<UnixListener> for ServerListener {
+    fn from(listener: UnixListener) -> ServerListener {
+        ServerListener::Unix(listener)
+    }
+}
 
 /// `Listener` 结构体负责监听TCP连接，并管理与每个连接相关的资源。
 #[derive(Debug)]
-pub(crate) struct Listener {
-    pub(crate) listener: TcpListener,                    // 监听 TCP 连接
-    pub(crate) db_holder: DbDropGuard,                   //内部存储数据库
+pub(crate) struct Listener<K = Db> {
+    pub(crate) listener: ServerListener, // 监听 TCP 或 unix 域套接字连接
+    pub(crate) db_holder: DbDropGuard,   //内部存储数据库
+    pub(crate) store: Arc<K>, // GET、SET 和 DEL 使用的存储，默认与 db_holder 中的是同一个数据库
     pub(crate) limit_conn: Arc<Semaphore>, // 使用信号量 Semaphore 实现的连接令牌，当超过了最大连接数，则需要等待其他连接释放后才能创建新的连接
     pub(crate) notify_shutdown: broadcast::Sender<()>, // 通知所有 TCP 服务器 shutdown 信号
     pub(crate) shutdown_complete_tx: mpsc::Sender<()>, // 用于发送服务器 shutdown 完成信号的发送器
     pub(crate) shutdown_complete_rx: mpsc::Receiver<()>, // 用于接收服务器 shutdown 完成信号的接收器
+    pub(crate) metrics: Arc<Metrics>,      // 指标记录器和服务器运行统计，供 INFO 命令读取
+    pub(crate) clients: Arc<Clients>,      // 已连接的客户端，供 CLIENT LIST 命令读取
+    pub(crate) next_client_id: AtomicU64,  // 下一个连接的 id，从 1 开始递增
+    pub(crate) config: Arc<RwLock<ServerConfig>>, // 服务器配置，所有连接共享，可能在运行期间被重新加载
+    pub(crate) tls: Option<Arc<rustls::ServerConfig>>, // 配置了 TLS 时用于在 TCP 连接上完成握手
 }
 
-impl Listener {
+impl<K: AsyncKvStore + Send + Sync + 'static> Listener<K> {
     /// 运行服务器
     ///
     /// 监听入站连接。对于每个入站连接，生成一个任务来处理该连接。
@@ -38,34 +120,86 @@ impl Listener {
     /// 进程无法检测瞬态错误何时自行解决。处理此问题的一种策略是实施 back off 策略，这就是我们在这里所做的。
     pub(crate) async fn run(&mut self) -> Result<(), MiniRedisConnectionError> {
         info!("accepting inbound connections");
-        // 等待 permit 可用
-        //
-        // `acquire_owned` 返回与信号量绑定的许可证。
-        // 当许可证值被删除时，它会自动返回给信号量。
-        //
-        // 当信号量已关闭时，`acquire_owned()` 返回 `Err`。我们永远不会关闭信号量，因此 `unwrap()` 是安全的。
         loop {
-            let permit = self.limit_conn.clone().acquire_owned().await.unwrap();
-
             // 接收一个连接（调用下面实现的 accept 函数）
-            let socket = self.accept().await?;
-
-            // 创建一个新的 Handler 来处理连接
-            let mut handler = Handler {
-                // 获取共享数据库的句柄
-                db: self.db_holder.db(),
-                // 初始化连接状态。这分配了读/写缓冲区以执行 redis 协议帧解析
-                conn: Connection::new(socket),
-                // shutdown 信号通知
-                shutdown: Shutdown::new(self.notify_shutdown.subscribe()),
-                // 当所有 clone drop 时，通知接收者
-                _shutdown_complete: self.shutdown_complete_tx.clone(),
+            //
+            // 先接受连接再获取 permit，这样即使连接数已满，新的连接也不会停留在操作系统的 backlog 中无人处理。
+            let (socket, addr) = self.accept().await?;
+
+            // 获取 permit
+            //
+            // `acquire_owned` 返回与信号量绑定的许可证。
+            // 当许可证值被删除时，它会自动返回给信号量。
+            //
+            // 当信号量已关闭时，`acquire_owned()` 返回 `Err`。我们永远不会关闭信号量，因此 `unwrap()` 是安全的。
+            let (reject_on_full, max_inline_len, max_bulk_len) = {
+                let config = self.config.read().unwrap();
+                (
+                    config.reject_on_full,
+                    config.max_inline_len,
+                    config.proto_max_bulk_len,
+                )
             };
 
+            let permit = if reject_on_full {
+                // 连接数已满时立即回复错误，不等待
+                match self.limit_conn.clone().try_acquire_owned() {
+                    Ok(permit) => permit,
+                    Err(_) => {
+                        self.reject(socket, max_inline_len, max_bulk_len);
+                        continue;
+                    }
+                }
+            } else {
+                // 等待其他连接释放 permit
+                self.limit_conn.clone().acquire_owned().await.unwrap()
+            };
+
+            // 获取连接需要的共享状态。在这里订阅关闭通知，这样握手期间发出的通知也不会丢失
+            let db = self.db_holder.db();
+            let store = self.store.clone();
+            let shutdown = Shutdown::new(self.notify_shutdown.subscribe());
+            let shutdown_complete = self.shutdown_complete_tx.clone();
+            let config = self.config.clone();
+            let tls = self.tls.clone();
+            let clients = self.clients.clone();
+            let id = self.next_client_id.fetch_add(1, Ordering::Relaxed);
+            // id 从 1 开始按照接受的顺序分配，因此它就是接受的连接总数
+            let metrics = self.metrics.clone();
+            metrics.set_gauge("total_connections_received", id);
+
             // 生成一个新的任务来处理连接，异步并发执行
             tokio::spawn(async move {
-                if let Err(err) = handler.run().await {
-                    error!("connection error: {:?}", err);
+                // TLS 握手在连接自己的任务中进行，握手很慢的客户端不会阻塞接受新的连接
+                match handshake(tls, socket).await {
+                    Ok(socket) => {
+                        // 创建一个新的 Handler 来处理连接
+                        let mut handler = Handler {
+                            // 共享数据库的句柄
+                            db,
+                            // GET、SET 和 DEL 使用的存储
+                            store,
+                            // 初始化连接状态。这分配了读/写缓冲区以执行 redis 协议帧解析
+                            conn: Connection::with_limits(socket, max_inline_len, max_bulk_len),
+                            // shutdown 信号通知
+                            shutdown,
+                            // 当所有 clone drop 时，通知接收者
+                            _shutdown_complete: shutdown_complete,
+                            // 共享的指标记录器和服务器统计
+                            metrics,
+                            // 共享的服务器配置
+                            config,
+                            // 新的连接不在事务中，没有监视任何键；
+                            // 在已连接的客户端中登记，handler 被 drop 时注销
+                            state: ConnState::new(clients.register(id, addr)),
+                            // 新的连接有一秒的配额
+                            rate_limiter: RateLimiter::new(),
+                        };
+                        if let Err(err) = handler.run().await {
+                            error!("connection error: {:?}", err);
+                        }
+                    }
+                    Err(err) => debug!("tls handshake failed: {}", err),
                 }
                 // 释放 permit
                 drop(permit);
@@ -73,18 +207,41 @@ impl Listener {
         }
     }
 
+    /// 回复连接数已满的错误并关闭连接。
+    ///
+    /// 在单独的任务中写入，这样不读取响应的客户端不会阻塞接受新的连接。
+    fn reject(&self, socket: Stream, max_inline_len: usize, max_bulk_len: usize) {
+        debug!("max number of clients reached, rejecting connection");
+        let tls = self.tls.clone();
+        tokio::spawn(async move {
+            // TLS 客户端只有在握手之后才能读到错误
+            let socket = match handshake(tls, socket).await {
+                Ok(socket) => socket,
+                Err(err) => {
+                    debug!("tls handshake failed: {}", err);
+                    return;
+                }
+            };
+            let mut conn = Connection::with_limits(socket, max_inline_len, max_bulk_len);
+            let response = Frame::error(ErrorKind::Err, "max number of clients reached");
+            if let Err(err) = conn.write_frame(&response).await {
+                debug!("failed to reject connection: {:?}", err);
+            }
+        });
+    }
+
     /// 接受入站连接。
     ///
     /// 通过 back off 和 retry 来处理错误。使用 exponential backoff 策略。
     /// 即第一次失败后，任务等待 1 秒。第二次失败后，任务等待 2 秒。
     /// 后续每次失败都会使等待时间加倍。如果在等待 64 秒后即第 6 次尝试接受失败，则此函数返回 error。
-    async fn accept(&mut self) -> Result<TcpStream, MiniRedisConnectionError> {
+    async fn accept(&mut self) -> Result<(Stream, String), MiniRedisConnectionError> {
         let mut backoff = 1;
         loop {
             // 执行接受操作。如果成功接受套接字，则返回它。否则，保存错误。
             match self.listener.accept().await {
-                Ok((socket, _)) => {
-                    return Ok(socket);
+                Ok(accepted) => {
+                    return Ok(accepted);
                 }
                 Err(err) => {
                     if backoff > 64 {
@@ -104,3 +261,17 @@ impl Listener {
         }
     }
 }
+
+/// 配置了 TLS 时在 TCP 连接上完成 TLS 握手，unix 域套接字连接原样返回。
+///
+/// 握手超过 `TLS_HANDSHAKE_TIMEOUT` 仍未完成时返回错误。
+async fn handshake(tls: Option<Arc<rustls::ServerConfig>>, socket: Stream) -> io::Result<Stream> {
+    match (tls, socket) {
+        (Some(tls), Stream::Tcp(stream)) => {
+            let accept = TlsAcceptor::from(tls).accept(stream);
+            let stream = time::timeout(TLS_HANDSHAKE_TIMEOUT, accept).await??;
+            Ok(stream.into())
+        }
+        (_, socket) => Ok(socket),
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::metrics::{InMemoryMetrics, MetricsRecorder};
+use crate::server::slowlog::SlowLog;
+
+/// 服务器使用的指标记录器，同时保存启动时间和慢查询日志，由 `INFO` 和 `SLOWLOG` 命令读取。
+///
+/// 服务器的所有指标都经过它上报：先记录到进程内的 `InMemoryMetrics`，`INFO` 从这里读取，
+/// 再转发给 `ServerConfig::metrics` 配置的记录器，因此替换记录器不影响 `INFO` 的输出。
+/// `server::start` 创建一份，并共享给数据库和每个连接的 `Handler`。
+#[derive(Debug)]
+pub(crate) struct Metrics {
+    // 服务器启动的时间
+    started_at: Instant,
+    // 进程内保存的指标
+    recorded: InMemoryMetrics,
+    // `ServerConfig::metrics` 配置的记录器
+    external: Arc<dyn MetricsRecorder>,
+    // 慢查询日志
+    slowlog: SlowLog,
+}
+
+impl Metrics {
+    /// 创建一个新的 `Metrics`，从现在开始计算运行时间，指标同时转发给 `external`。
+    pub(crate) fn new(external: Arc<dyn MetricsRecorder>) -> Metrics {
+        Metrics {
+            started_at: Instant::now(),
+            recorded: InMemoryMetrics::new(),
+            external,
+            slowlog: SlowLog::new(),
+        }
+    }
+
+    /// 服务器的运行时间。
+    pub(crate) fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// 进程内保存的指标。
+    pub(crate) fn recorded(&self) -> &InMemoryMetrics {
+        &self.recorded
+    }
+
+    /// 慢查询日志。
+    pub(crate) fn slowlog(&self) -> &SlowLog {
+        &self.slowlog
+    }
+}
+
+impl MetricsRecorder for Metrics {
+    fn incr_command(&self, name: &str) {
+        self.recorded.incr_command(name);
+        self.external.incr_command(name);
+    }
+
+    fn observe_latency(&self, name: &str, latency: Duration) {
+        self.recorded.observe_latency(name, latency);
+        self.external.observe_latency(name, latency);
+    }
+
+    fn set_gauge(&self, name: &str, value: u64) {
+        self.recorded.set_gauge(name, value);
+        self.external.set_gauge(name, value);
+    }
+}
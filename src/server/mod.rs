@@ -5,36 +5,165 @@
 
 // 引入标准库中的 Future 和 Arc（原子引用计数类型）
 use std::future::Future;
-use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
-use log::{debug, error, info}; // 引入日志库的不同日志级别
-use tokio::net::TcpListener; // 引入 tokio 异步网络库的 TcpListener
+use bytes::Bytes;
+
+use log::{debug, error, info, warn}; // 引入日志库的不同日志级别
 use tokio::sync::{broadcast, mpsc, Semaphore}; // 引入 tokio 的同步原语：broadcast（广播通道）、mpsc（消息传递通道）、Semaphore（信号量）
+use tokio::time; // 引入 tokio 的时间处理工具，用于关闭超时
 
-use crate::consts::MAX_CONNECTIONS; // 引入 crate 内定义的常量 MAX_CONNECTIONS
+use crate::config::ServerConfig; // 引入服务器配置
+use crate::server::clients::Clients;
 use crate::server::listener::Listener; // 引入 Listener 结构体和 DbDropGuard 结构体
-use crate::storage::db::DbDropGuard;
+pub use crate::server::listener::ServerListener;
+use crate::server::metrics::Metrics;
+use crate::storage::db::{Db, DbDropGuard};
+pub use crate::storage::store::WrongType;
+pub use crate::storage::traits::{AsyncKvStore, BoxFuture};
 
 // handler 模块和 listener、shutdown 模块声明为 crate 内部可见
+pub(crate) mod clients;
 mod handler;
 pub(crate) mod listener;
+pub(crate) mod metrics;
+mod ratelimit;
+#[cfg(unix)]
+mod reload;
 pub(crate) mod shutdown;
+pub(crate) mod slowlog;
+pub(crate) mod state;
+mod tls;
 
 /// 运行 mini-redis 服务器。
 ///
-/// 这个函数启动 mini-redis 服务器并在提供的 `TcpListener` 上监听传入的连接
+/// 这个函数启动 mini-redis 服务器并在提供的 `TcpListener` 或 `UnixListener` 上监听传入的连接
 /// 它还接受一个 `shutdown` future 作为参数，该参数可以用来地关闭服务器
 ///
 /// # 参数
 ///
-/// * `listener` - 用于监听传入连接的 `TcpListener`
+/// * `listener` - 用于监听传入连接的 `TcpListener` 或 `UnixListener`
 /// * `shutdown` - 表示服务器关闭信号的 future (我们默认使用 `tokio::signal::ctrl_c`)
 /// ```
-pub async fn run(listener: TcpListener, shutdown: impl Future) {
+pub async fn run(listener: impl Into<ServerListener>, shutdown: impl Future) {
+    run_with_config(listener, shutdown, ServerConfig::default()).await
+}
+
+/// 使用指定的配置运行 mini-redis 服务器。
+///
+/// 与 `run` 相同，但允许调用者通过 `ServerConfig` 定制服务器的行为，例如替换指标记录器。
+pub async fn run_with_config(
+    listener: impl Into<ServerListener>,
+    shutdown: impl Future,
+    config: ServerConfig,
+) {
+    let (_, server) = start(listener, shutdown, config);
+    server.await
+}
+
+/// 使用指定的配置运行 mini-redis 服务器，`GET`、`SET` 和 `DEL` 读写 `store`。
+///
+/// 与 `run_with_config` 相同，但字符串的读写交给调用者实现的 [`AsyncKvStore`]，
+/// 例如 SQLite 或 sled 上的存储，等待 I/O 时不会阻塞其他连接。
+/// 其他命令仍然使用内存中的数据库，见 [`AsyncKvStore`] 的说明。
+pub async fn run_with_store<K>(
+    listener: impl Into<ServerListener>,
+    shutdown: impl Future,
+    mut config: ServerConfig,
+    store: K,
+) where
+    K: AsyncKvStore + Send + Sync + 'static,
+{
+    let (db_holder, metrics) = open_db(&mut config);
+    serve(
+        listener.into(),
+        shutdown,
+        config,
+        db_holder,
+        Arc::new(store),
+        metrics,
+    )
+    .await
+}
+
+/// 创建服务器的数据库，返回数据库的句柄和运行服务器的 future。
+///
+/// 与 `run_with_config` 相同，但在服务器运行之前就返回 [`DbHandle`]，
+/// 把服务器嵌入到更大的应用中时，可以用它直接读取数据而不经过 RESP 协议。
+/// 数据库的后台任务在这里启动，因此必须在 tokio 运行时中调用。
+pub fn start(
+    listener: impl Into<ServerListener>,
+    shutdown: impl Future,
+    mut config: ServerConfig,
+) -> (DbHandle, impl Future<Output = ()>) {
+    let (db_holder, metrics) = open_db(&mut config);
+    let db = db_holder.db();
+    let handle = DbHandle { db: db.clone() };
+    // 没有指定存储时，GET、SET 和 DEL 与其他命令使用同一个数据库
+    let store = Arc::new(db);
+    (
+        handle,
+        serve(listener.into(), shutdown, config, db_holder, store, metrics),
+    )
+}
+
+// 创建服务器的数据库和统计，数据库的后台任务在这里启动
+fn open_db(config: &mut ServerConfig) -> (DbDropGuard, Arc<Metrics>) {
+    // 数据库和连接都通过 `metrics` 上报指标，它在内存中保存一份再转发给配置的记录器
+    let metrics = Arc::new(Metrics::new(config.metrics.clone()));
+    config.metrics = metrics.clone();
+    (DbDropGuard::new(config), metrics)
+}
+
+/// 服务器数据库的句柄，由 [`start`] 返回。
+///
+/// 克隆的句柄共享同一个数据库。服务器关闭之后句柄仍然可以读取关闭时的数据。
+#[derive(Debug, Clone)]
+pub struct DbHandle {
+    db: Db,
+}
+
+impl DbHandle {
+    /// 返回所有没有过期的字符串键、值和剩余生存时间，没有设置过期时间的键为 `None`。
+    ///
+    /// 只包括字符串类型的键，列表、哈希表和集合类型的键不包括在内。结果没有特定的顺序。
+    /// 数据库的分片依次复制，每次只阻塞一个分片上的写入，因此结果不是所有键同一时刻的快照。
+    pub fn iter_entries(&self) -> Vec<(String, Bytes, Option<Duration>)> {
+        self.db.iter_entries()
+    }
+
+    /// 设置键过期时的回调，替换 [`ServerConfig::on_expire`] 或者之前设置的回调。
+    ///
+    /// 后台任务清理过期的键，或者命令访问到已经过期但还未被清理的键时调用，参数是过期的键名。
+    /// 回调在释放存储锁之后调用，应当尽量轻量，不要阻塞。
+    pub fn on_expire(&self, callback: impl Fn(&str) + Send + Sync + 'static) {
+        self.db.on_expire(callback);
+    }
+}
+
+// 使用 `db_holder` 中的数据库和 `store` 运行服务器，直到 `shutdown` 完成
+async fn serve<K: AsyncKvStore + Send + Sync + 'static>(
+    listener: ServerListener,
+    shutdown: impl Future,
+    config: ServerConfig,
+    db_holder: DbDropGuard,
+    store: Arc<K>,
+    metrics: Arc<Metrics>,
+) {
+    // 证书或私钥无法读取时不启动服务器
+    let tls = match config.tls.as_ref().map(tls::load).transpose() {
+        Ok(tls) => tls,
+        Err(e) => {
+            error!("failed to load tls certificate: {}", e);
+            return;
+        }
+    };
     // 打印服务器启动信息，监听的地址和端口
     info!(
         "mini-redis server start, listening on: {}",
-        listener.local_addr().unwrap()
+        listener.local_addr()
     );
 
     // 创建一个广播通道，用于通知关闭
@@ -45,13 +174,23 @@ pub async fn run(listener: TcpListener, shutdown: impl Future) {
     // 创建一个 Listener 实例
     let mut server = Listener {
         listener,
-        db_holder: DbDropGuard::new(),
-        limit_conn: Arc::new(Semaphore::new(MAX_CONNECTIONS)), // 最多允许 MAX_CONNECTIONS 个连接
+        db_holder,
+        store,
+        limit_conn: Arc::new(Semaphore::new(config.max_connections)), // 最多允许 max_connections 个连接
         notify_shutdown,
         shutdown_complete_tx,
         shutdown_complete_rx,
+        clients: Arc::new(Clients::new(metrics.clone())),
+        metrics,
+        next_client_id: AtomicU64::new(1),
+        config: Arc::new(RwLock::new(config)),
+        tls,
     };
 
+    // 收到 SIGHUP 时重新读取配置文件
+    #[cfg(unix)]
+    let reload = reload::spawn(&server.config, server.db_holder.db());
+
     // 使用 tokio 的 select 宏来同时运行 server 和监听 shutdown 信号
     tokio::select! {
         res = server.run() => {
@@ -67,18 +206,34 @@ pub async fn run(listener: TcpListener, shutdown: impl Future) {
         }
     }
 
+    #[cfg(unix)]
+    if let Some(reload) = reload {
+        reload.abort();
+    }
+
     // 解构 Listener 实例，获取需要的字段（是个不错的写法）
     let Listener {
         mut shutdown_complete_rx,
         shutdown_complete_tx,
         notify_shutdown,
+        config,
         ..
     } = server;
+    // 读取关闭超时时间，它可能在运行期间被重新加载
+    let shutdown_timeout = config.read().unwrap().shutdown_timeout;
 
     // 丢弃 notify_shutdown 和 shutdown_complete_tx，以便它们可以被正确关闭，此时其他 TCP 连接也能够接收到 shutdown 信号
     drop(notify_shutdown);
     drop(shutdown_complete_tx);
 
-    // 等待关闭完成
-    let _ = shutdown_complete_rx.recv().await;
+    // 等待关闭完成，最多等待 shutdown_timeout，避免某个卡住的连接让关闭永远无法结束
+    if time::timeout(shutdown_timeout, shutdown_complete_rx.recv())
+        .await
+        .is_err()
+    {
+        warn!(
+            "shutdown timed out after {:?}, some connections are still active",
+            shutdown_timeout
+        );
+    }
 }
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// 连接的令牌桶，限制每秒执行的命令数。
+///
+/// 桶的容量等于每秒允许的命令数，令牌按照同样的速率补充，因此连接可以突发执行一秒的配额，
+/// 之后被限制在这个速率。速率每次调用时传入，重新加载配置之后立即生效。
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    // 当前的令牌数，可以是小数
+    tokens: f64,
+    // 上次补充令牌的时间
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// 创建一个装满令牌的桶。
+    pub(crate) fn new() -> RateLimiter {
+        RateLimiter {
+            // 第一次调用时按照当时的速率截断为桶的容量
+            tokens: f64::MAX,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// 以每秒 `rate` 个令牌的速率补充到 `now` 之后取出一个令牌。
+    ///
+    /// 令牌不足时不取出，返回需要等待多久才会有一个令牌。
+    pub(crate) fn try_acquire(&mut self, rate: u32, now: Instant) -> Result<(), Duration> {
+        let rate = f64::from(rate.max(1));
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(rate);
+        self.refilled_at = self.refilled_at.max(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 用完一秒的配额之后需要等待，令牌按照速率补充
+    #[test]
+    fn refills_at_rate() {
+        let mut limiter = RateLimiter::new();
+        let mut now = Instant::now();
+        for _ in 0..10 {
+            assert_eq!(Ok(()), limiter.try_acquire(10, now));
+        }
+        let wait = limiter.try_acquire(10, now).unwrap_err();
+        assert!(wait > Duration::from_millis(99) && wait <= Duration::from_millis(100));
+
+        now += wait;
+        assert_eq!(Ok(()), limiter.try_acquire(10, now));
+        assert!(limiter.try_acquire(10, now).is_err());
+
+        // 空闲再久也只能积累一秒的配额
+        now += Duration::from_secs(60);
+        for _ in 0..10 {
+            assert_eq!(Ok(()), limiter.try_acquire(10, now));
+        }
+        assert!(limiter.try_acquire(10, now).is_err());
+    }
+}
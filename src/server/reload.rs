@@ -0,0 +1,59 @@
+//! 在 unix 上收到 SIGHUP 时重新加载配置文件。
+
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use log::{error, info};
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tokio::task::JoinHandle;
+
+use crate::config::{parse_config_file, ServerConfig};
+use crate::storage::db::Db;
+
+/// 如果配置来自配置文件，启动一个任务，每次收到 SIGHUP 时重新读取配置文件并更新 `config` 和 `db`。
+///
+/// 信号处理在返回之前就已经注册，之后收到的 SIGHUP 不会终止进程。
+/// 返回任务的句柄，服务器关闭时由调用者终止任务。
+pub(crate) fn spawn(config: &Arc<RwLock<ServerConfig>>, db: Db) -> Option<JoinHandle<()>> {
+    let path = config.read().unwrap().config_file.clone()?;
+
+    let hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(err) => {
+            error!(
+                "failed to listen for SIGHUP, config reload disabled: {}",
+                err
+            );
+            return None;
+        }
+    };
+
+    Some(tokio::spawn(reload_on_hangup(
+        config.clone(),
+        db,
+        path,
+        hangup,
+    )))
+}
+
+async fn reload_on_hangup(
+    config: Arc<RwLock<ServerConfig>>,
+    db: Db,
+    path: PathBuf,
+    mut hangup: Signal,
+) {
+    while hangup.recv().await.is_some() {
+        info!("received SIGHUP, reloading config file {:?}", path);
+
+        // 文件有错误时保留当前的配置
+        match parse_config_file(&path) {
+            Ok(new) => {
+                let mut config = config.write().unwrap();
+                config.reload(new);
+                // 数据库在写入时直接读取键空间事件的开关，不经过共享的配置
+                db.set_notify_keyspace_events(config.notify_keyspace_events);
+            }
+            Err(err) => error!("failed to reload config file {:?}: {}", path, err),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bytes::{Bytes, BytesMut};
+
+// 每条记录最多保存的参数个数，包括命令名称
+const MAX_ARGC: usize = 32;
+// 每个参数最多保存的字节数
+const MAX_ARG_LEN: usize = 128;
+
+/// 慢查询日志中的一条记录。
+#[derive(Debug, Clone)]
+pub(crate) struct SlowLogEntry {
+    /// 记录的编号，从 0 开始递增，`SLOWLOG RESET` 之后也不会重新计数。
+    pub(crate) id: u64,
+    /// 命令执行完成的时间，从 UNIX 纪元开始的秒数。
+    pub(crate) timestamp: u64,
+    /// 执行命令的耗时。
+    pub(crate) duration: Duration,
+    /// 命令名称和参数，过多的参数和过长的参数会被截断。
+    pub(crate) args: Vec<Bytes>,
+}
+
+/// 执行时间超过 `slowlog_threshold` 的命令的记录，由 `SLOWLOG` 命令读取。
+///
+/// 最多保存 `slowlog_max_len` 条记录，超过之后丢弃最早的记录。
+#[derive(Debug, Default)]
+pub(crate) struct SlowLog {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    // 最新的记录在最前面
+    entries: VecDeque<SlowLogEntry>,
+    // 下一条记录的编号
+    next_id: u64,
+}
+
+impl SlowLog {
+    /// 创建一个空的慢查询日志。
+    pub(crate) fn new() -> SlowLog {
+        SlowLog::default()
+    }
+
+    /// 添加一条记录，`args` 是命令名称和参数，记录数量超过 `max_len` 时丢弃最早的记录。
+    pub(crate) fn push(&self, args: Vec<Bytes>, duration: Duration, max_len: usize) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+
+        let mut inner = self.inner.lock().unwrap();
+        let entry = SlowLogEntry {
+            id: inner.next_id,
+            timestamp,
+            duration,
+            args: truncate_args(args),
+        };
+        inner.next_id += 1;
+        inner.entries.push_front(entry);
+        inner.entries.truncate(max_len);
+    }
+
+    /// 返回最新的 `count` 条记录，最新的在最前面。
+    pub(crate) fn get(&self, count: usize) -> Vec<SlowLogEntry> {
+        let inner = self.inner.lock().unwrap();
+        inner.entries.iter().take(count).cloned().collect()
+    }
+
+    /// 清空所有记录。
+    pub(crate) fn reset(&self) {
+        self.inner.lock().unwrap().entries.clear();
+    }
+}
+
+// 与 Redis 一样截断过多和过长的参数，避免一条记录占用大量内存
+fn truncate_args(mut args: Vec<Bytes>) -> Vec<Bytes> {
+    if args.len() > MAX_ARGC {
+        let more = args.len() - MAX_ARGC + 1;
+        args.truncate(MAX_ARGC - 1);
+        args.push(Bytes::from(format!("... ({} more arguments)", more)));
+    }
+
+    args.into_iter()
+        .map(|arg| {
+            if arg.len() <= MAX_ARG_LEN {
+                return arg;
+            }
+            let mut truncated = BytesMut::from(&arg[..MAX_ARG_LEN]);
+            let suffix = format!("... ({} more bytes)", arg.len() - MAX_ARG_LEN);
+            truncated.extend_from_slice(suffix.as_bytes());
+            truncated.freeze()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_newest_entries() {
+        let slowlog = SlowLog::new();
+        for i in 0..5 {
+            slowlog.push(vec![Bytes::from(format!("cmd{}", i))], Duration::ZERO, 3);
+        }
+
+        let ids: Vec<_> = slowlog.get(10).iter().map(|entry| entry.id).collect();
+        assert_eq!(vec![4, 3, 2], ids);
+        assert_eq!(1, slowlog.get(1).len());
+
+        // 重置之后编号继续递增
+        slowlog.reset();
+        assert!(slowlog.get(10).is_empty());
+        slowlog.push(vec![Bytes::from("cmd")], Duration::ZERO, 3);
+        assert_eq!(5, slowlog.get(10)[0].id);
+    }
+
+    #[test]
+    fn truncates_long_arguments() {
+        let slowlog = SlowLog::new();
+        let mut args = vec![Bytes::from(vec![b'x'; 200])];
+        args.extend((0..40).map(|i| Bytes::from(i.to_string())));
+        slowlog.push(args, Duration::ZERO, 1);
+
+        let args = &slowlog.get(1)[0].args;
+        assert_eq!(MAX_ARGC, args.len());
+        assert_eq!(
+            format!("{}... (72 more bytes)", "x".repeat(MAX_ARG_LEN)).as_bytes(),
+            &args[0][..]
+        );
+        assert_eq!(&b"... (10 more arguments)"[..], &args[MAX_ARGC - 1][..]);
+    }
+}
@@ -0,0 +1,53 @@
+use std::sync::RwLock;
+
+use crate::cmd::multi::Transaction;
+use crate::cmd::watch::WatchedKeys;
+use crate::config::ServerConfig;
+use crate::server::clients::ClientHandle;
+use crate::server::metrics::Metrics;
+use crate::storage::db::Db;
+
+/// 所有连接共享的服务器状态，`Handler` 执行命令时借用自己持有的句柄组成它，交给 `Command::apply`。
+///
+/// `store` 是 `GET`、`SET` 和 `DEL` 使用的存储，默认就是 `db` 本身；
+/// 其他命令需要过期、阻塞、发布订阅等只有内存中的 `Db` 才有的功能，仍然直接使用 `db`。
+#[derive(Debug)]
+pub(crate) struct Shared<'a, K = Db> {
+    /// 内存中的数据库。
+    pub(crate) db: &'a Db,
+
+    /// `GET`、`SET` 和 `DEL` 使用的存储，见 [`run_with_store`](crate::server::run_with_store)。
+    pub(crate) store: &'a K,
+
+    /// 指标记录器和服务器运行统计。
+    pub(crate) metrics: &'a Metrics,
+
+    /// 服务器配置，可能在运行期间被重新加载。
+    pub(crate) config: &'a RwLock<ServerConfig>,
+}
+
+/// 一个连接自己的状态，由 `Handler` 持有，执行命令时交给 `Command::apply`。
+///
+/// 与共享的数据库和配置不同，这些状态只属于一个连接，连接关闭时一起丢弃。
+#[derive(Debug)]
+pub(crate) struct ConnState {
+    /// 事务状态，`MULTI` 之后到 `EXEC` 或 `DISCARD` 之前为 `Some`，其中保存排队的命令。
+    pub(crate) transaction: Option<Transaction>,
+
+    /// `WATCH` 监视的键和当时的版本号，`EXEC` 时检查它们是否被修改过。
+    pub(crate) watched: WatchedKeys,
+
+    /// 连接在已连接的客户端中的登记，保存连接的 id 和由 `CLIENT SETNAME` 设置的名称。
+    pub(crate) client: ClientHandle,
+}
+
+impl ConnState {
+    /// 新连接的状态：不在事务中，没有监视任何键，在已连接的客户端中登记为 `client`。
+    pub(crate) fn new(client: ClientHandle) -> ConnState {
+        ConnState {
+            transaction: None,
+            watched: WatchedKeys::default(),
+            client,
+        }
+    }
+}
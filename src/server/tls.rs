@@ -0,0 +1,51 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+
+use crate::config::TlsConfig;
+use crate::error::MiniRedisConfigError;
+
+/// 读取 `tls` 中的证书链和私钥，创建服务端的 TLS 配置。
+///
+/// 文件无法读取、其中没有证书或私钥，或者私钥与证书不匹配时返回错误。
+pub(crate) fn load(tls: &TlsConfig) -> Result<Arc<ServerConfig>, MiniRedisConfigError> {
+    let certs = load_certs(&tls.cert_path)?;
+    let key = load_key(&tls.key_path)?;
+
+    let config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| MiniRedisConfigError::Tls(e.to_string()))?;
+    Ok(Arc::new(config))
+}
+
+// 读取 PEM 文件中的全部证书
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, MiniRedisConfigError> {
+    let mut reader = BufReader::new(open(path)?);
+    let certs = rustls_pemfile::certs(&mut reader).collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err(MiniRedisConfigError::Tls(format!(
+            "no certificate found in {}",
+            path.display()
+        )));
+    }
+    Ok(certs)
+}
+
+// 读取 PEM 文件中的第一个私钥
+fn load_key(path: &Path) -> Result<PrivateKeyDer<'static>, MiniRedisConfigError> {
+    let mut reader = BufReader::new(open(path)?);
+    rustls_pemfile::private_key(&mut reader)?.ok_or_else(|| {
+        MiniRedisConfigError::Tls(format!("no private key found in {}", path.display()))
+    })
+}
+
+// 打开文件，错误信息中带上文件的路径
+fn open(path: &Path) -> Result<File, MiniRedisConfigError> {
+    File::open(path)
+        .map_err(|e| MiniRedisConfigError::Tls(format!("can't open {}: {}", path.display(), e)))
+}
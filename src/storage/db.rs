@@ -1,19 +1,42 @@
 // 引入需要使用的标准库模块
-use std::sync::{Arc, Mutex};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak};
+use std::time::SystemTime;
 
 // 引入字节流库
 use bytes::Bytes;
 // 引入日志库中的info宏
-use log::info;
+use log::{debug, info};
 // 引入Tokio异步库中的广播和通知模块
 use tokio::sync::{broadcast, Notify};
 // 引入Tokio异步库中的时间相关模块
 use tokio::time::{self, Duration, Instant};
 
+// 引入服务器配置和过期回调
+use crate::config::{ExpireCallback, MaxmemoryPolicy, ServerConfig};
+// 引入指标记录器
+use crate::metrics::MetricsRecorder;
+// 引入pubsub.rs中的PubSub结构体
+use crate::storage::pubsub::PubSub;
 // 引入store.rs中的Store结构体
-use crate::storage::store::Store;
+use crate::storage::store::{ExpireCondition, IncrError, Store, Usage, Value, WrongType};
 // 引入traits.rs中的KvStore特性
-use crate::storage::traits::KvStore;
+use crate::storage::traits::{self, BoxFuture, KvStore};
+
+// 一组键值对
+type Pairs = Vec<(String, Bytes)>;
+
+// SCAN 游标中分片内键 id 占用的位数，高位是分片下标
+const SCAN_ID_BITS: u32 = 48;
+const SCAN_ID_MASK: u64 = (1 << SCAN_ID_BITS) - 1;
+
+// 由分片下标和分片内的键 id 组成 SCAN 游标
+fn scan_cursor(shard: usize, id: u64) -> u64 {
+    (shard as u64) << SCAN_ID_BITS | id
+}
 
 // Debug 和 Clone trait 都自动实现
 #[derive(Debug, Clone)]
@@ -25,16 +48,180 @@ pub(crate) struct Db {
 // 实现Db结构体
 impl Db {
     /// 创建一个新的 `Db` 实例。
-    pub(crate) fn new() -> Db {
+    pub(crate) fn new(config: &ServerConfig) -> Db {
         // 创建一个新的SharedDb实例，并使用Arc进行包裹
-        let shared = Arc::new(SharedDb::new());
+        let shared = Arc::new(SharedDb::new(config));
         // 使用Tokio异步库启动一个任务来清理过期键
         tokio::spawn(Db::purge_expired_tasks(shared.clone()));
+        // 定期整理键空间，释放大量删除之后多余的内存
+        if let Some(interval) = config.compact_interval {
+            tokio::spawn(Db::compact_task(Arc::downgrade(&shared), interval));
+        }
 
         // 返回创建好的Db实例
         Db { shared }
     }
 
+    /// 返回所有分片中的键数量之和，包括已经过期但还未被清理的键。
+    pub(crate) fn len(&self) -> usize {
+        self.shared.keys.load(Ordering::SeqCst)
+    }
+
+    /// 返回所有分片中的键和值占用的字节数之和，是一个近似值，见 `Store::usage`。
+    ///
+    /// 与键数量一样在每次写入之后更新，读取时不需要锁住任何分片。
+    pub(crate) fn used_memory(&self) -> usize {
+        self.shared.memory.load(Ordering::SeqCst)
+    }
+
+    /// 返回所有没有过期的字符串键、值和剩余生存时间，没有设置过期时间的键为 `None`。
+    ///
+    /// 每次只获取一个分片的读锁，复制期间其他分片上的写入不会被阻塞，因此同一个分片中的键是同一时刻的快照，
+    /// 不同分片之间则不是。列表、哈希表和集合没有单一的字节表示，不包括在结果中。
+    /// 不会更新键的访问时间，结果没有特定的顺序。
+    pub(crate) fn iter_entries(&self) -> Vec<(String, Bytes, Option<Duration>)> {
+        let mut entries = Vec::new();
+        for shard in &self.shared.shards {
+            let store = shard.read().unwrap();
+            store.collect_entries(Instant::now(), &mut entries);
+        }
+        entries
+    }
+
+    /// 打开或者关闭键空间事件，之后的写入立即按照新的设置发布事件。
+    ///
+    /// 配置重新加载时调用，见 [`ServerConfig::notify_keyspace_events`]。
+    pub(crate) fn set_notify_keyspace_events(&self, enabled: bool) {
+        self.shared
+            .notify_keyspace_events
+            .store(enabled, Ordering::Relaxed);
+    }
+
+    /// 设置键过期时的回调，替换之前设置的回调，包括 [`ServerConfig::on_expire`]。
+    ///
+    /// 后台任务清理过期的键，或者命令访问到已经过期但还未被清理的键并删除它时，都会调用回调。
+    /// 回调在释放存储锁之后调用，因此可以在回调中再次访问数据库。
+    pub(crate) fn on_expire(&self, callback: impl Fn(&str) + Send + Sync + 'static) {
+        *self.shared.on_expire.write().unwrap() = Some(ExpireCallback::new(callback));
+    }
+
+    /// 返回键当前的版本号，键不存在或者已经过期时返回 `None`。
+    ///
+    /// 每次写入键都会得到一个新的版本号，`WATCH` 记下它，`EXEC` 时比较版本号判断键是否被修改过。
+    pub(crate) fn version(&self, key: &str) -> Option<u64> {
+        self.shared.read_shard(key).version(key)
+    }
+
+    /// 按照 `policy` 删除键，直到键和值占用的内存不超过 `max_memory`。
+    ///
+    /// 轮流从每个分片删除一个键，每次只锁住一个分片。返回是否回到了限制以内，
+    /// 没有可以删除的键时返回 `false`，例如策略是 `NoEviction` 或者只删除设置了过期时间的键但没有这样的键。
+    pub(crate) fn evict(&self, policy: MaxmemoryPolicy, max_memory: usize) -> bool {
+        loop {
+            let mut evicted = false;
+            for shard in &self.shared.shards {
+                if self.used_memory() <= max_memory {
+                    return true;
+                }
+
+                let mut store = shard.write().unwrap();
+                let before = store.usage();
+                if let Some(key) = store.evict(policy) {
+                    debug!("evicted key {} by policy {}", key, policy.as_str());
+                    let after = store.usage();
+                    drop(store);
+                    self.shared.update_usage(before, after);
+                    evicted = true;
+                }
+            }
+
+            if !evicted {
+                return self.used_memory() <= max_memory;
+            }
+        }
+    }
+
+    /// 是否设置了 `maxmemory` 并且按照 `maxmemory_policy` 删除键之后，键和值占用的内存仍然超过它。
+    ///
+    /// 服务器在执行可能占用更多内存的命令之前调用，包括 `EXEC` 中排队的每个命令。
+    pub(crate) fn over_max_memory(&self, config: &RwLock<ServerConfig>) -> bool {
+        let (max_memory, policy) = {
+            let config = config.read().unwrap();
+            (config.max_memory, config.maxmemory_policy)
+        };
+        match max_memory {
+            Some(max_memory) if self.used_memory() > max_memory => !self.evict(policy, max_memory),
+            _ => false,
+        }
+    }
+
+    /// 删除所有分片中的所有键，发布订阅的频道不受影响。
+    ///
+    /// 按下标升序锁住所有分片之后再一起清空，其他客户端不会看到只清空了一部分分片的状态。
+    pub(crate) fn flush_all(&self) {
+        let mut locked: Vec<RwLockWriteGuard<'_, Store>> = self
+            .shared
+            .shards
+            .iter()
+            .map(|shard| shard.write().unwrap())
+            .collect();
+        let mut before = Usage::default();
+        for store in locked.iter_mut() {
+            before += store.usage();
+            store.flush();
+        }
+
+        drop(locked);
+        self.shared.update_usage(before, Usage::default());
+    }
+
+    /// 从 `keys` 中第一个非空的列表移除一个值，所有列表都为空时阻塞等待，`front` 决定从头部还是尾部移除。
+    ///
+    /// 返回值所在的键和值，等待超过 `timeout` 时返回 `None`，`timeout` 为 `None` 表示一直等待。
+    /// 只有向这些键插入值时才会被唤醒。同一个键上的所有等待者都会被唤醒并在分片的锁内重新尝试移除，
+    /// 值已经被其他客户端取走时继续等待。
+    pub(crate) async fn blocking_pop(
+        &self,
+        keys: &[String],
+        front: bool,
+        timeout: Option<Duration>,
+    ) -> Result<Option<(String, Bytes)>, WrongType> {
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        // 先登记再检查列表：检查之后、开始等待之前插入的值会在 `Notify` 中留下一个许可，唤醒不会丢失
+        let blocked = Blocked::new(&self.shared, keys);
+
+        loop {
+            for key in keys {
+                let value = if front {
+                    self.lpop(key)?
+                } else {
+                    self.rpop(key)?
+                };
+                if let Some(value) = value {
+                    return Ok(Some((key.clone(), value)));
+                }
+            }
+
+            // 每次等待都使用同一个截止时间，醒来之后没有取到值时只等待剩余的时间
+            match deadline {
+                Some(deadline) => {
+                    if time::timeout_at(deadline, blocked.notify.notified())
+                        .await
+                        .is_err()
+                    {
+                        return Ok(None);
+                    }
+                }
+                None => blocked.notify.notified().await,
+            }
+        }
+    }
+
+    /// 数据库是否可以处理请求，即后台清理任务没有被关闭。
+    pub(crate) fn is_ready(&self) -> bool {
+        !self.shared.is_shutdown()
+    }
+
     /// 异步任务：清理过期键的方法。
     async fn purge_expired_tasks(shared: Arc<SharedDb>) {
         // 当共享的数据库未关闭时
@@ -56,15 +243,27 @@ impl Db {
         info!("Purge background task shut down");
     }
 
+    /// 异步任务：每隔 `interval` 整理一次键空间。
+    ///
+    /// 只持有数据库的弱引用，数据库被释放或者关闭之后任务在下一次醒来时退出。
+    async fn compact_task(shared: Weak<SharedDb>, interval: Duration) {
+        loop {
+            time::sleep(interval).await;
+
+            let shared = match shared.upgrade() {
+                Some(shared) if !shared.is_shutdown() => shared,
+                _ => break,
+            };
+            shared.compact();
+        }
+        info!("Compact background task shut down");
+    }
+
     /// 关闭清理任务的方法。
     fn shutdown_purge_task(&self) {
-        // 获取存储层的互斥锁来修改共享数据
-        let mut store = self.shared.store.lock().unwrap();
         // 设置存储层为关闭状态
-        store.set_shutdown(true);
+        self.shared.shutdown.store(true, Ordering::SeqCst);
 
-        // 释放锁
-        drop(store);
         // 通知后台任务
         self.shared.background_task.notify_one();
     }
@@ -73,22 +272,91 @@ impl Db {
 // 实现KvStore特性为Db
 impl KvStore for Db {
     /// 获取指定键的值。
-    fn get(&self, key: &str) -> Option<Bytes> {
-        // 获取存储层的互斥锁
-        let store = self.shared.store.lock().unwrap();
+    fn get(&self, key: &str) -> Result<Option<Bytes>, WrongType> {
+        // 获取键所在分片的读锁，读操作之间不会互相阻塞
+        let store = self.shared.read_shard(key);
         // 调用存储层的get方法获取键的值
-        store.get(key)
+        store.get_typed(key)
+    }
+
+    /// 获取指定键的剩余生存时间。
+    fn ttl(&self, key: &str) -> Option<Option<Duration>> {
+        let store = self.shared.read_shard(key);
+        store.ttl(key)
+    }
+
+    /// 访问指定的键，返回键是否存在。
+    fn touch(&self, key: &str) -> bool {
+        let store = self.shared.read_shard(key);
+        store.touch(key)
+    }
+
+    /// 返回键的值的编码名称。
+    fn object_encoding(&self, key: &str) -> Option<&'static str> {
+        let store = self.shared.read_shard(key);
+        store.encoding(key)
+    }
+
+    /// 返回键距离上次被访问经过的时间。
+    fn object_idle_time(&self, key: &str) -> Option<Duration> {
+        let store = self.shared.read_shard(key);
+        store.idle_time(key)
+    }
+
+    /// 估算键占用的字节数。
+    fn memory_usage(&self, key: &str) -> Option<usize> {
+        let store = self.shared.read_shard(key);
+        store.memory_usage(key)
+    }
+
+    /// 从游标 `cursor` 开始遍历键空间。
+    ///
+    /// 游标的高 16 位是分片下标，低 48 位是分片内的键 id。分片内按 id 顺序遍历，
+    /// 遍历期间一直存在的键一定会被返回，期间被重新设置的键可能会被返回多次。
+    fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<String>) {
+        let mut shard = (cursor >> SCAN_ID_BITS) as usize;
+        let mut id = cursor & SCAN_ID_MASK;
+        let mut keys = vec![];
+        let mut remaining = count.max(1);
+
+        while shard < self.shared.shards.len() {
+            let store = self.shared.shards[shard].read().unwrap();
+            let (visited, next) = store.scan(id, remaining, &mut keys);
+            drop(store);
+            remaining -= visited;
+
+            // 当前分片还有没遍历的键，下一次从这里继续
+            if let Some(next) = next {
+                return (scan_cursor(shard, next), keys);
+            }
+
+            // 当前分片已经遍历完，从下一个分片的开头继续
+            shard += 1;
+            id = 0;
+            if remaining == 0 && shard < self.shared.shards.len() {
+                return (scan_cursor(shard, 0), keys);
+            }
+        }
+
+        (0, keys)
     }
 
     /// 设置键值对和可选的过期时间。
     fn set(&self, key: String, value: Bytes, expire: Option<Duration>) {
-        // 获取存储层的互斥锁
-        let mut store = self.shared.store.lock().unwrap();
+        let event_key = self.shared.keyspace_events_enabled().then(|| key.clone());
+        // 获取键所在分片的写锁
+        let mut store = self.shared.write_shard(&key);
+        let before = store.usage();
         // 调用存储层的set方法设置键值对
         let notify = store.set(key, value, expire);
+        let after = store.usage();
 
-        // 释放存储层的互斥锁
+        // 释放分片的写锁
         drop(store);
+        self.shared.update_usage(before, after);
+        if let Some(key) = event_key {
+            self.shared.notify_keyspace_event("set", &key);
+        }
 
         // 如果需要通知后台任务，则发送通知
         if notify {
@@ -96,62 +364,712 @@ impl KvStore for Db {
         }
     }
 
+    /// 设置键值对，保留键原有的过期时间。
+    fn set_keep_ttl(&self, key: String, value: Bytes) {
+        let event_key = self.shared.keyspace_events_enabled().then(|| key.clone());
+        let mut store = self.shared.write_shard(&key);
+        let before = store.usage();
+        store.set_keep_ttl(key, value);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        if let Some(key) = event_key {
+            self.shared.notify_keyspace_event("set", &key);
+        }
+    }
+
+    /// 为已存在的键设置过期时间。
+    fn expire(&self, key: &str, expire: Duration, condition: ExpireCondition) -> bool {
+        let mut store = self.shared.write_shard(key);
+        let before = store.usage();
+        let notify = store.expire(key, Instant::now() + expire, condition);
+        let after = store.usage();
+
+        drop(store);
+        // 过期时间为零时键被删除
+        self.shared.update_usage(before, after);
+
+        // 新的过期时间早于后台任务等待的时间点时，唤醒它重新计算
+        if notify == Some(true) {
+            self.shared.background_task.notify_one();
+        }
+        notify.is_some()
+    }
+
+    /// 为已存在的键设置绝对的过期时间点。
+    ///
+    /// 存储中的过期时间使用单调时钟，这里先换算为距离现在的时长，之后系统时间的调整不会影响这个键。
+    fn expire_at(&self, key: &str, when: SystemTime) -> bool {
+        match when.duration_since(SystemTime::now()) {
+            Ok(expire) if !expire.is_zero() => self.expire(key, expire, ExpireCondition::Always),
+            // 时间点已经过去，直接删除
+            _ => self.del(key.to_string()) > 0,
+        }
+    }
+
+    /// 原子地设置多个键值对。
+    ///
+    /// 与 `SET` 一样，按参数的顺序为每个键发布一个 `set` 事件。
+    fn mset(&self, pairs: Vec<(String, Bytes)>) {
+        let event_keys: Vec<String> = if self.shared.keyspace_events_enabled() {
+            pairs.iter().map(|(key, _)| key.clone()).collect()
+        } else {
+            Vec::new()
+        };
+
+        // 按分片分组，BTreeMap 保证按分片下标升序加锁
+        let mut by_shard: BTreeMap<usize, Pairs> = BTreeMap::new();
+        for (key, value) in pairs {
+            by_shard
+                .entry(self.shared.shard_index(&key))
+                .or_default()
+                .push((key, value));
+        }
+
+        // 先锁住涉及的所有分片再写入，其他客户端不会看到部分写入的状态。
+        // 所有跨分片的操作都按下标升序加锁，因此不会死锁。
+        let mut locked: Vec<(RwLockWriteGuard<'_, Store>, Pairs)> = by_shard
+            .into_iter()
+            .map(|(idx, pairs)| (self.shared.shards[idx].write().unwrap(), pairs))
+            .collect();
+
+        let mut before = Usage::default();
+        let mut after = Usage::default();
+        for (store, pairs) in locked.iter_mut() {
+            before += store.usage();
+            for (key, value) in pairs.drain(..) {
+                store.set(key, value, None);
+            }
+            after += store.usage();
+        }
+
+        drop(locked);
+        self.shared.update_usage(before, after);
+        for key in &event_keys {
+            self.shared.notify_keyspace_event("set", key);
+        }
+    }
+
+    /// 获取指定键的值并删除该键，两个操作在同一把锁内完成。
+    ///
+    /// 与 `DEL` 一样，删除了键时发布 `del` 事件；已经过期的键视为不存在，不会发布事件。
+    fn get_del(&self, key: &str) -> Result<Option<Bytes>, WrongType> {
+        let mut store = self.shared.write_shard(key);
+        let before = store.usage();
+        let value = store.get_del(key);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        if let Ok(Some(_)) = value {
+            self.shared.notify_keyspace_event("del", key);
+        }
+        value
+    }
+
+    /// 将 `value` 追加到键原有的值之后，返回追加后值的长度，超过 `max_len` 时不做修改。
+    fn append(
+        &self,
+        key: String,
+        value: Bytes,
+        max_len: usize,
+    ) -> Result<Option<usize>, WrongType> {
+        let mut store = self.shared.write_shard(&key);
+        let before = store.usage();
+        let len = store.append(key, value, max_len);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        len
+    }
+
+    /// 将字符串解析为浮点数并加上 `delta`，返回相加之后的值。
+    fn incr_by_float(&self, key: String, delta: f64) -> Result<f64, IncrError> {
+        let mut store = self.shared.write_shard(&key);
+        let before = store.usage();
+        let value = store.incr_by_float(key, delta);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        value
+    }
+
+    /// 从 `offset` 开始用 `value` 覆盖字符串的一部分，返回修改后字符串的长度。
+    fn setrange(&self, key: String, offset: usize, value: Bytes) -> Result<usize, WrongType> {
+        let mut store = self.shared.write_shard(&key);
+        let before = store.usage();
+        let len = store.setrange(key, offset, value);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        len
+    }
+
+    /// 将字符串中第 `offset` 位设置为 `on`，返回这一位原来的值。
+    fn setbit(&self, key: String, offset: usize, on: bool) -> Result<bool, WrongType> {
+        let mut store = self.shared.write_shard(&key);
+        let before = store.usage();
+        let old = store.setbit(key, offset, on);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        old
+    }
+
+    /// 返回字符串中第 `offset` 位的值。
+    fn getbit(&self, key: &str, offset: usize) -> Result<bool, WrongType> {
+        let store = self.shared.read_shard(key);
+        store.getbit(key, offset)
+    }
+
+    /// 将 `values` 依次插入列表的头部，返回插入后列表的长度。
+    fn lpush(&self, key: String, values: Vec<Bytes>) -> Result<usize, WrongType> {
+        let mut store = self.shared.write_shard(&key);
+        let before = store.usage();
+        let len = store.lpush(key.clone(), values);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        if len.is_ok() {
+            self.shared.wake_blocked(&key);
+        }
+        len
+    }
+
+    /// 将 `values` 依次插入列表的尾部，返回插入后列表的长度。
+    fn rpush(&self, key: String, values: Vec<Bytes>) -> Result<usize, WrongType> {
+        let mut store = self.shared.write_shard(&key);
+        let before = store.usage();
+        let len = store.rpush(key.clone(), values);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        if len.is_ok() {
+            self.shared.wake_blocked(&key);
+        }
+        len
+    }
+
+    /// 移除并返回列表头部的值。
+    fn lpop(&self, key: &str) -> Result<Option<Bytes>, WrongType> {
+        let mut store = self.shared.write_shard(key);
+        let before = store.usage();
+        let value = store.lpop(key);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        value
+    }
+
+    /// 移除并返回列表尾部的值。
+    fn rpop(&self, key: &str) -> Result<Option<Bytes>, WrongType> {
+        let mut store = self.shared.write_shard(key);
+        let before = store.usage();
+        let value = store.rpop(key);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        value
+    }
+
+    /// 返回列表中下标从 `start` 到 `stop` 的值。
+    fn lrange(&self, key: &str, start: i64, stop: i64) -> Result<Vec<Bytes>, WrongType> {
+        let store = self.shared.read_shard(key);
+        store.lrange(key, start, stop)
+    }
+
+    /// 返回列表的长度。
+    fn llen(&self, key: &str) -> Result<usize, WrongType> {
+        let store = self.shared.read_shard(key);
+        store.llen(key)
+    }
+
+    /// 设置哈希表中的字段，返回新增的字段数量。
+    fn hset(&self, key: String, pairs: Vec<(String, Bytes)>) -> Result<usize, WrongType> {
+        let mut store = self.shared.write_shard(&key);
+        let before = store.usage();
+        let added = store.hset(key, pairs);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        added
+    }
+
+    /// 获取哈希表中字段的值。
+    fn hget(&self, key: &str, field: &str) -> Result<Option<Bytes>, WrongType> {
+        let store = self.shared.read_shard(key);
+        store.hget(key, field)
+    }
+
+    /// 判断哈希表中是否存在字段。
+    fn hexists(&self, key: &str, field: &str) -> Result<bool, WrongType> {
+        let store = self.shared.read_shard(key);
+        store.hexists(key, field)
+    }
+
+    /// 获取哈希表中所有的字段和值。
+    fn hgetall(&self, key: &str) -> Result<Vec<(String, Bytes)>, WrongType> {
+        let store = self.shared.read_shard(key);
+        store.hgetall(key)
+    }
+
+    /// 删除哈希表中的字段，返回实际删除的字段数量。
+    fn hdel(&self, key: &str, fields: &[String]) -> Result<usize, WrongType> {
+        let mut store = self.shared.write_shard(key);
+        let before = store.usage();
+        let removed = store.hdel(key, fields);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        removed
+    }
+
+    /// 返回哈希表中字段的数量。
+    fn hlen(&self, key: &str) -> Result<usize, WrongType> {
+        let store = self.shared.read_shard(key);
+        store.hlen(key)
+    }
+
+    /// 向集合中添加成员，返回新增的成员数量。
+    fn sadd(&self, key: String, members: Vec<Bytes>) -> Result<usize, WrongType> {
+        let mut store = self.shared.write_shard(&key);
+        let before = store.usage();
+        let added = store.sadd(key, members);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        added
+    }
+
+    /// 获取集合中所有的成员。
+    fn smembers(&self, key: &str) -> Result<Vec<Bytes>, WrongType> {
+        let store = self.shared.read_shard(key);
+        store.smembers(key)
+    }
+
+    /// 判断成员是否在集合中。
+    fn sismember(&self, key: &str, member: &[u8]) -> Result<bool, WrongType> {
+        let store = self.shared.read_shard(key);
+        store.sismember(key, member)
+    }
+
+    /// 将 `src` 的值和剩余生存时间移动到 `dst`。
+    fn rename(&self, src: &str, dst: String) -> bool {
+        let src_idx = self.shared.shard_index(src);
+        let dst_idx = self.shared.shard_index(&dst);
+
+        // 两个键在同一个分片中，直接在分片内移动
+        if src_idx == dst_idx {
+            let mut store = self.shared.shards[src_idx].write().unwrap();
+            let before = store.usage();
+            let renamed = store.rename(src, dst);
+            let after = store.usage();
+
+            drop(store);
+            self.shared.update_usage(before, after);
+            return renamed;
+        }
+
+        // 两个键在不同的分片中，按下标升序锁住两个分片，避免死锁
+        let first = self.shared.shards[src_idx.min(dst_idx)].write().unwrap();
+        let second = self.shared.shards[src_idx.max(dst_idx)].write().unwrap();
+        let (mut src_store, mut dst_store) = if src_idx < dst_idx {
+            (first, second)
+        } else {
+            (second, first)
+        };
+
+        // 已经过期但还未被清理的 src 视为不存在，不覆盖 dst
+        if !src_store.touch(src) {
+            return false;
+        }
+        let before = src_store.usage() + dst_store.usage();
+        let (value, expires_at) = match src_store.remove_entry(src) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        // 在目标分片中以相同的过期时间点重新插入
+        let notify = dst_store.set_at(dst, value, expires_at);
+        let after = src_store.usage() + dst_store.usage();
+
+        drop(src_store);
+        drop(dst_store);
+        self.shared.update_usage(before, after);
+
+        if notify {
+            self.shared.background_task.notify_one();
+        }
+        true
+    }
+
+    /// 序列化键的值。
+    fn dump(&self, key: &str) -> Option<Bytes> {
+        let store = self.shared.read_shard(key);
+        store.dump(key)
+    }
+
+    /// 使用 `DUMP` 得到的值创建键。
+    fn restore(&self, key: String, value: Value, expire: Option<Duration>, replace: bool) -> bool {
+        let mut store = self.shared.write_shard(&key);
+        let before = store.usage();
+        let notify = store.restore(
+            key.clone(),
+            value,
+            expire.map(|expire| Instant::now() + expire),
+            replace,
+        );
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+
+        if notify == Some(true) {
+            self.shared.background_task.notify_one();
+        }
+        if notify.is_some() {
+            // 恢复的值可能是列表，唤醒阻塞在这个键上的客户端
+            self.shared.wake_blocked(&key);
+        }
+        notify.is_some()
+    }
+
     /// 删除指定键
-    fn del(&self, key: String) -> usize{
-        let mut store = self.shared.store.lock().unwrap();
-        store.del(&key)
+    fn del(&self, key: String) -> usize {
+        let mut store = self.shared.write_shard(&key);
+        let before = store.usage();
+        let deleted = store.del(&key);
+        let after = store.usage();
+
+        drop(store);
+        self.shared.update_usage(before, after);
+        if deleted > 0 {
+            self.shared.notify_keyspace_event("del", &key);
+        }
+        deleted
+    }
+
+    /// 删除指定的键，返回它们的值。
+    fn unlink(&self, keys: &[String]) -> Vec<Value> {
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            let mut store = self.shared.write_shard(key);
+            let before = store.usage();
+            let removed = store.remove_entry(key);
+            let after = store.usage();
+
+            drop(store);
+            self.shared.update_usage(before, after);
+            if let Some((value, _)) = removed {
+                self.shared.notify_keyspace_event("del", key);
+                values.push(value);
+            }
+        }
+        values
     }
 
     /// 订阅指定键的消息。
     fn subscribe(&self, key: String) -> broadcast::Receiver<Bytes> {
-        // 获取存储层的互斥锁
-        let mut store = self.shared.store.lock().unwrap();
-        // 调用存储层的subscribe方法订阅消息
-        store.subscribe(key)
+        // 获取频道表的写锁
+        let mut pub_sub = self.shared.pub_sub.write().unwrap();
+        // 调用频道表的subscribe方法订阅消息
+        pub_sub.subscribe(key)
     }
 
     /// 发布指定键的消息。
     fn publish(&self, key: &str, value: Bytes) -> usize {
-        // 获取存储层的互斥锁
-        let state = self.shared.store.lock().unwrap();
-        // 调用存储层的publish方法发布消息
-        state.publish(key, value)
+        // 发布只需要读取频道表，`broadcast::Sender::send` 只需要 `&self`，因此获取读锁即可
+        let pub_sub = self.shared.pub_sub.read().unwrap();
+        // 调用频道表的publish方法发布消息
+        pub_sub.publish(key, value)
+    }
+
+    /// 发布指定键的消息，同时统计没有订阅者的频道数量。
+    fn publish_detailed(&self, key: &str, value: Bytes) -> (usize, usize) {
+        // 在同一把读锁下统计，两个数字来自同一时刻的频道表
+        let pub_sub = self.shared.pub_sub.read().unwrap();
+        let receivers = pub_sub.publish(key, value);
+        (receivers, pub_sub.stale_channels())
+    }
+}
+
+// 内存中的数据不需要等待 I/O，异步的方法直接调用同步的实现。
+// 这里不导入 `AsyncKvStore`，避免与 `KvStore` 的同名方法产生歧义
+impl traits::AsyncKvStore for Db {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Bytes>, WrongType>> {
+        Box::pin(async move { KvStore::get(self, key) })
+    }
+
+    fn set(&self, key: String, value: Bytes, expire: Option<Duration>) -> BoxFuture<'_, ()> {
+        Box::pin(async move { KvStore::set(self, key, value, expire) })
+    }
+
+    fn set_keep_ttl(&self, key: String, value: Bytes) -> BoxFuture<'_, ()> {
+        Box::pin(async move { KvStore::set_keep_ttl(self, key, value) })
+    }
+
+    fn del(&self, key: String) -> BoxFuture<'_, usize> {
+        Box::pin(async move { KvStore::del(self, key) })
     }
 }
 
 // SharedDb结构体定义
+//
+// 键值数据被分成多个分片，每个分片是一把独立的 `RwLock<Store>`，根据键的哈希选择分片。
+// 访问单个键的命令只锁住它所在的分片，不同分片上的写入可以并发进行。
+// 只读命令（`GET`、`TTL` 等）获取读锁，同一分片上的读操作之间也不会互相阻塞。
+//
+// 代价是跨多个键的命令（例如 `MSET`）需要同时锁住涉及的所有分片，
+// 为了避免死锁，这些分片总是按下标升序加锁。这类命令仍然是原子的，
+// 但会与这些分片上的所有其他命令串行执行，涉及的分片越多，开销越大。
+// 发布订阅的频道与键无关，使用单独的锁，不参与分片。
 #[derive(Debug)]
 struct SharedDb {
-    // 存储层的互斥锁
-    store: Mutex<Store>,
+    // 键值存储的分片，每个分片有自己的读写锁
+    shards: Vec<RwLock<Store>>,
+    // 发布订阅频道表的读写锁
+    pub_sub: RwLock<PubSub>,
+    // 所有分片中的键数量之和
+    keys: AtomicUsize,
+    // 所有分片中的键和值占用的字节数之和
+    memory: AtomicUsize,
+    // 被 BLPOP 等命令阻塞的客户端，按等待的键分组
+    blocked: Mutex<HashMap<String, Vec<Arc<Notify>>>>,
+    // 数据库是否已关闭。关闭后后台清理任务退出
+    shutdown: AtomicBool,
     // 后台任务的通知机制
     background_task: Notify,
+    // 指标记录器，用于上报键数量等仪表
+    metrics: Arc<dyn MetricsRecorder>,
+    // 键过期时的回调，可以由 `Db::on_expire` 随时替换
+    on_expire: RwLock<Option<ExpireCallback>>,
+    // 是否发布键空间事件，重新加载配置时由 `Db::set_notify_keyspace_events` 修改
+    notify_keyspace_events: AtomicBool,
 }
 
 // 实现SharedDb
 impl SharedDb {
     /// 创建一个新的 `SharedDb` 实例。
-    fn new() -> Self {
-        // 初始化存储层
+    fn new(config: &ServerConfig) -> Self {
+        // 至少需要一个分片
+        let shards = (0..config.shards.max(1))
+            .map(|_| RwLock::new(Store::new()))
+            .collect();
+
         SharedDb {
-            store: Mutex::new(Store::new()),
+            shards,
+            pub_sub: RwLock::new(PubSub::new()),
+            keys: AtomicUsize::new(0),
+            memory: AtomicUsize::new(0),
+            blocked: Mutex::new(HashMap::new()),
+            shutdown: AtomicBool::new(false),
             // 初始化后台任务的通知
             background_task: Notify::new(),
+            metrics: config.metrics.clone(),
+            on_expire: RwLock::new(config.on_expire.clone()),
+            notify_keyspace_events: AtomicBool::new(config.notify_keyspace_events),
         }
     }
 
-    /// 清理过期的键。
+    /// 返回键所在分片的下标。
+    fn shard_index(&self, key: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    /// 获取键所在分片的读锁，`key` 已经过期时先删除它，见 `expire_if_needed`。
+    fn read_shard(&self, key: &str) -> RwLockReadGuard<'_, Store> {
+        self.expire_if_needed(key);
+        self.shards[self.shard_index(key)].read().unwrap()
+    }
+
+    /// 获取键所在分片的写锁，`key` 已经过期时先删除它，见 `expire_if_needed`。
+    fn write_shard(&self, key: &str) -> RwLockWriteGuard<'_, Store> {
+        self.expire_if_needed(key);
+        self.shards[self.shard_index(key)].write().unwrap()
+    }
+
+    /// 惰性删除：`key` 已经过期但还未被后台任务清理时立即删除它，并调用过期回调。
+    ///
+    /// 大部分访问的键都没有过期，先用读锁检查，只有确实过期时才获取写锁。
+    /// 回调在释放写锁之后调用，调用者随后再获取分片的锁。
+    fn expire_if_needed(&self, key: &str) {
+        let shard = &self.shards[self.shard_index(key)];
+        if !shard.read().unwrap().is_expired(key) {
+            return;
+        }
+
+        let mut store = shard.write().unwrap();
+        // 释放读锁之后键可能已经被其他命令覆盖或者被后台任务清理，需要再次检查
+        if !store.is_expired(key) {
+            return;
+        }
+        let before = store.usage();
+        store.remove_entry(key);
+        let after = store.usage();
+
+        drop(store);
+        self.update_usage(before, after);
+        if let Some(on_expire) = self.expire_callback() {
+            on_expire.call(key);
+        }
+    }
+
+    /// 返回当前的过期回调，克隆之后立即释放锁，回调中可以再次调用 `Db::on_expire`。
+    fn expire_callback(&self) -> Option<ExpireCallback> {
+        self.on_expire.read().unwrap().clone()
+    }
+
+    /// 是否开启了键空间事件，配置重新加载之后立即生效。
+    fn keyspace_events_enabled(&self) -> bool {
+        self.notify_keyspace_events.load(Ordering::Relaxed)
+    }
+
+    /// 开启了键空间事件时，向频道 `__keyevent@0__:<event>` 发布键名。
+    ///
+    /// 频道表使用单独的锁，调用者应当先释放分片的锁，订阅者收到事件时键已经修改完成。
+    fn notify_keyspace_event(&self, event: &str, key: &str) {
+        if !self.keyspace_events_enabled() {
+            return;
+        }
+        let channel = format!("__keyevent@0__:{}", event);
+        let pub_sub = self.pub_sub.read().unwrap();
+        pub_sub.publish(&channel, Bytes::copy_from_slice(key.as_bytes()));
+    }
+
+    /// 根据一次操作前后分片中的键数量和内存更新总数，并上报键数量的指标。
+    fn update_usage(&self, before: Usage, after: Usage) {
+        let keys = update_counter(&self.keys, before.keys, after.keys);
+        update_counter(&self.memory, before.memory, after.memory);
+        self.metrics.set_gauge("keys", keys as u64);
+    }
+
+    /// 唤醒所有等待 `key` 的阻塞客户端。
+    fn wake_blocked(&self, key: &str) {
+        if let Some(waiters) = self.blocked.lock().unwrap().get(key) {
+            for notify in waiters {
+                notify.notify_one();
+            }
+        }
+    }
+
+    /// 清理所有分片中过期的键，返回下一个最早的过期时间点。
     fn purge_expired_keys(&self) -> Option<Instant> {
-        // 获取存储层的互斥锁
-        let mut store = self.store.lock().unwrap();
-        // 调用存储层的purge_expired_keys方法清理过期的键
-        store.purge_expired_keys()
+        // 如果数据库已经关闭，则返回None，表示不执行过期清理操作
+        if self.is_shutdown() {
+            return None;
+        }
+
+        let mut next: Option<Instant> = None;
+        let mut expired = vec![];
+        for shard in &self.shards {
+            // 清理会修改分片，需要写锁，每次只锁住一个分片
+            let mut store = shard.write().unwrap();
+            let before = store.usage();
+            // 调用存储层的purge_expired_keys方法清理过期的键
+            let when = store.purge_expired_keys(&mut expired);
+            let after = store.usage();
+
+            drop(store);
+            if before != after {
+                self.update_usage(before, after);
+            }
+
+            // 释放锁之后再调用回调，回调中再次访问数据库也不会死锁
+            if let Some(on_expire) = self.expire_callback() {
+                for key in expired.drain(..) {
+                    on_expire.call(&key);
+                }
+            } else {
+                expired.clear();
+            }
+            next = match (next, when) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+        next
+    }
+
+    /// 整理所有分片，释放大量删除之后多余的内存。
+    ///
+    /// 每次只锁住一个分片，并且只在能立即获得写锁时整理，正在被其他命令使用的分片留到下一次。
+    fn compact(&self) {
+        let mut compacted = 0;
+        for shard in &self.shards {
+            if let Ok(mut store) = shard.try_write() {
+                if store.compact() {
+                    compacted += 1;
+                }
+            }
+        }
+        if compacted > 0 {
+            debug!("compacted {} shards", compacted);
+        }
     }
 
-    /// 检查存储层是否已关闭。
+    /// 检查数据库是否已关闭。
     fn is_shutdown(&self) -> bool {
-        // 调用存储层的is_shutdown方法检查是否已关闭
-        self.store.lock().unwrap().is_shutdown()
+        self.shutdown.load(Ordering::SeqCst)
+    }
+}
+
+// 按照一次操作前后的差值更新计数，返回更新之后的值。
+// 使用 `fetch_add` 和 `fetch_sub`，并发的更新不会互相覆盖
+fn update_counter(counter: &AtomicUsize, before: usize, after: usize) -> usize {
+    if after >= before {
+        counter.fetch_add(after - before, Ordering::SeqCst) + (after - before)
+    } else {
+        counter.fetch_sub(before - after, Ordering::SeqCst) - (before - after)
+    }
+}
+
+// 一个阻塞客户端在 `SharedDb::blocked` 中的登记，被释放时取消登记。
+// 客户端等待期间连接被关闭或者服务器关闭时，等待的 future 被丢弃，登记也随之取消。
+struct Blocked<'a> {
+    shared: &'a SharedDb,
+    keys: &'a [String],
+    notify: Arc<Notify>,
+}
+
+impl<'a> Blocked<'a> {
+    fn new(shared: &'a SharedDb, keys: &'a [String]) -> Blocked<'a> {
+        let notify = Arc::new(Notify::new());
+        let mut blocked = shared.blocked.lock().unwrap();
+        for key in keys {
+            blocked.entry(key.clone()).or_default().push(notify.clone());
+        }
+        Blocked {
+            shared,
+            keys,
+            notify,
+        }
+    }
+}
+
+impl Drop for Blocked<'_> {
+    fn drop(&mut self) {
+        let mut blocked = self.shared.blocked.lock().unwrap();
+        for key in self.keys {
+            if let Some(waiters) = blocked.get_mut(key) {
+                waiters.retain(|notify| !Arc::ptr_eq(notify, &self.notify));
+                if waiters.is_empty() {
+                    blocked.remove(key);
+                }
+            }
+        }
     }
 }
 
@@ -166,8 +1084,10 @@ pub(crate) struct DbDropGuard {
 impl DbDropGuard {
     /// 创建一个新的 `DbDropGuard`，封装一个 `Db` 实例。
     /// 当这个 `DbDropGuard` 被回收（dropped）时，将关闭 `Db` 的过期键清理任务。
-    pub(crate) fn new() -> DbDropGuard {
-        DbDropGuard { db: Db::new() }
+    pub(crate) fn new(config: &ServerConfig) -> DbDropGuard {
+        DbDropGuard {
+            db: Db::new(config),
+        }
     }
 
     /// 获取共享的数据库实例。内部实际上是一个 `Arc`，所以克隆操作只会增加引用计数。
@@ -183,3 +1103,268 @@ impl Drop for DbDropGuard {
         self.db.shutdown_purge_task();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::thread;
+
+    use super::*;
+
+    // 持有分片的读锁时，另一个线程仍然可以读取同一分片上的键
+    #[tokio::test]
+    async fn reads_do_not_block_each_other() {
+        let db = Db::new(&ServerConfig::default());
+        db.set("foo".into(), Bytes::from("bar"), None);
+
+        let guard = db.shared.read_shard("foo");
+
+        let (tx, rx) = mpsc::channel();
+        let reader = db.clone();
+        thread::spawn(move || {
+            tx.send((reader.get("foo"), reader.ttl("foo"))).unwrap();
+        });
+
+        let (value, ttl) = rx
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .expect("read blocked by another reader");
+        assert_eq!(Ok(Some(Bytes::from("bar"))), value);
+        assert_eq!(Some(None), ttl);
+
+        drop(guard);
+    }
+
+    // 多个线程并发地在同一组键上写入、删除和设置过期时间，停止之后键计数和内存与所有分片中的总和一致
+    #[tokio::test]
+    async fn key_count_matches_entries() {
+        let db = Db::new(&ServerConfig::default());
+
+        let workers: Vec<_> = (0..8)
+            .map(|worker| {
+                let db = db.clone();
+                thread::spawn(move || {
+                    for i in 0..2_000 {
+                        let key = format!("key:{}", (i * 7 + worker) % 64);
+                        match i % 6 {
+                            0 => db.set(key, Bytes::from("v"), Some(Duration::ZERO)),
+                            1 | 2 => db.set(key, Bytes::from("v"), None),
+                            3 => {
+                                db.rename(&key, format!("key:{}", i % 64));
+                            }
+                            4 => {
+                                db.hset(key, vec![("f".into(), Bytes::from("v"))]).ok();
+                            }
+                            _ => {
+                                db.del(key);
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        let usage = |db: &Db| -> Usage {
+            db.shared
+                .shards
+                .iter()
+                .map(|shard| shard.read().unwrap().usage())
+                .fold(Usage::default(), |total, usage| total + usage)
+        };
+        let totals = |db: &Db| Usage {
+            keys: db.len(),
+            memory: db.used_memory(),
+        };
+        assert_eq!(usage(&db), totals(&db));
+
+        // 清理过期键之后仍然一致
+        db.shared.purge_expired_keys();
+        assert_eq!(usage(&db), totals(&db));
+    }
+
+    // GETDEL 删除键时发布 del 事件，已经过期但还未被清理的键视为不存在，不返回值也不发布事件
+    #[tokio::test(start_paused = true)]
+    async fn get_del_skips_expired_key() {
+        let config = ServerConfig {
+            notify_keyspace_events: true,
+            ..ServerConfig::default()
+        };
+        // 不启动后台任务，过期的键不会被清理
+        let db = Db {
+            shared: Arc::new(SharedDb::new(&config)),
+        };
+        let mut events = db.subscribe("__keyevent@0__:del".into());
+
+        db.set("foo".into(), Bytes::from("1"), Some(Duration::from_secs(1)));
+        db.set("bar".into(), Bytes::from("2"), None);
+        time::advance(Duration::from_secs(2)).await;
+
+        assert_eq!(Ok(None), db.get_del("foo"));
+        assert_eq!(Ok(Some(Bytes::from("2"))), db.get_del("bar"));
+        assert_eq!(Bytes::from("bar"), events.try_recv().unwrap());
+        assert!(events.try_recv().is_err());
+    }
+
+    // 跨分片的 RENAME 同样把已经过期但还未被清理的 src 视为不存在，不覆盖 dst
+    #[tokio::test(start_paused = true)]
+    async fn rename_across_shards_skips_expired_src() {
+        // 不启动后台任务，过期的键不会被清理
+        let db = Db {
+            shared: Arc::new(SharedDb::new(&ServerConfig::default())),
+        };
+        let dst = (0..)
+            .map(|i| format!("dst{}", i))
+            .find(|dst| db.shared.shard_index(dst) != db.shared.shard_index("src"))
+            .unwrap();
+
+        db.set(
+            "src".into(),
+            Bytes::from("old"),
+            Some(Duration::from_secs(1)),
+        );
+        db.set(dst.clone(), Bytes::from("keep"), None);
+        time::advance(Duration::from_secs(2)).await;
+
+        assert!(!db.rename("src", dst.clone()));
+        assert_eq!(Ok(Some(Bytes::from("keep"))), db.get(&dst));
+    }
+
+    // 命令访问到已经过期但还未被清理的键时立即删除它，并调用 Db::on_expire 设置的回调
+    #[tokio::test(start_paused = true)]
+    async fn lazy_expiry_calls_on_expire() {
+        // 不启动后台任务，过期的键只能被惰性删除
+        let db = Db {
+            shared: Arc::new(SharedDb::new(&ServerConfig::default())),
+        };
+        let (tx, rx) = mpsc::channel();
+        db.on_expire(move |key| tx.send(key.to_string()).unwrap());
+
+        db.set("foo".into(), Bytes::from("1"), Some(Duration::from_secs(1)));
+        db.set("bar".into(), Bytes::from("2"), None);
+        time::advance(Duration::from_secs(2)).await;
+        assert_eq!(2, db.len());
+
+        assert_eq!(Ok(None), db.get("foo"));
+        assert_eq!(1, db.len());
+        assert_eq!("foo", rx.try_recv().unwrap());
+
+        // 已经删除的键和没有过期的键不会再触发回调
+        assert_eq!(Ok(None), db.get("foo"));
+        assert_eq!(Ok(Some(Bytes::from("2"))), db.get("bar"));
+        assert!(rx.try_recv().is_err());
+    }
+
+    // 快照包括所有没有过期的字符串键和它们的剩余生存时间，跳过过期的键和其他类型的值
+    #[tokio::test]
+    async fn iter_entries_snapshots_live_strings() {
+        let db = Db::new(&ServerConfig::default());
+        db.set("foo".into(), Bytes::from("1"), None);
+        db.set("bar".into(), Bytes::from("2"), None);
+        db.set(
+            "ttl".into(),
+            Bytes::from("3"),
+            Some(Duration::from_secs(100)),
+        );
+        db.set("expired".into(), Bytes::from("4"), Some(Duration::ZERO));
+        db.rpush("list".into(), vec![Bytes::from("5")]).unwrap();
+        db.hset("hash".into(), vec![("f".into(), Bytes::from("6"))])
+            .unwrap();
+        db.sadd("set".into(), vec![Bytes::from("7")]).unwrap();
+
+        let mut entries = db.iter_entries();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let keys: Vec<_> = entries
+            .iter()
+            .map(|(key, value, _)| (key.as_str(), value.clone()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("bar", Bytes::from("2")),
+                ("foo", Bytes::from("1")),
+                ("ttl", Bytes::from("3")),
+            ],
+            keys
+        );
+        assert_eq!(None, entries[0].2);
+        assert_eq!(None, entries[1].2);
+        let ttl = entries[2].2.unwrap();
+        assert!(ttl > Duration::from_secs(90) && ttl <= Duration::from_secs(100));
+    }
+
+    // 关闭后台任务之后数据库不再就绪
+    #[tokio::test]
+    async fn not_ready_after_shutdown() {
+        let db = Db::new(&ServerConfig::default());
+        assert!(db.is_ready());
+
+        db.shutdown_purge_task();
+        assert!(!db.is_ready());
+    }
+
+    // 大量写入再全部删除之后，整理任务会释放分片中多余的容量
+    #[tokio::test]
+    async fn compact_task_releases_capacity() {
+        let config = ServerConfig {
+            compact_interval: Some(Duration::from_millis(10)),
+            ..ServerConfig::default()
+        };
+        let db = Db::new(&config);
+
+        let capacity = |db: &Db| -> usize {
+            db.shared
+                .shards
+                .iter()
+                .map(|shard| shard.read().unwrap().capacity())
+                .sum()
+        };
+
+        let keys: Vec<String> = (0..20_000).map(|i| format!("key:{}", i)).collect();
+        for key in &keys {
+            db.set(key.clone(), Bytes::from("v"), Some(Duration::from_secs(60)));
+        }
+        for key in keys {
+            db.del(key);
+        }
+        let before = capacity(&db);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while capacity(&db) >= before {
+            assert!(Instant::now() < deadline, "capacity not reduced");
+            time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(0, db.len());
+    }
+
+    // 等待途中被唤醒但没有取到值时，只继续等待剩余的时间，总的等待时间不超过超时时间
+    #[tokio::test]
+    async fn blocking_pop_spurious_wake_keeps_deadline() {
+        let db = Db::new(&ServerConfig::default());
+        let keys = vec!["list".to_string()];
+
+        let waiter = {
+            let db = db.clone();
+            let keys = keys.clone();
+            tokio::spawn(async move {
+                let start = Instant::now();
+                let popped = db
+                    .blocking_pop(&keys, true, Some(Duration::from_secs(1)))
+                    .await;
+                (popped, start.elapsed())
+            })
+        };
+
+        // 等待途中唤醒它，但列表仍然是空的
+        time::sleep(Duration::from_millis(500)).await;
+        db.shared.wake_blocked("list");
+
+        let (popped, elapsed) = waiter.await.unwrap();
+        assert_eq!(Ok(None), popped);
+        assert!(elapsed >= Duration::from_secs(1), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(1400), "{:?}", elapsed);
+        // 返回之后不再登记在等待表中
+        assert!(db.shared.blocked.lock().unwrap().is_empty());
+    }
+}
@@ -1,3 +1,5 @@
 pub mod db;
+pub(crate) mod persistence;
+pub(crate) mod pubsub;
 pub mod store;
 pub(crate) mod traits;
@@ -0,0 +1,222 @@
+//! 值的二进制序列化格式，`DUMP` 和 `RESTORE` 使用它在服务器之间迁移键。
+//!
+//! 格式如下，所有整数都是小端序：
+//!
+//! ```text
+//! 类型(u8) 值的内容 版本(u16) 校验和(u64)
+//! ```
+//!
+//! 值的内容按类型编码：字符串是一段数据；列表和集合是元素个数(u32)加上每个元素；
+//! 哈希表是字段个数(u32)加上每对字段和值。每段数据都是长度(u32)加上数据本身。
+//! 校验和是对前面所有字节计算的 FNV-1a 64 位哈希，用于发现传输中损坏或者被截断的数据。
+//!
+//! 格式只包含值，不包含键和过期时间，它们由 `RESTORE` 的参数给出。
+//! 以后加入快照持久化时应当复用这里的格式，格式有变化时增加 `VERSION`。
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::storage::store::Value;
+
+/// 当前格式的版本号。
+const VERSION: u16 = 1;
+
+// 类型标记
+const TYPE_STRING: u8 = 0;
+const TYPE_LIST: u8 = 1;
+const TYPE_HASH: u8 = 2;
+const TYPE_SET: u8 = 3;
+
+/// 版本号和校验和占用的字节数。
+const TRAILER_LEN: usize = 2 + 8;
+
+/// 解析序列化数据失败。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DecodeError {
+    /// 版本号不支持或者校验和不匹配。
+    Checksum,
+    /// 校验通过，但内容不是合法的值。
+    Format,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Checksum => f.write_str("DUMP payload version or checksum are wrong"),
+            DecodeError::Format => f.write_str("Bad data format"),
+        }
+    }
+}
+
+/// 将值序列化为字节。
+pub(crate) fn encode(value: &Value) -> Bytes {
+    let mut buf = BytesMut::new();
+
+    match value {
+        Value::String(data) => {
+            buf.put_u8(TYPE_STRING);
+            put_bytes(&mut buf, data);
+        }
+        Value::List(list) => {
+            buf.put_u8(TYPE_LIST);
+            buf.put_u32_le(list.len() as u32);
+            for item in list {
+                put_bytes(&mut buf, item);
+            }
+        }
+        Value::Hash(hash) => {
+            buf.put_u8(TYPE_HASH);
+            buf.put_u32_le(hash.len() as u32);
+            for (field, value) in hash {
+                put_bytes(&mut buf, field.as_bytes());
+                put_bytes(&mut buf, value);
+            }
+        }
+        Value::Set(set) => {
+            buf.put_u8(TYPE_SET);
+            buf.put_u32_le(set.len() as u32);
+            for member in set {
+                put_bytes(&mut buf, member);
+            }
+        }
+    }
+
+    buf.put_u16_le(VERSION);
+    let checksum = fnv1a(&buf);
+    buf.put_u64_le(checksum);
+    buf.freeze()
+}
+
+/// 从 `encode` 生成的字节中解析值。
+pub(crate) fn decode(data: &[u8]) -> Result<Value, DecodeError> {
+    if data.len() < 1 + TRAILER_LEN {
+        return Err(DecodeError::Checksum);
+    }
+
+    let (body, mut checksum) = data.split_at(data.len() - 8);
+    if checksum.get_u64_le() != fnv1a(body) {
+        return Err(DecodeError::Checksum);
+    }
+    let (mut body, mut version) = body.split_at(body.len() - 2);
+    if version.get_u16_le() != VERSION {
+        return Err(DecodeError::Checksum);
+    }
+
+    let value = match body.get_u8() {
+        TYPE_STRING => Value::String(get_bytes(&mut body)?),
+        TYPE_LIST => {
+            let len = get_len(&mut body)?;
+            let mut list = VecDeque::new();
+            for _ in 0..len {
+                list.push_back(get_bytes(&mut body)?);
+            }
+            Value::List(list)
+        }
+        TYPE_HASH => {
+            let len = get_len(&mut body)?;
+            let mut hash = HashMap::new();
+            for _ in 0..len {
+                let field = get_bytes(&mut body)?;
+                let field = String::from_utf8(field.to_vec()).map_err(|_| DecodeError::Format)?;
+                hash.insert(field, get_bytes(&mut body)?);
+            }
+            Value::Hash(hash)
+        }
+        TYPE_SET => {
+            let len = get_len(&mut body)?;
+            let mut set = HashSet::new();
+            for _ in 0..len {
+                set.insert(get_bytes(&mut body)?);
+            }
+            Value::Set(set)
+        }
+        _ => return Err(DecodeError::Format),
+    };
+
+    // 值的内容之后不应该有多余的数据
+    if body.has_remaining() {
+        return Err(DecodeError::Format);
+    }
+
+    Ok(value)
+}
+
+// 写入一段带长度的数据
+fn put_bytes(buf: &mut BytesMut, data: &[u8]) {
+    buf.put_u32_le(data.len() as u32);
+    buf.put_slice(data);
+}
+
+// 读取一个长度
+fn get_len(src: &mut &[u8]) -> Result<usize, DecodeError> {
+    if src.remaining() < 4 {
+        return Err(DecodeError::Format);
+    }
+    Ok(src.get_u32_le() as usize)
+}
+
+// 读取一段带长度的数据
+fn get_bytes(src: &mut &[u8]) -> Result<Bytes, DecodeError> {
+    let len = get_len(src)?;
+    if src.remaining() < len {
+        return Err(DecodeError::Format);
+    }
+    Ok(src.copy_to_bytes(len))
+}
+
+// FNV-1a 64 位哈希
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(value: Value) {
+        let encoded = encode(&value);
+        assert_eq!(
+            format!("{:?}", value),
+            format!("{:?}", decode(&encoded).unwrap())
+        );
+    }
+
+    #[test]
+    fn round_trip_every_type() {
+        round_trip(Value::String(Bytes::from_static(b"\xff\x00\r\n")));
+        round_trip(Value::String(Bytes::new()));
+        round_trip(Value::List(
+            vec![Bytes::from("a"), Bytes::from("bb")]
+                .into_iter()
+                .collect(),
+        ));
+        round_trip(Value::Hash(
+            vec![("field".to_string(), Bytes::from("value"))]
+                .into_iter()
+                .collect(),
+        ));
+        round_trip(Value::Set(vec![Bytes::from("a")].into_iter().collect()));
+    }
+
+    #[test]
+    fn reject_corrupted_payload() {
+        let encoded = encode(&Value::String(Bytes::from("hello")));
+
+        // 任何一个字节被修改都无法通过校验
+        for i in 0..encoded.len() {
+            let mut corrupted = encoded.to_vec();
+            corrupted[i] ^= 1;
+            assert_eq!(DecodeError::Checksum, decode(&corrupted).unwrap_err());
+        }
+        // 被截断的数据
+        assert_eq!(
+            DecodeError::Checksum,
+            decode(&encoded[..encoded.len() - 1]).unwrap_err()
+        );
+        assert_eq!(DecodeError::Checksum, decode(b"").unwrap_err());
+    }
+}
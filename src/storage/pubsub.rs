@@ -0,0 +1,54 @@
+use bytes::Bytes; // 导入字节流Bytes类型
+use std::collections::HashMap; // 导入HashMap类型
+use tokio::sync::broadcast; // 导入tokio异步广播通道类型
+
+// 发布订阅的频道表。频道与键无关，因此不随键值存储分片，整个数据库只有一份。
+#[derive(Debug)]
+pub(crate) struct PubSub {
+    // 键是String，值是bytes类型的消息广播发送者。其用于存放 pub-sub 数据。
+    channels: HashMap<String, broadcast::Sender<Bytes>>,
+}
+
+impl PubSub {
+    // 创建新的PubSub实例
+    pub(crate) fn new() -> PubSub {
+        PubSub {
+            channels: HashMap::new(), // 初始化订阅频道存储
+        }
+    }
+
+    // 订阅指定键的消息
+    pub(crate) fn subscribe(&mut self, key: String) -> broadcast::Receiver<Bytes> {
+        // 引入hash_map模块中的Entry枚举
+        use std::collections::hash_map::Entry;
+
+        // 根据键在channels哈希表中查找对应的条目
+        match self.channels.entry(key) {
+            // 如果Entry::Occupied表示该键已经存在，则返回对应的广播发送者的订阅接收者
+            Entry::Occupied(e) => e.get().subscribe(),
+            // 如果Entry::Vacant表示该键不存在，则创建一个新的广播频道，将发送者存入哈希表，并返回对应的接收者
+            Entry::Vacant(e) => {
+                let (tx, rx) = broadcast::channel(1024); // 创建一个新的广播频道，容量为1024
+                e.insert(tx); // 将新创建的发送者存入哈希表
+                rx // 返回新创建的接收者
+            }
+        }
+    }
+
+    // 发布指定键的消息
+    pub(crate) fn publish(&self, key: &str, value: Bytes) -> usize {
+        // 根据键在channels哈希表中查找对应的广播发送者
+        self.channels
+            .get(key)
+            .map(|tx| tx.send(value).unwrap_or(0)) // 如果找到发送者，则发送消息并返回发送成功的接收者数量；如果发送失败，则返回0
+            .unwrap_or(0) // 如果找不到对应的发送者，则返回0
+    }
+
+    // 统计已经没有订阅者的频道数量。订阅者断开后频道不会被移除，这些频道上发布的消息没有人接收
+    pub(crate) fn stale_channels(&self) -> usize {
+        self.channels
+            .values()
+            .filter(|tx| tx.receiver_count() == 0)
+            .count()
+    }
+}
@@ -1,27 +1,155 @@
-use bytes::Bytes; // 导入字节流Bytes类型
-use std::collections::{BTreeMap, HashMap}; // 导入BTreeMap和HashMap类型
-use tokio::sync::broadcast; // 导入tokio异步广播通道类型
+use bytes::{Bytes, BytesMut}; // 导入字节流Bytes和BytesMut类型
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque}; // 导入BTreeMap、HashMap、HashSet和VecDeque类型
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::mem;
+use std::ops;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::time::{Duration, Instant}; // 导入tokio时间相关类型
 
+use crate::config::MaxmemoryPolicy;
+use crate::storage::persistence;
+
+// 容量不超过这个值的哈希表不需要整理，收缩它节省的内存不值得重新分配
+const COMPACT_MIN_CAPACITY: usize = 1024;
+
+// LRU 策略每次随机抽取的键的数量，与 Redis 的 `maxmemory-samples` 默认值相同
+const EVICTION_SAMPLES: usize = 5;
+
+// 键值存储的一个分片，数据库由多个分片组成，每个键只属于一个分片。
 #[derive(Debug)]
 pub(crate) struct Store {
     // 结构体的一个字段叫entries, 它用于存放 k-v 的数据。
     entries: HashMap<String, Entry>,
-    // 键是String，值是bytes类型的消息广播发送者。其用于存放 pub-sub 数据。
-    pub_sub: HashMap<String, broadcast::Sender<Bytes>>,
     // 键是一个元组，包含Instant和u64类型，值是String。元组并按顺序排列。类似于优先队列，便于快速扫描过期键并移除。
     expirations: BTreeMap<(Instant, u64), String>,
+    // 按 id 排序的所有键，SCAN 按 id 顺序遍历，游标即下一个 id，插入和删除其他键不会影响游标的位置。
+    ids: BTreeMap<u64, String>,
     // u64类型字段用于存储下一个id。对每一个过期键分配的 id，避免找不到对应键。
     next_id: u64,
-    // bool类型字段用于指示是否关闭数据库。如果数据库关闭，则此时不再接受请求，但需要释放连接等资源。
-    shutdown: bool,
+    // 创建存储的时间点，键的上次访问时间记录为相对于它的毫秒数
+    epoch: Instant,
+    // 所有键和值占用的字节数，见 `Store::usage`
+    used_memory: usize,
+    // 下一次写入分配给键的版本号，见 `Store::version`
+    next_version: u64,
 }
 
 #[derive(Debug)]
 struct Entry {
     id: u64,                     // 条目ID
-    data: Bytes,                 // 数据字节流
+    data: Value,                 // 键对应的值
     expires_at: Option<Instant>, // 过期时间点，可选
+    // 上次访问的时间，相对于 Store::epoch 的毫秒数。
+    // GET 等只读命令只持有读锁，因此使用原子变量，不需要写锁也能更新。
+    last_access: AtomicU64,
+    // 键的版本号，每次写入都会更新，WATCH 用它判断键是否被修改过
+    version: u64,
+}
+
+// 键对应的值，不同类型的值支持不同的命令
+#[derive(Debug)]
+pub(crate) enum Value {
+    // 字符串，GET/SET 等命令操作的值
+    String(Bytes),
+    // 列表，LPUSH/LRANGE 等命令操作的值
+    List(VecDeque<Bytes>),
+    // 哈希表，HSET/HGET 等命令操作的值
+    Hash(HashMap<String, Bytes>),
+    // 集合，SADD/SMEMBERS 等命令操作的值
+    Set(HashSet<Bytes>),
+}
+
+// 分片中键的数量和占用的内存，`Db` 根据一次操作前后的差值更新所有分片的总数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Usage {
+    // 键的数量
+    pub(crate) keys: usize,
+    // 键和值占用的字节数，见 `Store::usage`
+    pub(crate) memory: usize,
+}
+
+impl ops::Add for Usage {
+    type Output = Usage;
+
+    fn add(self, other: Usage) -> Usage {
+        Usage {
+            keys: self.keys + other.keys,
+            memory: self.memory + other.memory,
+        }
+    }
+}
+
+impl ops::AddAssign for Usage {
+    fn add_assign(&mut self, other: Usage) {
+        *self = *self + other;
+    }
+}
+
+/// 对类型不匹配的值执行了命令，例如对列表执行 `GET`，回复 `WRONGTYPE` 错误。
+///
+/// 自定义的 [`AsyncKvStore`] 在键的值不是字符串时返回它。
+///
+/// [`AsyncKvStore`]: crate::server::AsyncKvStore
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrongType;
+
+impl fmt::Display for WrongType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Operation against a key holding the wrong kind of value")
+    }
+}
+
+// INCRBYFLOAT 失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IncrError {
+    // 键的值不是字符串
+    WrongType,
+    // 原有的值不能解析为浮点数
+    NotAFloat,
+    // 结果是无穷大或 NaN，不能保存
+    NanOrInfinity,
+}
+
+impl fmt::Display for IncrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IncrError::WrongType => WrongType.fmt(f),
+            IncrError::NotAFloat => f.write_str("value is not a valid float"),
+            IncrError::NanOrInfinity => f.write_str("increment would produce NaN or Infinity"),
+        }
+    }
+}
+
+// EXPIRE 的 NX/XX/GT/LT 选项，决定是否覆盖键原有的过期时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExpireCondition {
+    // 总是设置
+    Always,
+    // 只在键没有过期时间时设置
+    Nx,
+    // 只在键已经有过期时间时设置
+    Xx,
+    // 只在新的过期时间晚于原有的过期时间时设置，没有过期时间视为永不过期
+    Gt,
+    // 只在新的过期时间早于原有的过期时间时设置，没有过期时间视为永不过期
+    Lt,
+}
+
+impl ExpireCondition {
+    // 键原有的过期时间点是 current 时，是否可以设置为 when
+    fn allows(self, current: Option<Instant>, when: Instant) -> bool {
+        match (self, current) {
+            (ExpireCondition::Always, _) => true,
+            (ExpireCondition::Nx, current) => current.is_none(),
+            (ExpireCondition::Xx, current) => current.is_some(),
+            (ExpireCondition::Gt, Some(current)) => when > current,
+            (ExpireCondition::Gt, None) => false,
+            (ExpireCondition::Lt, Some(current)) => when < current,
+            (ExpireCondition::Lt, None) => true,
+        }
+    }
 }
 
 impl Store {
@@ -29,10 +157,12 @@ impl Store {
     pub(crate) fn new() -> Store {
         Store {
             entries: HashMap::new(),      // 初始化键值对存储
-            pub_sub: HashMap::new(),      // 初始化订阅频道存储
             expirations: BTreeMap::new(), // 初始化过期时间映射
+            ids: BTreeMap::new(),         // 初始化 SCAN 使用的键索引
             next_id: 0,                   // 初始ID为0
-            shutdown: false,              // 初始未关闭
+            epoch: Instant::now(),        // 记录访问时间的起点
+            used_memory: 0,               // 还没有任何键
+            next_version: 0,              // 初始版本号为0
         }
     }
 
@@ -43,15 +173,152 @@ impl Store {
         self.expirations.keys().next().map(|expire| expire.0)
     }
 
-    // 获取指定键的值
-    pub(crate) fn get(&self, key: &str) -> Option<Bytes> {
-        // 使用HashMap的get()方法根据键获取对应的值，如果存在则使用map()方法处理，
-        // 返回其Entry中的data字段的克隆，即Bytes类型的值的拷贝。
-        self.entries.get(key).map(|entry| entry.data.clone())
+    // 获取指定键的值，键的值不是字符串时返回 WrongType
+    // 已经过期但还未被后台任务清理的键视为不存在，与 TTL 等读取一致
+    pub(crate) fn get_typed(&self, key: &str) -> Result<Option<Bytes>, WrongType> {
+        // 返回没有过期的键的Entry中data字段的克隆，即Bytes类型的值的拷贝。
+        match self.live_entry(key).map(|entry| &entry.data) {
+            Some(Value::String(data)) => Ok(Some(data.clone())),
+            Some(_) => Err(WrongType),
+            None => Ok(None),
+        }
+    }
+
+    // 返回当前的键数量和所有键和值占用的字节数
+    // 只统计键名、字符串、列表元素以及哈希表字段和值的长度，不包括哈希表等结构本身的开销，是一个近似值
+    pub(crate) fn usage(&self) -> Usage {
+        Usage {
+            keys: self.entries.len(),
+            memory: self.used_memory,
+        }
+    }
+
+    // 返回键是否已经过期但还未被清理，不会记录访问
+    pub(crate) fn is_expired(&self, key: &str) -> bool {
+        self.entries.get(key).is_some_and(|entry| !entry.is_alive())
+    }
+
+    // 访问指定的键，返回键是否存在
+    // 已经过期但还未被后台任务清理的键视为不存在
+    pub(crate) fn touch(&self, key: &str) -> bool {
+        self.live_entry(key).is_some()
+    }
+
+    // 返回值的编码名称，只用于观察，与 Redis 的 `OBJECT ENCODING` 对应，不会更新访问时间
+    pub(crate) fn encoding(&self, key: &str) -> Option<&'static str> {
+        self.entries
+            .get(key)
+            .filter(|entry| entry.is_alive())
+            .map(|entry| entry.data.encoding())
+    }
+
+    // 估算键占用的字节数：键名和值的长度加上条目本身固定的开销，与 Redis 的 `MEMORY USAGE` 对应，不会更新访问时间
+    pub(crate) fn memory_usage(&self, key: &str) -> Option<usize> {
+        self.entries
+            .get(key)
+            .filter(|entry| entry.is_alive())
+            .map(|entry| key.len() + entry.data.memory_usage() + mem::size_of::<Entry>())
+    }
+
+    // 返回键距离上次被访问经过的时间，不会更新访问时间
+    pub(crate) fn idle_time(&self, key: &str) -> Option<Duration> {
+        let entry = self.entries.get(key).filter(|entry| entry.is_alive())?;
+        let idle = self
+            .now_millis()
+            .saturating_sub(entry.last_access.load(Ordering::Relaxed));
+        Some(Duration::from_millis(idle))
+    }
+
+    // 从 id 为 cursor 的位置开始遍历最多 count 个键，没有过期的键追加到 keys 中。
+    // 返回遍历的键数量，包括被跳过的已经过期的键，以及下一次遍历的起始 id，遍历完成时为 None。
+    pub(crate) fn scan(
+        &self,
+        cursor: u64,
+        count: usize,
+        keys: &mut Vec<String>,
+    ) -> (usize, Option<u64>) {
+        let now = Instant::now();
+        let mut iter = self.ids.range(cursor..);
+        let mut visited = 0;
+
+        for (_, key) in iter.by_ref().take(count) {
+            visited += 1;
+            let alive = self.entries[key]
+                .expires_at
+                .map(|when| when > now)
+                .unwrap_or(true);
+            if alive {
+                keys.push(key.clone());
+            }
+        }
+
+        (visited, iter.next().map(|(&id, _)| id))
+    }
+
+    // 将 now 时没有过期的字符串键、值和剩余生存时间追加到 entries 中，不会更新访问时间
+    pub(crate) fn collect_entries(
+        &self,
+        now: Instant,
+        entries: &mut Vec<(String, Bytes, Option<Duration>)>,
+    ) {
+        for (key, entry) in &self.entries {
+            let ttl = match entry.expires_at {
+                Some(when) if when <= now => continue,
+                Some(when) => Some(when - now),
+                None => None,
+            };
+            if let Value::String(data) = &entry.data {
+                entries.push((key.clone(), data.clone(), ttl));
+            }
+        }
+    }
+
+    // 返回键当前的版本号，不会更新访问时间，键不存在或者已经过期时返回 None
+    //
+    // 版本号在分片内递增，每次写入键都会分配一个新的版本号，删除之后重新创建的键也不会得到以前的版本号，
+    // 因此两次读取之间版本号不变说明键没有被修改过
+    pub(crate) fn version(&self, key: &str) -> Option<u64> {
+        self.entries
+            .get(key)
+            .filter(|entry| entry.is_alive())
+            .map(|entry| entry.version)
+    }
+
+    // 获取指定键的剩余生存时间
+    // 键不存在时返回 None；键存在但没有设置过期时间时返回 Some(None)
+    pub(crate) fn ttl(&self, key: &str) -> Option<Option<Duration>> {
+        let entry = self.entries.get(key)?;
+        match entry.expires_at {
+            Some(when) => {
+                let now = Instant::now();
+                // 已经过期但还未被后台任务清理的键，视为不存在
+                if when <= now {
+                    None
+                } else {
+                    Some(Some(when - now))
+                }
+            }
+            None => Some(None),
+        }
     }
 
     // 设置键值对和可选的过期时间
     pub(crate) fn set(&mut self, key: String, value: Bytes, expire: Option<Duration>) -> bool {
+        // 计算过期时间点
+        self.set_at(
+            key,
+            Value::String(value),
+            expire.map(|duration| Instant::now() + duration),
+        )
+    }
+
+    // 设置键值对和可选的过期时间点
+    pub(crate) fn set_at(
+        &mut self,
+        key: String,
+        value: Value,
+        expires_at: Option<Instant>,
+    ) -> bool {
         // 获取当前的唯一标识符，并立即为下一个键值对递增
         let id = self.next_id;
         self.next_id += 1;
@@ -59,11 +326,8 @@ impl Store {
         // 初始化一个标志变量，用于指示是否需要通知过期任务
         let mut notify = false;
 
-        // 处理可选的过期时间
-        let expires_at = expire.map(|duration| {
-            // 计算过期时间点
-            let when = Instant::now() + duration;
-
+        // 处理可选的过期时间点
+        if let Some(when) = expires_at {
             // 判断是否需要更新过期时间处理队列，如果当前设置的过期时间早于队列中最早的过期时间，或者队列为空，需要通知
             notify = self
                 .next_expiration()
@@ -72,8 +336,11 @@ impl Store {
 
             // 在过期时间映射中插入新的过期时间和键值标识符
             self.expirations.insert((when, id), key.clone());
-            when // 返回设置的过期时间点
-        });
+        }
+        self.ids.insert(id, key.clone());
+        self.used_memory += key.len() + value.memory_usage();
+        let key_len = key.len();
+        let version = self.new_version();
 
         // 插入新的键值对到HashMap，如果该键之前存在，则返回之前的值
         let prev = self.entries.insert(
@@ -82,70 +349,704 @@ impl Store {
                 id,
                 data: value,
                 expires_at,
+                last_access: AtomicU64::new(self.now_millis()),
+                version,
             },
         );
 
         // 如果之前的键存在且有设置过期时间，则从过期时间映射中删除之前的过期信息
         if let Some(prev) = prev {
+            self.ids.remove(&prev.id);
             if let Some(when) = prev.expires_at {
                 self.expirations.remove(&(when, prev.id));
             }
+            self.used_memory -= key_len + prev.data.memory_usage();
         }
 
         // 返回是否需要通知过期任务的标志
         notify
     }
 
-    // 删除指定的键  
-    pub(crate) fn del(&mut self, key: &str) -> usize {  
-        // 尝试从entries中移除键，并获取移除的结果  
-        let removed_entry = self.entries.remove(key);  
-  
-        // 如果成功移除了键，则还需要从expirations中移除相关的过期信息  
-        if let Some(entry) = removed_entry {  
-            if let Some(expires_at) = entry.expires_at {  
-                self.expirations.remove(&(expires_at, entry.id));  
-            }  
-            1 // 返回true表示成功移除了键  
-        } else {  
-            0 // 返回false表示键不存在，未进行移除操作  
-        }  
-    }  
-
-    // 订阅指定键的消息
-    pub(crate) fn subscribe(&mut self, key: String) -> broadcast::Receiver<Bytes> {
-        // 引入hash_map模块中的Entry枚举
-        use std::collections::hash_map::Entry;
-
-        // 根据键在pub_sub哈希表中查找对应的条目
-        match self.pub_sub.entry(key) {
-            // 如果Entry::Occupied表示该键已经存在，则返回对应的广播发送者的订阅接收者
-            Entry::Occupied(e) => e.get().subscribe(),
-            // 如果Entry::Vacant表示该键不存在，则创建一个新的广播频道，将发送者存入哈希表，并返回对应的接收者
-            Entry::Vacant(e) => {
-                let (tx, rx) = broadcast::channel(1024); // 创建一个新的广播频道，容量为1024
-                e.insert(tx); // 将新创建的发送者存入哈希表
-                rx // 返回新创建的接收者
-            }
-        }
-    }
-
-    // 发布指定键的消息
-    pub(crate) fn publish(&self, key: &str, value: Bytes) -> usize {
-        // 根据键在pub_sub哈希表中查找对应的广播发送者
-        self.pub_sub
-            .get(key)
-            .map(|tx| tx.send(value).unwrap_or(0)) // 如果找到发送者，则发送消息并返回发送成功的接收者数量；如果发送失败，则返回0
-            .unwrap_or(0) // 如果找不到对应的发送者，则返回0
+    // 设置键值对，保留键原有的过期时间
+    // 如果键不存在或者已经过期，则设置为没有过期时间的新键
+    pub(crate) fn set_keep_ttl(&mut self, key: String, value: Bytes) {
+        let now = self.now_millis();
+        let version = self.new_version();
+        if let Some(entry) = self.entries.get_mut(&key) {
+            let alive = entry
+                .expires_at
+                .map(|when| when > Instant::now())
+                .unwrap_or(true);
+            if alive {
+                // 只替换数据，原来的值是什么类型都会被覆盖，id 和过期时间保持不变，过期时间映射无需更新
+                self.used_memory = self.used_memory - entry.data.memory_usage() + value.len();
+                entry.data = Value::String(value);
+                *entry.last_access.get_mut() = now;
+                entry.version = version;
+                return;
+            }
+        }
+
+        self.set(key, value, None);
     }
 
-    // 清理过期键
-    pub(crate) fn purge_expired_keys(&mut self) -> Option<Instant> {
-        // 如果存储层已经关闭，则返回None，表示不执行过期清理操作
-        if self.shutdown {
+    // 为键设置新的过期时间点，condition 满足时覆盖原有的过期时间，时间点已经过去时直接删除键
+    // 键不存在、已经过期或者条件不满足时返回 None，否则返回是否需要通知过期任务
+    pub(crate) fn expire(
+        &mut self,
+        key: &str,
+        when: Instant,
+        condition: ExpireCondition,
+    ) -> Option<bool> {
+        let notify = self
+            .next_expiration()
+            .map(|expiration| expiration > when)
+            .unwrap_or(true);
+
+        let current = self.live_entry_mut(key)?.expires_at;
+        if !condition.allows(current, when) {
             return None;
         }
+        if when <= Instant::now() {
+            self.remove_entry(key);
+            return Some(false);
+        }
+
+        let entry = self.entries.get_mut(key)?;
+        let id = entry.id;
+        if let Some(prev) = entry.expires_at.replace(when) {
+            self.expirations.remove(&(prev, id));
+        }
+        self.expirations.insert((when, id), key.to_string());
+
+        Some(notify)
+    }
 
+    // 将 value 追加到键原有的值之后，保留原有的过期时间，返回追加后值的长度
+    // 如果键不存在或者已经过期，则等同于设置一个没有过期时间的新键
+    // 追加后的长度超过 max_len 时返回 None，不做修改，也不会分配新的内存
+    pub(crate) fn append(
+        &mut self,
+        key: String,
+        value: Bytes,
+        max_len: usize,
+    ) -> Result<Option<usize>, WrongType> {
+        if let Some(entry) = self.live_entry_mut(&key) {
+            let prev = match &entry.data {
+                Value::String(prev) => prev,
+                _ => return Err(WrongType),
+            };
+            if prev.len() + value.len() > max_len {
+                return Ok(None);
+            }
+            let mut data = BytesMut::with_capacity(prev.len() + value.len());
+            data.extend_from_slice(prev);
+            data.extend_from_slice(&value);
+            let len = data.len();
+            entry.data = Value::String(data.freeze());
+            self.used_memory += value.len();
+            return Ok(Some(len));
+        }
+
+        let len = value.len();
+        if len > max_len {
+            return Ok(None);
+        }
+        self.set(key, value, None);
+        Ok(Some(len))
+    }
+
+    // 从 offset 开始用 value 覆盖字符串的一部分，返回修改后字符串的长度，保留原有的过期时间
+    // 字符串不够长时用 0 补齐；value 为空时不做修改，键不存在时也不会创建
+    pub(crate) fn setrange(
+        &mut self,
+        key: String,
+        offset: usize,
+        value: Bytes,
+    ) -> Result<usize, WrongType> {
+        let end = offset + value.len();
+
+        if let Some(entry) = self.live_entry_mut(&key) {
+            let prev = match &entry.data {
+                Value::String(prev) => prev,
+                _ => return Err(WrongType),
+            };
+            if value.is_empty() {
+                return Ok(prev.len());
+            }
+            let mut data = BytesMut::from(&prev[..]);
+            let grown = end.saturating_sub(data.len());
+            if grown > 0 {
+                data.resize(end, 0);
+            }
+            data[offset..end].copy_from_slice(&value);
+            let len = data.len();
+            entry.data = Value::String(data.freeze());
+            self.used_memory += grown;
+            return Ok(len);
+        }
+
+        if value.is_empty() {
+            return Ok(0);
+        }
+        let mut data = BytesMut::zeroed(end);
+        data[offset..].copy_from_slice(&value);
+        self.set_at(key, Value::String(data.freeze()), None);
+        Ok(end)
+    }
+
+    // 将字符串中第 offset 位设置为 on，返回这一位原来的值，第 0 位是第一个字节的最高位
+    // 字符串不够长时用 0 补齐，保留键原有的过期时间；键不存在或者已经过期时创建一个没有过期时间的新字符串
+    pub(crate) fn setbit(
+        &mut self,
+        key: String,
+        offset: usize,
+        on: bool,
+    ) -> Result<bool, WrongType> {
+        let (byte, mask) = (offset / 8, 0x80 >> (offset % 8));
+
+        if let Some(entry) = self.live_entry_mut(&key) {
+            let prev = match &entry.data {
+                Value::String(prev) => prev,
+                _ => return Err(WrongType),
+            };
+            let mut data = BytesMut::from(&prev[..]);
+            let grown = (byte + 1).saturating_sub(data.len());
+            if grown > 0 {
+                data.resize(byte + 1, 0);
+            }
+            let old = data[byte] & mask != 0;
+            if on {
+                data[byte] |= mask;
+            } else {
+                data[byte] &= !mask;
+            }
+            entry.data = Value::String(data.freeze());
+            self.used_memory += grown;
+            return Ok(old);
+        }
+
+        let mut data = BytesMut::zeroed(byte + 1);
+        if on {
+            data[byte] |= mask;
+        }
+        self.set_at(key, Value::String(data.freeze()), None);
+        Ok(false)
+    }
+
+    // 将字符串解析为浮点数加上 delta，保存并返回结果，保留键原有的过期时间
+    // 键不存在或者已经过期时视为 0；结果按最短的十进制写法保存，没有多余的 0，例如 10.5 加 0.1 保存为 "10.6"
+    pub(crate) fn incr_by_float(&mut self, key: String, delta: f64) -> Result<f64, IncrError> {
+        if let Some(entry) = self.live_entry_mut(&key) {
+            let prev = match &entry.data {
+                Value::String(prev) => prev,
+                _ => return Err(IncrError::WrongType),
+            };
+            let value = parse_float(prev).ok_or(IncrError::NotAFloat)? + delta;
+            if !value.is_finite() {
+                return Err(IncrError::NanOrInfinity);
+            }
+            let prev_len = prev.len();
+            let data = Bytes::from(value.to_string());
+            let len = data.len();
+            entry.data = Value::String(data);
+            self.used_memory = self.used_memory - prev_len + len;
+            return Ok(value);
+        }
+
+        if !delta.is_finite() {
+            return Err(IncrError::NanOrInfinity);
+        }
+        self.set(key, Bytes::from(delta.to_string()), None);
+        Ok(delta)
+    }
+
+    // 返回字符串中第 offset 位的值，超出字符串长度的位和不存在的键都是 0
+    pub(crate) fn getbit(&self, key: &str, offset: usize) -> Result<bool, WrongType> {
+        let data = match self.live_entry(key).map(|entry| &entry.data) {
+            Some(Value::String(data)) => data,
+            Some(_) => return Err(WrongType),
+            None => return Ok(false),
+        };
+
+        Ok(data
+            .get(offset / 8)
+            .map(|byte| byte & (0x80 >> (offset % 8)) != 0)
+            .unwrap_or(false))
+    }
+
+    // 将 values 依次插入列表的头部，返回插入后列表的长度
+    // 键不存在或者已经过期时创建一个新的列表
+    pub(crate) fn lpush(&mut self, key: String, values: Vec<Bytes>) -> Result<usize, WrongType> {
+        self.push(key, values, true)
+    }
+
+    // 将 values 依次插入列表的尾部，返回插入后列表的长度
+    pub(crate) fn rpush(&mut self, key: String, values: Vec<Bytes>) -> Result<usize, WrongType> {
+        self.push(key, values, false)
+    }
+
+    fn push(&mut self, key: String, values: Vec<Bytes>, front: bool) -> Result<usize, WrongType> {
+        if let Some(entry) = self.live_entry_mut(&key) {
+            let list = match &mut entry.data {
+                Value::List(list) => list,
+                _ => return Err(WrongType),
+            };
+            let added: usize = values.iter().map(Bytes::len).sum();
+            for value in values {
+                if front {
+                    list.push_front(value);
+                } else {
+                    list.push_back(value);
+                }
+            }
+            let len = list.len();
+            self.used_memory += added;
+            return Ok(len);
+        }
+
+        // 依次插入头部之后，最后一个值在最前面，与 Redis 一致
+        let list: VecDeque<Bytes> = if front {
+            values.into_iter().rev().collect()
+        } else {
+            values.into_iter().collect()
+        };
+        let len = list.len();
+        self.set_at(key, Value::List(list), None);
+        Ok(len)
+    }
+
+    // 移除并返回列表头部的值，列表为空后键会被删除
+    pub(crate) fn lpop(&mut self, key: &str) -> Result<Option<Bytes>, WrongType> {
+        self.pop(key, true)
+    }
+
+    // 移除并返回列表尾部的值
+    pub(crate) fn rpop(&mut self, key: &str) -> Result<Option<Bytes>, WrongType> {
+        self.pop(key, false)
+    }
+
+    fn pop(&mut self, key: &str, front: bool) -> Result<Option<Bytes>, WrongType> {
+        let list = match self.live_entry_mut(key).map(|entry| &mut entry.data) {
+            Some(Value::List(list)) => list,
+            Some(_) => return Err(WrongType),
+            None => return Ok(None),
+        };
+
+        let value = if front {
+            list.pop_front()
+        } else {
+            list.pop_back()
+        };
+        let empty = list.is_empty();
+        self.used_memory -= value.as_ref().map_or(0, Bytes::len);
+        // 与 Redis 一样不保留空列表
+        if empty {
+            self.remove_entry(key);
+        }
+        Ok(value)
+    }
+
+    // 返回列表中下标从 start 到 stop（包含）的值，负数下标从列表尾部开始计算，-1 表示最后一个
+    pub(crate) fn lrange(&self, key: &str, start: i64, stop: i64) -> Result<Vec<Bytes>, WrongType> {
+        let list = match self.live_entry(key).map(|entry| &entry.data) {
+            Some(Value::List(list)) => list,
+            Some(_) => return Err(WrongType),
+            None => return Ok(vec![]),
+        };
+
+        let len = list.len() as i64;
+        // 负数下标从尾部计算，超出范围的下标截断到列表的两端
+        let start = if start < 0 {
+            (len + start).max(0)
+        } else {
+            start
+        };
+        let stop = if stop < 0 {
+            len + stop
+        } else {
+            stop.min(len - 1)
+        };
+        if start > stop {
+            return Ok(vec![]);
+        }
+
+        Ok(list
+            .range(start as usize..=stop as usize)
+            .cloned()
+            .collect())
+    }
+
+    // 返回列表的长度，键不存在时为 0
+    pub(crate) fn llen(&self, key: &str) -> Result<usize, WrongType> {
+        match self.live_entry(key).map(|entry| &entry.data) {
+            Some(Value::List(list)) => Ok(list.len()),
+            Some(_) => Err(WrongType),
+            None => Ok(0),
+        }
+    }
+
+    // 设置哈希表中的字段，返回新增的字段数量，已有字段的值会被覆盖
+    // 键不存在或者已经过期时创建一个新的哈希表
+    pub(crate) fn hset(
+        &mut self,
+        key: String,
+        pairs: Vec<(String, Bytes)>,
+    ) -> Result<usize, WrongType> {
+        if let Some(entry) = self.live_entry_mut(&key) {
+            let hash = match &mut entry.data {
+                Value::Hash(hash) => hash,
+                _ => return Err(WrongType),
+            };
+            let mut added = 0;
+            let (mut grown, mut freed) = (0, 0);
+            for (field, value) in pairs {
+                grown += field.len() + value.len();
+                let field_len = field.len();
+                match hash.insert(field, value) {
+                    Some(prev) => freed += field_len + prev.len(),
+                    None => added += 1,
+                }
+            }
+            self.used_memory = self.used_memory + grown - freed;
+            return Ok(added);
+        }
+
+        let hash: HashMap<String, Bytes> = pairs.into_iter().collect();
+        let added = hash.len();
+        self.set_at(key, Value::Hash(hash), None);
+        Ok(added)
+    }
+
+    // 获取哈希表中字段的值，键或者字段不存在时返回 None
+    pub(crate) fn hget(&self, key: &str, field: &str) -> Result<Option<Bytes>, WrongType> {
+        match self.live_entry(key).map(|entry| &entry.data) {
+            Some(Value::Hash(hash)) => Ok(hash.get(field).cloned()),
+            Some(_) => Err(WrongType),
+            None => Ok(None),
+        }
+    }
+
+    // 判断哈希表中是否存在字段
+    pub(crate) fn hexists(&self, key: &str, field: &str) -> Result<bool, WrongType> {
+        match self.live_entry(key).map(|entry| &entry.data) {
+            Some(Value::Hash(hash)) => Ok(hash.contains_key(field)),
+            Some(_) => Err(WrongType),
+            None => Ok(false),
+        }
+    }
+
+    // 获取哈希表中所有的字段和值，顺序不确定，键不存在时返回空数组
+    pub(crate) fn hgetall(&self, key: &str) -> Result<Vec<(String, Bytes)>, WrongType> {
+        match self.live_entry(key).map(|entry| &entry.data) {
+            Some(Value::Hash(hash)) => Ok(hash
+                .iter()
+                .map(|(field, value)| (field.clone(), value.clone()))
+                .collect()),
+            Some(_) => Err(WrongType),
+            None => Ok(vec![]),
+        }
+    }
+
+    // 删除哈希表中的字段，返回实际删除的字段数量，哈希表为空后键会被删除
+    pub(crate) fn hdel(&mut self, key: &str, fields: &[String]) -> Result<usize, WrongType> {
+        let hash = match self.live_entry_mut(key).map(|entry| &mut entry.data) {
+            Some(Value::Hash(hash)) => hash,
+            Some(_) => return Err(WrongType),
+            None => return Ok(0),
+        };
+
+        let mut removed = 0;
+        let mut freed = 0;
+        for field in fields {
+            if let Some(value) = hash.remove(field) {
+                removed += 1;
+                freed += field.len() + value.len();
+            }
+        }
+        let empty = hash.is_empty();
+        self.used_memory -= freed;
+        // 与 Redis 一样不保留空哈希表
+        if empty {
+            self.remove_entry(key);
+        }
+        Ok(removed)
+    }
+
+    // 返回哈希表中字段的数量，键不存在时为 0
+    pub(crate) fn hlen(&self, key: &str) -> Result<usize, WrongType> {
+        match self.live_entry(key).map(|entry| &entry.data) {
+            Some(Value::Hash(hash)) => Ok(hash.len()),
+            Some(_) => Err(WrongType),
+            None => Ok(0),
+        }
+    }
+
+    // 向集合中添加成员，返回新增的成员数量，已有的成员不会重复计数
+    // 键不存在或者已经过期时创建一个新的集合
+    pub(crate) fn sadd(&mut self, key: String, members: Vec<Bytes>) -> Result<usize, WrongType> {
+        if let Some(entry) = self.live_entry_mut(&key) {
+            let set = match &mut entry.data {
+                Value::Set(set) => set,
+                _ => return Err(WrongType),
+            };
+            let mut added = 0;
+            let mut grown = 0;
+            for member in members {
+                let len = member.len();
+                if set.insert(member) {
+                    added += 1;
+                    grown += len;
+                }
+            }
+            self.used_memory += grown;
+            return Ok(added);
+        }
+
+        let set: HashSet<Bytes> = members.into_iter().collect();
+        let added = set.len();
+        self.set_at(key, Value::Set(set), None);
+        Ok(added)
+    }
+
+    // 获取集合中所有的成员，顺序不确定，键不存在时返回空数组
+    pub(crate) fn smembers(&self, key: &str) -> Result<Vec<Bytes>, WrongType> {
+        match self.live_entry(key).map(|entry| &entry.data) {
+            Some(Value::Set(set)) => Ok(set.iter().cloned().collect()),
+            Some(_) => Err(WrongType),
+            None => Ok(vec![]),
+        }
+    }
+
+    // 判断成员是否在集合中，键不存在时返回 false
+    pub(crate) fn sismember(&self, key: &str, member: &[u8]) -> Result<bool, WrongType> {
+        match self.live_entry(key).map(|entry| &entry.data) {
+            Some(Value::Set(set)) => Ok(set.contains(member)),
+            Some(_) => Err(WrongType),
+            None => Ok(false),
+        }
+    }
+
+    // 获取没有过期的键，并记录这次访问
+    fn live_entry(&self, key: &str) -> Option<&Entry> {
+        let entry = self.entries.get(key).filter(|entry| entry.is_alive())?;
+        self.access(entry);
+        Some(entry)
+    }
+
+    // 获取没有过期的键的可变引用，记录这次访问并更新版本号
+    // 只有写入命令会调用它，类型不匹配等没有真正修改键的写入也会更新版本号，
+    // 这只会让 EXEC 多放弃一次事务，不会漏掉真正的修改
+    fn live_entry_mut(&mut self, key: &str) -> Option<&mut Entry> {
+        let now = self.now_millis();
+        let version = self.next_version;
+        let entry = self.entries.get_mut(key).filter(|entry| entry.is_alive())?;
+        *entry.last_access.get_mut() = now;
+        entry.version = version;
+        self.next_version += 1;
+        Some(entry)
+    }
+
+    // 分配一个新的版本号
+    fn new_version(&mut self) -> u64 {
+        let version = self.next_version;
+        self.next_version += 1;
+        version
+    }
+
+    // 记录键被访问的时间
+    fn access(&self, entry: &Entry) {
+        entry
+            .last_access
+            .store(self.now_millis(), Ordering::Relaxed);
+    }
+
+    // 当前时间相对于 epoch 的毫秒数
+    fn now_millis(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64
+    }
+
+    // 将 src 的值和剩余生存时间移动到 dst，覆盖 dst 原有的值
+    // src 不存在时返回 false，已经过期但还未被清理的 src 视为不存在，dst 保持不变
+    pub(crate) fn rename(&mut self, src: &str, dst: String) -> bool {
+        if !self.touch(src) {
+            return false;
+        }
+        let mut entry = match self.entries.remove(src) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        // dst 的值被替换，视为一次写入
+        entry.version = self.new_version();
+
+        // id 保持不变，只需要把过期时间映射中的键名改为 dst
+        if let Some(when) = entry.expires_at {
+            self.expirations.insert((when, entry.id), dst.clone());
+        }
+        self.ids.insert(entry.id, dst.clone());
+        self.used_memory = self.used_memory - src.len() + dst.len();
+        let dst_len = dst.len();
+
+        // dst 原有的值被覆盖，同时删除它的过期信息
+        if let Some(prev) = self.entries.insert(dst, entry) {
+            self.ids.remove(&prev.id);
+            if let Some(when) = prev.expires_at {
+                self.expirations.remove(&(when, prev.id));
+            }
+            self.used_memory -= dst_len + prev.data.memory_usage();
+        }
+
+        true
+    }
+
+    // 序列化键的值，键不存在时返回 None
+    pub(crate) fn dump(&self, key: &str) -> Option<Bytes> {
+        self.live_entry(key)
+            .map(|entry| persistence::encode(&entry.data))
+    }
+
+    // 使用反序列化得到的值创建键，replace 为 false 时不覆盖已经存在的键
+    // 键已经存在并且没有指定 replace 时返回 None，否则返回是否需要通知过期任务
+    pub(crate) fn restore(
+        &mut self,
+        key: String,
+        value: Value,
+        expires_at: Option<Instant>,
+        replace: bool,
+    ) -> Option<bool> {
+        if !replace && self.live_entry(&key).is_some() {
+            return None;
+        }
+        Some(self.set_at(key, value, expires_at))
+    }
+
+    // 获取并删除指定的键，同时删除它的过期信息，返回原来的值
+    // 键的值不是字符串时返回 WrongType，键保持不变
+    // 已经过期但还未被后台任务清理的键视为不存在，返回 None，不会删除它
+    pub(crate) fn get_del(&mut self, key: &str) -> Result<Option<Bytes>, WrongType> {
+        let value = match self.live_entry(key).map(|entry| &entry.data) {
+            Some(Value::String(data)) => data.clone(),
+            Some(_) => return Err(WrongType),
+            None => return Ok(None),
+        };
+        self.remove_entry(key);
+        Ok(Some(value))
+    }
+
+    // 移除指定的键，返回它的值和过期时间点
+    pub(crate) fn remove_entry(&mut self, key: &str) -> Option<(Value, Option<Instant>)> {
+        let entry = self.entries.remove(key)?;
+        self.ids.remove(&entry.id);
+        if let Some(when) = entry.expires_at {
+            self.expirations.remove(&(when, entry.id));
+        }
+        self.used_memory -= key.len() + entry.data.memory_usage();
+        Some((entry.data, entry.expires_at))
+    }
+
+    // 删除指定的键
+    pub(crate) fn del(&mut self, key: &str) -> usize {
+        // 尝试从entries中移除键，并获取移除的结果
+        let removed_entry = self.entries.remove(key);
+
+        // 如果成功移除了键，则还需要从expirations中移除相关的过期信息
+        if let Some(entry) = removed_entry {
+            self.ids.remove(&entry.id);
+            self.used_memory -= key.len() + entry.data.memory_usage();
+            if let Some(expires_at) = entry.expires_at {
+                self.expirations.remove(&(expires_at, entry.id));
+            }
+            1 // 返回true表示成功移除了键
+        } else {
+            0 // 返回false表示键不存在，未进行移除操作
+        }
+    }
+
+    // 删除所有的键，返回删除的键的数量
+    // 直接换成新的空表以释放内存，`next_id` 保持递增，之前的 SCAN 游标不会指向新的键
+    pub(crate) fn flush(&mut self) -> usize {
+        let len = self.entries.len();
+        self.entries = HashMap::new();
+        self.expirations = BTreeMap::new();
+        self.ids = BTreeMap::new();
+        self.used_memory = 0;
+        len
+    }
+
+    // 按照 `policy` 选择一个键删除，返回被删除的键，没有可以删除的键时返回 None
+    //
+    // 与 Redis 一样，LRU 策略是近似的：随机抽取几个键，删除其中最久没有被访问的键
+    pub(crate) fn evict(&mut self, policy: MaxmemoryPolicy) -> Option<String> {
+        let key = match policy {
+            MaxmemoryPolicy::NoEviction => return None,
+            MaxmemoryPolicy::AllKeysRandom => self.random_key()?,
+            MaxmemoryPolicy::AllKeysLru => self.least_recently_used(false)?,
+            MaxmemoryPolicy::VolatileLru => self.least_recently_used(true)?,
+            MaxmemoryPolicy::VolatileTtl => self.expirations.values().next()?.clone(),
+        };
+
+        self.remove_entry(&key);
+        Some(key)
+    }
+
+    // 随机选择一个键
+    // 在 id 的范围内取一个随机数，选择不小于它的第一个键，超过最大的 id 时从头开始
+    fn random_key(&self) -> Option<String> {
+        let (&first, _) = self.ids.first_key_value()?;
+        let id = first + random() % (self.next_id - first);
+        let (_, key) = self
+            .ids
+            .range(id..)
+            .next()
+            .or_else(|| self.ids.first_key_value())?;
+        Some(key.clone())
+    }
+
+    // 在随机抽取的键中选择最久没有被访问的键，`volatile` 为 true 时只考虑设置了过期时间的键
+    // 只考虑设置了过期时间的键时，抽取到的键可能都没有过期时间，这时从最早过期的键中选择
+    fn least_recently_used(&self, volatile: bool) -> Option<String> {
+        let mut samples: Vec<String> = (0..EVICTION_SAMPLES * 4)
+            .filter_map(|_| self.random_key())
+            .filter(|key| !volatile || self.entries[key].expires_at.is_some())
+            .take(EVICTION_SAMPLES)
+            .collect();
+        if samples.is_empty() && volatile {
+            samples = self
+                .expirations
+                .values()
+                .take(EVICTION_SAMPLES)
+                .cloned()
+                .collect();
+        }
+
+        samples
+            .into_iter()
+            .min_by_key(|key| self.entries[key].last_access.load(Ordering::Relaxed))
+    }
+
+    // 键被大量删除之后释放多余的内存，返回是否进行了整理
+    // 只有哈希表的容量远大于键的数量时才整理，避免在键的数量正常波动时反复分配内存
+    pub(crate) fn compact(&mut self) -> bool {
+        let len = self.entries.len();
+        if self.entries.capacity() <= (len * 4).max(COMPACT_MIN_CAPACITY) {
+            return false;
+        }
+
+        self.entries.shrink_to_fit();
+        // BTreeMap 没有容量，删除之后留下的半空节点只能通过重建来回收
+        self.expirations = mem::take(&mut self.expirations).into_iter().collect();
+        self.ids = mem::take(&mut self.ids).into_iter().collect();
+        true
+    }
+
+    // 哈希表当前的容量
+    #[cfg(test)]
+    pub(crate) fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    // 清理过期键，被删除的键追加到 expired 中
+    pub(crate) fn purge_expired_keys(&mut self, expired: &mut Vec<String>) -> Option<Instant> {
         let now = Instant::now(); // 获取当前时间点的Instant对象
         while let Some((&(when, id), key)) = self.expirations.first_key_value() {
             // 如果最早的过期时间大于当前时间，则返回该过期时间点，表示暂时不需要清理
@@ -153,21 +1054,558 @@ impl Store {
                 return Some(when);
             }
 
-            // 否则，从entries和expirations中移除过期键对应的条目
-            self.entries.remove(key);
-            self.expirations.remove(&(when, id));
+            // 否则，从entries和expirations中移除过期键对应的条目。
+            // 过期信息总是和键一起删除，因此这里的键一定还存在并且 id 相同；
+            // 仍然检查 id，保证不会误删同名的新键
+            if self.entries.get(key).map(|entry| entry.id) == Some(id) {
+                if let Some(entry) = self.entries.remove(key) {
+                    self.used_memory -= key.len() + entry.data.memory_usage();
+                }
+            }
+            self.ids.remove(&id);
+            if let Some(key) = self.expirations.remove(&(when, id)) {
+                expired.push(key);
+            }
         }
 
         None // 清理完成后返回None，表示没有需要清理的过期键
     }
+}
+
+impl Value {
+    // 值的编码名称，模仿 Redis 的内部编码：能解析为整数的字符串是 int，短字符串是 embstr，其他字符串是 raw
+    fn encoding(&self) -> &'static str {
+        match self {
+            Value::String(data) if data.len() <= 20 && parses_as_int(data) => "int",
+            Value::String(data) if data.len() <= 44 => "embstr",
+            Value::String(_) => "raw",
+            Value::List(_) => "quicklist",
+            Value::Hash(_) | Value::Set(_) => "hashtable",
+        }
+    }
+
+    // 值占用的字节数，计入 `Store::usage`
+    fn memory_usage(&self) -> usize {
+        match self {
+            Value::String(data) => data.len(),
+            Value::List(list) => list.iter().map(Bytes::len).sum(),
+            Value::Hash(hash) => hash
+                .iter()
+                .map(|(field, value)| field.len() + value.len())
+                .sum(),
+            Value::Set(set) => set.iter().map(Bytes::len).sum(),
+        }
+    }
+}
+
+// 判断字符串是否是一个完整的 64 位整数
+fn parses_as_int(data: &[u8]) -> bool {
+    std::str::from_utf8(data)
+        .map(|s| s.parse::<i64>().is_ok())
+        .unwrap_or(false)
+}
+
+impl Entry {
+    // 键是否还没有过期
+    fn is_alive(&self) -> bool {
+        self.expires_at
+            .map(|when| when > Instant::now())
+            .unwrap_or(true)
+    }
+}
+
+// 返回一个随机数，只用于选择删除的键，不需要很好的随机性
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+// 将字符串值解析为浮点数，与 Redis 一样不接受 NaN
+fn parse_float(data: &[u8]) -> Option<f64> {
+    std::str::from_utf8(data)
+        .ok()
+        .and_then(|data| data.parse::<f64>().ok())
+        .filter(|value| !value.is_nan())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 检查 entries、expirations 和 ids 三者一致，没有悬空的过期信息或索引
+    fn assert_consistent(store: &Store) {
+        for (&(when, id), key) in &store.expirations {
+            let entry = store.entries.get(key).expect("dangling expiration");
+            assert_eq!((id, Some(when)), (entry.id, entry.expires_at));
+        }
+        for (key, entry) in &store.entries {
+            assert_eq!(Some(key), store.ids.get(&entry.id));
+            if let Some(when) = entry.expires_at {
+                assert_eq!(Some(key), store.expirations.get(&(when, entry.id)));
+            }
+        }
+        assert_eq!(store.entries.len(), store.ids.len());
+        let used: usize = store
+            .entries
+            .iter()
+            .map(|(key, entry)| key.len() + entry.data.memory_usage())
+            .sum();
+        assert_eq!(used, store.used_memory);
+    }
+
+    // 在同一组键上交替执行 DEL、设置过期时间和清理过期键，每一步之后三者都保持一致
+    #[test]
+    fn del_and_purge_keep_expirations_consistent() {
+        let mut store = Store::new();
+        let mut expired = vec![];
+        // 固定种子的线性同余生成器，保证测试可以重现
+        let mut seed: u64 = 42;
+        let mut next = move |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+
+        for _ in 0..10_000 {
+            let key = format!("key:{}", next(8));
+            match next(5) {
+                // 立即过期，等待被清理
+                0 => {
+                    store.set(key, Bytes::from("v"), Some(Duration::ZERO));
+                }
+                1 => {
+                    store.set(key, Bytes::from("v"), Some(Duration::from_secs(3600)));
+                }
+                2 => {
+                    store.set(key, Bytes::from("v"), None);
+                }
+                3 => {
+                    store.del(&key);
+                }
+                _ => {
+                    store.purge_expired_keys(&mut expired);
+                }
+            }
+            assert_consistent(&store);
+        }
+
+        store.purge_expired_keys(&mut expired);
+        assert_consistent(&store);
+        assert!(store
+            .expirations
+            .keys()
+            .all(|&(when, _)| when > Instant::now()));
+    }
+
+    // 每一种修改键空间的操作之后，used_memory 都等于所有键和值的长度之和
+    #[test]
+    fn used_memory_tracks_every_write() {
+        let mut store = Store::new();
+        let mut expired = vec![];
+        let bytes = |s: &str| Bytes::from(s.to_string());
+
+        store.set("str".into(), bytes("hello"), None);
+        store.set("str".into(), bytes("hi"), None);
+        store.set_keep_ttl("str".into(), bytes("hello world"));
+        store.append("str".into(), bytes("!"), usize::MAX).unwrap();
+        store
+            .append("new".into(), bytes("abc"), usize::MAX)
+            .unwrap();
+        assert_consistent(&store);
+        assert_eq!(3 + 12 + 3 + 3, store.usage().memory);
+
+        store.setbit("bits".into(), 100, true).unwrap();
+        store.setbit("new".into(), 40, true).unwrap();
+        store.setrange("new".into(), 2, bytes("xyz")).unwrap();
+        store.setrange("range".into(), 3, bytes("ab")).unwrap();
+        assert_consistent(&store);
+        store.del("range");
+        store.incr_by_float("float".into(), 1.5).unwrap();
+        store.incr_by_float("float".into(), 100.25).unwrap();
+        assert_consistent(&store);
+        store.del("float");
+        store.del("bits");
+
+        store
+            .rpush("list".into(), vec![bytes("a"), bytes("bb")])
+            .unwrap();
+        store.lpush("list".into(), vec![bytes("ccc")]).unwrap();
+        store.lpop("list").unwrap();
+        assert_consistent(&store);
+
+        store
+            .hset(
+                "hash".into(),
+                vec![("f1".into(), bytes("v1")), ("f2".into(), bytes("v2"))],
+            )
+            .unwrap();
+        store
+            .hset("hash".into(), vec![("f1".into(), bytes("longer"))])
+            .unwrap();
+        store
+            .hdel("hash", &["f2".to_string(), "missing".to_string()])
+            .unwrap();
+        assert_consistent(&store);
+
+        store
+            .sadd("set".into(), vec![bytes("a"), bytes("bb"), bytes("a")])
+            .unwrap();
+        store
+            .sadd("set".into(), vec![bytes("bb"), bytes("ccc")])
+            .unwrap();
+        assert_consistent(&store);
+        store.del("set");
+
+        store.rename("hash", "str".into());
+        store.rename("str", "renamed".into());
+        store.get_del("new").unwrap();
+        store.set("gone".into(), bytes("x"), None);
+        store.remove_entry("gone");
+        assert_consistent(&store);
+
+        store.set("short".into(), bytes("v"), Some(Duration::ZERO));
+        store.purge_expired_keys(&mut expired);
+        store.rpop("list").unwrap();
+        store.rpop("list").unwrap();
+        store.del("renamed");
+        assert_consistent(&store);
+        assert_eq!(0, store.usage().memory);
+    }
+
+    // 每种策略只删除它允许删除的键
+    #[test]
+    fn evict_by_policy() {
+        let persistent = |store: &mut Store| {
+            store.set("a".into(), Bytes::from("1"), None);
+            store.set("b".into(), Bytes::from("2"), None);
+        };
+
+        let mut store = Store::new();
+        persistent(&mut store);
+        store.set(
+            "later".into(),
+            Bytes::from("3"),
+            Some(Duration::from_secs(20)),
+        );
+        store.set(
+            "sooner".into(),
+            Bytes::from("4"),
+            Some(Duration::from_secs(10)),
+        );
+        assert_eq!(None, store.evict(MaxmemoryPolicy::NoEviction));
+        assert_eq!(
+            Some("sooner".into()),
+            store.evict(MaxmemoryPolicy::VolatileTtl)
+        );
+        assert_eq!(
+            Some("later".into()),
+            store.evict(MaxmemoryPolicy::VolatileTtl)
+        );
+        assert_eq!(None, store.evict(MaxmemoryPolicy::VolatileTtl));
+        assert_consistent(&store);
+
+        // volatile-lru 只删除设置了过期时间的键，即使它们刚刚被访问过
+        let mut store = Store::new();
+        persistent(&mut store);
+        store.set(
+            "volatile".into(),
+            Bytes::from("3"),
+            Some(Duration::from_secs(10)),
+        );
+        store.get_typed("volatile").unwrap();
+        assert_eq!(
+            Some("volatile".into()),
+            store.evict(MaxmemoryPolicy::VolatileLru)
+        );
+        assert_eq!(None, store.evict(MaxmemoryPolicy::VolatileLru));
+        assert_eq!(2, store.usage().keys);
+        assert_consistent(&store);
+
+        // allkeys 策略删除所有的键，直到存储为空
+        for policy in [MaxmemoryPolicy::AllKeysLru, MaxmemoryPolicy::AllKeysRandom] {
+            let mut store = Store::new();
+            persistent(&mut store);
+            store.set(
+                "volatile".into(),
+                Bytes::from("3"),
+                Some(Duration::from_secs(10)),
+            );
+            for _ in 0..3 {
+                assert!(store.evict(policy).is_some());
+            }
+            assert_eq!(None, store.evict(policy));
+            assert_consistent(&store);
+            assert_eq!(0, store.usage().memory);
+        }
+    }
+
+    // 写入会改变键的版本号，读取不会，删除之后重新创建的键不会得到以前的版本号
+    #[test]
+    fn version_changes_on_write() {
+        let mut store = Store::new();
+        assert_eq!(None, store.version("foo"));
+
+        store.set("foo".into(), Bytes::from("1"), None);
+        let created = store.version("foo").unwrap();
+        store.get_typed("foo").unwrap();
+        assert_eq!(Some(created), store.version("foo"));
+
+        store
+            .append("foo".into(), Bytes::from("2"), usize::MAX)
+            .unwrap();
+        let appended = store.version("foo").unwrap();
+        assert_ne!(created, appended);
 
-    // 设置关闭标志
-    pub(crate) fn set_shutdown(&mut self, value: bool) {
-        self.shutdown = value;
+        store.del("foo");
+        assert_eq!(None, store.version("foo"));
+        store.set("foo".into(), Bytes::from("12"), None);
+        let recreated = store.version("foo").unwrap();
+        assert!(recreated != created && recreated != appended);
+
+        // 被 RENAME 覆盖的键同样视为被修改
+        store.set("bar".into(), Bytes::from("3"), None);
+        store.rename("bar", "foo".into());
+        assert_ne!(Some(recreated), store.version("foo"));
+    }
+
+    // FLUSHALL 删除所有的键和过期时间，之后仍然可以正常写入
+    #[test]
+    fn flush() {
+        let mut store = Store::new();
+        store.set("a".into(), Bytes::from("1"), None);
+        store.set("b".into(), Bytes::from("2"), Some(Duration::from_secs(10)));
+        store.sadd("c".into(), vec![Bytes::from("x")]).unwrap();
+
+        assert_eq!(3, store.flush());
+        assert_consistent(&store);
+        assert_eq!(0, store.usage().keys);
+        assert_eq!(None, store.next_expiration());
+        assert_eq!(0, store.usage().memory);
+
+        store.set("a".into(), Bytes::from("1"), None);
+        assert_eq!(Some(Bytes::from("1")), store.get_typed("a").unwrap());
+        assert_consistent(&store);
     }
 
-    // 检查是否已关闭
-    pub(crate) fn is_shutdown(&self) -> bool {
-        self.shutdown
+    // get_typed 只返回字符串的值，其他类型的值返回 WrongType 并且不修改键
+    #[test]
+    fn get_typed_checks_value_type() {
+        let mut store = Store::new();
+        store.set("str".into(), Bytes::from("v"), None);
+        store.lpush("list".into(), vec![Bytes::from("a")]).unwrap();
+        store
+            .hset("hash".into(), vec![("f".to_string(), Bytes::from("v"))])
+            .unwrap();
+        store.sadd("set".into(), vec![Bytes::from("a")]).unwrap();
+
+        assert_eq!(Ok(Some(Bytes::from("v"))), store.get_typed("str"));
+        assert_eq!(Ok(None), store.get_typed("missing"));
+        for key in ["list", "hash", "set"] {
+            assert_eq!(Err(WrongType), store.get_typed(key));
+        }
+        assert_eq!(4, store.usage().keys);
+        assert_consistent(&store);
+    }
+
+    // 已经过期但还未被清理的键，get_typed 与 ttl 一样视为不存在
+    #[tokio::test(start_paused = true)]
+    async fn get_typed_skips_expired_key() {
+        let mut store = Store::new();
+        store.set("foo".into(), Bytes::from("v"), Some(Duration::from_secs(1)));
+        assert_eq!(Ok(Some(Bytes::from("v"))), store.get_typed("foo"));
+
+        tokio::time::advance(Duration::from_secs(2)).await;
+        // 还没有清理，键仍然在存储中
+        assert_eq!(1, store.usage().keys);
+        assert_eq!(None, store.ttl("foo"));
+        assert_eq!(Ok(None), store.get_typed("foo"));
+    }
+
+    // 已经过期但还未被清理的 src 视为不存在，RENAME 失败并且不覆盖 dst
+    #[tokio::test(start_paused = true)]
+    async fn rename_skips_expired_src() {
+        let mut store = Store::new();
+        store.set(
+            "src".into(),
+            Bytes::from("old"),
+            Some(Duration::from_secs(1)),
+        );
+        store.set("dst".into(), Bytes::from("keep"), None);
+
+        tokio::time::advance(Duration::from_secs(2)).await;
+        assert!(!store.rename("src", "dst".into()));
+        assert_eq!(Ok(Some(Bytes::from("keep"))), store.get_typed("dst"));
+    }
+
+    // SETBIT 按需用 0 补齐字符串并返回原来的位，GETBIT 超出长度的位是 0
+    #[test]
+    fn setbit_and_getbit() {
+        let mut store = Store::new();
+
+        assert_eq!(Ok(false), store.setbit("key".into(), 7, true));
+        assert_eq!(
+            Ok(Some(Bytes::from_static(&[0x01]))),
+            store.get_typed("key")
+        );
+        assert_eq!(Ok(true), store.getbit("key", 7));
+        assert_eq!(Ok(false), store.getbit("key", 6));
+        assert_eq!(Ok(false), store.getbit("key", 1000));
+
+        // 扩展已有的字符串，原有的字节不变
+        assert_eq!(Ok(false), store.setbit("key".into(), 16, true));
+        assert_eq!(
+            Ok(Some(Bytes::from_static(&[0x01, 0x00, 0x80]))),
+            store.get_typed("key")
+        );
+        assert_eq!(Ok(true), store.setbit("key".into(), 7, false));
+        assert_eq!(Ok(false), store.getbit("key", 7));
+        assert_consistent(&store);
+
+        store.rpush("list".into(), vec![Bytes::from("a")]).unwrap();
+        assert_eq!(Err(WrongType), store.setbit("list".into(), 0, true));
+        assert_eq!(Err(WrongType), store.getbit("list", 0));
+    }
+
+    // EXPIRE 的每个选项分别作用于有和没有过期时间的键
+    #[test]
+    fn expire_conditions() {
+        use ExpireCondition::*;
+
+        let mut store = Store::new();
+        let now = Instant::now();
+        let (soon, later) = (now + Duration::from_secs(10), now + Duration::from_secs(20));
+        let ttl = |store: &Store, key: &str| store.entries[key].expires_at;
+
+        // 每个用例：条件、键原有的过期时间、新的过期时间、是否设置
+        let cases = [
+            (Nx, None, soon, true),
+            (Nx, Some(soon), later, false),
+            (Xx, None, soon, false),
+            (Xx, Some(soon), later, true),
+            (Gt, None, soon, false),
+            (Gt, Some(soon), later, true),
+            (Gt, Some(later), soon, false),
+            (Lt, None, soon, true),
+            (Lt, Some(later), soon, true),
+            (Lt, Some(soon), later, false),
+            (Always, Some(later), soon, true),
+        ];
+        for (condition, current, when, applied) in cases {
+            store.set_at("key".into(), Value::String(Bytes::from("v")), current);
+            assert_eq!(
+                applied,
+                store.expire("key", when, condition).is_some(),
+                "{:?} {:?}",
+                condition,
+                current
+            );
+            let expected = if applied { Some(when) } else { current };
+            assert_eq!(expected, ttl(&store, "key"), "{:?}", condition);
+            assert_consistent(&store);
+        }
+
+        assert_eq!(None, store.expire("missing", soon, Always));
+        // 已经过去的时间点满足条件时删除键，不满足时键保持不变
+        assert_eq!(None, store.expire("key", now, Nx));
+        assert_eq!(Some(false), store.expire("key", now, Xx));
+        assert!(!store.entries.contains_key("key"));
+        assert_consistent(&store);
+    }
+
+    // SETRANGE 原地覆盖或者用 0 补齐后扩展字符串，空的 value 不会创建键
+    #[test]
+    fn setrange() {
+        let mut store = Store::new();
+
+        store.set("key".into(), Bytes::from("Hello World"), None);
+        assert_eq!(
+            Ok(11),
+            store.setrange("key".into(), 6, Bytes::from("Redis"))
+        );
+        assert_eq!(Ok(Some(Bytes::from("Hello Redis"))), store.get_typed("key"));
+        assert_eq!(Ok(13), store.setrange("key".into(), 11, Bytes::from("!!")));
+        assert_eq!(
+            Ok(Some(Bytes::from("Hello Redis!!"))),
+            store.get_typed("key")
+        );
+
+        assert_eq!(Ok(5), store.setrange("new".into(), 3, Bytes::from("ab")));
+        assert_eq!(
+            Ok(Some(Bytes::from_static(b"\0\0\0ab"))),
+            store.get_typed("new")
+        );
+        assert_eq!(Ok(0), store.setrange("empty".into(), 10, Bytes::new()));
+        assert_eq!(Ok(None), store.get_typed("empty"));
+        assert_eq!(Ok(5), store.setrange("new".into(), 100, Bytes::new()));
+
+        store.rpush("list".into(), vec![Bytes::from("a")]).unwrap();
+        assert_eq!(
+            Err(WrongType),
+            store.setrange("list".into(), 0, Bytes::from("b"))
+        );
+        assert_consistent(&store);
+    }
+
+    #[test]
+    fn incr_by_float() {
+        let mut store = Store::new();
+
+        assert_eq!(Ok(10.5), store.incr_by_float("key".into(), 10.5));
+        assert_eq!(Ok(10.6), store.incr_by_float("key".into(), 0.1));
+        assert_eq!(Ok(Some(Bytes::from("10.6"))), store.get_typed("key"));
+        // 结果是整数时不保存小数部分
+        assert_eq!(Ok(3.0), store.incr_by_float("key".into(), -7.6));
+        assert_eq!(Ok(Some(Bytes::from("3"))), store.get_typed("key"));
+
+        assert_eq!(
+            Err(IncrError::NanOrInfinity),
+            store.incr_by_float("key".into(), f64::INFINITY)
+        );
+        assert_eq!(Ok(Some(Bytes::from("3"))), store.get_typed("key"));
+
+        store.set("text".into(), Bytes::from("abc"), None);
+        assert_eq!(
+            Err(IncrError::NotAFloat),
+            store.incr_by_float("text".into(), 1.0)
+        );
+        store.rpush("list".into(), vec![Bytes::from("a")]).unwrap();
+        assert_eq!(
+            Err(IncrError::WrongType),
+            store.incr_by_float("list".into(), 1.0)
+        );
+        assert_consistent(&store);
+    }
+
+    // SADD 只计算新增的成员，集合和其他类型的命令互相返回 WrongType
+    #[test]
+    fn set_members() {
+        let mut store = Store::new();
+        let bytes = |s: &str| Bytes::from(s.to_string());
+
+        assert_eq!(
+            Ok(2),
+            store.sadd("set".into(), vec![bytes("a"), bytes("b"), bytes("a")])
+        );
+        assert_eq!(
+            Ok(1),
+            store.sadd("set".into(), vec![bytes("b"), bytes("c")])
+        );
+        assert_eq!(Ok(0), store.sadd("set".into(), vec![bytes("c")]));
+
+        let mut members = store.smembers("set").unwrap();
+        members.sort();
+        assert_eq!(vec![bytes("a"), bytes("b"), bytes("c")], members);
+        assert_eq!(Ok(true), store.sismember("set", b"a"));
+        assert_eq!(Ok(false), store.sismember("set", b"d"));
+        assert_eq!(Ok(false), store.sismember("missing", b"a"));
+        assert_eq!(Ok(vec![]), store.smembers("missing"));
+
+        store.set("str".into(), bytes("v"), None);
+        assert_eq!(Err(WrongType), store.sadd("str".into(), vec![bytes("a")]));
+        assert_eq!(Err(WrongType), store.smembers("str"));
+        assert_eq!(Err(WrongType), store.sismember("str", b"a"));
+        assert!(store.get_typed("set").is_err());
+        assert!(store.lpush("set".into(), vec![bytes("a")]).is_err());
+        assert_consistent(&store);
     }
 }
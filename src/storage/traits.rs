@@ -1,13 +1,49 @@
 use bytes::Bytes; // 引入 bytes crate 中的 Bytes 类型
-use std::time::Duration; // 引入标准库中的 Duration 类型
+use std::future::Future; // 引入标准库中的 Future 特性
+use std::pin::Pin; // 引入标准库中的 Pin 类型
+use std::time::{Duration, SystemTime}; // 引入标准库中的 Duration 和 SystemTime 类型
 use tokio::sync::broadcast; // 引入 tokio crate 中的 broadcast 模块
 
+use crate::storage::store::{ExpireCondition, IncrError, Value, WrongType}; // 引入值的类型和命令执行失败的错误
+
 // KvStore trait 定义了键值存储的基本行为
 pub trait KvStore {
     // 根据给定的键返回关联的值，如果键不存在，则返回 None。
     // # 参数`key`: 要查找的键的引用
-    // # 返回一个 Option 类型，如果键存在，则返回 Some 包含的 Bytes 值，否则返回 None。
-    fn get(&self, key: &str) -> Option<Bytes>;
+    // # 返回一个 Option 类型，如果键存在，则返回 Some 包含的 Bytes 值，否则返回 None。键的值不是字符串时返回 WrongType。
+    fn get(&self, key: &str) -> Result<Option<Bytes>, WrongType>;
+
+    // 返回键的剩余生存时间。
+    // # 参数`key`: 要查找的键的引用
+    // # 返回键不存在时返回 None；键没有设置过期时间时返回 Some(None)，否则返回 Some(Some(剩余时间))。
+    fn ttl(&self, key: &str) -> Option<Option<Duration>>;
+
+    // 访问指定的键但不返回值。
+    // # 参数`key`: 要访问的键的引用
+    // # 返回键是否存在。
+    fn touch(&self, key: &str) -> bool;
+
+    // 返回键的值的编码名称，例如 "int"、"embstr"。不会更新键的访问时间。
+    // # 参数`key`: 要查找的键的引用
+    // # 返回键不存在时返回 None。
+    fn object_encoding(&self, key: &str) -> Option<&'static str>;
+
+    // 返回键距离上次被访问经过的时间。不会更新键的访问时间。
+    // # 参数`key`: 要查找的键的引用
+    // # 返回键不存在时返回 None。
+    fn object_idle_time(&self, key: &str) -> Option<Duration>;
+
+    // 估算键占用的字节数，包括键名、值和固定的开销。不会更新键的访问时间。
+    // # 参数`key`: 要查找的键的引用
+    // # 返回键不存在时返回 None。
+    fn memory_usage(&self, key: &str) -> Option<usize>;
+
+    // 从游标 `cursor` 开始遍历键空间。
+    // # 参数
+    // - `cursor`: 上一次遍历返回的游标，从头开始遍历时为 0
+    // - `count`: 本次最多遍历的键数量，已经过期的键也计入其中
+    // # 返回下一次遍历的游标和遍历到的键，游标为 0 表示遍历已经完成。
+    fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<String>);
 
     // 设置与键关联的值以及一个可选的过期时间。
     // 如果键已经存在，则旧的值会被移除。
@@ -17,6 +53,147 @@ pub trait KvStore {
     // - `expire`: 可选的过期时间，类型为 Option<Duration>
     fn set(&self, key: String, value: Bytes, expire: Option<Duration>);
 
+    // 设置与键关联的值，并保留键原有的过期时间。
+    // 如果键不存在，则设置为没有过期时间的新键。
+    // # 参数
+    // - `key`: 要设置的键，类型为 String
+    // - `value`: 要存储的值，类型为 Bytes
+    fn set_keep_ttl(&self, key: String, value: Bytes);
+
+    // 为已存在的键设置过期时间，`condition` 满足时覆盖原有的过期时间。过期时间为零时删除键。
+    // # 参数
+    // - `key`: 要设置过期时间的键的引用
+    // - `expire`: 从现在开始计算的过期时间
+    // - `condition`: 是否覆盖原有过期时间的条件
+    // # 返回是否设置了过期时间，键不存在或者条件不满足时返回 false。
+    fn expire(&self, key: &str, expire: Duration, condition: ExpireCondition) -> bool;
+
+    // 将已存在的键的过期时间设置为绝对的时间点，覆盖原有的过期时间。已经过去的时间点会立即删除键。
+    // # 参数
+    // - `key`: 要设置过期时间的键的引用
+    // - `when`: 过期的时间点
+    // # 返回键是否存在。
+    fn expire_at(&self, key: &str, when: SystemTime) -> bool;
+
+    // 原子地设置多个键值对，已存在的键的过期时间会被丢弃。
+    // # 参数
+    // - `pairs`: 要设置的键值对
+    fn mset(&self, pairs: Vec<(String, Bytes)>);
+
+    // 获取指定键的值并删除该键。
+    // # 参数`key`: 要获取并删除的键的引用
+    // # 返回一个 Option 类型，如果键存在，则返回 Some 包含的 Bytes 值，否则返回 None。键的值不是字符串时返回 WrongType。
+    fn get_del(&self, key: &str) -> Result<Option<Bytes>, WrongType>;
+
+    // 将 `value` 追加到键原有的值之后，键不存在时等同于设置新键。
+    // # 参数
+    // - `key`: 键，类型为 String
+    // - `value`: 要追加的值，类型为 Bytes
+    // - `max_len`: 追加后的值允许的最大长度
+    // 返回追加后值的长度，超过 `max_len` 时返回 None，不做修改。键的值不是字符串时返回 WrongType。
+    fn append(&self, key: String, value: Bytes, max_len: usize)
+        -> Result<Option<usize>, WrongType>;
+
+    // 将字符串解析为浮点数并加上 `delta`，键不存在时视为 0，返回相加之后的值。
+    // 读取和写入在同一把锁下完成，并发的调用不会丢失更新。
+    fn incr_by_float(&self, key: String, delta: f64) -> Result<f64, IncrError>;
+
+    // 从 `offset` 开始用 `value` 覆盖字符串的一部分，字符串不够长时用 0 补齐，键不存在时创建新的字符串。
+    // 返回修改后字符串的长度。键的值不是字符串时返回 WrongType。
+    fn setrange(&self, key: String, offset: usize, value: Bytes) -> Result<usize, WrongType>;
+
+    // 将字符串中第 `offset` 位设置为 `on`，字符串不够长时用 0 补齐，键不存在时创建新的字符串。
+    // 返回这一位原来的值。键的值不是字符串时返回 WrongType。
+    fn setbit(&self, key: String, offset: usize, on: bool) -> Result<bool, WrongType>;
+
+    // 返回字符串中第 `offset` 位的值，超出字符串长度的位和不存在的键都是 0。
+    // 键的值不是字符串时返回 WrongType。
+    fn getbit(&self, key: &str, offset: usize) -> Result<bool, WrongType>;
+
+    // 将 `values` 依次插入列表的头部，键不存在时创建新的列表。
+    // # 参数
+    // - `key`: 键，类型为 String
+    // - `values`: 要插入的值
+    // 返回一个 usize 类型，表示插入后列表的长度。键的值不是列表时返回 WrongType。
+    fn lpush(&self, key: String, values: Vec<Bytes>) -> Result<usize, WrongType>;
+
+    // 将 `values` 依次插入列表的尾部，键不存在时创建新的列表。
+    // 返回插入后列表的长度。键的值不是列表时返回 WrongType。
+    fn rpush(&self, key: String, values: Vec<Bytes>) -> Result<usize, WrongType>;
+
+    // 移除并返回列表头部的值，列表为空后键被删除。
+    // # 参数`key`: 列表的键
+    // # 返回键不存在时返回 None。键的值不是列表时返回 WrongType。
+    fn lpop(&self, key: &str) -> Result<Option<Bytes>, WrongType>;
+
+    // 移除并返回列表尾部的值，列表为空后键被删除。
+    fn rpop(&self, key: &str) -> Result<Option<Bytes>, WrongType>;
+
+    // 返回列表中下标从 `start` 到 `stop`（包含）的值，负数下标从列表尾部开始计算。
+    // # 参数
+    // - `key`: 列表的键
+    // - `start`, `stop`: 起止下标
+    // # 返回键不存在时返回空数组。键的值不是列表时返回 WrongType。
+    fn lrange(&self, key: &str, start: i64, stop: i64) -> Result<Vec<Bytes>, WrongType>;
+
+    // 返回列表的长度，键不存在时返回 0。键的值不是列表时返回 WrongType。
+    fn llen(&self, key: &str) -> Result<usize, WrongType>;
+
+    // 设置哈希表中的字段，键不存在时创建新的哈希表。
+    // # 参数
+    // - `key`: 哈希表的键
+    // - `pairs`: 要设置的字段和值
+    // 返回一个 usize 类型，表示新增的字段数量。键的值不是哈希表时返回 WrongType。
+    fn hset(&self, key: String, pairs: Vec<(String, Bytes)>) -> Result<usize, WrongType>;
+
+    // 获取哈希表中字段的值，键或者字段不存在时返回 None。键的值不是哈希表时返回 WrongType。
+    fn hget(&self, key: &str, field: &str) -> Result<Option<Bytes>, WrongType>;
+
+    // 判断哈希表中是否存在字段。键的值不是哈希表时返回 WrongType。
+    fn hexists(&self, key: &str, field: &str) -> Result<bool, WrongType>;
+
+    // 获取哈希表中所有的字段和值，顺序不确定。键的值不是哈希表时返回 WrongType。
+    fn hgetall(&self, key: &str) -> Result<Vec<(String, Bytes)>, WrongType>;
+
+    // 删除哈希表中的字段，哈希表为空后键被删除。
+    // 返回一个 usize 类型，表示实际删除的字段数量。键的值不是哈希表时返回 WrongType。
+    fn hdel(&self, key: &str, fields: &[String]) -> Result<usize, WrongType>;
+
+    // 返回哈希表中字段的数量，键不存在时返回 0。键的值不是哈希表时返回 WrongType。
+    fn hlen(&self, key: &str) -> Result<usize, WrongType>;
+
+    // 向集合中添加成员，键不存在时创建新的集合。
+    // # 参数
+    // - `key`: 集合的键
+    // - `members`: 要添加的成员
+    // 返回一个 usize 类型，表示新增的成员数量。键的值不是集合时返回 WrongType。
+    fn sadd(&self, key: String, members: Vec<Bytes>) -> Result<usize, WrongType>;
+
+    // 获取集合中所有的成员，顺序不确定。键的值不是集合时返回 WrongType。
+    fn smembers(&self, key: &str) -> Result<Vec<Bytes>, WrongType>;
+
+    // 判断成员是否在集合中，键不存在时返回 false。键的值不是集合时返回 WrongType。
+    fn sismember(&self, key: &str, member: &[u8]) -> Result<bool, WrongType>;
+
+    // 将 `src` 的值和剩余生存时间移动到 `dst`，覆盖 `dst` 原有的值。
+    // # 参数
+    // - `src`: 原来的键
+    // - `dst`: 新的键
+    // 返回一个 bool 类型，`src` 不存在时返回 false。
+    fn rename(&self, src: &str, dst: String) -> bool;
+
+    // 序列化键的值，格式见 `persistence` 模块。键不存在时返回 None。
+    fn dump(&self, key: &str) -> Option<Bytes>;
+
+    // 使用 `DUMP` 得到的值创建键。
+    // # 参数
+    // - `key`: 要创建的键
+    // - `value`: 反序列化得到的值
+    // - `expire`: 从现在开始计算的过期时间，None 表示不过期
+    // - `replace`: 是否覆盖已经存在的键
+    // 返回一个 bool 类型，键已经存在并且 `replace` 为 false 时返回 false，不做任何修改。
+    fn restore(&self, key: String, value: Value, expire: Option<Duration>, replace: bool) -> bool;
+
     // 返回一个接收者，用于接收指定频道的消息。
     // 返回的 `Receiver` 用于接收由 `PUBLISH` 命令广播的值。
     // # 参数- `key`: 订阅的频道，类型为 String
@@ -30,9 +207,115 @@ pub trait KvStore {
     // 返回一个 usize 类型，表示监听该频道的订阅者数量。
     fn publish(&self, key: &str, value: Bytes) -> usize;
 
+    // 与 `publish` 相同，同时统计已经没有订阅者的频道数量，调用者可以据此发现失效的频道。
+    // 需要遍历所有频道，因此只在调用者需要时使用。
+    // 返回监听该频道的订阅者数量和没有订阅者的频道数量。
+    fn publish_detailed(&self, key: &str, value: Bytes) -> (usize, usize);
+
     // 删除指定的键。
     // # 参数
     // - `key`: 键的名称，类型为 String
     // 返回一个 usize 类型，表示删除的数量。
     fn del(&self, key: String) -> usize;
+
+    // 删除指定的键，但不释放它们的值，而是交给调用者稍后释放。
+    // # 参数
+    // - `keys`: 要删除的键
+    // 返回被删除的键的值，数量即删除的键的数量。
+    fn unlink(&self, keys: &[String]) -> Vec<Value>;
+}
+
+/// [`AsyncKvStore`] 的方法返回的 future，装箱之后 `AsyncKvStore` 可以作为特性对象使用。
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// 异步的键值存储，`GET`、`SET` 和 `DEL` 命令通过它访问数据。
+///
+/// 访问磁盘或者远程服务的存储（例如 SQLite 或 sled）实现这个特性之后，等待 I/O 时不会阻塞运行时的工作线程，
+/// 再通过 [`run_with_store`] 接入服务器。服务器默认使用内存中的数据库，它的实现直接调用同步的方法。
+///
+/// 其他命令需要过期、阻塞、发布订阅等只有内存中的数据库才有的功能，仍然使用内存中的数据库，
+/// 因此自定义存储中的键对 `TTL`、`EXPIRE` 等命令不可见，写入也不会触发键空间事件或者让 `WATCH` 失效。
+///
+/// [`run_with_store`]: crate::server::run_with_store
+pub trait AsyncKvStore {
+    /// 返回键关联的值，键不存在时返回 `None`，键的值不是字符串时返回 [`WrongType`]。
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Bytes>, WrongType>>;
+
+    /// 设置键关联的值以及一个可选的过期时间，覆盖原有的值和过期时间。
+    fn set(&self, key: String, value: Bytes, expire: Option<Duration>) -> BoxFuture<'_, ()>;
+
+    /// 设置键关联的值，并保留键原有的过期时间，键不存在时设置为没有过期时间的新键。
+    fn set_keep_ttl(&self, key: String, value: Bytes) -> BoxFuture<'_, ()>;
+
+    /// 删除指定的键，返回删除的数量。
+    fn del(&self, key: String) -> BoxFuture<'_, usize>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::RwLock;
+
+    use tokio::sync::Mutex;
+
+    use super::*;
+    use crate::cmd::del::Del;
+    use crate::cmd::get::Get;
+    use crate::cmd::set::Set;
+    use crate::config::ServerConfig;
+    use crate::connection::connect::{pair, Connection};
+    use crate::connection::frame::Frame;
+
+    // 一个简单的异步存储，使用异步的锁，不支持过期时间
+    #[derive(Default)]
+    struct MemoryStore {
+        entries: Mutex<HashMap<String, Bytes>>,
+    }
+
+    impl AsyncKvStore for MemoryStore {
+        fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Bytes>, WrongType>> {
+            Box::pin(async move { Ok(self.entries.lock().await.get(key).cloned()) })
+        }
+
+        fn set(&self, key: String, value: Bytes, _expire: Option<Duration>) -> BoxFuture<'_, ()> {
+            Box::pin(async move {
+                self.entries.lock().await.insert(key, value);
+            })
+        }
+
+        fn set_keep_ttl(&self, key: String, value: Bytes) -> BoxFuture<'_, ()> {
+            self.set(key, value, None)
+        }
+
+        fn del(&self, key: String) -> BoxFuture<'_, usize> {
+            Box::pin(async move { self.entries.lock().await.remove(&key).map_or(0, |_| 1) })
+        }
+    }
+
+    // 命令可以在其他的异步存储上执行
+    #[tokio::test]
+    async fn commands_apply_to_async_store() {
+        let store = MemoryStore::default();
+        let (client, mut server) = pair();
+        let mut client = Connection::with_stream(client);
+        let config = RwLock::new(ServerConfig::default());
+
+        Set::new("hello", Bytes::from("world"), None)
+            .apply(&store, &config, &mut server)
+            .await
+            .unwrap();
+        Get::new("hello").apply(&store, &mut server).await.unwrap();
+        Del::new("hello").apply(&store, &mut server).await.unwrap();
+        Get::new("hello").apply(&store, &mut server).await.unwrap();
+
+        let expected = [
+            Frame::Simple("OK".to_string()),
+            Frame::Bulk(Bytes::from("world")),
+            Frame::Integer(1),
+            Frame::Null,
+        ];
+        for frame in expected {
+            assert_eq!(Some(frame), client.read_frame().await.unwrap());
+        }
+    }
 }
@@ -0,0 +1,62 @@
+use std::net::SocketAddr;
+
+use bytes::Bytes;
+use tokio::net::TcpListener;
+use tokio::process::Command;
+
+use mini_redis::{client, server};
+
+/// 通过 CLI 发送包含多字节字符和 `\r\n` 等 RESP 控制字符的 PING 消息。
+/// 消息以带长度前缀的 bulk 发送，嵌入的 `\r\n` 不会被当作帧边界，原样返回。
+#[tokio::test]
+async fn ping_message_with_resp_control_characters() {
+    let addr = start_server().await;
+
+    let msg = "你好\r\n*1\r\n$4\r\nPING\r\n世界";
+    let output = Command::new(env!("CARGO_BIN_EXE_mini-redis-cli"))
+        .args(["--hostname", &addr.ip().to_string()])
+        .args(["--port", &addr.port().to_string()])
+        .args(["ping", msg])
+        .env("LOG_LEVEL", "ERROR")
+        .output()
+        .await
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(format!("\"{}\"\n", msg), stdout);
+}
+
+/// 通过 CLI 执行 GET，值为空字符串时打印 `""`，键不存在时打印 `(nil)`。
+#[tokio::test]
+async fn get_empty_value_and_missing_key() {
+    let addr = start_server().await;
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.set("empty", Bytes::new()).await.unwrap();
+
+    for (key, expected) in [("empty", "\"\"\n"), ("missing", "(nil)\n")] {
+        let output = Command::new(env!("CARGO_BIN_EXE_mini-redis-cli"))
+            .args(["--hostname", &addr.ip().to_string()])
+            .args(["--port", &addr.port().to_string()])
+            .args(["get", key])
+            .env("LOG_LEVEL", "ERROR")
+            .output()
+            .await
+            .unwrap();
+
+        assert!(output.status.success(), "{}", key);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(expected, stdout, "{}", key);
+    }
+}
+
+/// 运行 Redis 服务器并返回绑定的套接字地址
+async fn start_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move { server::run(listener, tokio::signal::ctrl_c()).await });
+
+    addr
+}
@@ -1,7 +1,18 @@
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use bytes::Bytes;
+use mini_redis::client::{Frame, ResilientSubscriber, SubscriberEvent};
+use mini_redis::config::{AccessLogCallback, ExpireCallback, MaxmemoryPolicy, ServerConfig};
+use mini_redis::error::MiniRedisConnectionError;
+use mini_redis::metrics::{InMemoryMetrics, MetricsRecorder};
 use mini_redis::{client, server};
 use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::time::{self, Instant};
+use tokio_stream::StreamExt;
 
 /// 一个没有提供消息的 PING PONG 测试。
 /// 它应该返回 "PONG"。
@@ -39,6 +50,33 @@ async fn key_value_get_set() {
     assert_eq!(b"world", &value[..])
 }
 
+/// 通过 `raw` 发送任意命令，得到未经解释的响应帧，错误帧被转换为 `Err`。
+#[tokio::test]
+async fn raw_commands() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    let response = client
+        .raw(&["SET".into(), "hello".into(), "world".into()])
+        .await
+        .unwrap();
+    assert_eq!(Frame::Simple("OK".to_string()), response);
+
+    let response = client.raw(&["GET".into(), "hello".into()]).await.unwrap();
+    assert_eq!(Frame::Bulk("world".into()), response);
+
+    match client.raw(&["FOO".into(), "bar".into()]).await {
+        Err(MiniRedisConnectionError::CommandExecute(msg)) => {
+            assert_eq!("ERR unknown command 'foo'", msg)
+        }
+        res => panic!("unexpected response {:?}", res),
+    }
+
+    // 错误之后连接仍然可用
+    let response = client.raw(&["GET".into(), "missing".into()]).await.unwrap();
+    assert_eq!(Frame::Null, response);
+}
+
 /// 类似于 "hello world" 风格的测试，但这次测试一个单频道订阅。
 #[tokio::test]
 async fn receive_message_subscribed_channel() {
@@ -57,6 +95,189 @@ async fn receive_message_subscribed_channel() {
     assert_eq!(b"world", &message.content[..])
 }
 
+/// 连续发布一批消息，订阅者按顺序收到全部消息，服务器批量写入消息时不会丢失或者打乱它们。
+#[tokio::test]
+async fn receive_burst_of_messages() {
+    let addr = start_server().await;
+
+    let client = client::connect(addr).await.unwrap();
+    let mut subscriber = client.subscribe(vec!["hello".into()]).await.unwrap();
+
+    // 先全部发布再开始读取，消息在服务器和套接字中积压
+    let mut publisher = client::connect(addr).await.unwrap();
+    for i in 0..500 {
+        publisher
+            .publish("hello", i.to_string().into())
+            .await
+            .unwrap();
+    }
+
+    for i in 0..500 {
+        let message = subscriber.next_message().await.unwrap().unwrap();
+        assert_eq!("hello", &message.channel);
+        assert_eq!(i.to_string().as_bytes(), &message.content[..]);
+    }
+}
+
+/// 订阅者断开之后，`publish_detailed` 报告的接收者数量下降，频道被计为没有订阅者的频道。
+#[tokio::test]
+async fn publish_detailed_after_subscriber_disconnects() {
+    let addr = start_server().await;
+
+    let client = client::connect(addr).await.unwrap();
+    let subscriber = client.subscribe(vec!["hello".into()]).await.unwrap();
+
+    let mut publisher = client::connect(addr).await.unwrap();
+    let details = publisher
+        .publish_detailed("hello", "world".into())
+        .await
+        .unwrap();
+    assert_eq!(1, details.receivers);
+    assert_eq!(0, details.stale_channels);
+
+    // 服务器在处理完断开的连接之后才会释放订阅
+    drop(subscriber);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let details = loop {
+        let details = publisher
+            .publish_detailed("hello", "world".into())
+            .await
+            .unwrap();
+        if details.receivers == 0 {
+            break details;
+        }
+        assert!(Instant::now() < deadline, "subscriber was not released");
+        time::sleep(Duration::from_millis(10)).await;
+    };
+    assert_eq!(1, details.stale_channels);
+}
+
+/// 空闲的订阅 stream 在超时之后返回错误并结束。
+#[tokio::test]
+async fn subscriber_stream_with_timeout_ends_when_idle() {
+    let addr = start_server().await;
+
+    let client = client::connect(addr).await.unwrap();
+    let subscriber = client.subscribe(vec!["hello".into()]).await.unwrap();
+
+    let mut publisher = client::connect(addr).await.unwrap();
+    publisher.publish("hello", "world".into()).await.unwrap();
+
+    let timeout = Duration::from_millis(200);
+    let stream = subscriber.into_stream_with_timeout(timeout);
+    tokio::pin!(stream);
+
+    let message = stream.next().await.unwrap().unwrap();
+    assert_eq!(b"world", &message.content[..]);
+
+    // 之后没有新消息，stream 在超时后返回错误
+    let start = Instant::now();
+    match stream.next().await.unwrap() {
+        Err(MiniRedisConnectionError::Timeout(_)) => {}
+        res => panic!("expected Timeout, got {:?}", res),
+    }
+    assert!(start.elapsed() >= timeout);
+
+    // 返回错误之后 stream 结束
+    assert!(stream.next().await.is_none());
+}
+
+/// 测试订阅确认之后立即发布的消息不会丢失。
+#[tokio::test]
+async fn receive_message_published_right_after_subscribe() {
+    let addr = start_server().await;
+    let mut publisher = client::connect(addr).await.unwrap();
+
+    for i in 0..50 {
+        let channel = format!("channel-{}", i);
+
+        let client = client::connect(addr).await.unwrap();
+        let mut subscriber = client.subscribe(vec![channel.clone()]).await.unwrap();
+
+        // `subscribe` 返回时已经收到了确认，此时立即发布
+        let num = publisher.publish(&channel, "hi".into()).await.unwrap();
+        assert_eq!(1, num);
+
+        let message = subscriber.next_message().await.unwrap().unwrap();
+        assert_eq!(channel, message.channel);
+        assert_eq!(b"hi", &message.content[..]);
+    }
+}
+
+/// 服务器重启后 `ResilientSubscriber` 自动重连并重新订阅，先返回重连事件，然后继续接收消息。
+#[tokio::test]
+async fn resilient_subscriber_resumes_after_server_restart() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let server_task = tokio::spawn(server::run(listener, shutdown_rx));
+
+    let mut subscriber = ResilientSubscriber::connect(addr.to_string(), vec!["hello".into()])
+        .await
+        .unwrap()
+        .with_backoff(Duration::from_millis(10), Duration::from_millis(100));
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.publish("hello", "before".into()).await.unwrap();
+    match subscriber.next_message().await.unwrap() {
+        SubscriberEvent::Message(message) => assert_eq!(b"before", &message.content[..]),
+        event => panic!("unexpected event: {:?}", event),
+    }
+
+    // 关闭服务器，订阅连接随之断开，然后在同一个地址上重新启动
+    shutdown_tx.send(()).unwrap();
+    server_task.await.unwrap();
+    let listener = TcpListener::bind(addr).await.unwrap();
+    tokio::spawn(server::run(listener, tokio::signal::ctrl_c()));
+
+    let event = time::timeout(Duration::from_secs(5), subscriber.next_message())
+        .await
+        .expect("subscriber should reconnect")
+        .unwrap();
+    assert!(matches!(event, SubscriberEvent::Reconnected));
+
+    let mut client = client::connect(addr).await.unwrap();
+    assert_eq!(1, client.publish("hello", "after".into()).await.unwrap());
+    match subscriber.next_message().await.unwrap() {
+        SubscriberEvent::Message(message) => {
+            assert_eq!("hello", &message.channel);
+            assert_eq!(b"after", &message.content[..]);
+        }
+        event => panic!("unexpected event: {:?}", event),
+    }
+    assert_eq!(&["hello".to_string()], subscriber.get_subscribed());
+}
+
+/// FLUSHALL 删除所有的键，已有的订阅不受影响。
+///
+/// 服务器只有一个数据库，这里通过很多个键确保它们分布在不同的分片中。
+#[tokio::test]
+async fn flushall_keeps_subscriptions() {
+    let addr = start_server().await;
+
+    let subscriber = client::connect(addr).await.unwrap();
+    let mut subscriber = subscriber.subscribe(vec!["hello".into()]).await.unwrap();
+
+    let mut client = client::connect(addr).await.unwrap();
+    for i in 0..100 {
+        client
+            .set(&format!("key{}", i), "value".into())
+            .await
+            .unwrap();
+    }
+    client.rpush("list", &["a".into()]).await.unwrap();
+    assert_eq!(101, client.dbsize().await.unwrap());
+
+    client.flushall().await.unwrap();
+    assert_eq!(0, client.dbsize().await.unwrap());
+    assert_eq!(None, client.get("key0").await.unwrap());
+    assert_eq!(0, client.llen("list").await.unwrap());
+
+    assert_eq!(1, client.publish("hello", "world".into()).await.unwrap());
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!(b"world", &message.content[..]);
+}
+
 /// 测试客户端从多个订阅频道接收消息。
 #[tokio::test]
 async fn receive_message_multiple_subscribed_channels() {
@@ -102,33 +323,1481 @@ async fn unsubscribes_from_channels() {
     assert_eq!(subscriber.get_subscribed().len(), 0);
 }
 
+/// 测试 RESET 取消所有订阅，返回的客户端可以执行普通命令，`CLIENT SETNAME` 设置的名称也被清除。
+#[tokio::test]
+async fn reset_leaves_subscribe_mode() {
+    let addr = start_server().await;
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.client_setname("worker-1").await.unwrap();
+    let subscriber = client.subscribe(vec!["hello".into()]).await.unwrap();
+
+    let mut client = subscriber.reset().await.unwrap();
+    assert_eq!(None, client.client_getname().await.unwrap());
+    client.set("foo", "bar".into()).await.unwrap();
+    assert_eq!(b"bar", &client.get("foo").await.unwrap().unwrap()[..]);
+    assert_eq!(0, client.publish("hello", "world".into()).await.unwrap());
+
+    client.client_setname("worker-2").await.unwrap();
+    client.reset().await.unwrap();
+    assert_eq!(None, client.client_getname().await.unwrap());
+}
+
 /// 测试 DEL 命令，确保键被删除并且返回正确的删除数量。  
-#[tokio::test]  
-async fn test_del_command() {  
-    let addr = start_server().await;  
-  
-    let mut client = client::connect(addr).await.unwrap();  
-  
-    // 首先设置一个键  
-    client.set("hello", "world".into()).await.unwrap();  
-  
-    // 然后尝试删除这个键  
-    let deleted_count = client.del("hello").await.unwrap();  
-  
-    // 断言键被成功删除，且删除数量为 1  
-    assert_eq!(deleted_count, 1);  
-  
+#[tokio::test]
+async fn test_del_command() {
+    let addr = start_server().await;
+
+    let mut client = client::connect(addr).await.unwrap();
+
+    // 首先设置一个键
+    client.set("hello", "world".into()).await.unwrap();
+
+    // 然后尝试删除这个键
+    let deleted_count = client.del("hello").await.unwrap();
+
+    // 断言键被成功删除，且删除数量为 1
+    assert_eq!(deleted_count, 1);
+
     // 再次尝试获取被删除的键， 会出错
-    // let value = client.get("hello").await.unwrap();  
-    // assert!(value.is_none());  
+    // let value = client.get("hello").await.unwrap();
+    // assert!(value.is_none());
 }
 
-/// 启动服务器并返回服务器地址
-async fn start_server() -> SocketAddr {
-    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
-    let addr = listener.local_addr().unwrap();
+/// 包含 `\r\n` 和 NUL 字节的值在 SET/GET、PUBLISH 和 APPEND 中都能原样往返。
+#[tokio::test]
+async fn values_with_crlf_and_nul_round_trip() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
 
-    tokio::spawn(async move { server::run(listener, tokio::signal::ctrl_c()).await });
+    let value = Bytes::from_static(b"line1\r\n*2\r\n$3\r\n\0nul\0\r\nend");
 
-    addr
-}
\ No newline at end of file
+    client.set("foo", value.clone()).await.unwrap();
+    assert_eq!(value, client.get("foo").await.unwrap().unwrap());
+
+    let len = client.append("foo", value.clone()).await.unwrap();
+    assert_eq!(value.len() as u64 * 2, len);
+    let mut expected = value.to_vec();
+    expected.extend_from_slice(&value);
+    assert_eq!(
+        &expected[..],
+        &client.get("foo").await.unwrap().unwrap()[..]
+    );
+
+    let subscriber_client = client::connect(addr).await.unwrap();
+    let mut subscriber = subscriber_client
+        .subscribe(vec!["hello".into()])
+        .await
+        .unwrap();
+    client.publish("hello", value.clone()).await.unwrap();
+
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!(value, message.content);
+}
+
+/// 发布空消息时，订阅者收到内容为空的消息，PUBLISH 返回订阅者数量。
+#[tokio::test]
+async fn publish_empty_message() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    let subscriber_client = client::connect(addr).await.unwrap();
+    let mut subscriber = subscriber_client
+        .subscribe(vec!["hello".into()])
+        .await
+        .unwrap();
+
+    let receivers = client.publish("hello", Bytes::new()).await.unwrap();
+    assert_eq!(1, receivers);
+
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!("hello", &message.channel);
+    assert!(message.content.is_empty());
+}
+
+/// 以每批 10 个键遍历 100 个键，每个键恰好返回一次；MATCH 只返回匹配的键。
+#[tokio::test]
+async fn scan_iterates_all_keys_once() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    for i in 0..100 {
+        client.set(&format!("key:{}", i), "v".into()).await.unwrap();
+    }
+
+    let mut seen = HashSet::new();
+    let mut cursor = 0;
+    loop {
+        let (next, keys) = client.scan(cursor, None, Some(10)).await.unwrap();
+        assert!(keys.len() <= 10);
+        for key in keys {
+            assert!(seen.insert(key), "key returned twice");
+        }
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    let expected: HashSet<_> = (0..100).map(|i| format!("key:{}", i)).collect();
+    assert_eq!(expected, seen);
+
+    let mut matched = vec![];
+    let mut cursor = 0;
+    loop {
+        let (next, keys) = client.scan(cursor, Some("key:1?"), None).await.unwrap();
+        matched.extend(keys);
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    matched.sort();
+    let expected: Vec<_> = (10..20).map(|i| format!("key:{}", i)).collect();
+    assert_eq!(expected, matched);
+}
+
+/// scan_iter 自动翻页，返回全部 200 个键，每个键只返回一次。
+#[tokio::test]
+async fn scan_iter_yields_every_key() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    for i in 0..200 {
+        client.set(&format!("key:{}", i), "v".into()).await.unwrap();
+    }
+    client.set("other", "v".into()).await.unwrap();
+
+    let keys: Vec<String> = client
+        .scan_iter(Some("key:*".to_string()))
+        .collect::<Result<_, _>>()
+        .await
+        .unwrap();
+    assert_eq!(200, keys.len());
+    let keys: HashSet<_> = keys.into_iter().collect();
+    let expected: HashSet<_> = (0..200).map(|i| format!("key:{}", i)).collect();
+    assert_eq!(expected, keys);
+
+    let all: Vec<String> = client
+        .scan_iter(None)
+        .collect::<Result<_, _>>()
+        .await
+        .unwrap();
+    assert_eq!(201, all.len());
+}
+
+/// 通过客户端的列表方法操作列表，包括不存在的键和弹出最后一个值之后的空列表。
+#[tokio::test]
+async fn list_commands() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    // 不存在的键等同于空列表
+    assert_eq!(0, client.llen("list").await.unwrap());
+    assert!(client.lrange("list", 0, -1).await.unwrap().is_empty());
+    assert_eq!(None, client.lpop("list").await.unwrap());
+    assert_eq!(None, client.rpop("list").await.unwrap());
+
+    let len = client
+        .rpush("list", &["b".into(), "c".into()])
+        .await
+        .unwrap();
+    assert_eq!(2, len);
+    let len = client
+        .lpush("list", &["a".into(), "z".into()])
+        .await
+        .unwrap();
+    assert_eq!(4, len);
+    assert_eq!(4, client.llen("list").await.unwrap());
+    assert_eq!(
+        vec!["z", "a", "b", "c"],
+        client.lrange("list", 0, -1).await.unwrap()
+    );
+    assert_eq!(vec!["a", "b"], client.lrange("list", 1, -2).await.unwrap());
+    let values = client.lrange("list", -2, 100).await.unwrap();
+    assert_eq!(vec!["b", "c"], values);
+    assert!(client.lrange("list", 3, 1).await.unwrap().is_empty());
+
+    assert_eq!(Some("z".into()), client.lpop("list").await.unwrap());
+    assert_eq!(Some("c".into()), client.rpop("list").await.unwrap());
+    assert_eq!(Some("a".into()), client.lpop("list").await.unwrap());
+    assert_eq!(Some("b".into()), client.lpop("list").await.unwrap());
+
+    // 弹出最后一个值之后键被删除
+    assert_eq!(0, client.llen("list").await.unwrap());
+    assert!(client.lrange("list", 0, -1).await.unwrap().is_empty());
+    assert_eq!(0, client.touch(&["list".into()]).await.unwrap());
+}
+
+/// 列表不为空时 BLPOP 和 BRPOP 立即返回，按键的顺序选择第一个非空的列表；所有列表都为空时等待到超时。
+#[tokio::test]
+async fn blocking_pop_commands() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client
+        .rpush("second", &["a".into(), "b".into(), "c".into()])
+        .await
+        .unwrap();
+    let keys = vec!["first".to_string(), "second".to_string()];
+    assert_eq!(
+        Some(("second".into(), "a".into())),
+        client.blpop(&keys, Duration::ZERO).await.unwrap()
+    );
+    assert_eq!(
+        Some(("second".into(), "c".into())),
+        client.brpop(&keys, Duration::ZERO).await.unwrap()
+    );
+
+    let start = Instant::now();
+    let popped = client
+        .blpop(&["missing".into()], Duration::from_millis(100))
+        .await
+        .unwrap();
+    assert_eq!(None, popped);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    client.set("string", "value".into()).await.unwrap();
+    let err = client
+        .blpop(&["string".into()], Duration::ZERO)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, MiniRedisConnectionError::WrongType(_)));
+}
+
+/// 两个客户端阻塞在同一个列表上，只插入一个值时只有一个客户端取到它，另一个醒来后继续等待直到超时。
+#[tokio::test]
+async fn blpop_single_push_serves_one_client() {
+    let addr = start_server().await;
+
+    let waiters: Vec<_> = (0..2)
+        .map(|_| {
+            tokio::spawn(async move {
+                let mut client = client::connect(addr).await.unwrap();
+                client
+                    .blpop(&["list".into()], Duration::from_millis(500))
+                    .await
+                    .unwrap()
+            })
+        })
+        .collect();
+    // 等待两个客户端都进入阻塞状态
+    time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.rpush("list", &["a".into()]).await.unwrap();
+
+    let mut served = vec![];
+    for waiter in waiters {
+        if let Some(popped) = waiter.await.unwrap() {
+            served.push(popped);
+        }
+    }
+    assert_eq!(vec![("list".to_string(), Bytes::from("a"))], served);
+    assert_eq!(0, client.llen("list").await.unwrap());
+}
+
+/// RESTORE 一个列表会唤醒阻塞在这个键上的 BLPOP。
+#[tokio::test]
+async fn restore_wakes_blocked_pop() {
+    let mut src = client::connect(start_server().await).await.unwrap();
+    src.rpush("list", &["a".into()]).await.unwrap();
+    let payload = src.dump("list").await.unwrap().unwrap();
+
+    let addr = start_server().await;
+    let waiter = tokio::spawn(async move {
+        let mut client = client::connect(addr).await.unwrap();
+        client
+            .blpop(&["list".into()], Duration::from_secs(5))
+            .await
+            .unwrap()
+    });
+    // 等待客户端进入阻塞状态
+    time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.restore("list", None, payload, false).await.unwrap();
+    let popped = time::timeout(Duration::from_secs(1), waiter)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(Some(("list".to_string(), Bytes::from("a"))), popped);
+}
+
+/// 对类型不匹配的键执行命令时，客户端返回 `WrongType` 错误。
+#[tokio::test]
+async fn list_commands_wrong_type() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("string", "value".into()).await.unwrap();
+    client.rpush("list", &["a".into()]).await.unwrap();
+
+    for err in [
+        client.lpush("string", &["a".into()]).await.unwrap_err(),
+        client.rpop("string").await.unwrap_err(),
+        client.lrange("string", 0, -1).await.unwrap_err(),
+        client.llen("string").await.unwrap_err(),
+        client.get("list").await.unwrap_err(),
+        client.append("list", "a".into()).await.unwrap_err(),
+        client.getdel("list").await.unwrap_err(),
+    ] {
+        match err {
+            MiniRedisConnectionError::WrongType(msg) => assert_eq!(
+                "WRONGTYPE Operation against a key holding the wrong kind of value",
+                msg
+            ),
+            err => panic!("expected WrongType, got {:?}", err),
+        }
+    }
+
+    // 出错的命令不会修改原来的值，SET 可以覆盖任何类型的值
+    assert_eq!(Some("value".into()), client.get("string").await.unwrap());
+    assert_eq!(vec!["a"], client.lrange("list", 0, -1).await.unwrap());
+    client.set("list", "value".into()).await.unwrap();
+    assert_eq!(Some("value".into()), client.get("list").await.unwrap());
+}
+
+/// DBSIZE 返回数据库中键的数量。
+#[tokio::test]
+async fn dbsize_counts_keys() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    assert_eq!(0, client.dbsize().await.unwrap());
+
+    client.set("foo", "bar".into()).await.unwrap();
+    client.rpush("list", &["a".into()]).await.unwrap();
+    client.hset("hash", &[("f", "v".into())]).await.unwrap();
+    assert_eq!(3, client.dbsize().await.unwrap());
+
+    client.del("foo").await.unwrap();
+    client.lpop("list").await.unwrap();
+    assert_eq!(1, client.dbsize().await.unwrap());
+}
+
+/// UNLINK 删除多个键并返回删除的数量，不存在的键被忽略。
+#[tokio::test]
+async fn unlink_keys() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("foo", "bar".into()).await.unwrap();
+    client.set("big", vec![0u8; 1 << 20].into()).await.unwrap();
+    client.rpush("list", &["a".into()]).await.unwrap();
+
+    let keys = ["foo", "big", "list", "missing"].map(String::from);
+    assert_eq!(3, client.unlink(&keys).await.unwrap());
+    assert_eq!(0, client.dbsize().await.unwrap());
+    assert_eq!(None, client.get_with_ttl("foo").await.unwrap());
+    assert_eq!(0, client.llen("list").await.unwrap());
+
+    // 再次删除时键已经不存在
+    assert_eq!(0, client.unlink(&keys).await.unwrap());
+}
+
+/// EXPIRE 为已存在的键设置生存时间，生存时间为零时立即删除键。
+#[tokio::test]
+async fn expire_existing_key() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("foo", "bar".into()).await.unwrap();
+    let updated = client.expire("foo", Duration::from_secs(100)).await;
+    assert!(updated.unwrap());
+    let (_, ttl) = client.get_with_ttl("foo").await.unwrap().unwrap();
+    let ttl = ttl.unwrap();
+    assert!(ttl > Duration::from_secs(99) && ttl <= Duration::from_secs(100));
+
+    let updated = client.expire("missing", Duration::from_secs(100)).await;
+    assert!(!updated.unwrap());
+
+    assert!(client.expire("foo", Duration::ZERO).await.unwrap());
+    assert_eq!(None, client.get_with_ttl("foo").await.unwrap());
+}
+
+/// `expireat` 使用绝对的时间点，到达时间点之后键过期；已经过去的时间点会立即删除键。
+#[tokio::test]
+async fn expireat_in_the_future() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("foo", "bar".into()).await.unwrap();
+    // 时间点按秒截断，留出足够的余量，避免在读取之前键就已经过期
+    let when = SystemTime::now() + Duration::from_secs(3);
+    assert!(client.expireat("foo", when).await.unwrap());
+    let (_, ttl) = client.get_with_ttl("foo").await.unwrap().unwrap();
+    assert!(ttl.unwrap() <= Duration::from_secs(3));
+
+    let remaining = when.duration_since(SystemTime::now()).unwrap_or_default();
+    time::sleep(remaining + Duration::from_millis(100)).await;
+    assert_eq!(None, client.get_with_ttl("foo").await.unwrap());
+    assert!(!client.expireat("foo", when).await.unwrap());
+
+    client.set("bar", "baz".into()).await.unwrap();
+    assert!(client.expireat("bar", UNIX_EPOCH).await.unwrap());
+    assert_eq!(None, client.get_with_ttl("bar").await.unwrap());
+}
+
+/// 新启动的服务器是就绪的。
+#[tokio::test]
+async fn ready_on_fresh_server() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    assert!(client.ready().await.unwrap());
+}
+
+/// 值为空字符串的键与不存在的键可以区分。
+#[tokio::test]
+async fn get_empty_value_and_missing_key() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("empty", Bytes::new()).await.unwrap();
+    assert_eq!(Some(Bytes::new()), client.get("empty").await.unwrap());
+    assert_eq!(None, client.get("missing").await.unwrap());
+}
+
+/// `setex` 写入值的同时设置生存时间。
+#[tokio::test]
+async fn setex_sets_value_and_ttl() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client
+        .setex("foo", Duration::from_secs(100), "bar".into())
+        .await
+        .unwrap();
+    let (value, ttl) = client.get_with_ttl("foo").await.unwrap().unwrap();
+    assert_eq!("bar", value);
+    let ttl = ttl.unwrap();
+    assert!(ttl > Duration::from_secs(99) && ttl <= Duration::from_secs(100));
+}
+
+/// 通过哈希表命令写入字段并读回，字段的值可以是任意二进制数据。
+#[tokio::test]
+async fn hash_commands() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    let binary = Bytes::from_static(b"\xff\x00\r\n");
+    let added = client
+        .hset(
+            "user",
+            &[("name", "alice".into()), ("avatar", binary.clone())],
+        )
+        .await
+        .unwrap();
+    assert_eq!(2, added);
+
+    // 覆盖已有字段不计入新增数量
+    let added = client
+        .hset("user", &[("name", "bob".into()), ("age", "30".into())])
+        .await
+        .unwrap();
+    assert_eq!(1, added);
+
+    assert_eq!(
+        Some("bob".into()),
+        client.hget("user", "name").await.unwrap()
+    );
+    assert_eq!(
+        Some(binary.clone()),
+        client.hget("user", "avatar").await.unwrap()
+    );
+    assert_eq!(None, client.hget("user", "missing").await.unwrap());
+    assert_eq!(None, client.hget("missing", "name").await.unwrap());
+    assert!(client.hexists("user", "age").await.unwrap());
+    assert!(!client.hexists("user", "missing").await.unwrap());
+    assert_eq!(3, client.hlen("user").await.unwrap());
+
+    let expected: HashMap<String, Bytes> = vec![
+        ("name".to_string(), Bytes::from("bob")),
+        ("avatar".to_string(), binary),
+        ("age".to_string(), Bytes::from("30")),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(expected, client.hgetall("user").await.unwrap());
+    assert!(client.hgetall("missing").await.unwrap().is_empty());
+
+    let removed = client
+        .hdel("user", &["name".to_string(), "missing".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(1, removed);
+    assert_eq!(2, client.hlen("user").await.unwrap());
+
+    // 最后一个字段被删除后 key 也被删除
+    let removed = client
+        .hdel("user", &["avatar".to_string(), "age".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(2, removed);
+    assert_eq!(0, client.hlen("user").await.unwrap());
+    assert_eq!(0, client.del("user").await.unwrap());
+}
+
+/// 对非哈希表的值执行哈希表命令返回 WRONGTYPE 错误。
+#[tokio::test]
+async fn hash_commands_wrong_type() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("string", "value".into()).await.unwrap();
+    client
+        .hset("hash", &[("field", "value".into())])
+        .await
+        .unwrap();
+
+    for err in [
+        client
+            .hset("string", &[("field", "value".into())])
+            .await
+            .unwrap_err(),
+        client.hget("string", "field").await.unwrap_err(),
+        client.hgetall("string").await.unwrap_err(),
+        client.hlen("string").await.unwrap_err(),
+        client.get("hash").await.unwrap_err(),
+        client.append("hash", "a".into()).await.unwrap_err(),
+        client.getdel("hash").await.unwrap_err(),
+        client.llen("hash").await.unwrap_err(),
+        client.rpush("hash", &["a".into()]).await.unwrap_err(),
+        client.lrange("hash", 0, -1).await.unwrap_err(),
+    ] {
+        assert!(
+            matches!(err, MiniRedisConnectionError::WrongType(_)),
+            "{:?}",
+            err
+        );
+    }
+
+    assert_eq!(Some("value".into()), client.get("string").await.unwrap());
+    assert_eq!(
+        Some("value".into()),
+        client.hget("hash", "field").await.unwrap()
+    );
+}
+
+/// SADD 只计算新增的成员，SMEMBERS 返回所有成员，SISMEMBER 判断成员是否存在。
+#[tokio::test]
+async fn set_commands() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    let added = client
+        .sadd("tags", &["a".into(), "b".into(), "a".into()])
+        .await
+        .unwrap();
+    assert_eq!(2, added);
+
+    // 重复的成员不计入新增数量
+    let added = client
+        .sadd("tags", &["b".into(), "c".into()])
+        .await
+        .unwrap();
+    assert_eq!(1, added);
+    assert_eq!(0, client.sadd("tags", &["c".into()]).await.unwrap());
+
+    let mut members = client.smembers("tags").await.unwrap();
+    members.sort();
+    assert_eq!(vec!["a", "b", "c"], members);
+    assert!(client.smembers("missing").await.unwrap().is_empty());
+
+    assert!(client.sismember("tags", "a".into()).await.unwrap());
+    assert!(!client.sismember("tags", "d".into()).await.unwrap());
+    assert!(!client.sismember("missing", "a".into()).await.unwrap());
+
+    client.set("string", "value".into()).await.unwrap();
+    for err in [
+        client.sadd("string", &["a".into()]).await.unwrap_err(),
+        client.smembers("string").await.unwrap_err(),
+        client.sismember("string", "a".into()).await.unwrap_err(),
+        client.get("tags").await.unwrap_err(),
+        client.rpush("tags", &["a".into()]).await.unwrap_err(),
+        client.hget("tags", "a").await.unwrap_err(),
+    ] {
+        assert!(
+            matches!(err, MiniRedisConnectionError::WrongType(_)),
+            "{:?}",
+            err
+        );
+    }
+}
+
+/// OBJECT IDLETIME 返回键距离上次访问经过的秒数，访问之后重新计时；OBJECT ENCODING 按值返回编码。
+#[tokio::test]
+async fn object_idle_time_and_encoding() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("foo", "bar".into()).await.unwrap();
+    time::sleep(Duration::from_secs(1)).await;
+
+    let idle = client.object_idle_time("foo").await.unwrap().unwrap();
+    assert!(idle >= Duration::from_secs(1));
+
+    // GET 是一次访问，OBJECT 本身不是
+    client.get("foo").await.unwrap();
+    let idle = client.object_idle_time("foo").await.unwrap().unwrap();
+    assert_eq!(Duration::from_secs(0), idle);
+
+    client.set("int", "-123".into()).await.unwrap();
+    client.set("raw", "x".repeat(100).into()).await.unwrap();
+    client.rpush("list", &["a".into()]).await.unwrap();
+    for (key, encoding) in [
+        ("int", "int"),
+        ("foo", "embstr"),
+        ("raw", "raw"),
+        ("list", "quicklist"),
+    ] {
+        let actual = client.object_encoding(key).await.unwrap();
+        assert_eq!(Some(encoding), actual.as_deref());
+    }
+
+    assert_eq!(None, client.object_encoding("missing").await.unwrap());
+    assert_eq!(None, client.object_idle_time("missing").await.unwrap());
+}
+
+/// MEMORY USAGE 随着值变长而增加，OBJECT REFCOUNT 对存在的键返回 1。
+#[tokio::test]
+async fn memory_usage_and_refcount() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("foo", "bar".into()).await.unwrap();
+    let before = client.memory_usage("foo").await.unwrap().unwrap();
+    // 至少包括键名和值的长度
+    assert!(before >= 6);
+
+    client.append("foo", "x".repeat(100).into()).await.unwrap();
+    let after = client.memory_usage("foo").await.unwrap().unwrap();
+    assert_eq!(before + 100, after);
+
+    assert_eq!(Some(1), client.object_refcount("foo").await.unwrap());
+
+    assert_eq!(None, client.memory_usage("missing").await.unwrap());
+    assert_eq!(None, client.object_refcount("missing").await.unwrap());
+}
+
+/// CLIENT SETNAME 设置的名称只属于当前连接，新的连接没有名称，包含空格的名称被拒绝。
+#[tokio::test]
+async fn client_setname_and_getname() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    assert_eq!(None, client.client_getname().await.unwrap());
+    client.client_setname("worker-1").await.unwrap();
+    assert_eq!(
+        Some("worker-1".to_string()),
+        client.client_getname().await.unwrap()
+    );
+
+    // 不合法的名称不会覆盖原来的名称
+    assert!(client.client_setname("bad name").await.is_err());
+    assert_eq!(
+        Some("worker-1".to_string()),
+        client.client_getname().await.unwrap()
+    );
+
+    let mut fresh = client::connect(addr).await.unwrap();
+    assert_eq!(None, fresh.client_getname().await.unwrap());
+
+    // 空字符串清除名称
+    client.client_setname("").await.unwrap();
+    assert_eq!(None, client.client_getname().await.unwrap());
+}
+
+/// 测试 CLIENT ID 为每个连接分配不同的 id，CLIENT LIST 列出所有连接，连接关闭之后不再列出。
+#[tokio::test]
+async fn client_id_and_list() {
+    let addr = start_server().await;
+    let mut first = client::connect(addr).await.unwrap();
+    let mut second = client::connect(addr).await.unwrap();
+
+    let first_id = first.client_id().await.unwrap();
+    let second_id = second.client_id().await.unwrap();
+    assert!(second_id > first_id);
+    assert_eq!(first_id, first.client_id().await.unwrap());
+
+    second.client_setname("worker-2").await.unwrap();
+    let list = first.client_list().await.unwrap();
+    let lines: Vec<&str> = list.lines().collect();
+    assert_eq!(2, lines.len());
+    assert!(lines[0].starts_with(&format!("id={} addr=127.0.0.1:", first_id)));
+    assert!(lines[0].contains(" name= age="));
+    assert!(lines[1].starts_with(&format!("id={} ", second_id)));
+    assert!(lines[1].contains(" name=worker-2 age="));
+
+    // 服务器注意到连接关闭之后注销它
+    drop(second);
+    let mut list = first.client_list().await.unwrap();
+    for _ in 0..100 {
+        if list.lines().count() == 1 {
+            break;
+        }
+        time::sleep(Duration::from_millis(10)).await;
+        list = first.client_list().await.unwrap();
+    }
+    assert_eq!(1, list.lines().count());
+    assert!(list.starts_with(&format!("id={} ", first_id)));
+}
+
+/// 测试 GETDEL 返回值并删除键，再次执行时返回空值。
+#[tokio::test]
+async fn getdel_returns_and_removes_value() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("hello", "world".into()).await.unwrap();
+
+    let value = client.getdel("hello").await.unwrap().unwrap();
+    assert_eq!(b"world", &value[..]);
+
+    assert!(client.getdel("hello").await.unwrap().is_none());
+}
+
+/// 测试 INCRBYFLOAT 的正负增量，以及原有的值不是浮点数时的错误。
+#[tokio::test]
+async fn incrbyfloat_adds_to_value() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    assert_eq!(10.5, client.incrbyfloat("float", 10.5).await.unwrap());
+    assert_eq!(10.6, client.incrbyfloat("float", 0.1).await.unwrap());
+    assert_eq!(b"10.6", &client.get("float").await.unwrap().unwrap()[..]);
+    assert_eq!(5.1, client.incrbyfloat("float", -5.5).await.unwrap());
+
+    client.set("text", "abc".into()).await.unwrap();
+    match client.incrbyfloat("text", 1.0).await.unwrap_err() {
+        MiniRedisConnectionError::CommandExecute(msg) => {
+            assert_eq!("ERR value is not a valid float", msg)
+        }
+        err => panic!("unexpected error {:?}", err),
+    }
+    assert_eq!(b"abc", &client.get("text").await.unwrap().unwrap()[..]);
+}
+
+/// 测试 SETBIT 和 GETBIT，包括超出字符串长度的位。
+#[tokio::test]
+async fn setbit_and_getbit() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    assert!(!client.setbit("bits", 7, true).await.unwrap());
+    assert!(client.setbit("bits", 7, true).await.unwrap());
+    assert!(client.getbit("bits", 7).await.unwrap());
+    assert!(!client.getbit("bits", 6).await.unwrap());
+    assert_eq!(b"\x01", &client.get("bits").await.unwrap().unwrap()[..]);
+
+    // 超出字符串长度的位和不存在的键都是 0
+    assert!(!client.getbit("bits", 100).await.unwrap());
+    assert!(!client.getbit("missing", 0).await.unwrap());
+}
+
+/// SETBIT 补齐后的长度超过 proto-max-bulk-len 时返回错误，不修改字符串。
+#[tokio::test]
+async fn setbit_rejects_offset_over_max_bulk_len() {
+    let config = ServerConfig {
+        proto_max_bulk_len: 1024,
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    // 最后一个字节的最后一位刚好不超过限制
+    assert!(!client.setbit("bits", 1024 * 8 - 1, true).await.unwrap());
+    assert_eq!(1024, client.get("bits").await.unwrap().unwrap().len());
+
+    match client.setbit("bits", 1024 * 8, true).await {
+        Err(MiniRedisConnectionError::CommandExecute(msg)) => assert_eq!(
+            "ERR string exceeds maximum allowed size (proto-max-bulk-len)",
+            msg
+        ),
+        res => panic!("unexpected result: {:?}", res),
+    }
+    assert_eq!(1024, client.get("bits").await.unwrap().unwrap().len());
+}
+
+/// 测试 INFO 中的命令总数随着执行的命令增加。
+#[tokio::test]
+async fn info_reports_commands_processed() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+    client.set("foo", "bar".into()).await.unwrap();
+
+    let before = total_commands_processed(&client.info().await.unwrap());
+    client.get("foo").await.unwrap().unwrap();
+    let after = total_commands_processed(&client.info().await.unwrap());
+
+    // GET 和第二次 INFO 本身
+    assert_eq!(before + 2, after);
+
+    let info = client.info().await.unwrap();
+    assert!(info.contains("connected_clients:1\r\n"), "{}", info);
+}
+
+/// 测试 TOUCH 返回存在的键的数量。
+#[tokio::test]
+async fn touch_counts_existing_keys() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("a", "1".into()).await.unwrap();
+    client.set("b", "2".into()).await.unwrap();
+
+    let keys = ["a".to_string(), "missing".to_string(), "b".to_string()];
+    assert_eq!(2, client.touch(&keys).await.unwrap());
+}
+
+/// 测试 RENAME 移动值和剩余生存时间，以及源键不存在时返回错误。
+#[tokio::test]
+async fn rename_moves_value_and_ttl() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client
+        .set_expire("foo", "bar".into(), Duration::from_secs(100))
+        .await
+        .unwrap();
+    client.set("baz", "old".into()).await.unwrap();
+
+    // 覆盖已存在的目标键
+    client.rename("foo", "baz").await.unwrap();
+
+    let (value, ttl) = client.get_with_ttl("baz").await.unwrap().unwrap();
+    assert_eq!(b"bar", &value[..]);
+    let ttl = ttl.unwrap();
+    assert!(ttl > Duration::from_secs(90) && ttl <= Duration::from_secs(100));
+
+    assert!(client.get_with_ttl("foo").await.unwrap().is_none());
+
+    // 源键不存在
+    match client.rename("foo", "qux").await {
+        Err(MiniRedisConnectionError::CommandExecute(msg)) => assert_eq!("ERR no such key", msg),
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
+
+/// 测试 `get_with_ttl` 同时返回值和剩余的生存时间。
+#[tokio::test]
+async fn get_with_ttl_reports_remaining_expiry() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client
+        .set_expire("hello", "world".into(), Duration::from_secs(10))
+        .await
+        .unwrap();
+
+    let (value, ttl) = client.get_with_ttl("hello").await.unwrap().unwrap();
+    assert_eq!(b"world", &value[..]);
+    let ttl = ttl.unwrap();
+    assert!(ttl > Duration::from_secs(9), "ttl = {:?}", ttl);
+    assert!(ttl <= Duration::from_secs(10), "ttl = {:?}", ttl);
+
+    // 没有设置过期时间的键
+    client.set("foo", "bar".into()).await.unwrap();
+    let (value, ttl) = client.get_with_ttl("foo").await.unwrap().unwrap();
+    assert_eq!(b"bar", &value[..]);
+    assert!(ttl.is_none());
+
+    // 不存在的键
+    assert!(client.get_with_ttl("missing").await.unwrap().is_none());
+}
+
+/// `GET` 出错时 `get_with_ttl` 仍然读取 `PTTL` 的响应，连接可以继续使用。
+#[tokio::test]
+async fn get_with_ttl_error_leaves_connection_usable() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.rpush("list", &["x".into()]).await.unwrap();
+    match client.get_with_ttl("list").await {
+        Err(MiniRedisConnectionError::WrongType(_)) => {}
+        res => panic!("unexpected result: {:?}", res),
+    }
+
+    client.set("foo", "bar".into()).await.unwrap();
+    assert_eq!(b"bar", &client.get("foo").await.unwrap().unwrap()[..]);
+}
+
+/// `get_multi` 按请求的顺序返回每个键的值，不存在的键是 `None`，出错的键不影响其他键。
+#[tokio::test]
+async fn get_multi_returns_values_in_order() {
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("a", "1".into()).await.unwrap();
+    client.set("c", "3".into()).await.unwrap();
+    client.rpush("list", &["x".into()]).await.unwrap();
+    client.set("e", "5".into()).await.unwrap();
+
+    let keys: Vec<String> = ["a", "b", "c", "list", "e"]
+        .iter()
+        .map(|key| key.to_string())
+        .collect();
+    let mut values = client.get_multi(&keys).await.unwrap().into_iter();
+
+    assert_eq!(Some("1".into()), values.next().unwrap().unwrap());
+    assert_eq!(None, values.next().unwrap().unwrap());
+    assert_eq!(Some("3".into()), values.next().unwrap().unwrap());
+    assert!(matches!(
+        values.next().unwrap(),
+        Err(MiniRedisConnectionError::WrongType(_))
+    ));
+    assert_eq!(Some("5".into()), values.next().unwrap().unwrap());
+    assert!(values.next().is_none());
+
+    // 所有响应都已读取，连接可以继续使用
+    assert_eq!(Some("1".into()), client.get("a").await.unwrap());
+}
+
+/// DUMP 一个服务器上的键，RESTORE 到另一个服务器上，值和过期时间都保持不变。
+#[tokio::test]
+async fn dump_and_restore_between_servers() {
+    let mut src = client::connect(start_server().await).await.unwrap();
+    let mut dst = client::connect(start_server().await).await.unwrap();
+
+    src.set_expire("foo", "bar".into(), Duration::from_secs(100))
+        .await
+        .unwrap();
+    src.rpush("list", &["a".into(), "b".into()]).await.unwrap();
+    assert_eq!(None, src.dump("missing").await.unwrap());
+
+    let (_, ttl) = src.get_with_ttl("foo").await.unwrap().unwrap();
+    let payload = src.dump("foo").await.unwrap().unwrap();
+    dst.restore("foo", ttl, payload.clone(), false)
+        .await
+        .unwrap();
+
+    let (value, ttl) = dst.get_with_ttl("foo").await.unwrap().unwrap();
+    assert_eq!(b"bar", &value[..]);
+    let ttl = ttl.unwrap();
+    assert!(ttl > Duration::from_secs(99) && ttl <= Duration::from_secs(100));
+
+    let list = src.dump("list").await.unwrap().unwrap();
+    dst.restore("list", None, list, false).await.unwrap();
+    assert_eq!(vec!["a", "b"], dst.lrange("list", 0, -1).await.unwrap());
+
+    // 已经存在的键只有指定 REPLACE 时才会被覆盖
+    match dst.restore("foo", None, payload.clone(), false).await {
+        Err(MiniRedisConnectionError::CommandExecute(msg)) => {
+            assert_eq!("BUSYKEY Target key name already exists.", msg)
+        }
+        res => panic!("unexpected result: {:?}", res),
+    }
+    dst.restore("foo", None, payload.clone(), true)
+        .await
+        .unwrap();
+    let (_, ttl) = dst.get_with_ttl("foo").await.unwrap().unwrap();
+    assert!(ttl.is_none());
+
+    // 损坏的数据不会创建键
+    let mut corrupted = payload.to_vec();
+    corrupted[0] ^= 1;
+    let err = dst
+        .restore("other", None, corrupted.into(), false)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, MiniRedisConnectionError::CommandExecute(ref msg) if msg.contains("checksum")),
+        "{:?}",
+        err
+    );
+    assert_eq!(None, dst.get("other").await.unwrap());
+}
+
+/// SETRANGE 原地覆盖字符串的一部分，偏移量超出长度时用 0 补齐，超过 proto-max-bulk-len 时返回错误。
+#[tokio::test]
+async fn setrange_overwrites_and_pads() {
+    let config = ServerConfig {
+        proto_max_bulk_len: 1024,
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client
+        .set_expire("foo", "Hello World".into(), Duration::from_secs(100))
+        .await
+        .unwrap();
+    assert_eq!(11, client.setrange("foo", 6, "Redis".into()).await.unwrap());
+    let (value, ttl) = client.get_with_ttl("foo").await.unwrap().unwrap();
+    assert_eq!(b"Hello Redis", &value[..]);
+    assert!(ttl.is_some());
+
+    assert_eq!(5, client.setrange("bar", 3, "ab".into()).await.unwrap());
+    assert_eq!(
+        Some(Bytes::from_static(b"\0\0\0ab")),
+        client.get("bar").await.unwrap()
+    );
+
+    match client.setrange("bar", 1024, "x".into()).await {
+        Err(MiniRedisConnectionError::CommandExecute(msg)) => assert_eq!(
+            "ERR string exceeds maximum allowed size (proto-max-bulk-len)",
+            msg
+        ),
+        res => panic!("unexpected result: {:?}", res),
+    }
+    assert_eq!(
+        Some(Bytes::from_static(b"\0\0\0ab")),
+        client.get("bar").await.unwrap()
+    );
+}
+
+/// 写入字符串的命令在结果超过 max-value-size 时返回错误，不修改任何键。
+#[tokio::test]
+async fn max_value_size_limits_string_writes() {
+    let config = ServerConfig {
+        max_value_size: Some(16),
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    let too_large = |res: Result<(), MiniRedisConnectionError>| match res {
+        Err(MiniRedisConnectionError::CommandExecute(msg)) => assert_eq!(
+            "ERR string exceeds maximum allowed size (max-value-size)",
+            msg
+        ),
+        res => panic!("unexpected result: {:?}", res),
+    };
+
+    let value = Bytes::from("x".repeat(17));
+    too_large(client.set("foo", value.clone()).await);
+    too_large(
+        client
+            .setex("foo", Duration::from_secs(100), value.clone())
+            .await,
+    );
+    too_large(client.mset(&[("foo", "ok".into()), ("bar", value)]).await);
+    assert_eq!(None, client.get("foo").await.unwrap());
+    assert_eq!(None, client.get("bar").await.unwrap());
+
+    // 刚好等于限制的值可以写入，再追加一个字节就超过了
+    client.set("foo", "x".repeat(16).into()).await.unwrap();
+    too_large(client.append("foo", "y".into()).await.map(|_| ()));
+    assert_eq!(16, client.get("foo").await.unwrap().unwrap().len());
+
+    // 偏移量刚好超过限制时不会创建键
+    too_large(client.setrange("bar", 16, "z".into()).await.map(|_| ()));
+    too_large(client.setbit("bar", 16 * 8, true).await.map(|_| ()));
+    assert_eq!(None, client.get("bar").await.unwrap());
+    assert_eq!(16, client.setrange("bar", 15, "z".into()).await.unwrap());
+}
+
+/// 记录每个命令执行次数的自定义指标记录器。
+#[derive(Debug, Default)]
+struct CountingMetrics {
+    commands: Mutex<HashMap<String, u64>>,
+}
+
+impl MetricsRecorder for CountingMetrics {
+    fn incr_command(&self, name: &str) {
+        *self
+            .commands
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert(0) += 1;
+    }
+}
+
+/// 测试服务器通过自定义的 `MetricsRecorder` 上报命令次数，`INFO` 仍然使用服务器自己保存的指标。
+#[tokio::test]
+async fn custom_metrics_recorder_counts_commands() {
+    let metrics = Arc::new(CountingMetrics::default());
+    let config = ServerConfig {
+        metrics: metrics.clone(),
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    client.get("hello").await.unwrap();
+    client.get("hello").await.unwrap();
+
+    // INFO 在执行之前计数，因此包括它自己
+    let info = client.info().await.unwrap();
+    assert_eq!(4, total_commands_processed(&info));
+    assert!(info.contains("connected_clients:1\r\n"), "{}", info);
+
+    let commands = metrics.commands.lock().unwrap();
+    assert_eq!(Some(&1), commands.get("set"));
+    assert_eq!(Some(&2), commands.get("get"));
+    assert_eq!(Some(&1), commands.get("info"));
+}
+
+/// 开启 `debug_commands` 后，`DEBUG SLEEP` 在等待指定的时间之后才回复；默认关闭时回复错误。
+#[tokio::test]
+async fn debug_sleep_delays_reply() {
+    let config = ServerConfig {
+        debug_commands: true,
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    let start = Instant::now();
+    client
+        .debug_sleep(Duration::from_millis(200))
+        .await
+        .unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    let addr = start_server().await;
+    let mut client = client::connect(addr).await.unwrap();
+    match client.debug_sleep(Duration::ZERO).await.unwrap_err() {
+        MiniRedisConnectionError::CommandExecute(msg) => {
+            assert!(msg.starts_with("ERR DEBUG command not allowed"), "{}", msg)
+        }
+        err => panic!("unexpected error {:?}", err),
+    }
+}
+
+/// 执行时间超过 `command_timeout` 的命令回复超时错误，连接仍然可以继续使用。
+#[tokio::test]
+async fn command_timeout_cancels_slow_command() {
+    let config = ServerConfig {
+        debug_commands: true,
+        command_timeout: Some(Duration::from_millis(100)),
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    let start = Instant::now();
+    let err = client
+        .debug_sleep(Duration::from_secs(5))
+        .await
+        .unwrap_err();
+    match err {
+        MiniRedisConnectionError::CommandExecute(msg) => assert_eq!("ERR command timed out", msg),
+        err => panic!("unexpected error {:?}", err),
+    }
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    // 同一个连接上的下一个命令正常执行
+    let pong = client.ping(None).await.unwrap();
+    assert_eq!(b"PONG", &pong[..]);
+}
+
+/// `volatile-lru` 策略在超过 `max_memory` 时只删除设置了过期时间的键，没有这样的键时拒绝写入。
+#[tokio::test]
+async fn volatile_lru_evicts_only_keys_with_ttl() {
+    let config = ServerConfig {
+        max_memory: Some(100),
+        maxmemory_policy: MaxmemoryPolicy::VolatileLru,
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    // 每个键和值占用 22 字节
+    let value = Bytes::from("x".repeat(20));
+    client.set("p1", value.clone()).await.unwrap();
+    client.set("p2", value.clone()).await.unwrap();
+    client.set("v1", value.clone()).await.unwrap();
+    assert!(client.expire("v1", Duration::from_secs(100)).await.unwrap());
+    client.set("big", "x".repeat(40).into()).await.unwrap();
+
+    // 已经超过限制，写入之前删除设置了过期时间的键
+    client.set("p3", "y".into()).await.unwrap();
+    assert_eq!(None, client.get("v1").await.unwrap());
+    for key in ["p1", "p2", "big", "p3"] {
+        assert!(client.get(key).await.unwrap().is_some(), "{}", key);
+    }
+
+    // 没有设置了过期时间的键可以删除
+    client.set("p4", value.clone()).await.unwrap();
+    match client.set("p5", value.clone()).await {
+        Err(MiniRedisConnectionError::CommandExecute(msg)) => {
+            assert!(msg.starts_with("OOM"), "{}", msg)
+        }
+        res => panic!("unexpected response {:?}", res),
+    }
+    assert!(client.get("p1").await.unwrap().is_some());
+}
+
+/// 执行时间超过 `slowlog_threshold` 的命令被记录到慢查询日志，`SLOWLOG RESET` 清空日志。
+#[tokio::test]
+async fn slowlog_records_slow_commands() {
+    let config = ServerConfig {
+        debug_commands: true,
+        slowlog_threshold: Some(Duration::from_millis(50)),
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+    let mut client = client::connect(addr).await.unwrap();
+
+    client.set("hello", "world".into()).await.unwrap();
+    client
+        .debug_sleep(Duration::from_millis(100))
+        .await
+        .unwrap();
+
+    // 只有 DEBUG SLEEP 超过了阈值
+    let entries = match client.raw(&["SLOWLOG".into(), "GET".into()]).await.unwrap() {
+        Frame::Array(entries) => entries,
+        frame => panic!("unexpected frame {:?}", frame),
+    };
+    assert_eq!(1, entries.len());
+    match &entries[0] {
+        Frame::Array(entry) => {
+            assert_eq!(Frame::Integer(0), entry[0]);
+            match entry[2] {
+                Frame::Integer(micros) => assert!(micros >= 100_000, "{}", micros),
+                ref frame => panic!("unexpected duration {:?}", frame),
+            }
+            assert_eq!(
+                Frame::Array(vec![
+                    Frame::Bulk("debug".into()),
+                    Frame::Bulk("sleep".into()),
+                    Frame::Bulk("0.1".into()),
+                ]),
+                entry[3]
+            );
+        }
+        frame => panic!("unexpected entry {:?}", frame),
+    }
+
+    let response = client
+        .raw(&["SLOWLOG".into(), "RESET".into()])
+        .await
+        .unwrap();
+    assert_eq!(Frame::Simple("OK".to_string()), response);
+    let response = client
+        .raw(&["SLOWLOG".into(), "GET".into(), "-1".into()])
+        .await
+        .unwrap();
+    assert_eq!(Frame::Array(vec![]), response);
+}
+
+/// 打开访问日志时，每条命令都调用一次 `on_access`，记录命令名称、参数个数和回复的类型。
+#[tokio::test]
+async fn access_log_records_every_command() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let config = ServerConfig {
+        access_log: true,
+        on_access: Some(AccessLogCallback::new(move |entry| {
+            tx.send((entry.command.to_string(), entry.args, entry.reply))
+                .unwrap();
+        })),
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.set("foo", "bar".into()).await.unwrap();
+    client.get("foo").await.unwrap();
+    client.get("missing").await.unwrap();
+    client
+        .rpush("foo", &["a".into(), "b".into()])
+        .await
+        .unwrap_err();
+
+    let mut entries = vec![];
+    for _ in 0..4 {
+        entries.push(rx.recv().await.unwrap());
+    }
+    assert_eq!(
+        vec![
+            ("set".to_string(), 2, "simple"),
+            ("get".to_string(), 1, "bulk"),
+            ("get".to_string(), 1, "null"),
+            ("rpush".to_string(), 3, "error"),
+        ],
+        entries
+    );
+}
+
+/// 键过期被后台任务清理时调用 `on_expire` 回调。
+#[tokio::test]
+async fn on_expire_callback_fires() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let config = ServerConfig {
+        on_expire: Some(ExpireCallback::new(move |key| {
+            tx.send(key.to_string()).unwrap();
+        })),
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+
+    let mut client = client::connect(addr).await.unwrap();
+    client
+        .set_expire("foo", "bar".into(), Duration::from_millis(100))
+        .await
+        .unwrap();
+    client.set("baz", "qux".into()).await.unwrap();
+
+    let key = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!("foo", key);
+
+    // 没有过期时间的键不会触发回调
+    assert!(rx.try_recv().is_err());
+}
+
+/// 开启键空间事件之后，SET、DEL 和 UNLINK 向对应的频道发布键名。
+#[tokio::test]
+async fn keyspace_events_for_set_del_and_unlink() {
+    let config = ServerConfig {
+        notify_keyspace_events: true,
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+
+    let subscriber = client::connect(addr).await.unwrap();
+    let mut subscriber = subscriber
+        .subscribe(vec![
+            "__keyevent@0__:set".into(),
+            "__keyevent@0__:del".into(),
+        ])
+        .await
+        .unwrap();
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.set("foo", "bar".into()).await.unwrap();
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!("__keyevent@0__:set", &message.channel);
+    assert_eq!(b"foo", &message.content[..]);
+
+    // 删除不存在的键不发布事件
+    assert_eq!(0, client.del("missing").await.unwrap());
+    assert_eq!(1, client.del("foo").await.unwrap());
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!("__keyevent@0__:del", &message.channel);
+    assert_eq!(b"foo", &message.content[..]);
+
+    // UNLINK 为每个被删除的键发布一个事件，跳过不存在的键
+    client.set("a", "1".into()).await.unwrap();
+    client.set("b", "2".into()).await.unwrap();
+    for key in ["a", "b"] {
+        let message = subscriber.next_message().await.unwrap().unwrap();
+        assert_eq!("__keyevent@0__:set", &message.channel);
+        assert_eq!(key.as_bytes(), &message.content[..]);
+    }
+    let keys = vec!["a".to_string(), "missing".to_string(), "b".to_string()];
+    assert_eq!(2, client.unlink(&keys).await.unwrap());
+    for key in ["a", "b"] {
+        let message = subscriber.next_message().await.unwrap().unwrap();
+        assert_eq!("__keyevent@0__:del", &message.channel);
+        assert_eq!(key.as_bytes(), &message.content[..]);
+    }
+}
+
+/// 开启键空间事件之后，MSET 按参数的顺序为每个键发布 set 事件。
+#[tokio::test]
+async fn keyspace_events_for_mset() {
+    let config = ServerConfig {
+        notify_keyspace_events: true,
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+
+    let subscriber = client::connect(addr).await.unwrap();
+    let mut subscriber = subscriber
+        .subscribe(vec!["__keyevent@0__:set".into()])
+        .await
+        .unwrap();
+
+    let mut client = client::connect(addr).await.unwrap();
+    client
+        .mset(&[("a", "1".into()), ("b", "2".into()), ("c", "3".into())])
+        .await
+        .unwrap();
+    for key in ["a", "b", "c"] {
+        let message = subscriber.next_message().await.unwrap().unwrap();
+        assert_eq!("__keyevent@0__:set", &message.channel);
+        assert_eq!(key.as_bytes(), &message.content[..]);
+    }
+}
+
+/// 多个客户端并发读写分布在不同分片上的键，所有写入都能被读到，键数量指标与实际一致。
+#[tokio::test]
+async fn concurrent_clients_on_sharded_store() {
+    let metrics = Arc::new(InMemoryMetrics::new());
+    let config = ServerConfig {
+        metrics: metrics.clone(),
+        shards: 4,
+        ..ServerConfig::default()
+    };
+    let addr = start_server_with_config(config).await;
+
+    let mut tasks = vec![];
+    for task in 0..8 {
+        tasks.push(tokio::spawn(async move {
+            let mut client = client::connect(addr).await.unwrap();
+            for i in 0..100 {
+                let key = format!("key-{}-{}", task, i);
+                client.set(&key, i.to_string().into()).await.unwrap();
+                // 同时写入一个所有任务共享的键，以及跨分片的 MSET
+                client.set("shared", task.to_string().into()).await.unwrap();
+                client
+                    .mset(&[
+                        ("pair-a", i.to_string().into()),
+                        ("pair-b", i.to_string().into()),
+                    ])
+                    .await
+                    .unwrap();
+            }
+            for i in 0..50 {
+                client.del(&format!("key-{}-{}", task, i)).await.unwrap();
+            }
+        }));
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let mut client = client::connect(addr).await.unwrap();
+    for task in 0..8 {
+        for i in 0..100 {
+            let key = format!("key-{}-{}", task, i);
+            if i < 50 {
+                assert_eq!(0, client.del(&key).await.unwrap());
+            } else {
+                let value = client.get(&key).await.unwrap().unwrap();
+                assert_eq!(i.to_string().as_bytes(), &value[..]);
+            }
+        }
+    }
+    assert!(client.get("shared").await.unwrap().is_some());
+
+    // MSET 是原子的，两个键总是同一次写入的值
+    let a = client.get("pair-a").await.unwrap().unwrap();
+    let b = client.get("pair-b").await.unwrap().unwrap();
+    assert_eq!(a, b);
+
+    // 8 * 50 个剩余的键，加上 shared、pair-a 和 pair-b
+    assert_eq!(Some(8 * 50 + 3), metrics.gauge("keys"));
+}
+
+/// 从 INFO 的输出中读取 `total_commands_processed`
+fn total_commands_processed(info: &str) -> u64 {
+    info.lines()
+        .find_map(|line| line.strip_prefix("total_commands_processed:"))
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+/// 启动服务器并返回服务器地址
+async fn start_server() -> SocketAddr {
+    start_server_with_config(ServerConfig::default()).await
+}
+/// 使用指定配置启动服务器并返回服务器地址
+async fn start_server_with_config(config: ServerConfig) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        server::run_with_config(listener, tokio::signal::ctrl_c(), config).await
+    });
+
+    addr
+}
@@ -1,10 +1,18 @@
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
 
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use bytes::Bytes;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::net::{TcpListener, TcpStream};
+use tokio::process::{Child, Command};
 use tokio::time::{self, Duration};
 
-use mini_redis::server;
+use mini_redis::client;
+use mini_redis::config::{parse_config, parse_config_file, ServerConfig, TlsConfig};
+use mini_redis::error::MiniRedisConfigError;
+use mini_redis::server::{self, AsyncKvStore, BoxFuture, WrongType};
 
 /// 一个基本的 "hello world" 测试。服务器实例在后台任务中启动。
 /// 然后建立一个客户端 TCP 连接，并向服务器发送原始 Redis 命令。
@@ -33,12 +41,12 @@ async fn key_value_get_set() {
         .await
         .unwrap();
 
-     // 读取 OK 响应
+    // 读取 OK 响应
     let mut response = [0; 5];
     stream.read_exact(&mut response).await.unwrap();
     assert_eq!(b"+OK\r\n", &response);
 
-     // 获取键，数据存在
+    // 获取键，数据存在
     stream
         .write_all(b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n")
         .await
@@ -47,7 +55,7 @@ async fn key_value_get_set() {
     // 关闭写半部
     stream.shutdown().await.unwrap();
 
-     // 读取 "world" 响应
+    // 读取 "world" 响应
     let mut response = [0; 11];
     stream.read_exact(&mut response).await.unwrap();
     assert_eq!(b"$5\r\nworld\r\n", &response);
@@ -102,7 +110,7 @@ async fn key_value_timeout() {
     // 等待键过期
     time::sleep(Duration::from_secs(1)).await;
 
-     // 获取键，数据缺失
+    // 获取键，数据缺失
     stream
         .write_all(b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n")
         .await
@@ -157,7 +165,7 @@ async fn pub_sub() {
     publisher.read_exact(&mut response).await.unwrap();
     assert_eq!(b":1\r\n", &response);
 
-     // 第一个订阅者接收到消息
+    // 第一个订阅者接收到消息
     let mut response = [0; 39];
     sub1.read_exact(&mut response).await.unwrap();
     assert_eq!(
@@ -328,6 +336,78 @@ async fn manage_subscription() {
     );
 }
 
+/// 同一条 SUBSCRIBE 命令中重复的频道会收到两次确认，订阅数量都是 1，消息也只会收到一次。
+#[tokio::test]
+async fn subscribe_same_channel_twice() {
+    let addr = start_server().await;
+
+    let mut sub = TcpStream::connect(addr).await.unwrap();
+    sub.write_all(&encode(&["SUBSCRIBE", "foo", "foo"]))
+        .await
+        .unwrap();
+
+    for _ in 0..2 {
+        let mut response = [0; 32];
+        sub.read_exact(&mut response).await.unwrap();
+        assert_eq!(
+            &b"*3\r\n$9\r\nsubscribe\r\n$3\r\nfoo\r\n:1\r\n"[..],
+            &response[..]
+        );
+    }
+
+    let mut publisher = TcpStream::connect(addr).await.unwrap();
+    publisher
+        .write_all(&encode(&["PUBLISH", "foo", "bar"]))
+        .await
+        .unwrap();
+    let mut response = [0; 4];
+    publisher.read_exact(&mut response).await.unwrap();
+    assert_eq!(b":1\r\n", &response);
+
+    let mut response = [0; 35];
+    sub.read_exact(&mut response).await.unwrap();
+    assert_eq!(
+        &b"*3\r\n$7\r\nmessage\r\n$3\r\nfoo\r\n$3\r\nbar\r\n"[..],
+        &response[..]
+    );
+
+    // 没有重复的消息
+    let mut response = [0; 1];
+    time::timeout(Duration::from_millis(100), sub.read(&mut response))
+        .await
+        .unwrap_err();
+}
+
+/// 分两次 SUBSCRIBE 同一个频道时也只注册一个接收者，第二次的确认中订阅数量不变。
+#[tokio::test]
+async fn resubscribe_same_channel() {
+    let addr = start_server().await;
+
+    let mut sub = TcpStream::connect(addr).await.unwrap();
+    for _ in 0..2 {
+        sub.write_all(&encode(&["SUBSCRIBE", "hello"]))
+            .await
+            .unwrap();
+
+        let mut response = [0; 34];
+        sub.read_exact(&mut response).await.unwrap();
+        assert_eq!(
+            &b"*3\r\n$9\r\nsubscribe\r\n$5\r\nhello\r\n:1\r\n"[..],
+            &response[..]
+        );
+    }
+
+    // 发布返回的接收者数量是 1
+    let mut publisher = TcpStream::connect(addr).await.unwrap();
+    publisher
+        .write_all(&encode(&["PUBLISH", "hello", "world"]))
+        .await
+        .unwrap();
+    let mut response = [0; 4];
+    publisher.read_exact(&mut response).await.unwrap();
+    assert_eq!(b":1\r\n", &response);
+}
+
 /// 测试服务器在接收到未知命令时返回错误消息
 #[tokio::test]
 async fn send_error_unknown_command() {
@@ -346,7 +426,47 @@ async fn send_error_unknown_command() {
 
     stream.read_exact(&mut response).await.unwrap();
 
-    assert_eq!(b"-err unknown command \'foo\'\r\n", &response);
+    assert_eq!(b"-ERR unknown command \'foo\'\r\n", &response);
+}
+
+/// 测试 QUIT 回复 OK 之后关闭连接
+#[tokio::test]
+async fn quit_closes_connection() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream.write_all(&encode(&["QUIT"])).await.unwrap();
+
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    // 服务器已经关闭连接
+    assert_eq!(0, stream.read(&mut response).await.unwrap());
+}
+
+/// 测试在订阅之外发送 UNSUBSCRIBE 时返回错误消息，连接保持打开
+#[tokio::test]
+async fn send_error_unsubscribe_without_subscription() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(&encode(&["UNSUBSCRIBE", "foo"]))
+        .await
+        .unwrap();
+
+    let expected = b"-ERR 'unsubscribe' is only allowed in the context of a subscription\r\n";
+    let mut response = [0; 69];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response);
+
+    stream.write_all(&encode(&["PING"])).await.unwrap();
+    let mut response = [0; 7];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+PONG\r\n", &response);
 }
 
 /// 测试服务器在接收到订阅后发送 GET 或 SET 命令时返回错误消息
@@ -379,7 +499,7 @@ async fn send_error_get_set_after_subscribe() {
     let mut response = [0; 28];
 
     stream.read_exact(&mut response).await.unwrap();
-    assert_eq!(b"-err unknown command \'set\'\r\n", &response);
+    assert_eq!(b"-ERR unknown command \'set\'\r\n", &response);
 
     stream
         .write_all(b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n")
@@ -389,15 +509,1709 @@ async fn send_error_get_set_after_subscribe() {
     let mut response = [0; 28];
 
     stream.read_exact(&mut response).await.unwrap();
-    assert_eq!(b"-err unknown command \'get\'\r\n", &response);
+    assert_eq!(b"-ERR unknown command \'get\'\r\n", &response);
 }
 
-/// 运行 Redis 服务器并返回绑定的套接字地址
-async fn start_server() -> SocketAddr {
+/// 测试订阅模式下发送 PING 会收到 pong 数组帧，并且订阅仍然有效
+#[tokio::test]
+async fn ping_in_subscribe_mode() {
+    let addr = start_server().await;
+
+    let mut publisher = TcpStream::connect(addr).await.unwrap();
+    let mut sub = TcpStream::connect(addr).await.unwrap();
+
+    sub.write_all(b"*2\r\n$9\r\nSUBSCRIBE\r\n$5\r\nhello\r\n")
+        .await
+        .unwrap();
+
+    let mut response = [0; 34];
+    sub.read_exact(&mut response).await.unwrap();
+    assert_eq!(
+        &b"*3\r\n$9\r\nsubscribe\r\n$5\r\nhello\r\n:1\r\n"[..],
+        &response[..]
+    );
+
+    // 不带消息的 PING
+    sub.write_all(b"*1\r\n$4\r\nPING\r\n").await.unwrap();
+
+    let mut response = [0; 20];
+    sub.read_exact(&mut response).await.unwrap();
+    assert_eq!(&b"*2\r\n$4\r\npong\r\n$0\r\n\r\n"[..], &response[..]);
+
+    // 带消息的 PING
+    sub.write_all(b"*2\r\n$4\r\nPING\r\n$2\r\nhi\r\n")
+        .await
+        .unwrap();
+
+    let mut response = [0; 22];
+    sub.read_exact(&mut response).await.unwrap();
+    assert_eq!(&b"*2\r\n$4\r\npong\r\n$2\r\nhi\r\n"[..], &response[..]);
+
+    // 订阅没有被关闭，仍然可以收到消息
+    publisher
+        .write_all(b"*3\r\n$7\r\nPUBLISH\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+        .await
+        .unwrap();
+
+    let mut response = [0; 4];
+    publisher.read_exact(&mut response).await.unwrap();
+    assert_eq!(b":1\r\n", &response);
+
+    let mut response = [0; 39];
+    sub.read_exact(&mut response).await.unwrap();
+    assert_eq!(
+        &b"*3\r\n$7\r\nmessage\r\n$5\r\nhello\r\n$5\r\nworld\r\n"[..],
+        &response[..]
+    );
+}
+
+/// 发送一个超过 `max_inline_len` 字节仍然没有 `\r\n` 结尾的行，
+/// 服务器应当将其视为协议错误并关闭连接，而不是无限制地缓存数据。
+#[tokio::test]
+async fn oversized_line_closes_connection() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    let mut line = vec![b'+'];
+    line.resize(70 * 1024, b'a');
+    // 服务器可能在数据全部写完之前就关闭了连接，忽略写入错误
+    let _ = stream.write_all(&line).await;
+
+    // 关闭前服务器会回复协议错误，但客户端还有未被读取的数据时连接可能被重置，
+    // 错误回复不一定能收到
+    let mut response = Vec::new();
+    let res = time::timeout(Duration::from_secs(1), stream.read_to_end(&mut response))
+        .await
+        .expect("server did not close the connection");
+    if res.is_ok() {
+        assert_eq!(
+            &b"-ERR Protocol error: too big inline request\r\n"[..],
+            &response[..]
+        );
+    }
+}
+
+/// 声明的 Bulk 字符串或者数组长度超过 `proto-max-bulk-len` 时，服务器在收到长度后
+/// 立即回复协议错误并关闭连接，不会等待或者缓存这么多数据。
+#[tokio::test]
+async fn oversized_bulk_length_closes_connection() {
+    let addr = start_server().await;
+
+    for request in [&b"*1\r\n$999999999999\r\n"[..], b"*999999999999\r\n"] {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(request).await.unwrap();
+
+        let mut response = Vec::new();
+        time::timeout(Duration::from_secs(1), stream.read_to_end(&mut response))
+            .await
+            .expect("server did not close the connection")
+            .unwrap();
+        assert_eq!(
+            &b"-ERR Protocol error: invalid bulk or multibulk length\r\n"[..],
+            &response[..]
+        );
+    }
+
+    // 上限可以通过配置修改
     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
     let addr = listener.local_addr().unwrap();
+    let config = ServerConfig {
+        proto_max_bulk_len: 4,
+        ..ServerConfig::default()
+    };
+    tokio::spawn(server::run_with_config(
+        listener,
+        tokio::signal::ctrl_c(),
+        config,
+    ));
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(&encode(&["SET", "foo", "12345"]))
+        .await
+        .unwrap();
 
-    tokio::spawn(async move { server::run(listener, tokio::signal::ctrl_c()).await });
+    let mut response = Vec::new();
+    time::timeout(Duration::from_secs(1), stream.read_to_end(&mut response))
+        .await
+        .expect("server did not close the connection")
+        .unwrap();
+    assert_eq!(
+        &b"-ERR Protocol error: invalid bulk or multibulk length\r\n"[..],
+        &response[..]
+    );
+}
 
-    addr
-}
\ No newline at end of file
+/// 数组元素以无效的类型字节开头时，服务器回复 `-ERR Protocol error` 后关闭连接。
+#[tokio::test]
+async fn protocol_error_reply_before_close() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream.write_all(b"*1\r\n!GET\r\n").await.unwrap();
+
+    let mut response = Vec::new();
+    time::timeout(Duration::from_secs(1), stream.read_to_end(&mut response))
+        .await
+        .expect("server did not close the connection")
+        .unwrap();
+    assert_eq!(
+        &b"-ERR Protocol error: expected '$', got '!'\r\n"[..],
+        &response[..]
+    );
+}
+
+/// 对列表和哈希表执行 GET 回复 `WRONGTYPE` 错误，连接保持打开，键的值不变。
+#[tokio::test]
+async fn get_non_string_is_wrong_type() {
+    let addr = start_server().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(&encode(&["RPUSH", "list", "a"]))
+        .await
+        .unwrap();
+    stream
+        .write_all(&encode(&["HSET", "hash", "field", "value"]))
+        .await
+        .unwrap();
+    let mut response = [0; 8];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b":1\r\n:1\r\n", &response);
+
+    let wrong_type = b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n";
+    for key in ["list", "hash"] {
+        stream.write_all(&encode(&["GET", key])).await.unwrap();
+        let mut response = vec![0; wrong_type.len()];
+        stream.read_exact(&mut response).await.unwrap();
+        assert_eq!(&wrong_type[..], &response[..]);
+    }
+
+    stream
+        .write_all(&encode(&["LRANGE", "list", "0", "-1"]))
+        .await
+        .unwrap();
+    let mut response = [0; 11];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"*1\r\n$1\r\na\r\n", &response);
+}
+
+/// 空字符串是合法的键和值，`$0\r\n\r\n` 被当作空字符串处理。
+#[tokio::test]
+async fn empty_string_key_and_value() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(b"*2\r\n$3\r\nGET\r\n$0\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$-1\r\n", &response);
+
+    stream
+        .write_all(&encode(&["SET", "", "val"]))
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    stream.write_all(&encode(&["GET", ""])).await.unwrap();
+    let mut response = [0; 9];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$3\r\nval\r\n", &response);
+
+    // 值为空字符串时回复空的 bulk，而不是 nil
+    stream
+        .write_all(&encode(&["SET", "foo", ""]))
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    stream.write_all(&encode(&["GET", "foo"])).await.unwrap();
+    let mut response = [0; 6];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$0\r\n\r\n", &response);
+}
+
+/// 内联命令（例如通过 `nc` 直接输入的 `GET foo`）与数组帧的效果相同，空行会被跳过。
+#[tokio::test]
+async fn inline_commands() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream.write_all(b"GET foo\r\n").await.unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$-1\r\n", &response);
+
+    stream
+        .write_all(b"\r\n  \r\nSET  foo   bar\r\nGET foo\r\n")
+        .await
+        .unwrap();
+    let mut response = [0; 14];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n$3\r\nbar\r\n", &response);
+}
+
+/// 在一次写入中流水线发送 SET、PING 和 GET，响应按请求顺序返回，PING 的 PONG 位于中间。
+#[tokio::test]
+async fn pipelined_ping_between_set_and_get() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    let mut pipeline = encode(&["SET", "foo", "bar"]);
+    pipeline.extend(encode(&["PING"]));
+    pipeline.extend(encode(&["GET", "foo"]));
+    stream.write_all(&pipeline).await.unwrap();
+
+    let expected = b"+OK\r\n+PONG\r\n$3\r\nbar\r\n";
+    let mut response = [0; 21];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response);
+}
+
+/// `MULTI` 之后的命令排队，`EXEC` 依次执行并以数组返回每个命令的回复。
+#[tokio::test]
+async fn multi_exec_runs_queued_commands() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    let mut pipeline = encode(&["MULTI"]);
+    pipeline.extend(encode(&["SET", "foo", "bar"]));
+    pipeline.extend(encode(&["GET", "foo"]));
+    stream.write_all(&pipeline).await.unwrap();
+
+    let expected = b"+OK\r\n+QUEUED\r\n+QUEUED\r\n";
+    let mut response = [0; 23];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response);
+
+    stream.write_all(&encode(&["EXEC"])).await.unwrap();
+    let expected = b"*2\r\n+OK\r\n$3\r\nbar\r\n";
+    let mut response = [0; 18];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response);
+
+    // 事务已经结束，再次 EXEC 返回错误
+    stream.write_all(&encode(&["EXEC"])).await.unwrap();
+    let expected = b"-ERR EXEC without MULTI\r\n";
+    let mut response = [0; 25];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response);
+}
+
+/// `WATCH` 的键在 `EXEC` 之前被其他连接修改时事务不执行，回复 nil；没有被修改时正常执行。
+#[tokio::test]
+async fn watch_aborts_exec_when_key_modified() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let mut other = TcpStream::connect(addr).await.unwrap();
+
+    stream.write_all(&encode(&["WATCH", "foo"])).await.unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    // 另一个连接在 WATCH 之后修改了 foo
+    other
+        .write_all(&encode(&["SET", "foo", "other"]))
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    other.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    let mut pipeline = encode(&["MULTI"]);
+    pipeline.extend(encode(&["SET", "foo", "bar"]));
+    pipeline.extend(encode(&["EXEC"]));
+    pipeline.extend(encode(&["GET", "foo"]));
+    stream.write_all(&pipeline).await.unwrap();
+    let expected = b"+OK\r\n+QUEUED\r\n$-1\r\n$5\r\nother\r\n";
+    let mut response = vec![0; expected.len()];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(&expected[..], &response[..]);
+
+    // EXEC 之后不再监视 foo，WATCH 一个没有被修改的键时事务正常执行，
+    // 同一个连接自己在 MULTI 之前的修改同样会让事务被放弃
+    let mut pipeline = encode(&["WATCH", "foo"]);
+    pipeline.extend(encode(&["MULTI"]));
+    pipeline.extend(encode(&["SET", "foo", "bar"]));
+    pipeline.extend(encode(&["EXEC"]));
+    pipeline.extend(encode(&["WATCH", "foo"]));
+    pipeline.extend(encode(&["SET", "foo", "baz"]));
+    pipeline.extend(encode(&["MULTI"]));
+    pipeline.extend(encode(&["GET", "foo"]));
+    pipeline.extend(encode(&["EXEC"]));
+    stream.write_all(&pipeline).await.unwrap();
+    let expected = b"+OK\r\n+OK\r\n+QUEUED\r\n*1\r\n+OK\r\n+OK\r\n+OK\r\n+OK\r\n+QUEUED\r\n$-1\r\n";
+    let mut response = vec![0; expected.len()];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(&expected[..], &response[..]);
+
+    // UNWATCH 之后修改键不影响事务，事务中不能执行 WATCH
+    let mut pipeline = encode(&["WATCH", "foo"]);
+    pipeline.extend(encode(&["UNWATCH"]));
+    pipeline.extend(encode(&["DEL", "foo"]));
+    pipeline.extend(encode(&["MULTI"]));
+    pipeline.extend(encode(&["WATCH", "foo"]));
+    pipeline.extend(encode(&["EXEC"]));
+    stream.write_all(&pipeline).await.unwrap();
+    let expected = b"+OK\r\n+OK\r\n:1\r\n+OK\r\n-ERR Command not allowed inside a transaction\r\n-EXECABORT Transaction discarded because of previous errors.\r\n";
+    let mut response = vec![0; expected.len()];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(&expected[..], &response[..]);
+}
+
+/// 排队时出现错误的事务在 `EXEC` 时被放弃，`DISCARD` 放弃排队的命令。
+#[tokio::test]
+async fn multi_aborts_on_queueing_errors() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    let mut pipeline = encode(&["MULTI"]);
+    pipeline.extend(encode(&["SET", "foo", "bar"]));
+    pipeline.extend(encode(&["EXPIRE", "foo", "abc"]));
+    pipeline.extend(encode(&["EXEC"]));
+    stream.write_all(&pipeline).await.unwrap();
+
+    let expected = b"+OK\r\n+QUEUED\r\n\
+        -ERR value is not an integer or out of range\r\n\
+        -EXECABORT Transaction discarded because of previous errors.\r\n";
+    let mut response = vec![0; expected.len()];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(&expected[..], &response[..]);
+
+    let mut pipeline = encode(&["MULTI"]);
+    pipeline.extend(encode(&["SET", "foo", "bar"]));
+    pipeline.extend(encode(&["DISCARD"]));
+    pipeline.extend(encode(&["GET", "foo"]));
+    stream.write_all(&pipeline).await.unwrap();
+
+    // 两个事务中的 SET 都没有执行
+    let expected = b"+OK\r\n+QUEUED\r\n+OK\r\n$-1\r\n";
+    let mut response = [0; 24];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response);
+}
+
+/// `SET` 同时指定多个过期选项时返回语法错误，并且连接保持可用。
+#[tokio::test]
+async fn set_conflicting_expiry_options() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    let conflicts: &[&[&str]] = &[
+        &["SET", "foo", "bar", "EX", "10", "PX", "10000"],
+        &[
+            "SET",
+            "foo",
+            "bar",
+            "EXAT",
+            "4102444800",
+            "PXAT",
+            "4102444800000",
+        ],
+        &["SET", "foo", "bar", "EX", "10", "KEEPTTL"],
+        &["SET", "foo", "bar", "KEEPTTL", "PX", "10000"],
+    ];
+
+    for args in conflicts {
+        stream.write_all(&encode(args)).await.unwrap();
+
+        let mut response = [0; 19];
+        stream.read_exact(&mut response).await.unwrap();
+        assert_eq!(b"-ERR syntax error\r\n", &response, "{:?}", args);
+    }
+
+    // 被拒绝的命令没有写入任何数据
+    stream.write_all(&encode(&["GET", "foo"])).await.unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$-1\r\n", &response);
+}
+
+/// 过期时间过大，计算过期时间点会溢出时返回错误，而不是让服务器崩溃。
+#[tokio::test]
+async fn expire_time_overflow() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(&encode(&["SET", "foo", "bar"]))
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    stream
+        .write_all(&encode(&["EXPIRE", "foo", "9999999999999999"]))
+        .await
+        .unwrap();
+    let expected = b"-ERR invalid expire time in 'expire' command\r\n";
+    let mut response = [0; 46];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response);
+
+    let options = [
+        ("EX", "9999999999999999"),
+        ("EXAT", "9999999999999999"),
+        ("PX", "9223372036854775807"),
+    ];
+    for (option, value) in options {
+        stream
+            .write_all(&encode(&["SET", "foo", "baz", option, value]))
+            .await
+            .unwrap();
+        let expected = b"-ERR invalid expire time in 'set' command\r\n";
+        let mut response = [0; 43];
+        stream.read_exact(&mut response).await.unwrap();
+        assert_eq!(expected, &response, "{}", option);
+    }
+
+    // 被拒绝的命令没有修改原来的值和过期时间
+    stream.write_all(&encode(&["GET", "foo"])).await.unwrap();
+    let mut response = [0; 9];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$3\r\nbar\r\n", &response);
+
+    stream.write_all(&encode(&["TTL", "foo"])).await.unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b":-1\r\n", &response);
+}
+
+/// 参数个数不对的命令回复错误，连接保持打开。
+#[tokio::test]
+async fn wrong_number_of_arguments() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    let requests: &[(&[&str], &[u8])] = &[
+        (
+            &["GET"],
+            b"-ERR wrong number of arguments for 'get' command\r\n",
+        ),
+        (
+            &["get", "foo", "bar"],
+            b"-ERR wrong number of arguments for 'get' command\r\n",
+        ),
+        (
+            &["SET", "foo"],
+            b"-ERR wrong number of arguments for 'set' command\r\n",
+        ),
+        (
+            &["LRANGE", "foo", "0"],
+            b"-ERR wrong number of arguments for 'lrange' command\r\n",
+        ),
+        (
+            &["DBSIZE", "foo"],
+            b"-ERR wrong number of arguments for 'dbsize' command\r\n",
+        ),
+        (
+            &["PUBLISH", "chan"],
+            b"-ERR wrong number of arguments for 'publish' command\r\n",
+        ),
+        (
+            &["PUBLISH", "chan", "msg", "extra"],
+            b"-ERR syntax error\r\n",
+        ),
+        (
+            &["PUBLISH", "chan", "msg", "detailed", "extra"],
+            b"-ERR wrong number of arguments for 'publish' command\r\n",
+        ),
+        (
+            &["PUBLISH", "chan", "msg", "detailed"],
+            b"*2\r\n:0\r\n:0\r\n",
+        ),
+        (&["PING"], b"+PONG\r\n"),
+    ];
+    for (cmd, expected) in requests {
+        stream.write_all(&encode(cmd)).await.unwrap();
+        let mut response = vec![0; expected.len()];
+        stream.read_exact(&mut response).await.unwrap();
+        assert_eq!(*expected, &response[..], "{:?}", cmd);
+    }
+}
+
+/// EXPIRE 的 NX/XX/GT/LT 选项只在条件满足时设置过期时间，不满足时返回 0。
+#[tokio::test]
+async fn expire_conditions() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    // 每一步：命令和期望的回复，`persist` 没有过期时间，`volatile` 的过期时间是 100 秒
+    let steps: &[(&[&str], &[u8])] = &[
+        (&["SET", "persist", "v"], b"+OK\r\n"),
+        (&["SET", "volatile", "v", "EX", "100"], b"+OK\r\n"),
+        (&["EXPIRE", "persist", "100", "XX"], b":0\r\n"),
+        (&["EXPIRE", "persist", "100", "GT"], b":0\r\n"),
+        (&["EXPIRE", "volatile", "200", "NX"], b":0\r\n"),
+        (&["EXPIRE", "volatile", "50", "GT"], b":0\r\n"),
+        (&["EXPIRE", "volatile", "200", "LT"], b":0\r\n"),
+        (&["TTL", "volatile"], b":100\r\n"),
+        (&["EXPIRE", "volatile", "200", "gt"], b":1\r\n"),
+        (&["TTL", "volatile"], b":200\r\n"),
+        (&["EXPIRE", "volatile", "150", "XX"], b":1\r\n"),
+        (&["EXPIRE", "volatile", "50", "LT"], b":1\r\n"),
+        (&["TTL", "volatile"], b":50\r\n"),
+        (&["EXPIRE", "persist", "300", "LT"], b":1\r\n"),
+        (&["SET", "persist", "v"], b"+OK\r\n"),
+        (&["EXPIRE", "persist", "100", "NX"], b":1\r\n"),
+        (&["TTL", "persist"], b":100\r\n"),
+        (&["EXPIRE", "missing", "100", "NX"], b":0\r\n"),
+        (
+            &["EXPIRE", "persist", "100", "NX", "XX"],
+            b"-ERR syntax error\r\n",
+        ),
+        (
+            &["EXPIRE", "persist", "100", "FOO"],
+            b"-ERR syntax error\r\n",
+        ),
+    ];
+    for (cmd, expected) in steps {
+        stream.write_all(&encode(cmd)).await.unwrap();
+        let mut response = vec![0; expected.len()];
+        stream.read_exact(&mut response).await.unwrap();
+        assert_eq!(*expected, &response[..], "{:?}", cmd);
+    }
+}
+
+/// `SETEX` 的生存时间位于值之前，生存时间不是整数或者不是正数时返回错误，连接保持可用。
+#[tokio::test]
+async fn setex_invalid_seconds() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(&encode(&["SETEX", "k", "abc", "v"]))
+        .await
+        .unwrap();
+    let expected = b"-ERR value is not an integer or out of range\r\n";
+    let mut response = [0; 46];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response);
+
+    for seconds in ["0", "-10"] {
+        stream
+            .write_all(&encode(&["SETEX", "k", seconds, "v"]))
+            .await
+            .unwrap();
+        let expected = b"-ERR invalid expire time in 'setex' command\r\n";
+        let mut response = [0; 45];
+        stream.read_exact(&mut response).await.unwrap();
+        assert_eq!(expected, &response, "{}", seconds);
+    }
+
+    // 被拒绝的命令没有写入任何数据
+    stream.write_all(&encode(&["GET", "k"])).await.unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$-1\r\n", &response);
+
+    stream
+        .write_all(&encode(&["SETEX", "k", "100", "v"]))
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    stream.write_all(&encode(&["TTL", "k"])).await.unwrap();
+    let mut response = [0; 6];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b":100\r\n", &response);
+}
+
+/// `SET ... KEEPTTL` 保留键原有的过期时间。
+#[tokio::test]
+async fn set_keep_ttl() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(&encode(&["SET", "foo", "bar", "PX", "100000"]))
+        .await
+        .unwrap();
+    stream
+        .write_all(&encode(&["SET", "foo", "baz", "KEEPTTL"]))
+        .await
+        .unwrap();
+    stream.write_all(&encode(&["TTL", "foo"])).await.unwrap();
+    stream.write_all(&encode(&["GET", "foo"])).await.unwrap();
+
+    let mut response = [0; 10 + 6 + 9];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n+OK\r\n:100\r\n$3\r\nbaz\r\n", &response);
+}
+
+/// `TTL` 把剩余时间向上取整到秒，`PTTL` 返回精确的毫秒数。
+#[tokio::test]
+async fn ttl_rounds_up_and_pttl_is_exact() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(&encode(&["SET", "foo", "bar", "PX", "1500"]))
+        .await
+        .unwrap();
+    stream.write_all(&encode(&["TTL", "foo"])).await.unwrap();
+
+    let mut response = [0; 5 + 4];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n:2\r\n", &response);
+
+    stream.write_all(&encode(&["PTTL", "foo"])).await.unwrap();
+
+    // 剩余时间在 1400 到 1500 毫秒之间，总是四位数
+    let mut response = [0; 7];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b":", &response[..1]);
+    assert_eq!(b"\r\n", &response[5..]);
+    let pttl: u64 = std::str::from_utf8(&response[1..5])
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1400..=1500).contains(&pttl), "pttl = {}", pttl);
+}
+
+/// `HELLO 3` 切换到 RESP3，服务器信息以 Map 返回；RESP2 连接收到的是数组。
+#[tokio::test]
+async fn hello_negotiates_resp3() {
+    let addr = start_server().await;
+
+    let version = env!("CARGO_PKG_VERSION");
+    let entries = format!(
+        "$6\r\nserver\r\n$10\r\nmini-redis\r\n\
+         $7\r\nversion\r\n${}\r\n{}\r\n\
+         $5\r\nproto\r\n:{{}}\r\n\
+         $4\r\nmode\r\n$10\r\nstandalone\r\n\
+         $4\r\nrole\r\n$6\r\nmaster\r\n",
+        version.len(),
+        version
+    );
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream.write_all(&encode(&["HELLO", "3"])).await.unwrap();
+    let expected = format!("%5\r\n{}", entries.replace("{}", "3"));
+    let mut response = vec![0; expected.len()];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected.as_bytes(), &response[..]);
+
+    // 不支持的协议版本返回错误，协议版本保持不变
+    stream.write_all(&encode(&["HELLO", "4"])).await.unwrap();
+    let expected = b"-NOPROTO unsupported protocol version\r\n";
+    let mut response = [0; 39];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response);
+
+    // 切换回 RESP2 后，服务器信息退化为键值交替排列的数组
+    stream.write_all(&encode(&["HELLO", "2"])).await.unwrap();
+    let expected = format!("*10\r\n{}", entries.replace("{}", "2"));
+    let mut response = vec![0; expected.len()];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected.as_bytes(), &response[..]);
+}
+
+/// `RESET` 退出订阅状态并把协议版本恢复为 RESP2，之后的命令按普通的命令模式执行。
+#[tokio::test]
+async fn reset_exits_subscribe_mode_and_resp3() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    // 服务器信息的最后一项是 role，读到它说明整个 Map 都已经收到
+    stream.write_all(&encode(&["HELLO", "3"])).await.unwrap();
+    let mut response = vec![];
+    while !response.ends_with(b"$4\r\nrole\r\n$6\r\nmaster\r\n") {
+        response.push(stream.read_u8().await.unwrap());
+    }
+    assert!(response.starts_with(b"%5\r\n"));
+
+    stream
+        .write_all(&encode(&["SUBSCRIBE", "foo"]))
+        .await
+        .unwrap();
+    let expected = b"*3\r\n$9\r\nsubscribe\r\n$3\r\nfoo\r\n:1\r\n";
+    let mut response = [0; 32];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response);
+
+    stream.write_all(&encode(&["RESET"])).await.unwrap();
+    let mut response = [0; 8];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+RESET\r\n", &response);
+
+    // 订阅已经取消，发布的消息没有接收者
+    let mut publisher = TcpStream::connect(addr).await.unwrap();
+    publisher
+        .write_all(&encode(&["PUBLISH", "foo", "bar"]))
+        .await
+        .unwrap();
+    let mut response = [0; 4];
+    publisher.read_exact(&mut response).await.unwrap();
+    assert_eq!(b":0\r\n", &response);
+
+    // 回到普通的命令模式，空的哈希表以 RESP2 的数组返回
+    stream
+        .write_all(&encode(&["HGETALL", "missing"]))
+        .await
+        .unwrap();
+    let mut response = [0; 4];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"*0\r\n", &response);
+}
+
+/// `MSET` 的参数个数不匹配时返回参数错误，并且不会写入任何键。
+#[tokio::test]
+async fn mset_unbalanced_arguments() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(&encode(&["MSET", "a", "1", "b"]))
+        .await
+        .unwrap();
+
+    let expected = b"-ERR wrong number of arguments for 'mset' command\r\n";
+    let mut response = [0; 51];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response);
+
+    for key in ["a", "b"] {
+        stream.write_all(&encode(&["GET", key])).await.unwrap();
+        let mut response = [0; 5];
+        stream.read_exact(&mut response).await.unwrap();
+        assert_eq!(b"$-1\r\n", &response);
+    }
+
+    stream
+        .write_all(&encode(&["MSET", "a", "1", "b", "2"]))
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    stream.write_all(&encode(&["GET", "b"])).await.unwrap();
+    let mut response = [0; 7];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$1\r\n2\r\n", &response);
+}
+
+/// 开启 `reject_on_full` 后，连接数已满时新的客户端收到错误并被关闭，而不是一直等待。
+#[tokio::test]
+async fn reject_connections_when_full() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let config = ServerConfig {
+        max_connections: 2,
+        reject_on_full: true,
+        ..ServerConfig::default()
+    };
+    tokio::spawn(server::run_with_config(
+        listener,
+        tokio::signal::ctrl_c(),
+        config,
+    ));
+
+    // 占满所有连接，PING 确认服务器已经开始处理它们
+    let mut streams = vec![];
+    for _ in 0..2 {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(&encode(&["PING"])).await.unwrap();
+        let mut response = [0; 7];
+        stream.read_exact(&mut response).await.unwrap();
+        assert_eq!(b"+PONG\r\n", &response);
+        streams.push(stream);
+    }
+
+    // 连接数已满时服务器仍然继续接受连接，每个多出来的客户端都会收到错误
+    for _ in 0..3 {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let mut response = vec![];
+        time::timeout(Duration::from_secs(1), stream.read_to_end(&mut response))
+            .await
+            .expect("rejected client should not hang")
+            .unwrap();
+        assert_eq!(
+            &b"-ERR max number of clients reached\r\n"[..],
+            &response[..]
+        );
+    }
+
+    // 已有的连接不受影响
+    streams[0].write_all(&encode(&["PING"])).await.unwrap();
+    let mut response = [0; 7];
+    streams[0].read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+PONG\r\n", &response);
+
+    // 释放一个连接后，新的客户端可以正常连接
+    drop(streams.pop());
+    time::sleep(Duration::from_millis(50)).await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(&encode(&["PING"])).await.unwrap();
+    let mut response = [0; 7];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+PONG\r\n", &response);
+}
+
+/// `command_timeout` 只限制命令的执行时间，客户端读取很大的回复很慢时不会被算作超时。
+#[tokio::test]
+async fn command_timeout_excludes_reply_write() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let config = ServerConfig {
+        command_timeout: Some(Duration::from_millis(100)),
+        ..ServerConfig::default()
+    };
+    tokio::spawn(server::run_with_config(
+        listener,
+        tokio::signal::ctrl_c(),
+        config,
+    ));
+
+    // 值比套接字的缓冲区大得多，客户端不读取时服务器写不完回复
+    let value = vec![b'x'; 32 * 1024 * 1024];
+    let mut client = client::connect(addr).await.unwrap();
+    client.set("big", value.clone().into()).await.unwrap();
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(&encode(&["GET", "big"])).await.unwrap();
+    // 等待超过 `command_timeout` 之后再开始读取
+    time::sleep(Duration::from_millis(300)).await;
+
+    let header = format!("${}\r\n", value.len());
+    let mut response = vec![0; header.len() + value.len() + 2];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(header.as_bytes(), &response[..header.len()]);
+    assert!(response[header.len()..header.len() + value.len()]
+        .iter()
+        .all(|b| *b == b'x'));
+    assert_eq!(b"\r\n", &response[header.len() + value.len()..]);
+
+    // 连接上没有多余的超时错误，下一个命令正常执行
+    stream.write_all(&encode(&["PING"])).await.unwrap();
+    let mut response = [0; 7];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+PONG\r\n", &response);
+}
+
+/// 设置了 `client_idle_timeout` 后，空闲的连接会被服务器关闭，处于订阅状态的连接不受影响。
+#[tokio::test]
+async fn idle_connections_are_closed() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let config = ServerConfig {
+        client_idle_timeout: Some(Duration::from_millis(100)),
+        ..ServerConfig::default()
+    };
+    tokio::spawn(server::run_with_config(
+        listener,
+        tokio::signal::ctrl_c(),
+        config,
+    ));
+
+    let mut subscriber = TcpStream::connect(addr).await.unwrap();
+    subscriber
+        .write_all(&encode(&["SUBSCRIBE", "hello"]))
+        .await
+        .unwrap();
+    let mut response = [0; 34];
+    subscriber.read_exact(&mut response).await.unwrap();
+    assert_eq!(
+        &b"*3\r\n$9\r\nsubscribe\r\n$5\r\nhello\r\n:1\r\n"[..],
+        &response[..]
+    );
+
+    // 不发送任何命令，服务器在超时之后关闭连接
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let mut response = vec![];
+    time::timeout(Duration::from_secs(1), stream.read_to_end(&mut response))
+        .await
+        .expect("idle client should be disconnected")
+        .unwrap();
+    assert!(response.is_empty());
+
+    // 订阅的连接仍然可以收到消息
+    let mut publisher = TcpStream::connect(addr).await.unwrap();
+    publisher
+        .write_all(&encode(&["PUBLISH", "hello", "world"]))
+        .await
+        .unwrap();
+    let mut response = [0; 4];
+    publisher.read_exact(&mut response).await.unwrap();
+    assert_eq!(b":1\r\n", &response);
+
+    let mut response = [0; 39];
+    subscriber.read_exact(&mut response).await.unwrap();
+    assert_eq!(
+        &b"*3\r\n$7\r\nmessage\r\n$5\r\nhello\r\n$5\r\nworld\r\n"[..],
+        &response[..]
+    );
+}
+
+/// 设置了 `max_commands_per_sec` 后，`reject` 模式下超过速率的命令收到错误，`delay` 模式下命令被推迟执行。
+#[tokio::test]
+async fn rate_limit_rejects_or_delays_commands() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let config = parse_config("max-commands-per-sec 10\nrate-limit-action reject\n").unwrap();
+    tokio::spawn(server::run_with_config(
+        listener,
+        tokio::signal::ctrl_c(),
+        config,
+    ));
+
+    // 一次发送 30 个命令，只有前面一秒的配额被执行
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(&encode(&["PING"]).repeat(30))
+        .await
+        .unwrap();
+    let mut lines = BufReader::new(stream).lines();
+    let mut rejected = 0;
+    for _ in 0..30 {
+        match &lines.next_line().await.unwrap().unwrap()[..] {
+            "+PONG" => {}
+            "-ERR rate limit exceeded" => rejected += 1,
+            line => panic!("unexpected response {:?}", line),
+        }
+    }
+    assert!(rejected >= 15, "only {} commands rejected", rejected);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let config = ServerConfig {
+        max_commands_per_sec: Some(20),
+        ..ServerConfig::default()
+    };
+    tokio::spawn(server::run_with_config(
+        listener,
+        tokio::signal::ctrl_c(),
+        config,
+    ));
+
+    // 超出配额的 10 个命令以每秒 20 个的速率执行，全部成功但至少需要半秒
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let start = time::Instant::now();
+    stream
+        .write_all(&encode(&["PING"]).repeat(30))
+        .await
+        .unwrap();
+    let mut response = vec![0; 30 * b"+PONG\r\n".len()];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+PONG\r\n".repeat(30), response);
+    assert!(start.elapsed() >= Duration::from_millis(400));
+}
+
+/// 排队之后内存超过了 `max_memory`，`EXEC` 拒绝其中可能占用更多内存的命令，读取仍然执行。
+#[tokio::test]
+async fn max_memory_checked_for_queued_commands() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let config = ServerConfig {
+        max_memory: Some(1000),
+        ..ServerConfig::default()
+    };
+    tokio::spawn(server::run_with_config(
+        listener,
+        tokio::signal::ctrl_c(),
+        config,
+    ));
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let mut pipeline = encode(&["MULTI"]);
+    pipeline.extend(encode(&["SET", "foo", "bar"]));
+    pipeline.extend(encode(&["GET", "big"]));
+    stream.write_all(&pipeline).await.unwrap();
+    let mut response = [0; 23];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n+QUEUED\r\n+QUEUED\r\n", &response);
+
+    // 排队时内存还在限制以内，另一个连接的写入让它超过了限制
+    let value = "x".repeat(1200);
+    let mut other = TcpStream::connect(addr).await.unwrap();
+    other
+        .write_all(&encode(&["SET", "big", &value]))
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    other.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    stream.write_all(&encode(&["EXEC"])).await.unwrap();
+    let expected = format!(
+        "*2\r\n-OOM command not allowed when used memory > 'maxmemory'\r\n${}\r\n{}\r\n",
+        value.len(),
+        value
+    );
+    let mut response = vec![0; expected.len()];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected.as_bytes(), &response[..]);
+
+    // 被拒绝的命令没有执行
+    stream.write_all(&encode(&["GET", "foo"])).await.unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$-1\r\n", &response);
+}
+
+/// 设置了 `max_memory` 后，超过限制时写入被拒绝，读取、`EXPIRE` 和 `DEL` 仍然可以执行，删除之后可以继续写入。
+#[tokio::test]
+async fn max_memory_rejects_writes_but_allows_del() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let config = ServerConfig {
+        max_memory: Some(1000),
+        ..ServerConfig::default()
+    };
+    tokio::spawn(server::run_with_config(
+        listener,
+        tokio::signal::ctrl_c(),
+        config,
+    ));
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let value = "x".repeat(100);
+    let oom = b"-OOM command not allowed when used memory > 'maxmemory'\r\n";
+
+    // 写入直到被拒绝
+    let mut written = 0;
+    loop {
+        let key = format!("key:{:03}", written);
+        stream
+            .write_all(&encode(&["SET", &key, &value]))
+            .await
+            .unwrap();
+        let mut response = [0; 5];
+        stream.read_exact(&mut response).await.unwrap();
+        if &response == b"+OK\r\n" {
+            written += 1;
+            assert!(written < 100, "SET was never rejected");
+            continue;
+        }
+
+        let mut rest = vec![0; oom.len() - response.len()];
+        stream.read_exact(&mut rest).await.unwrap();
+        assert_eq!(&oom[..], &[&response[..], &rest[..]].concat()[..]);
+        break;
+    }
+    // 每个键和值占用 107 字节，只要还没有超过 1000 字节就可以写入
+    assert_eq!(10, written);
+
+    // 读取不受影响
+    stream
+        .write_all(&encode(&["GET", "key:000"]))
+        .await
+        .unwrap();
+    let mut response = vec![0; 108];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(format!("$100\r\n{}\r\n", value).as_bytes(), &response[..]);
+
+    stream
+        .write_all(&encode(&["EXPIRE", "key:001", "100"]))
+        .await
+        .unwrap();
+    let mut response = [0; 4];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b":1\r\n", &response);
+
+    // 删除一个键之后回到限制以内，可以再次写入
+    stream
+        .write_all(&encode(&["DEL", "key:000"]))
+        .await
+        .unwrap();
+    let mut response = [0; 4];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b":1\r\n", &response);
+
+    stream
+        .write_all(&encode(&["SET", "key:010", &value]))
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+}
+
+/// 某个连接卡在写响应时，关闭服务器不会无限等待，`run` 在 `shutdown_timeout` 之后返回。
+#[tokio::test]
+async fn shutdown_timeout_with_stuck_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let config = ServerConfig {
+        shutdown_timeout: Duration::from_millis(500),
+        ..ServerConfig::default()
+    };
+    let server = tokio::spawn(server::run_with_config(listener, shutdown_rx, config));
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    // 写入一个较大的值
+    let value = "x".repeat(4 * 1024 * 1024);
+    stream
+        .write_all(&encode(&["SET", "foo", &value]))
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    // 连续请求这个值但不读取响应，服务器写满套接字缓冲区后会卡在写响应上
+    for _ in 0..16 {
+        stream.write_all(&encode(&["GET", "foo"])).await.unwrap();
+    }
+    time::sleep(Duration::from_millis(200)).await;
+
+    shutdown_tx.send(()).unwrap();
+
+    time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("server did not shut down within the timeout")
+        .unwrap();
+}
+
+/// 将命令参数编码为 RESP 数组
+fn encode(args: &[&str]) -> Vec<u8> {
+    let mut buf = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        buf.extend_from_slice(format!("${}\r\n{}\r\n", arg.len(), arg).as_bytes());
+    }
+    buf
+}
+
+/// 服务器可执行文件通过 `--config` 读取配置文件，并监听文件中指定的地址和端口。
+#[tokio::test]
+async fn server_loads_config_file() {
+    let port = free_port().await;
+
+    let path = std::env::temp_dir().join(format!("mini-redis-{}.conf", port));
+    let contents = format!(
+        "# 测试配置\nbind 127.0.0.1\nport {}\n\nMAXCLIENTS 8\nshutdown-timeout 5\ntimeout 60\n",
+        port
+    );
+    std::fs::write(&path, contents).unwrap();
+
+    let config = parse_config_file(&path).unwrap();
+    assert_eq!("127.0.0.1", config.bind);
+    assert_eq!(port, config.port);
+    assert_eq!(8, config.max_connections);
+    assert_eq!(Duration::from_secs(5), config.shutdown_timeout);
+    assert_eq!(Some(Duration::from_secs(60)), config.client_idle_timeout);
+
+    let (_server, mut stream) = start_server_binary(&path, port).await;
+
+    stream.write_all(&encode(&["PING"])).await.unwrap();
+    let mut response = [0; 7];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+PONG\r\n", &response);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// 收到 SIGHUP 后服务器重新读取配置文件，可以在运行时修改的配置立即生效，端口等配置保持不变。
+#[cfg(unix)]
+#[tokio::test]
+async fn sighup_reloads_config_file() {
+    let port = free_port().await;
+
+    let path = std::env::temp_dir().join(format!("mini-redis-{}.conf", port));
+    let contents = format!("bind 127.0.0.1\nport {}\nshutdown-timeout 5\n", port);
+    std::fs::write(&path, contents).unwrap();
+
+    let (server, _) = start_server_binary(&path, port).await;
+    let mut client = client::connect(("127.0.0.1", port)).await.unwrap();
+    assert_eq!(
+        Some("5".to_string()),
+        client.config_get("shutdown-timeout").await.unwrap()
+    );
+    assert_eq!(
+        Some("no".to_string()),
+        client.config_get("notify-keyspace-events").await.unwrap()
+    );
+
+    let contents = format!(
+        "bind 127.0.0.1\nport {}\nshutdown-timeout 7\nloglevel error\nnotify-keyspace-events yes\n",
+        port + 1
+    );
+    std::fs::write(&path, contents).unwrap();
+
+    let status = std::process::Command::new("kill")
+        .arg("-HUP")
+        .arg(server.id().unwrap().to_string())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // 信号是异步处理的，等待新的配置生效
+    time::timeout(Duration::from_secs(5), async {
+        while client.config_get("shutdown-timeout").await.unwrap() != Some("7".to_string()) {
+            time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("config was not reloaded");
+
+    assert_eq!(
+        Some("error".to_string()),
+        client.config_get("loglevel").await.unwrap()
+    );
+    // 端口不能在运行时修改
+    assert_eq!(
+        Some(port.to_string()),
+        client.config_get("port").await.unwrap()
+    );
+    assert_eq!(None, client.config_get("no-such-parameter").await.unwrap());
+
+    // 键空间事件在重新加载之后立即开启
+    assert_eq!(
+        Some("yes".to_string()),
+        client.config_get("notify-keyspace-events").await.unwrap()
+    );
+    let subscriber = client::connect(("127.0.0.1", port)).await.unwrap();
+    let mut subscriber = subscriber
+        .subscribe(vec!["__keyevent@0__:set".into()])
+        .await
+        .unwrap();
+    client.set("foo", "bar".into()).await.unwrap();
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!(b"foo", &message.content[..]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// 收到 SIGTERM 后服务器可执行文件优雅地关闭并正常退出。
+#[cfg(unix)]
+#[tokio::test]
+async fn sigterm_shuts_down_server() {
+    let port = free_port().await;
+
+    let path = std::env::temp_dir().join(format!("mini-redis-{}.conf", port));
+    let contents = format!("bind 127.0.0.1\nport {}\nshutdown-timeout 5\n", port);
+    std::fs::write(&path, contents).unwrap();
+
+    let (mut server, mut stream) = start_server_binary(&path, port).await;
+
+    // 确认服务器已经开始处理命令，信号处理已经注册
+    stream.write_all(&encode(&["PING"])).await.unwrap();
+    let mut response = [0; 7];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+PONG\r\n", &response);
+
+    let status = std::process::Command::new("kill")
+        .arg("-TERM")
+        .arg(server.id().unwrap().to_string())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // 已有的连接被关闭，进程以成功状态退出，而不是被信号终止
+    let mut response = vec![];
+    time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("connection was not closed")
+        .unwrap();
+    let status = time::timeout(Duration::from_secs(5), server.wait())
+        .await
+        .expect("server did not exit")
+        .unwrap();
+    assert!(status.success(), "{:?}", status);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// 配置文件中未知的配置名或不合法的参数会报告所在的行。
+#[test]
+fn config_file_errors_report_line() {
+    let err = parse_config("port 6380\nappendfsync always\n").unwrap_err();
+    assert!(
+        matches!(err, MiniRedisConfigError::Invalid { line: 2, .. }),
+        "{:?}",
+        err
+    );
+
+    let err = parse_config("# comment\nport abc\n").unwrap_err();
+    assert!(
+        matches!(err, MiniRedisConfigError::Invalid { line: 2, .. }),
+        "{:?}",
+        err
+    );
+}
+
+/// `max-value-size` 为 0 表示不限制，`CONFIG GET` 返回配置文件中的写法。
+#[test]
+fn config_file_max_value_size() {
+    let config = parse_config("max-value-size 1048576\n").unwrap();
+    assert_eq!(Some(1024 * 1024), config.max_value_size);
+    assert_eq!(Some("1048576".to_string()), config.get("max-value-size"));
+
+    let config = parse_config("max-value-size 0\n").unwrap();
+    assert_eq!(None, config.max_value_size);
+    assert_eq!(Some("0".to_string()), config.get("max-value-size"));
+
+    assert!(parse_config("max-value-size -1\n").is_err());
+}
+
+/// `notify-keyspace-events` 接受 Redis 的类型标志字符串，空字符串表示关闭。
+#[test]
+fn config_file_notify_keyspace_events() {
+    for (arg, enabled) in [
+        ("KEA", true),
+        ("\"KEA\"", true),
+        ("E$", true),
+        ("Eg", true),
+        ("\"\"", false),
+        ("''", false),
+        // 没有键事件或者没有事件类型时不会发布任何事件
+        ("KA", false),
+        ("E", false),
+        ("yes", true),
+        ("no", false),
+    ] {
+        let config = parse_config(&format!("notify-keyspace-events {}\n", arg)).unwrap();
+        assert_eq!(enabled, config.notify_keyspace_events, "{}", arg);
+    }
+
+    assert!(parse_config("notify-keyspace-events KEQ\n").is_err());
+    assert!(parse_config("notify-keyspace-events \"KEA\n").is_err());
+}
+
+/// 返回一个当前空闲的端口
+async fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// 使用 `--unixsocket` 运行服务器可执行文件，通过 unix 域套接字完成一次 SET/GET。
+#[cfg(unix)]
+#[tokio::test]
+async fn unix_socket_get_set() {
+    use tokio::net::UnixStream;
+
+    let path = std::env::temp_dir().join(format!("mini-redis-{}.sock", std::process::id()));
+    let _server = Command::new(env!("CARGO_BIN_EXE_mini-redis-server"))
+        .arg("--unixsocket")
+        .arg(&path)
+        .env("LOG_LEVEL", "ERROR")
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+
+    let mut stream = loop {
+        match UnixStream::connect(&path).await {
+            Ok(stream) => break stream,
+            Err(_) => time::sleep(Duration::from_millis(20)).await,
+        }
+    };
+
+    stream
+        .write_all(b"*3\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+        .await
+        .unwrap();
+
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    stream
+        .write_all(b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n")
+        .await
+        .unwrap();
+
+    let mut response = [0; 11];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$5\r\nworld\r\n", &response);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// 配置了 TLS 之后，客户端使用自签名证书完成握手，再通过 TLS 连接完成一次 SET/GET。
+#[tokio::test]
+async fn tls_get_set() {
+    use std::convert::TryFrom;
+    use std::sync::Arc;
+
+    use tokio_rustls::rustls::pki_types::ServerName;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+    use tokio_rustls::TlsConnector;
+
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let dir = std::env::temp_dir();
+    let id = std::process::id();
+    let cert_path = dir.join(format!("mini-redis-{}-cert.pem", id));
+    let key_path = dir.join(format!("mini-redis-{}-key.pem", id));
+    std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+    std::fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let config = ServerConfig {
+        tls: Some(TlsConfig {
+            cert_path: cert_path.clone(),
+            key_path: key_path.clone(),
+        }),
+        ..ServerConfig::default()
+    };
+    tokio::spawn(server::run_with_config(
+        listener,
+        tokio::signal::ctrl_c(),
+        config,
+    ));
+
+    let mut roots = RootCertStore::empty();
+    roots.add(cert.cert.der().clone()).unwrap();
+    let client_config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let connector = TlsConnector::from(Arc::new(client_config));
+
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let mut stream = connector
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+
+    stream
+        .write_all(&encode(&["SET", "hello", "world"]))
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    stream.write_all(&encode(&["GET", "hello"])).await.unwrap();
+    let mut response = [0; 11];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$5\r\nworld\r\n", &response);
+
+    // 不使用 TLS 的客户端无法完成握手，不会得到明文的回复
+    let mut plain = TcpStream::connect(addr).await.unwrap();
+    plain.write_all(&encode(&["PING"])).await.unwrap();
+    let mut response = vec![];
+    let _ = time::timeout(Duration::from_secs(1), plain.read_to_end(&mut response)).await;
+    assert!(!response.starts_with(b"+PONG"));
+
+    std::fs::remove_file(&cert_path).unwrap();
+    std::fs::remove_file(&key_path).unwrap();
+}
+
+/// 嵌入服务器的应用可以通过 `server::start` 返回的句柄直接读取客户端写入的字符串。
+#[tokio::test]
+async fn db_handle_iter_entries() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (handle, server) =
+        server::start(listener, tokio::signal::ctrl_c(), ServerConfig::default());
+    tokio::spawn(server);
+
+    let mut client = client::connect(addr).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    client
+        .set_expire("session", "abc".into(), Duration::from_secs(60))
+        .await
+        .unwrap();
+    // 其他类型的键不包括在结果中
+    client.rpush("list", &["a".into()]).await.unwrap();
+    client.sadd("set", &["a".into()]).await.unwrap();
+
+    let mut entries = handle.iter_entries();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(2, entries.len());
+    assert_eq!(
+        ("hello", &b"world"[..], None),
+        (&entries[0].0[..], &entries[0].1[..], entries[0].2)
+    );
+    assert_eq!(
+        ("session", &b"abc"[..]),
+        (&entries[1].0[..], &entries[1].1[..])
+    );
+    let ttl = entries[1].2.unwrap();
+    assert!(ttl > Duration::from_secs(50) && ttl <= Duration::from_secs(60));
+}
+
+/// 通过 `server::start` 返回的句柄设置的过期回调在键过期之后被调用。
+#[tokio::test]
+async fn db_handle_on_expire() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (handle, server) =
+        server::start(listener, tokio::signal::ctrl_c(), ServerConfig::default());
+    tokio::spawn(server);
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    handle.on_expire(move |key| tx.send(key.to_string()).unwrap());
+
+    let mut client = client::connect(addr).await.unwrap();
+    client
+        .set_expire("foo", "bar".into(), Duration::from_millis(100))
+        .await
+        .unwrap();
+    // 过期之后访问键，不论是后台任务还是这次访问删除了它，回调都只调用一次
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(None, client.get("foo").await.unwrap());
+
+    let key = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!("foo", key);
+    assert!(rx.try_recv().is_err());
+}
+
+/// 嵌入的服务器不会修改整个进程共享的日志级别，配置中的 `log_level` 由调用者决定是否应用。
+#[tokio::test]
+async fn embedded_server_keeps_global_log_level() {
+    let before = log::max_level();
+    let level = if before == log::LevelFilter::Trace {
+        log::LevelFilter::Error
+    } else {
+        log::LevelFilter::Trace
+    };
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let config = ServerConfig {
+        log_level: Some(level),
+        ..ServerConfig::default()
+    };
+    tokio::spawn(server::run_with_config(
+        listener,
+        tokio::signal::ctrl_c(),
+        config,
+    ));
+
+    // 收到回复时服务器已经开始处理连接
+    let mut client = client::connect(addr).await.unwrap();
+    client.ping(None).await.unwrap();
+    assert_eq!(before, log::max_level());
+}
+
+/// 一个简单的异步存储，克隆的存储共享同一个哈希表，测试可以直接检查其中的数据
+#[derive(Clone, Default)]
+struct MemoryStore {
+    entries: Arc<tokio::sync::Mutex<HashMap<String, Bytes>>>,
+}
+
+impl AsyncKvStore for MemoryStore {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Bytes>, WrongType>> {
+        Box::pin(async move { Ok(self.entries.lock().await.get(key).cloned()) })
+    }
+
+    fn set(&self, key: String, value: Bytes, _expire: Option<Duration>) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            self.entries.lock().await.insert(key, value);
+        })
+    }
+
+    fn set_keep_ttl(&self, key: String, value: Bytes) -> BoxFuture<'_, ()> {
+        self.set(key, value, None)
+    }
+
+    fn del(&self, key: String) -> BoxFuture<'_, usize> {
+        Box::pin(async move { self.entries.lock().await.remove(&key).map_or(0, |_| 1) })
+    }
+}
+
+/// `run_with_store` 启动的服务器中，GET、SET 和 DEL 读写调用者提供的存储，其他命令仍然使用内存中的数据库。
+#[tokio::test]
+async fn run_with_custom_store() {
+    let store = MemoryStore::default();
+    store
+        .entries
+        .lock()
+        .await
+        .insert("preloaded".into(), Bytes::from("value"));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(server::run_with_store(
+        listener,
+        tokio::signal::ctrl_c(),
+        ServerConfig::default(),
+        store.clone(),
+    ));
+
+    let mut client = client::connect(addr).await.unwrap();
+    assert_eq!(
+        Some(Bytes::from("value")),
+        client.get("preloaded").await.unwrap()
+    );
+
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(
+        Some(&Bytes::from("world")),
+        store.entries.lock().await.get("hello")
+    );
+    // 内存中的数据库里没有这个键
+    assert_eq!(0, client.dbsize().await.unwrap());
+
+    assert_eq!(1, client.del("hello").await.unwrap());
+    assert!(store.entries.lock().await.get("hello").is_none());
+    assert_eq!(None, client.get("hello").await.unwrap());
+}
+
+/// 使用配置文件 `path` 运行服务器可执行文件，等待它在 `port` 上开始监听后返回进程和一个连接。
+/// 进程在返回的 `Child` 被丢弃时终止。
+async fn start_server_binary(path: &Path, port: u16) -> (Child, TcpStream) {
+    let server = Command::new(env!("CARGO_BIN_EXE_mini-redis-server"))
+        .arg("--config")
+        .arg(path)
+        .env("LOG_LEVEL", "ERROR")
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+
+    loop {
+        match TcpStream::connect(("127.0.0.1", port)).await {
+            Ok(stream) => return (server, stream),
+            Err(_) => time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+}
+
+/// 运行 Redis 服务器并返回绑定的套接字地址
+async fn start_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move { server::run(listener, tokio::signal::ctrl_c()).await });
+
+    addr
+}